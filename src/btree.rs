@@ -30,10 +30,41 @@ pub const BTREE_PAGE_HEADER_MAX_SIZE: usize = BTREE_PAGE_INTERIOR_HEADER_SIZE;
 const LEAF_FLAG: u8 = 0x08;
 const INDEX_FLAG: u8 = 0x02;
 const TABLE_FLAG: u8 = 0x05;
+const LEAF_INDEX_FLAGS: u8 = LEAF_FLAG | INDEX_FLAG;
+const LEAF_TABLE_FLAGS: u8 = LEAF_FLAG | TABLE_FLAG;
 
 pub struct BtreePageType(u8);
 
+/// The 4 btree page types.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BtreePageKind {
+    /// 0x02
+    InteriorIndex,
+    /// 0x05
+    InteriorTable,
+    /// 0x0a
+    LeafIndex,
+    /// 0x0d
+    LeafTable,
+}
+
 impl BtreePageType {
+    /// Returns None if the page type is not a btree page type.
+    pub fn kind(&self) -> Option<BtreePageKind> {
+        match self.0 {
+            INDEX_FLAG => Some(BtreePageKind::InteriorIndex),
+            TABLE_FLAG => Some(BtreePageKind::InteriorTable),
+            LEAF_INDEX_FLAGS => Some(BtreePageKind::LeafIndex),
+            LEAF_TABLE_FLAGS => Some(BtreePageKind::LeafTable),
+            _ => None,
+        }
+    }
+
+    /// Whether the page type is one of the 4 btree page types.
+    pub fn is_valid(&self) -> bool {
+        self.kind().is_some()
+    }
+
     #[inline]
     pub fn is_leaf(&self) -> bool {
         self.0 & LEAF_FLAG != 0
@@ -54,7 +85,8 @@ pub struct BtreePageHeader<'page>(&'page [u8; BTREE_PAGE_HEADER_MAX_SIZE]);
 
 impl<'page> BtreePageHeader<'page> {
     pub fn from_page(page: &MemPage, buffer: &'page PageBuffer<'page>) -> Self {
-        // SAFETY: PageBuffer is always more than 512 bytes.
+        // PageBuffer is always more than 512 bytes and header_offset is at most
+        // 100.
         Self(
             buffer[page.header_offset..page.header_offset + BTREE_PAGE_HEADER_MAX_SIZE]
                 .try_into()
@@ -68,11 +100,31 @@ impl<'page> BtreePageHeader<'page> {
         BtreePageType(self.0[0])
     }
 
+    /// The offset of the first freeblock, or zero if there are no freeblocks.
+    pub fn first_freeblock_offset(&self) -> u16 {
+        u16::from_be_bytes(self.0[1..3].try_into().unwrap())
+    }
+
     /// The number of cells in this page
     pub fn n_cells(&self) -> u16 {
         u16::from_be_bytes(self.0[3..5].try_into().unwrap())
     }
 
+    /// The start of the cell content area.
+    ///
+    /// Zero in the header is interpreted as 65536.
+    pub fn cell_content_area_offset(&self) -> u32 {
+        match u16::from_be_bytes(self.0[5..7].try_into().unwrap()) {
+            0 => 1 << 16,
+            offset => offset as u32,
+        }
+    }
+
+    /// The number of fragmented free bytes within the cell content area.
+    pub fn n_fragmented_free_bytes(&self) -> u8 {
+        self.0[7]
+    }
+
     /// The right-most pointer
     ///
     /// This is only valid when the page is a interior page.
@@ -80,6 +132,22 @@ impl<'page> BtreePageHeader<'page> {
         u32::from_be_bytes(self.0[8..12].try_into().unwrap())
     }
 
+    /// Validate the header against the page.
+    ///
+    /// The page type must be valid and the cell pointer array must fit in the
+    /// page.
+    pub fn validate(&self, page: &MemPage, buffer: &PageBuffer) -> ParseResult<()> {
+        if !self.page_type().is_valid() {
+            return Err("invalid btree page type");
+        }
+        let cell_pointers_end =
+            page.header_offset + self.header_size() as usize + ((self.n_cells() as usize) << 1);
+        if cell_pointers_end > buffer.len() {
+            return Err("too many cells");
+        }
+        Ok(())
+    }
+
     /// The btree page header size.
     ///
     /// * Returns 8 if this is a leaf page.
@@ -123,8 +191,11 @@ impl<'a> TableCellKeyParser<'a> {
         } else {
             4
         };
-        let (key, _) =
-            parse_varint(&self.buffer[offset + offset_in_cell..]).ok_or("parse key varint")?;
+        let key_buf = self
+            .buffer
+            .get(offset + offset_in_cell..)
+            .ok_or("table cell out of range")?;
+        let (key, _) = parse_varint(key_buf).ok_or("parse key varint")?;
         Ok(key)
     }
 }
@@ -153,8 +224,9 @@ impl<'a> IndexCellKeyParser<'a> {
     pub fn get_cell_key(&self, cell_idx: u16) -> ParseResult<PayloadInfo> {
         let offset = get_cell_offset(self.page, self.buffer, cell_idx, self.header_size)?;
         let offset = offset + self.offset_in_cell as usize;
+        let payload_size_buf = self.buffer.get(offset..).ok_or("index cell out of range")?;
         let (payload_size, n) =
-            parse_varint(&self.buffer[offset..]).ok_or("parse payload length varint")?;
+            parse_varint(payload_size_buf).ok_or("parse payload length varint")?;
         let payload_size: i32 = payload_size
             .try_into()
             .map_err(|_| "payload length too large")?;
@@ -168,13 +240,14 @@ impl<'a> IndexCellKeyParser<'a> {
 /// Returns the offset of the cell in the buffer.
 ///
 /// Returned cell offset is in the range of the buffer.
-fn get_cell_offset(
+pub fn get_cell_offset(
     page: &MemPage,
     buffer: &PageBuffer,
     cell_idx: u16,
     header_size: u8,
 ) -> ParseResult<usize> {
-    let cell_pointer_offset = page.header_offset + header_size as usize + (cell_idx << 1) as usize;
+    let cell_pointer_offset =
+        page.header_offset + header_size as usize + ((cell_idx as usize) << 1);
     if cell_pointer_offset + 2 > buffer.len() {
         return Err("cell pointer out of range");
     }
@@ -220,11 +293,21 @@ impl BtreeContext {
         }
     }
 
+    /// The usable size of a page.
+    pub fn usable_size(&self) -> u32 {
+        self.usable_minus_4 as u32 + 4
+    }
+
     #[inline]
     fn max_local(&self, is_table: bool) -> u16 {
         self.max_local[is_table as usize]
     }
 
+    /// The local payload size of a payload which spills to overflow pages.
+    ///
+    /// This is K if K <= X, otherwise M.
+    ///
+    /// https://www.sqlite.org/fileformat2.html#b_tree_pages
     #[inline]
     fn n_local(&self, is_table: bool, payload_size: i32) -> u16 {
         let surplus = self.min_local as i32
@@ -250,18 +333,20 @@ impl OverflowPage {
 
     pub fn parse<'a>(
         &self,
+        ctx: &BtreeContext,
         buffer: &'a PageBuffer<'a>,
     ) -> ParseResult<(&'a [u8], Option<OverflowPage>)> {
         let next_page_id = PageId::from_be_bytes(buffer[..4].try_into().unwrap());
+        // The reserved bytes at the tail of the page are not a part of the
+        // payload.
+        let payload = &buffer[4..ctx.usable_size() as usize];
         if next_page_id == 0 {
-            let tail = 4 + self.remaining_size as usize;
-            if buffer.len() >= tail {
-                Ok((&buffer[4..tail], None))
+            if payload.len() >= self.remaining_size as usize {
+                Ok((&payload[..self.remaining_size as usize], None))
             } else {
                 Err("overflow payload does not have next page id")
             }
         } else {
-            let payload = &buffer[4..];
             if self.remaining_size > payload.len() as i32 {
                 Ok((
                     payload,
@@ -342,10 +427,9 @@ pub fn parse_btree_leaf_table_cell(
     buffer: &PageBuffer,
     cell_idx: u16,
 ) -> ParseResult<(i64, PayloadInfo)> {
-    let cell_offset =
-        get_cell_offset(page, buffer, cell_idx, BTREE_PAGE_LEAF_HEADER_SIZE as u8).unwrap();
+    let cell_offset = get_cell_offset(page, buffer, cell_idx, BTREE_PAGE_LEAF_HEADER_SIZE as u8)?;
     let (payload_size, consumed1) =
-        parse_varint(&buffer[cell_offset..]).map_or(Err("parse payload length varint"), Ok)?;
+        parse_varint(&buffer[cell_offset..]).ok_or("parse payload length varint")?;
     // The maximum payload length is 2147483647 (= i32::MAX).
     let payload_size: i32 = payload_size
         .try_into()
@@ -354,7 +438,7 @@ pub fn parse_btree_leaf_table_cell(
         return Err("payload length is negative");
     }
     let (key, consumed2) =
-        parse_varint(&buffer[cell_offset + consumed1..]).map_or(Err("parse key varint"), Ok)?;
+        parse_varint(&buffer[cell_offset + consumed1..]).ok_or("parse key varint")?;
 
     let payload = PayloadInfo::parse(
         ctx,
@@ -395,6 +479,7 @@ mod tests {
 
     use crate::test_utils::*;
     use crate::utils::unsafe_parse_varint;
+    use crate::DATABASE_HEADER_SIZE;
 
     const BTREE_PAGE_TYPE_INTERIOR_INDEX: u8 = INDEX_FLAG;
     const BTREE_PAGE_TYPE_INTERIOR_TABLE: u8 = TABLE_FLAG;
@@ -404,16 +489,54 @@ mod tests {
     #[test]
     fn pagetype() {
         let mut buf = [0_u8; 12];
-        for t in [
-            BTREE_PAGE_TYPE_INTERIOR_INDEX,
-            BTREE_PAGE_TYPE_INTERIOR_TABLE,
-            BTREE_PAGE_TYPE_LEAF_INDEX,
-            BTREE_PAGE_TYPE_LEAF_TABLE,
+        for (t, kind, is_leaf, is_table, is_index) in [
+            (
+                BTREE_PAGE_TYPE_INTERIOR_INDEX,
+                BtreePageKind::InteriorIndex,
+                false,
+                false,
+                true,
+            ),
+            (
+                BTREE_PAGE_TYPE_INTERIOR_TABLE,
+                BtreePageKind::InteriorTable,
+                false,
+                true,
+                false,
+            ),
+            (
+                BTREE_PAGE_TYPE_LEAF_INDEX,
+                BtreePageKind::LeafIndex,
+                true,
+                false,
+                true,
+            ),
+            (
+                BTREE_PAGE_TYPE_LEAF_TABLE,
+                BtreePageKind::LeafTable,
+                true,
+                true,
+                false,
+            ),
         ] {
             buf[0] = t;
             let header = BtreePageHeader(&buf);
 
-            assert_eq!(header.page_type().0, t);
+            let page_type = header.page_type();
+            assert_eq!(page_type.0, t);
+            assert_eq!(page_type.kind(), Some(kind));
+            assert!(page_type.is_valid());
+            assert_eq!(page_type.is_leaf(), is_leaf);
+            assert_eq!(page_type.is_table(), is_table);
+            assert_eq!(page_type.is_index(), is_index);
+        }
+
+        for t in [0x00, 0x01, 0x08, 0x0f, 0xff] {
+            buf[0] = t;
+            let header = BtreePageHeader(&buf);
+
+            assert_eq!(header.page_type().kind(), None);
+            assert!(!header.page_type().is_valid());
         }
     }
 
@@ -475,6 +598,100 @@ mod tests {
         assert_eq!(get_cell_offset(&page, &buffer, 0, 12).unwrap(), 1000);
     }
 
+    #[test]
+    fn test_validate_btree_page_header() {
+        const PAGESIZE: usize = 512;
+        let mut content = [0_u8; 4 * PAGESIZE];
+        // page 1: invalid page type.
+        content[DATABASE_HEADER_SIZE] = 0x01;
+        // page 2: too many cells.
+        content[PAGESIZE] = BTREE_PAGE_TYPE_LEAF_TABLE;
+        content[PAGESIZE + 3..PAGESIZE + 5].copy_from_slice(&253_u16.to_be_bytes());
+        // page 3: the cell pointer array fits in the page.
+        content[2 * PAGESIZE] = BTREE_PAGE_TYPE_LEAF_TABLE;
+        content[2 * PAGESIZE + 3..2 * PAGESIZE + 5].copy_from_slice(&252_u16.to_be_bytes());
+        // page 4: empty page.
+        content[3 * PAGESIZE] = BTREE_PAGE_TYPE_INTERIOR_INDEX;
+        let pager = create_empty_pager(&content, PAGESIZE);
+
+        for (page_id, valid) in [(1, false), (2, false), (3, true), (4, true)] {
+            let page = pager.get_page(page_id).unwrap();
+            let buffer = page.buffer();
+            let header = BtreePageHeader::from_page(&page, &buffer);
+            assert_eq!(
+                header.validate(&page, &buffer).is_ok(),
+                valid,
+                "page_id: {page_id}"
+            );
+        }
+    }
+
+    #[test]
+    fn test_parse_malformed_cells() {
+        const PAGESIZE: usize = 512;
+        let mut content = [0_u8; 4 * PAGESIZE];
+        // page 1: a leaf table page with the cell offset 0 (= 65536).
+        content[DATABASE_HEADER_SIZE] = BTREE_PAGE_TYPE_LEAF_TABLE;
+        content[DATABASE_HEADER_SIZE + 4] = 1;
+        // page 2: a leaf table page with a truncated varint at the tail.
+        content[PAGESIZE] = BTREE_PAGE_TYPE_LEAF_TABLE;
+        content[PAGESIZE + 4] = 1;
+        content[PAGESIZE + 8..PAGESIZE + 10].copy_from_slice(&510_u16.to_be_bytes());
+        content[2 * PAGESIZE - 2..2 * PAGESIZE].copy_from_slice(&[0xff, 0xff]);
+        // page 3: an interior table page with a cell at the tail.
+        content[2 * PAGESIZE] = BTREE_PAGE_TYPE_INTERIOR_TABLE;
+        content[2 * PAGESIZE + 4] = 1;
+        content[2 * PAGESIZE + 12..2 * PAGESIZE + 14].copy_from_slice(&510_u16.to_be_bytes());
+        // page 4: an interior index page with a cell at the tail.
+        content[3 * PAGESIZE] = BTREE_PAGE_TYPE_INTERIOR_INDEX;
+        content[3 * PAGESIZE + 4] = 1;
+        content[3 * PAGESIZE + 12..3 * PAGESIZE + 14].copy_from_slice(&510_u16.to_be_bytes());
+        let pager = create_empty_pager(&content, PAGESIZE);
+        let bctx = BtreeContext::new(PAGESIZE as i32);
+
+        let page = pager.get_page(1).unwrap();
+        let buffer = page.buffer();
+        assert_eq!(
+            parse_btree_leaf_table_cell(&bctx, &page, &buffer, 0).err(),
+            Some("cell offset out of range")
+        );
+        assert!(TableCellKeyParser::new(&page, &buffer)
+            .get_cell_key(0)
+            .is_err());
+        // The cell pointer is out of the page.
+        assert_eq!(
+            parse_btree_leaf_table_cell(&bctx, &page, &buffer, u16::MAX).err(),
+            Some("cell pointer out of range")
+        );
+
+        let page = pager.get_page(2).unwrap();
+        let buffer = page.buffer();
+        assert_eq!(
+            parse_btree_leaf_table_cell(&bctx, &page, &buffer, 0).err(),
+            Some("parse payload length varint")
+        );
+        assert!(TableCellKeyParser::new(&page, &buffer)
+            .get_cell_key(0)
+            .is_err());
+
+        let page = pager.get_page(3).unwrap();
+        let buffer = page.buffer();
+        assert_eq!(
+            TableCellKeyParser::new(&page, &buffer).get_cell_key(0),
+            Err("table cell out of range")
+        );
+        assert!(parse_btree_interior_cell_page_id(&page, &buffer, 0).is_err());
+
+        let page = pager.get_page(4).unwrap();
+        let buffer = page.buffer();
+        assert_eq!(
+            IndexCellKeyParser::new(&bctx, &page, &buffer)
+                .get_cell_key(0)
+                .err(),
+            Some("index cell out of range")
+        );
+    }
+
     #[test]
     fn test_parse_btree_leaf_table_cell() {
         let file = create_sqlite_database(&["CREATE TABLE example(col);"]);
@@ -538,9 +755,70 @@ mod tests {
                 .get_page(overflow.as_ref().unwrap().page_id())
                 .unwrap();
             let buffer = page.buffer();
-            (payload, overflow) = overflow.as_ref().unwrap().parse(&buffer).unwrap();
+            (payload, overflow) = overflow.as_ref().unwrap().parse(&bctx, &buffer).unwrap();
             assert_eq!(payload, &buf[cur..cur + payload.len()]);
             cur += payload.len();
         }
     }
+
+    #[test]
+    fn test_overflow_payload_boundary() {
+        // With the usable size 4096:
+        //
+        // * X (max local) = 4096 - 35 = 4061
+        // * M (min local) = ((4096 - 12) * 32 / 255) - 23 = 489
+        // * K = M + ((P - M) % (4096 - 4))
+        //
+        // A blob column of n bytes (4 <= n < 8186) has 3 bytes record header, so P
+        // = n + 3.
+        for (blob_size, local_size, has_overflow) in [
+            // P = X - 1.
+            (4057, 4060, false),
+            // P = X. The whole payload is stored locally.
+            (4058, 4061, false),
+            // P = X + 1. K > X.
+            (4059, 489, true),
+            // K = X - 1.
+            (8149, 4060, true),
+            // K = X.
+            (8150, 4061, true),
+            // K = X + 1.
+            (8151, 489, true),
+        ] {
+            let query = format!("INSERT INTO example(col) VALUES (zeroblob({}));", blob_size);
+            let file = create_sqlite_database(&[
+                "PRAGMA page_size = 4096;",
+                "CREATE TABLE example(col);",
+                &query,
+            ]);
+            let pager = create_pager(file.as_file().try_clone().unwrap()).unwrap();
+            let bctx = load_btree_context(file.as_file()).unwrap();
+            let page_id = find_table_page_id("example", file.path());
+            let page = pager.get_page(page_id).unwrap();
+            let buffer = page.buffer();
+            assert_eq!(buffer.len(), 4096);
+
+            let (_, payload_info) = parse_btree_leaf_table_cell(&bctx, &page, &buffer, 0).unwrap();
+
+            assert_eq!(payload_info.payload_size, blob_size + 3, "{}", blob_size);
+            assert_eq!(payload_info.local_range.len(), local_size, "{}", blob_size);
+            assert_eq!(
+                payload_info.overflow.is_some(),
+                has_overflow,
+                "{}",
+                blob_size
+            );
+
+            let mut size = payload_info.local_range.len();
+            let mut overflow = payload_info.overflow;
+            while let Some(overflow_page) = overflow {
+                let page = pager.get_page(overflow_page.page_id()).unwrap();
+                let buffer = page.buffer();
+                let payload;
+                (payload, overflow) = overflow_page.parse(&bctx, &buffer).unwrap();
+                size += payload.len();
+            }
+            assert_eq!(size, payload_info.payload_size as usize, "{}", blob_size);
+        }
+    }
 }
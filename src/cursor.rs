@@ -13,9 +13,11 @@
 // limitations under the License.
 
 use std::cmp::Ordering;
+use std::io::Write;
 use std::ptr::copy_nonoverlapping;
 
 use anyhow::bail;
+use anyhow::Context;
 
 use crate::btree::parse_btree_interior_cell_page_id;
 use crate::btree::parse_btree_leaf_table_cell;
@@ -30,10 +32,29 @@ use crate::pager::PageBuffer;
 use crate::pager::PageId;
 use crate::pager::Pager;
 use crate::record::compare_record;
+use crate::record::Record;
+use crate::record::RecordProjector;
+use crate::record::SerialTypeKind;
+use crate::value::TextEncoding;
+use crate::value::TypeAffinity;
+use crate::value::Value;
 use crate::value::ValueCmp;
 
+/// The maximum depth of a btree.
+///
+/// This is the same as BTCURSOR_MAX_DEPTH of SQLite. A deeper btree is corrupted
+/// e.g. cyclic page references.
+///
+/// An interior page holds many cells in practice, so a btree of a very large
+/// database is still only a few levels deep.
+const BTREE_MAX_DEPTH: usize = 20;
+
+/// The maximum number of contiguous overflow pages read with a single read.
+const MAX_READ_AHEAD_PAGES: u32 = 16;
+
 pub struct BtreePayload<'a, 'pager> {
     pager: &'pager Pager,
+    btree_ctx: &'a BtreeContext,
     local_payload_buffer: PageBuffer<'a>,
     payload_info: PayloadInfo,
 }
@@ -44,6 +65,17 @@ impl<'a, 'pager> BtreePayload<'a, 'pager> {
         self.payload_info.payload_size
     }
 
+    /// The number of bytes from the offset to the end of the payload.
+    ///
+    /// Returns 0 if the offset is not less than the size of the payload. A
+    /// negative offset is treated as 0.
+    pub fn remaining(&self, offset: i32) -> i32 {
+        self.payload_info
+            .payload_size
+            .saturating_sub(offset.max(0))
+            .max(0)
+    }
+
     /// The local payload.
     ///
     /// This may not be the entire payload if there is overflow page.
@@ -85,16 +117,8 @@ impl<'a, 'pager> BtreePayload<'a, 'pager> {
             buf = &mut buf[n..];
         }
 
-        let mut cur = payload.len() as i32;
-        let mut overflow = self.payload_info.overflow;
-        while !buf.is_empty() && cur < self.payload_info.payload_size {
-            let overflow_page =
-                overflow.ok_or_else(|| anyhow::anyhow!("overflow page is not found"))?;
-            let page = self.pager.get_page(overflow_page.page_id())?;
-            let buffer = page.buffer();
-            let (payload, next_overflow) = overflow_page
-                .parse(&buffer)
-                .map_err(|e| anyhow::anyhow!("parse overflow: {:?}", e))?;
+        let end = offset.saturating_add(buf.len().try_into().unwrap_or(i32::MAX));
+        self.for_each_overflow_page(end, true, |cur, payload| {
             if offset < cur + payload.len() as i32 {
                 let local_offset = (offset - cur) as usize;
                 let n = std::cmp::min(payload.len() - local_offset, buf.len());
@@ -106,13 +130,128 @@ impl<'a, 'pager> BtreePayload<'a, 'pager> {
                 }
                 n_loaded += n;
                 offset += n as i32;
-                buf = &mut buf[n..];
+                buf = &mut std::mem::take(&mut buf)[n..];
+            }
+            Ok(())
+        })?;
+
+        Ok(n_loaded)
+    }
+
+    /// Load the range of the payload into a new buffer.
+    ///
+    /// This is a safe alternative to [Self::load()] for small reads. Fails if
+    /// the range exceeds the payload.
+    pub fn load_vec(&self, offset: i32, size: i32) -> anyhow::Result<Vec<u8>> {
+        if offset < 0 || size < 0 {
+            bail!("offset and size must be non-negative");
+        } else if offset as i64 + size as i64 > self.payload_info.payload_size as i64 {
+            bail!("range exceeds payload size");
+        }
+        let mut buf = vec![0; size as usize];
+        if size > 0 {
+            // SAFETY: buf is not from MemPage.
+            let n = unsafe { self.load(offset, &mut buf)? };
+            if n != buf.len() {
+                bail!("loaded size {} does not match {}", n, size);
+            }
+        }
+        Ok(buf)
+    }
+
+    /// Copy the entire payload including the overflow pages into a new buffer.
+    pub fn to_vec(&self) -> anyhow::Result<Vec<u8>> {
+        self.load_vec(0, self.size())
+    }
+
+    /// Write the range of the payload to the writer.
+    ///
+    /// Unlike [Self::load()], this streams each page of the payload to the
+    /// writer without copying the range into a buffer.
+    ///
+    /// Returns the number of bytes written. It is less than the size if the
+    /// range exceeds the payload.
+    pub fn write_to(&self, offset: i32, size: i32, writer: &mut impl Write) -> anyhow::Result<u64> {
+        if offset < 0 || size < 0 {
+            bail!("offset and size must be non-negative");
+        }
+        let end = offset.saturating_add(size);
+        let mut n_written = 0;
+        let mut write = |cur: i32, payload: &[u8]| -> anyhow::Result<()> {
+            let start = offset.max(cur);
+            let stop = end.min(cur + payload.len() as i32);
+            if start < stop {
+                writer.write_all(&payload[(start - cur) as usize..(stop - cur) as usize])?;
+                n_written += (stop - start) as u64;
             }
+            Ok(())
+        };
+
+        let payload = &self.local_payload_buffer[self.payload_info.local_range.clone()];
+        write(0, payload)?;
+        // Each page is written as soon as it is loaded and the overflow pages
+        // are not read ahead.
+        self.for_each_overflow_page(end, false, write)?;
+
+        Ok(n_written)
+    }
+
+    /// Call the function with the offset and the payload of each overflow page
+    /// in the chain until the payload up to the end offset is visited.
+    ///
+    /// If read_ahead is true, a run of contiguous overflow pages is read with a
+    /// single read. A run is read only after a page confirms that its next
+    /// page id is the following page, and the read is capped by
+    /// [MAX_READ_AHEAD_PAGES] and the remaining size up to the end offset. The
+    /// pages read ahead are not cached. If the chain leaves the run, the rest of
+    /// the run is discarded and the chain is followed page by page again.
+    fn for_each_overflow_page(
+        &self,
+        end: i32,
+        read_ahead: bool,
+        mut f: impl FnMut(i32, &[u8]) -> anyhow::Result<()>,
+    ) -> anyhow::Result<()> {
+        let end = end.min(self.payload_info.payload_size);
+        // Each overflow page starts with the 4 bytes next page id.
+        let overflow_size = self.btree_ctx.usable_size() - 4;
+        let pagesize = self.pager.pagesize();
+        let mut run = Vec::new();
+        let mut run_start: PageId = 0;
+        let mut cur = self.payload_info.local_range.len() as i32;
+        let mut overflow = self.payload_info.overflow;
+        while cur < end {
+            let overflow_page =
+                overflow.ok_or_else(|| anyhow::anyhow!("overflow page is not found"))?;
+            let page_id = overflow_page.page_id();
+            let run_idx = page_id.wrapping_sub(run_start) as usize;
+            let page;
+            let buffer = if run_start != 0 && run_idx < run.len() / pagesize {
+                &run[run_idx * pagesize..(run_idx + 1) * pagesize]
+            } else {
+                page = self.pager.get_page(page_id)?;
+                page.buffer()
+            };
+            let (payload, next_overflow) = overflow_page
+                .parse(self.btree_ctx, &buffer)
+                .map_err(|e| anyhow::anyhow!("parse overflow: {:?}", e))?;
+            f(cur, payload)?;
             cur += payload.len() as i32;
             overflow = next_overflow;
-        }
 
-        Ok(n_loaded)
+            if let Some(next) = overflow {
+                let next_id = next.page_id();
+                let in_run = run_start != 0
+                    && (next_id.wrapping_sub(run_start) as usize) < run.len() / pagesize;
+                if read_ahead && cur < end && next_id == page_id.wrapping_add(1) && !in_run {
+                    let n_pages = ((end - cur) as u32)
+                        .div_ceil(overflow_size)
+                        .min(MAX_READ_AHEAD_PAGES);
+                    run = self.pager.read_pages(next_id, n_pages)?;
+                    run_start = next_id;
+                }
+            }
+        }
+        Ok(())
     }
 }
 
@@ -125,20 +264,22 @@ struct CursorPage {
 }
 
 impl CursorPage {
-    fn new(mem: MemPage) -> Self {
+    fn new(mem: MemPage) -> anyhow::Result<Self> {
         let buffer = mem.buffer();
         let page_header = BtreePageHeader::from_page(&mem, &buffer);
+        page_header
+            .validate(&mem, &buffer)
+            .map_err(|e| anyhow::anyhow!("validate btree page header: {:?}", e))?;
         let n_cells = page_header.n_cells();
         let page_type = page_header.page_type();
         let is_leaf = page_type.is_leaf();
-        drop(buffer);
-        Self {
+        Ok(Self {
             mem,
             idx_cell: 0,
             n_cells,
             page_type,
             is_leaf,
-        }
+        })
     }
 }
 
@@ -157,12 +298,13 @@ impl<'ctx, 'pager> BtreeCursor<'ctx, 'pager> {
         btree_ctx: &'ctx BtreeContext,
     ) -> anyhow::Result<Self> {
         let mem = pager.get_page(root_page_id)?;
-        let page = CursorPage::new(mem);
+        let page = CursorPage::new(mem)?;
         Ok(Self {
             pager,
             btree_ctx,
             current_page: page,
-            parent_pages: Vec::new(),
+            // Reserve the maximum depth so that descending never reallocates.
+            parent_pages: Vec::with_capacity(BTREE_MAX_DEPTH),
             initialized: false,
         })
     }
@@ -217,7 +359,6 @@ impl<'ctx, 'pager> BtreeCursor<'ctx, 'pager> {
                 )
                 .map_err(|e| anyhow::anyhow!("get btree interior cell page id: {:?}", e))?
             };
-            drop(buffer);
             self.move_to_child(next_page_id)?;
         }
     }
@@ -244,6 +385,7 @@ impl<'ctx, 'pager> BtreeCursor<'ctx, 'pager> {
                     .map_err(|e| anyhow::anyhow!("parse index cell key: {:?}", e))?;
                 let key_payload = BtreePayload {
                     pager: self.pager,
+                    btree_ctx: self.btree_ctx,
                     local_payload_buffer: self.current_page.mem.buffer(),
                     payload_info,
                 };
@@ -263,7 +405,6 @@ impl<'ctx, 'pager> BtreeCursor<'ctx, 'pager> {
             }
             self.current_page.idx_cell = i_min as u16;
             if self.current_page.is_leaf {
-                drop(buffer);
                 // If the key is between the last key of the index leaf page and the parent key,
                 // we need to adjust the cursor to parent cell.
                 if self.current_page.idx_cell == self.current_page.n_cells {
@@ -294,11 +435,13 @@ impl<'ctx, 'pager> BtreeCursor<'ctx, 'pager> {
                 )
                 .map_err(|e| anyhow::anyhow!("get btree interior cell page id: {:?}", e))?
             };
-            drop(buffer);
             self.move_to_child(next_page_id)?;
         }
     }
 
+    /// Move to the first cell of the btree.
+    ///
+    /// This can be called at any time to rescan the btree from the beginning.
     pub fn move_to_first(&mut self) -> anyhow::Result<()> {
         self.move_to_root()?;
         self.current_page.idx_cell = 0;
@@ -309,14 +452,36 @@ impl<'ctx, 'pager> BtreeCursor<'ctx, 'pager> {
         Ok(())
     }
 
+    /// Move to the last cell of the btree and return its payload.
+    ///
+    /// The cursor descends through the right-most child of each interior page
+    /// so that [Self::next()] completes the cursor and [Self::prev()] moves to
+    /// the previous cell afterwards. Returns `None` if the btree is empty.
+    pub fn move_to_last<'a>(&'a mut self) -> anyhow::Result<Option<BtreePayload<'a, 'pager>>> {
+        self.move_to_root()?;
+        if self.current_page.is_leaf {
+            self.current_page.idx_cell = self.current_page.n_cells.saturating_sub(1);
+        } else {
+            self.current_page.idx_cell = self.current_page.n_cells;
+            self.move_to_right_most()?;
+        }
+        self.initialized = true;
+        if self.current_page.page_type.is_table() {
+            Ok(self.get_table_payload()?.map(|(_, payload)| payload))
+        } else {
+            self.get_index_payload()
+        }
+    }
+
     pub fn next(&mut self) -> anyhow::Result<()> {
         if !self.initialized {
             bail!("cursor is not initialized");
         } else if self.parent_pages.is_empty()
-            && (self.current_page.idx_cell == self.current_page.n_cells + 1
+            && (self.current_page.idx_cell > self.current_page.n_cells
                 || self.current_page.n_cells == 0)
         {
-            // The cursor is completed.
+            // The cursor is completed. The cell index of the root page is
+            // n_cells + 1, or n_cells + 2 if the root is an interior table page.
             return Ok(());
         }
 
@@ -329,17 +494,7 @@ impl<'ctx, 'pager> BtreeCursor<'ctx, 'pager> {
             // table page never stops in the middle of the interior page.
             assert!(self.current_page.is_leaf);
             assert!(self.current_page.idx_cell == self.current_page.n_cells);
-            loop {
-                if !self.back_to_parent()? {
-                    // traversing completed.
-                    self.current_page.idx_cell += 1;
-                    break;
-                }
-                self.current_page.idx_cell += 1;
-                if self.move_to_left_most()? {
-                    break;
-                }
-            }
+            self.move_to_next_leaf()?;
         } else if self.current_page.page_type.is_index() {
             if !self.current_page.is_leaf && self.current_page.idx_cell <= self.current_page.n_cells
             {
@@ -367,6 +522,140 @@ impl<'ctx, 'pager> BtreeCursor<'ctx, 'pager> {
         Ok(())
     }
 
+    /// Move to the previous cell.
+    ///
+    /// If there is no previous cell, the cursor is completed as [Self::next()]
+    /// completes it at the end of the btree.
+    pub fn prev(&mut self) -> anyhow::Result<()> {
+        if !self.initialized {
+            bail!("cursor is not initialized");
+        } else if self.parent_pages.is_empty()
+            && (self.current_page.idx_cell > self.current_page.n_cells
+                || self.current_page.n_cells == 0)
+        {
+            return Ok(());
+        }
+
+        if !self.current_page.is_leaf {
+            // An index interior page stops on its cell. The previous cell is
+            // the last cell in the child on its left.
+            assert!(self.current_page.page_type.is_index());
+            return self.move_to_right_most();
+        } else if self.current_page.idx_cell > 0 {
+            self.current_page.idx_cell -= 1;
+            return Ok(());
+        }
+
+        loop {
+            if !self.back_to_parent()? {
+                // traversing completed.
+                self.current_page.idx_cell = self.current_page.n_cells + 1;
+                return Ok(());
+            }
+            if self.current_page.idx_cell > 0 {
+                break;
+            }
+        }
+        self.current_page.idx_cell -= 1;
+        if self.current_page.page_type.is_table() {
+            // The interior cell of a table page is not an entry. The previous
+            // cell is the last cell in the child of the interior cell.
+            self.move_to_right_most()?;
+        }
+        Ok(())
+    }
+
+    /// Returns the rowid of the current table cell without moving to the next
+    /// cell.
+    ///
+    /// [Self::table_move_to()] may stop at the end of a leaf page when the key
+    /// is greater than all keys in the leaf. In that case the cursor moves to
+    /// the first cell of the next leaf page first. Returns `None` if the cursor
+    /// is completed.
+    pub fn peek_rowid(&mut self) -> anyhow::Result<Option<i64>> {
+        if !self.initialized {
+            bail!("cursor is not initialized");
+        }
+        if !self.current_page.page_type.is_table() {
+            bail!("not a table page");
+        }
+        if self.current_page.is_leaf && self.current_page.idx_cell == self.current_page.n_cells {
+            self.move_to_next_leaf()?;
+        }
+        if self.current_page.idx_cell >= self.current_page.n_cells {
+            return Ok(None);
+        }
+        assert!(self.current_page.is_leaf);
+        let buffer = self.current_page.mem.buffer();
+        let cell_key_parser = TableCellKeyParser::new(&self.current_page.mem, &buffer);
+        let key = cell_key_parser
+            .get_cell_key(self.current_page.idx_cell)
+            .map_err(|e| anyhow::anyhow!("parse table cell key: {:?}", e))?;
+        Ok(Some(key))
+    }
+
+    /// Count the entries of the btree without parsing any cell payload.
+    ///
+    /// For a table btree this is the total number of cells in the leaf
+    /// pages. For an index btree, cells in interior pages are entries as well.
+    ///
+    /// This moves the cursor to the root page and makes it uninitialized.
+    pub fn count_rows(&mut self) -> anyhow::Result<u64> {
+        self.move_to_root()?;
+        self.initialized = false;
+        let is_table = self.current_page.page_type.is_table();
+        let mut count = 0;
+        let mut child_page_ids = Vec::new();
+        let mut page = None;
+        let mut depth = 0;
+        loop {
+            let current_page = page.as_ref().unwrap_or(&self.current_page);
+            if current_page.page_type.is_table() != is_table {
+                bail!("btree page type mismatch");
+            }
+            if current_page.is_leaf {
+                count += current_page.n_cells as u64;
+            } else {
+                if !is_table {
+                    count += current_page.n_cells as u64;
+                }
+                if depth >= BTREE_MAX_DEPTH {
+                    bail!("btree is too deep");
+                }
+                let buffer = current_page.mem.buffer();
+                for cell_idx in 0..current_page.n_cells {
+                    let page_id =
+                        parse_btree_interior_cell_page_id(&current_page.mem, &buffer, cell_idx)
+                            .map_err(|e| {
+                                anyhow::anyhow!("get btree interior cell page id: {:?}", e)
+                            })?;
+                    child_page_ids.push((page_id, depth + 1));
+                }
+                let page_header = BtreePageHeader::from_page(&current_page.mem, &buffer);
+                child_page_ids.push((page_header.right_page_id(), depth + 1));
+            }
+            let Some((page_id, child_depth)) = child_page_ids.pop() else {
+                break;
+            };
+            page = Some(CursorPage::new(self.pager.get_page(page_id)?)?);
+            depth = child_depth;
+        }
+        Ok(count)
+    }
+
+    /// The number of cells in the current page.
+    pub fn current_page_cell_count(&self) -> u16 {
+        self.current_page.n_cells
+    }
+
+    /// The index of the current cell in the current page.
+    ///
+    /// This is the same as [Self::current_page_cell_count()] if the cursor
+    /// passed the last cell.
+    pub fn current_cell_index(&self) -> u16 {
+        self.current_page.idx_cell.min(self.current_page.n_cells)
+    }
+
     pub fn get_table_payload<'a>(
         &'a self,
     ) -> anyhow::Result<Option<(i64, BtreePayload<'a, 'pager>)>> {
@@ -392,6 +681,7 @@ impl<'ctx, 'pager> BtreeCursor<'ctx, 'pager> {
             key,
             BtreePayload {
                 pager: self.pager,
+                btree_ctx: self.btree_ctx,
                 local_payload_buffer: buffer,
                 payload_info,
             },
@@ -416,6 +706,7 @@ impl<'ctx, 'pager> BtreeCursor<'ctx, 'pager> {
             .map_err(|e| anyhow::anyhow!("parse btree leaf index cell: {:?}", e))?;
         Ok(Some(BtreePayload {
             pager: self.pager,
+            btree_ctx: self.btree_ctx,
             local_payload_buffer: buffer,
             payload_info,
         }))
@@ -444,7 +735,6 @@ impl<'ctx, 'pager> BtreeCursor<'ctx, 'pager> {
                 return Ok(false);
             }
         };
-        drop(buffer);
         self.move_to_child(page_id)?;
         self.current_page.idx_cell = 0;
         loop {
@@ -454,12 +744,58 @@ impl<'ctx, 'pager> BtreeCursor<'ctx, 'pager> {
             let buffer = self.current_page.mem.buffer();
             let page_id = parse_btree_interior_cell_page_id(&self.current_page.mem, &buffer, 0)
                 .map_err(|e| anyhow::anyhow!("get btree interior cell page id: {:?}", e))?;
-            drop(buffer);
             self.move_to_child(page_id)?;
         }
         Ok(true)
     }
 
+    /// Move to the right most cell in the child pointed by the current cell and
+    /// its grand child page.
+    ///
+    /// The cursor must points to a interior page.
+    fn move_to_right_most(&mut self) -> anyhow::Result<()> {
+        assert!(!self.current_page.is_leaf);
+        loop {
+            let buffer = self.current_page.mem.buffer();
+            let page_id = if self.current_page.idx_cell < self.current_page.n_cells {
+                parse_btree_interior_cell_page_id(
+                    &self.current_page.mem,
+                    &buffer,
+                    self.current_page.idx_cell,
+                )
+                .map_err(|e| anyhow::anyhow!("get btree interior cell page id: {:?}", e))?
+            } else {
+                let page_header = BtreePageHeader::from_page(&self.current_page.mem, &buffer);
+                page_header.right_page_id()
+            };
+            self.move_to_child(page_id)?;
+            if self.current_page.is_leaf {
+                self.current_page.idx_cell = self.current_page.n_cells.saturating_sub(1);
+                return Ok(());
+            }
+            self.current_page.idx_cell = self.current_page.n_cells;
+        }
+    }
+
+    /// Move from the end of a table leaf page to the first cell of the next
+    /// leaf page.
+    ///
+    /// If there is no next leaf page, the cursor is completed.
+    fn move_to_next_leaf(&mut self) -> anyhow::Result<()> {
+        loop {
+            if !self.back_to_parent()? {
+                // traversing completed.
+                self.current_page.idx_cell += 1;
+                break;
+            }
+            self.current_page.idx_cell += 1;
+            if self.move_to_left_most()? {
+                break;
+            }
+        }
+        Ok(())
+    }
+
     fn move_to_root(&mut self) -> anyhow::Result<()> {
         if !self.parent_pages.is_empty() {
             self.parent_pages.truncate(1);
@@ -469,8 +805,14 @@ impl<'ctx, 'pager> BtreeCursor<'ctx, 'pager> {
     }
 
     fn move_to_child(&mut self, page_id: PageId) -> anyhow::Result<()> {
+        if self.parent_pages.len() >= BTREE_MAX_DEPTH {
+            bail!("btree is too deep");
+        }
         let mem = self.pager.get_page(page_id)?;
-        let mut page = CursorPage::new(mem);
+        let mut page = CursorPage::new(mem)?;
+        if page.page_type.is_table() != self.current_page.page_type.is_table() {
+            bail!("child page type does not match its parent: {}", page_id);
+        }
         std::mem::swap(&mut self.current_page, &mut page);
         self.parent_pages.push(page);
         Ok(())
@@ -485,87 +827,532 @@ impl<'ctx, 'pager> BtreeCursor<'ctx, 'pager> {
     }
 }
 
-#[cfg(test)]
-mod tests {
-    use super::*;
+/// A row of a table btree which owns its values.
+#[derive(Debug, PartialEq)]
+pub struct TableRow {
+    pub rowid: i64,
+    pub values: Vec<Value<'static>>,
+}
 
-    use crate::record::Record;
-    use crate::test_utils::*;
-    use crate::value::Collation;
-    use crate::value::Value;
+/// Iterator over all the rows in a table btree in rowid order.
+pub struct TableRows<'ctx, 'pager> {
+    pager: &'pager Pager,
+    cursor: BtreeCursor<'ctx, 'pager>,
+    text_encoding: TextEncoding,
+    rowid_alias_column: Option<usize>,
+    /// The values of the columns missing in short records.
+    default_values: Vec<Option<Value<'static>>>,
+    type_affinities: Vec<TypeAffinity>,
+    /// The columns filled by [Self::next_projected()].
+    projector: Option<RecordProjector>,
+    /// Whether the cursor points to the row to return next instead of the row
+    /// returned last.
+    is_on_next_row: bool,
+    completed: bool,
+}
 
-    #[test]
-    fn test_btree_cursor_single_table_page() {
-        let file = create_sqlite_database(&[
-            "CREATE TABLE example(col);",
-            "INSERT INTO example(col) VALUES (0);",
-            "INSERT INTO example(col) VALUES (1);",
-            "INSERT INTO example(col) VALUES (2);",
-        ]);
-        let pager = create_pager(file.as_file().try_clone().unwrap()).unwrap();
-        let bctx = load_btree_context(file.as_file()).unwrap();
-        let page_id = find_table_page_id("example", file.path());
+impl<'ctx, 'pager> TableRows<'ctx, 'pager> {
+    pub fn new(
+        root_page_id: PageId,
+        pager: &'pager Pager,
+        btree_ctx: &'ctx BtreeContext,
+        text_encoding: TextEncoding,
+        rowid_alias_column: Option<usize>,
+        default_values: Vec<Option<Value<'static>>>,
+        type_affinities: Vec<TypeAffinity>,
+    ) -> anyhow::Result<Self> {
+        pager.check_unchanged()?;
+        let mut cursor = BtreeCursor::new(root_page_id, pager, btree_ctx)?;
+        cursor.move_to_first()?;
+        Ok(Self {
+            pager,
+            cursor,
+            text_encoding,
+            rowid_alias_column,
+            default_values,
+            type_affinities,
+            projector: None,
+            is_on_next_row: true,
+            completed: false,
+        })
+    }
 
-        let mut cursor = BtreeCursor::new(page_id, &pager, &bctx).unwrap();
+    /// Set the columns filled by [Self::next_projected()].
+    ///
+    /// The columns are the indexes of the columns in the table. A column beyond
+    /// the table is NULL.
+    pub fn set_projection(&mut self, columns: Vec<usize>) {
+        self.projector = Some(RecordProjector::new(columns));
+    }
 
-        cursor.move_to_first().unwrap();
-        let payload = cursor.get_table_payload().unwrap();
-        assert!(payload.is_some());
-        let (key, payload) = payload.unwrap();
-        assert_eq!(key, 1);
-        assert_eq!(payload.buf(), &[2, 8]);
-        assert_eq!(payload.size(), payload.buf().len() as i32);
-        assert!(cursor.get_index_payload().is_err());
-        drop(payload);
+    /// Fill the values of the projected columns of the next row.
+    ///
+    /// Unlike [Iterator::next()], this reuses the buffer of values and parses
+    /// the record only up to the last projected column. The values are the
+    /// same as the values of [TableRow]. The length of values must be the same
+    /// as the number of the columns given to [Self::set_projection()].
+    ///
+    /// Returns the rowid of the row. Returns None if there is no next row.
+    pub fn next_projected(&mut self, values: &mut [Value<'static>]) -> Option<anyhow::Result<i64>> {
+        let Some(mut projector) = self.projector.take() else {
+            return Some(Err(anyhow::anyhow!("projection is not set")));
+        };
+        let result = self.next_row(None, |rows, rowid, payload| {
+            projector.project(payload, values, |i, value| {
+                rows.column_value(rowid, i, value.map(Value::into_owned))
+            })?;
+            Ok(rowid)
+        });
+        self.projector = Some(projector);
+        result
+    }
 
-        cursor.next().unwrap();
-        let payload = cursor.get_table_payload().unwrap();
-        assert!(payload.is_some());
-        let (key, payload) = payload.unwrap();
-        assert_eq!(key, 2);
-        assert_eq!(payload.buf(), &[2, 9]);
-        assert_eq!(payload.size(), payload.buf().len() as i32);
-        assert!(cursor.get_index_payload().is_err());
-        drop(payload);
+    /// Returns the rowid and the serial types of the columns of the next row.
+    ///
+    /// This parses only the record header without decoding any value, e.g. to
+    /// find the size of a large text or blob before loading it. The types are
+    /// of the record as stored, so the INTEGER PRIMARY KEY column is usually
+    /// NULL and the columns missing in the record are not included.
+    ///
+    /// Returns None if there is no next row.
+    pub fn next_serial_types(&mut self) -> Option<anyhow::Result<(i64, Vec<SerialTypeKind>)>> {
+        self.next_row(None, |_, rowid, payload| {
+            let mut record = Record::parse(payload)?;
+            let serial_types = record.serial_types()?.collect();
+            Ok((rowid, serial_types))
+        })
+    }
 
-        cursor.next().unwrap();
-        let payload = cursor.get_table_payload().unwrap();
-        assert!(payload.is_some());
-        let (key, payload) = payload.unwrap();
-        assert_eq!(key, 3);
-        assert_eq!(payload.buf(), &[2, 1, 2]);
-        assert_eq!(payload.size(), payload.buf().len() as i32);
-        assert!(cursor.get_index_payload().is_err());
-        drop(payload);
+    /// Call the function with the rowid and the record payload of the next row.
+    ///
+    /// The payload is the record as stored in the btree including the overflow
+    /// pages, which are loaded only when read. It is available only in the
+    /// function because the cursor moves on.
+    ///
+    /// Returns None if there is no next row.
+    pub fn next_payload<T>(
+        &mut self,
+        f: impl FnOnce(i64, &BtreePayload) -> anyhow::Result<T>,
+    ) -> Option<anyhow::Result<T>> {
+        self.next_row(None, |_, rowid, payload| f(rowid, payload))
+    }
 
-        cursor.next().unwrap();
-        assert!(cursor.get_table_payload().unwrap().is_none());
-        assert!(cursor.get_index_payload().is_err());
+    /// The number of rows in the current leaf page.
+    ///
+    /// Combined with [Self::current_cell_index()], this estimates the position
+    /// of a long scan without reading the page again.
+    pub fn current_page_cell_count(&self) -> u16 {
+        self.cursor.current_page_cell_count()
     }
 
-    #[test]
-    fn test_btree_cursor_single_index_page() {
-        let file = create_sqlite_database(&[
-            "CREATE TABLE example(col);",
-            "CREATE INDEX index1 ON example(col);",
-            "INSERT INTO example(col) VALUES (1);",
-            "INSERT INTO example(col) VALUES (0);",
-            "INSERT INTO example(col) VALUES (2);",
-        ]);
-        let pager = create_pager(file.as_file().try_clone().unwrap()).unwrap();
-        let bctx = load_btree_context(file.as_file()).unwrap();
-        let page_id = find_index_page_id("index1", file.path());
+    /// The index of the current row in the current leaf page.
+    ///
+    /// The current row is the row returned last, or the row to return next
+    /// before any row is returned after [Self::move_to()] or the creation.
+    pub fn current_cell_index(&self) -> u16 {
+        self.cursor.current_cell_index()
+    }
 
-        let mut cursor = BtreeCursor::new(page_id, &pager, &bctx).unwrap();
+    /// Move to the row with the rowid, or the next row if it does not exist.
+    ///
+    /// The iteration restarts from the row.
+    pub fn move_to(&mut self, rowid: i64) -> anyhow::Result<()> {
+        self.cursor.table_move_to(rowid)?;
+        self.is_on_next_row = true;
+        self.completed = false;
+        Ok(())
+    }
 
-        cursor.move_to_first().unwrap();
-        let payload = cursor.get_index_payload().unwrap();
-        assert!(payload.is_some());
-        let payload = payload.unwrap();
-        assert_eq!(payload.buf(), &[3, 8, 1, 2]);
-        assert_eq!(payload.size(), payload.buf().len() as i32);
+    /// Move to the end of the table.
+    ///
+    /// [Self::prev()] returns the last row, i.e. the row with the max rowid,
+    /// afterwards. The iteration with [Iterator::next()] completes.
+    pub fn move_to_last(&mut self) -> anyhow::Result<()> {
+        self.cursor.move_to_last()?;
+        self.is_on_next_row = false;
+        self.completed = false;
+        Ok(())
+    }
+
+    /// Returns the rowid of the row to return next without consuming the row.
+    ///
+    /// Returns None if there is no next row.
+    pub fn peek_rowid(&mut self) -> anyhow::Result<Option<i64>> {
+        if self.completed {
+            return Ok(None);
+        }
+        if !self.is_on_next_row {
+            self.cursor.next()?;
+            self.is_on_next_row = true;
+        }
+        self.cursor.peek_rowid()
+    }
+
+    /// Returns the row before the row to return next.
+    ///
+    /// Combined with [Self::move_to_last()], this iterates the rows in
+    /// descending rowid order. Returns None if there is no previous row or the
+    /// iteration completed.
+    pub fn prev(&mut self) -> Option<anyhow::Result<TableRow>> {
+        if self.completed {
+            return None;
+        }
+        if self.is_on_next_row {
+            if let Err(e) = self.cursor.prev() {
+                self.completed = true;
+                return Some(Err(e));
+            }
+        }
+        // The next iteration returns the row again.
+        self.is_on_next_row = true;
+        let result = match self.cursor.get_table_payload() {
+            Ok(Some((rowid, payload))) => self.read_row(rowid, &payload),
+            Ok(None) => {
+                self.completed = true;
+                return self.pager.check_unchanged().err().map(Err);
+            }
+            Err(e) => Err(e),
+        };
+        if result.is_err() {
+            self.completed = true;
+        }
+        Some(result)
+    }
+
+    /// Returns the next row only if its rowid is not greater than max_rowid.
+    ///
+    /// Otherwise returns None without consuming the row. Combined with
+    /// [Self::move_to()], this iterates the rows in a range of rowids.
+    pub fn next_until(&mut self, max_rowid: i64) -> Option<anyhow::Result<TableRow>> {
+        self.next_row(Some(max_rowid), Self::read_row)
+    }
+
+    fn next_row<T>(
+        &mut self,
+        max_rowid: Option<i64>,
+        read: impl FnOnce(&Self, i64, &BtreePayload) -> anyhow::Result<T>,
+    ) -> Option<anyhow::Result<T>> {
+        if self.completed {
+            return None;
+        }
+        let result = match self.move_next(max_rowid) {
+            Ok(true) => match self.cursor.get_table_payload() {
+                Ok(Some((rowid, payload))) => read(self, rowid, &payload).map(Some),
+                Ok(None) => Ok(None),
+                Err(e) => Err(e),
+            },
+            Ok(false) => Ok(None),
+            Err(e) => Err(e),
+        };
+        match result {
+            Ok(Some(row)) => Some(Ok(row)),
+            Ok(None) => {
+                // The iteration can continue after the row exceeding max_rowid.
+                self.completed = !self.is_on_next_row;
+                // The rows may be from pages of different states.
+                self.pager.check_unchanged().err().map(Err)
+            }
+            Err(e) => {
+                self.completed = true;
+                Some(Err(e))
+            }
+        }
+    }
+
+    /// Move the cursor to the next row.
+    ///
+    /// Returns false if there is no next row or its rowid exceeds max_rowid.
+    fn move_next(&mut self, max_rowid: Option<i64>) -> anyhow::Result<bool> {
+        if self.is_on_next_row {
+            self.is_on_next_row = false;
+        } else {
+            self.cursor.next()?;
+        }
+        // table_move_to() may stop at the end of a leaf page before the next
+        // row.
+        let Some(rowid) = self.cursor.peek_rowid()? else {
+            return Ok(false);
+        };
+        if max_rowid.is_some_and(|max_rowid| rowid > max_rowid) {
+            self.is_on_next_row = true;
+            return Ok(false);
+        }
+        Ok(true)
+    }
+
+    fn read_row(&self, rowid: i64, payload: &BtreePayload) -> anyhow::Result<TableRow> {
+        // Values are copied out of the payload because the payload borrows the
+        // cursor which moves on the next iteration.
+        let mut record = Record::parse(payload)?;
+        let n_record_columns = record.len()?;
+        let n_columns = n_record_columns.max(self.default_values.len());
+        let mut values = Vec::with_capacity(n_columns);
+        for i in 0..n_columns {
+            let value = if i < n_record_columns {
+                Some(record.get_owned(i)?)
+            } else {
+                None
+            };
+            values.push(self.column_value(rowid, i, value)?);
+        }
+        Ok(TableRow { rowid, values })
+    }
+
+    /// Returns the value of the column in the row.
+    ///
+    /// The value is None if the column is missing in the record.
+    fn column_value(
+        &self,
+        rowid: i64,
+        i: usize,
+        value: Option<Value<'static>>,
+    ) -> anyhow::Result<Value<'static>> {
+        if self.rowid_alias_column == Some(i) {
+            return Ok(Value::Integer(rowid));
+        }
+        let value = match value {
+            Some(value) => value.decode_text(self.text_encoding),
+            // Columns added by ALTER TABLE ADD COLUMN are missing in the records
+            // of the rows inserted before.
+            None => match self.default_values.get(i) {
+                Some(default_value) => default_value
+                    .clone()
+                    .context("unsupported default value of missing column")?,
+                None => Value::Null,
+            },
+        };
+        // Whole numbers in REAL columns may be stored as integers.
+        if self.type_affinities.get(i) == Some(&TypeAffinity::Real) {
+            Ok(value.apply_real_affinity())
+        } else {
+            Ok(value)
+        }
+    }
+}
+
+impl Iterator for TableRows<'_, '_> {
+    type Item = anyhow::Result<TableRow>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.next_row(None, Self::read_row)
+    }
+}
+
+/// Iterator over all the entries in an index btree in index order.
+///
+/// Each entry is the values of the indexed columns and the rowid of the row
+/// in the table, which is the last value of the index record.
+pub struct IndexRows<'ctx, 'pager> {
+    pager: &'pager Pager,
+    cursor: BtreeCursor<'ctx, 'pager>,
+    text_encoding: TextEncoding,
+    /// The type affinities of the indexed columns.
+    type_affinities: Vec<TypeAffinity>,
+    /// Whether the cursor points to the entry to return next instead of the
+    /// entry returned last.
+    is_on_next_row: bool,
+    completed: bool,
+}
+
+impl<'ctx, 'pager> IndexRows<'ctx, 'pager> {
+    pub fn new(
+        root_page_id: PageId,
+        pager: &'pager Pager,
+        btree_ctx: &'ctx BtreeContext,
+        text_encoding: TextEncoding,
+        type_affinities: Vec<TypeAffinity>,
+    ) -> anyhow::Result<Self> {
+        pager.check_unchanged()?;
+        let mut cursor = BtreeCursor::new(root_page_id, pager, btree_ctx)?;
+        cursor.move_to_first()?;
+        Ok(Self {
+            pager,
+            cursor,
+            text_encoding,
+            type_affinities,
+            is_on_next_row: true,
+            completed: false,
+        })
+    }
+
+    fn move_next(&mut self) -> anyhow::Result<Option<(Vec<Value<'static>>, i64)>> {
+        if self.is_on_next_row {
+            self.is_on_next_row = false;
+        } else {
+            self.cursor.next()?;
+        }
+        let Some(payload) = self.cursor.get_index_payload()? else {
+            return Ok(None);
+        };
+        let mut record = Record::parse(&payload)?;
+        let Some(n_keys) = record.len()?.checked_sub(1) else {
+            bail!("empty index record");
+        };
+        let Value::Integer(rowid) = record.get(n_keys)? else {
+            bail!("the last value of index record is not a rowid");
+        };
+        let mut keys = Vec::with_capacity(n_keys);
+        for i in 0..n_keys {
+            let value = record.get_owned(i)?.decode_text(self.text_encoding);
+            // Whole numbers in REAL columns may be stored as integers.
+            if self.type_affinities.get(i) == Some(&TypeAffinity::Real) {
+                keys.push(value.apply_real_affinity());
+            } else {
+                keys.push(value);
+            }
+        }
+        Ok(Some((keys, rowid)))
+    }
+}
+
+impl Iterator for IndexRows<'_, '_> {
+    type Item = anyhow::Result<(Vec<Value<'static>>, i64)>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.completed {
+            return None;
+        }
+        match self.move_next() {
+            Ok(Some(entry)) => Some(Ok(entry)),
+            Ok(None) => {
+                self.completed = true;
+                // The entries may be from pages of different states.
+                self.pager.check_unchanged().err().map(Err)
+            }
+            Err(e) => {
+                self.completed = true;
+                Some(Err(e))
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use std::sync::Arc;
+
+    use crate::test_utils::*;
+    use crate::value::Collation;
+    use crate::DATABASE_HEADER_SIZE;
+
+    #[test]
+    fn test_btree_cursor_malformed_pages() {
+        const PAGESIZE: usize = 512;
+        const INTERIOR_TABLE: u8 = 0x05;
+        const LEAF_INDEX: u8 = 0x0a;
+        let mut content = [0_u8; 4 * PAGESIZE];
+        // page 1: an interior table page pointing to itself.
+        content[DATABASE_HEADER_SIZE] = INTERIOR_TABLE;
+        content[DATABASE_HEADER_SIZE + 8..DATABASE_HEADER_SIZE + 12]
+            .copy_from_slice(&1_u32.to_be_bytes());
+        // page 2: an interior table page pointing to an index page.
+        content[PAGESIZE] = INTERIOR_TABLE;
+        content[PAGESIZE + 8..PAGESIZE + 12].copy_from_slice(&3_u32.to_be_bytes());
+        // page 3: a leaf index page.
+        content[2 * PAGESIZE] = LEAF_INDEX;
+        // page 4: an invalid page type.
+        content[3 * PAGESIZE] = 0x01;
+        let pager = create_empty_pager(&content, PAGESIZE);
+        let bctx = BtreeContext::new(PAGESIZE as i32);
+
+        let mut cursor = BtreeCursor::new(1, &pager, &bctx).unwrap();
+        assert!(cursor.move_to_first().is_err());
+        assert_eq!(
+            cursor.table_move_to(1).unwrap_err().to_string(),
+            "btree is too deep"
+        );
+        assert_eq!(
+            cursor.count_rows().unwrap_err().to_string(),
+            "btree is too deep"
+        );
+        // The parent pages never grow beyond the reserved capacity.
+        assert_eq!(cursor.parent_pages.capacity(), BTREE_MAX_DEPTH);
+
+        let mut cursor = BtreeCursor::new(2, &pager, &bctx).unwrap();
+        assert!(cursor.move_to_first().is_err());
+        assert!(cursor.table_move_to(1).is_err());
+        assert!(cursor.count_rows().is_err());
+
+        assert!(BtreeCursor::new(4, &pager, &bctx).is_err());
+    }
+
+    #[test]
+    fn test_btree_cursor_single_table_page() {
+        let file = create_sqlite_database(&[
+            "CREATE TABLE example(col);",
+            "INSERT INTO example(col) VALUES (0);",
+            "INSERT INTO example(col) VALUES (1);",
+            "INSERT INTO example(col) VALUES (2);",
+        ]);
+        let pager = create_pager(file.as_file().try_clone().unwrap()).unwrap();
+        let bctx = load_btree_context(file.as_file()).unwrap();
+        let page_id = find_table_page_id("example", file.path());
+
+        let mut cursor = BtreeCursor::new(page_id, &pager, &bctx).unwrap();
+
+        cursor.move_to_first().unwrap();
+        assert_eq!(cursor.current_page_cell_count(), 3);
+        assert_eq!(cursor.current_cell_index(), 0);
+        let payload = cursor.get_table_payload().unwrap();
+        assert!(payload.is_some());
+        let (key, payload) = payload.unwrap();
+        assert_eq!(key, 1);
+        assert_eq!(payload.buf(), &[2, 8]);
+        assert_eq!(payload.size(), payload.buf().len() as i32);
+        assert!(cursor.get_index_payload().is_err());
+
+        cursor.next().unwrap();
+        let payload = cursor.get_table_payload().unwrap();
+        assert!(payload.is_some());
+        let (key, payload) = payload.unwrap();
+        assert_eq!(key, 2);
+        assert_eq!(payload.buf(), &[2, 9]);
+        assert_eq!(payload.size(), payload.buf().len() as i32);
+        assert!(cursor.get_index_payload().is_err());
+
+        cursor.next().unwrap();
+        assert_eq!(cursor.current_cell_index(), 2);
+        let payload = cursor.get_table_payload().unwrap();
+        assert!(payload.is_some());
+        let (key, payload) = payload.unwrap();
+        assert_eq!(key, 3);
+        assert_eq!(payload.buf(), &[2, 1, 2]);
+        assert_eq!(payload.size(), payload.buf().len() as i32);
+        assert!(cursor.get_index_payload().is_err());
+
+        cursor.next().unwrap();
+        assert!(cursor.get_table_payload().unwrap().is_none());
+        assert!(cursor.get_index_payload().is_err());
+        assert_eq!(cursor.current_page_cell_count(), 3);
+        assert_eq!(cursor.current_cell_index(), 3);
+        cursor.next().unwrap();
+        assert_eq!(cursor.current_cell_index(), 3);
+    }
+
+    #[test]
+    fn test_btree_cursor_single_index_page() {
+        let file = create_sqlite_database(&[
+            "CREATE TABLE example(col);",
+            "CREATE INDEX index1 ON example(col);",
+            "INSERT INTO example(col) VALUES (1);",
+            "INSERT INTO example(col) VALUES (0);",
+            "INSERT INTO example(col) VALUES (2);",
+        ]);
+        let pager = create_pager(file.as_file().try_clone().unwrap()).unwrap();
+        let bctx = load_btree_context(file.as_file()).unwrap();
+        let page_id = find_index_page_id("index1", file.path());
+
+        let mut cursor = BtreeCursor::new(page_id, &pager, &bctx).unwrap();
+
+        cursor.move_to_first().unwrap();
+        let payload = cursor.get_index_payload().unwrap();
+        assert!(payload.is_some());
+        let payload = payload.unwrap();
+        assert_eq!(payload.buf(), &[3, 8, 1, 2]);
+        assert_eq!(payload.size(), payload.buf().len() as i32);
         assert!(cursor.get_table_payload().is_err());
-        drop(payload);
 
         cursor.next().unwrap();
         let payload = cursor.get_index_payload().unwrap();
@@ -574,7 +1361,6 @@ mod tests {
         assert_eq!(payload.buf(), &[3, 9, 9]);
         assert_eq!(payload.size(), payload.buf().len() as i32);
         assert!(cursor.get_table_payload().is_err());
-        drop(payload);
 
         cursor.next().unwrap();
         let payload = cursor.get_index_payload().unwrap();
@@ -583,7 +1369,6 @@ mod tests {
         assert_eq!(payload.buf(), &[3, 1, 1, 2, 3]);
         assert_eq!(payload.size(), payload.buf().len() as i32);
         assert!(cursor.get_table_payload().is_err());
-        drop(payload);
 
         cursor.next().unwrap();
         assert!(cursor.get_index_payload().unwrap().is_none());
@@ -702,7 +1487,6 @@ mod tests {
             assert_eq!(payload.size(), payload.buf().len() as i32);
             let mut table_record = Record::parse(&payload).unwrap();
             assert_eq!(table_record.get(0).unwrap(), Value::Integer(i));
-            drop(payload);
             table_cursor.next().unwrap();
 
             let payload = index1_cursor.get_index_payload().unwrap();
@@ -711,7 +1495,6 @@ mod tests {
             assert_eq!(index_record.get(1).unwrap(), Value::Integer(i + 1));
             assert!(payload.size() > BUFFER_SIZE as i32, "{}", i);
             assert_eq!(payload.size(), payload.buf().len() as i32);
-            drop(payload);
             index1_cursor.next().unwrap();
 
             let payload = index2_cursor.get_index_payload().unwrap();
@@ -720,7 +1503,6 @@ mod tests {
             assert_eq!(index_record.get(0).unwrap(), Value::Integer(i));
             assert_eq!(index_record.get(1).unwrap(), Value::Integer(i + 1));
             assert_eq!(payload.size(), payload.buf().len() as i32);
-            drop(payload);
             index2_cursor.next().unwrap();
         }
         for i in 4000..5000 {
@@ -731,7 +1513,6 @@ mod tests {
             let col_buf = (i as u16).to_be_bytes();
             assert_eq!(payload.buf(), &[3, 2, 14, col_buf[0], col_buf[1], 0xff]);
             assert_eq!(payload.size(), payload.buf().len() as i32);
-            drop(payload);
             table_cursor.next().unwrap();
 
             let payload = index1_cursor.get_index_payload().unwrap();
@@ -741,7 +1522,6 @@ mod tests {
             let rowid_buf = (i as u16 + 1).to_be_bytes();
             assert_eq!(payload.buf(), &[3, 14, 2, 0xff, rowid_buf[0], rowid_buf[1]]);
             assert_eq!(payload.size(), payload.buf().len() as i32);
-            drop(payload);
             index1_cursor.next().unwrap();
 
             let payload = index2_cursor.get_index_payload().unwrap();
@@ -750,7 +1530,6 @@ mod tests {
             assert_eq!(index_record.get(0).unwrap(), Value::Integer(i));
             assert_eq!(index_record.get(1).unwrap(), Value::Integer(i + 1));
             assert_eq!(payload.size(), payload.buf().len() as i32);
-            drop(payload);
             index2_cursor.next().unwrap();
         }
 
@@ -772,7 +1551,6 @@ mod tests {
         assert_eq!(index_record.get(0).unwrap(), Value::Integer(2000));
         assert_eq!(index_record.get(1).unwrap(), Value::Integer(2001));
         assert_eq!(payload.size(), payload.buf().len() as i32);
-        drop(payload);
 
         index2_cursor
             .index_move_to(&[
@@ -786,7 +1564,6 @@ mod tests {
         assert_eq!(index_record.get(0).unwrap(), Value::Integer(3000));
         assert_eq!(index_record.get(1).unwrap(), Value::Integer(3001));
         assert_eq!(payload.size(), payload.buf().len() as i32);
-        drop(payload);
 
         index2_cursor
             .index_move_to(&[
@@ -800,7 +1577,564 @@ mod tests {
         assert_eq!(index_record.get(0).unwrap(), Value::Integer(3001));
         assert_eq!(index_record.get(1).unwrap(), Value::Integer(3002));
         assert_eq!(payload.size(), payload.buf().len() as i32);
-        drop(payload);
+    }
+
+    #[test]
+    fn test_overflow_payload_with_reserved_bytes() {
+        let buf = (0..20000).map(|_| rand::random::<u8>()).collect::<Vec<_>>();
+        let query = format!(
+            "INSERT INTO example(col) VALUES (X'{}');",
+            buffer_to_hex(&buf)
+        );
+        let file =
+            create_sqlite_database_with_reserved_bytes(8, &["CREATE TABLE example(col);", &query]);
+        let pager = create_pager(file.as_file().try_clone().unwrap()).unwrap();
+        let bctx = load_btree_context(file.as_file()).unwrap();
+        assert_eq!(bctx.usable_size(), 4096 - 8);
+        let table_page_id = find_table_page_id("example", file.path());
+
+        let mut cursor = BtreeCursor::new(table_page_id, &pager, &bctx).unwrap();
+        cursor.move_to_first().unwrap();
+        let (_, payload) = cursor.get_table_payload().unwrap().unwrap();
+        assert_eq!(payload.size(), 20004);
+        assert_eq!(payload.load_vec(4, 20000).unwrap(), buf);
+        let mut payload_buf = Vec::new();
+        payload.write_to(4, 20000, &mut payload_buf).unwrap();
+        assert_eq!(payload_buf, buf);
+    }
+
+    #[test]
+    fn test_btree_cursor_three_level_pages() {
+        let mut inserts = Vec::new();
+        for i in 0..3000 {
+            inserts.push(format!(
+                "INSERT INTO example(col,buf) VALUES ({},zeroblob(100));",
+                i
+            ));
+        }
+        let mut queries = vec![
+            "PRAGMA page_size = 512;",
+            "CREATE TABLE example(col,buf);",
+            "CREATE INDEX index1 ON example(col);",
+        ];
+        queries.extend(inserts.iter().map(|s| s.as_str()));
+        let file = create_sqlite_database(&queries);
+        let pager = create_pager(file.as_file().try_clone().unwrap()).unwrap();
+        let bctx = load_btree_context(file.as_file()).unwrap();
+        let table_page_id = find_table_page_id("example", file.path());
+        let index_page_id = find_index_page_id("index1", file.path());
+
+        let mut table_cursor = BtreeCursor::new(table_page_id, &pager, &bctx).unwrap();
+        // The root page and the right-most interior page have multiple cells.
+        assert!(table_cursor.current_page_cell_count() > 1);
+        table_cursor.table_move_to(3000).unwrap();
+        assert_eq!(table_cursor.parent_pages.len(), 2);
+        assert!(table_cursor.parent_pages[1].n_cells > 1);
+        assert_eq!(
+            table_cursor.parent_pages[1].idx_cell,
+            table_cursor.parent_pages[1].n_cells
+        );
+
+        table_cursor.move_to_first().unwrap();
+        let mut max_depth = 0;
+        for i in 0..3000 {
+            max_depth = max_depth.max(table_cursor.parent_pages.len());
+            let (rowid, _) = table_cursor.get_table_payload().unwrap().unwrap();
+            assert_eq!(rowid, i + 1);
+            table_cursor.next().unwrap();
+        }
+        assert_eq!(max_depth, 2);
+        // No row is visited twice after the right-most leaf page.
+        for _ in 0..3 {
+            assert!(table_cursor.get_table_payload().unwrap().is_none());
+            table_cursor.next().unwrap();
+        }
+
+        let mut index_cursor = BtreeCursor::new(index_page_id, &pager, &bctx).unwrap();
+        index_cursor.move_to_first().unwrap();
+        let mut max_depth = 0;
+        for i in 0..3000 {
+            max_depth = max_depth.max(index_cursor.parent_pages.len());
+            let payload = index_cursor.get_index_payload().unwrap().unwrap();
+            let mut index_record = Record::parse(&payload).unwrap();
+            assert_eq!(index_record.get(0).unwrap(), Value::Integer(i));
+            assert_eq!(index_record.get(1).unwrap(), Value::Integer(i + 1));
+            index_cursor.next().unwrap();
+        }
+        assert_eq!(max_depth, 2);
+        for _ in 0..3 {
+            assert!(index_cursor.get_index_payload().unwrap().is_none());
+            index_cursor.next().unwrap();
+        }
+    }
+
+    #[test]
+    fn test_btree_cursor_move_to_first_again() {
+        let hex = buffer_to_hex(&[0; 500]);
+        let mut inserts = Vec::new();
+        for i in 0..1000 {
+            inserts.push(format!(
+                "INSERT INTO example(col,buf) VALUES ({},X'{}');",
+                i,
+                hex.as_str()
+            ));
+        }
+        let mut queries = vec![
+            "CREATE TABLE example(col,buf);",
+            "CREATE INDEX index1 ON example(col);",
+        ];
+        queries.extend(inserts.iter().map(|s| s.as_str()));
+        let file = create_sqlite_database(&queries);
+        let pager = create_pager(file.as_file().try_clone().unwrap()).unwrap();
+        let bctx = load_btree_context(file.as_file()).unwrap();
+        let table_page_id = find_table_page_id("example", file.path());
+        let index_page_id = find_index_page_id("index1", file.path());
+
+        let mut table_cursor = BtreeCursor::new(table_page_id, &pager, &bctx).unwrap();
+        let mut index_cursor = BtreeCursor::new(index_page_id, &pager, &bctx).unwrap();
+
+        // Rescan after a complete scan and after a seek in the middle.
+        for _ in 0..2 {
+            table_cursor.move_to_first().unwrap();
+            index_cursor.move_to_first().unwrap();
+            let mut n_leaf_pages = 0;
+            for i in 0..1000 {
+                if table_cursor.current_cell_index() == 0 {
+                    n_leaf_pages += 1;
+                }
+                assert!(table_cursor.current_cell_index() < table_cursor.current_page_cell_count());
+                let (rowid, _) = table_cursor.get_table_payload().unwrap().unwrap();
+                assert_eq!(rowid, i + 1);
+                table_cursor.next().unwrap();
+
+                let payload = index_cursor.get_index_payload().unwrap().unwrap();
+                let mut index_record = Record::parse(&payload).unwrap();
+                assert_eq!(index_record.get(0).unwrap(), Value::Integer(i));
+                index_cursor.next().unwrap();
+            }
+            assert!(table_cursor.get_table_payload().unwrap().is_none());
+            assert!(index_cursor.get_index_payload().unwrap().is_none());
+            assert!(n_leaf_pages > 1);
+        }
+
+        table_cursor.table_move_to(500).unwrap();
+        table_cursor.move_to_first().unwrap();
+        let (rowid, _) = table_cursor.get_table_payload().unwrap().unwrap();
+        assert_eq!(rowid, 1);
+
+        index_cursor
+            .index_move_to(&[ValueCmp::new(&Value::Integer(500), &Collation::Binary)])
+            .unwrap();
+        index_cursor.move_to_first().unwrap();
+        let payload = index_cursor.get_index_payload().unwrap().unwrap();
+        let mut index_record = Record::parse(&payload).unwrap();
+        assert_eq!(index_record.get(0).unwrap(), Value::Integer(0));
+    }
+
+    #[test]
+    fn test_btree_cursor_move_to_last() {
+        let mut inserts = Vec::new();
+        for i in 0..3000 {
+            inserts.push(format!(
+                "INSERT INTO example(col,buf) VALUES ({},zeroblob(100));",
+                i
+            ));
+        }
+        let mut queries = vec![
+            "PRAGMA page_size = 512;",
+            "CREATE TABLE example(col,buf);",
+            "CREATE INDEX index1 ON example(col);",
+            "CREATE TABLE empty(col);",
+            "CREATE INDEX index2 ON empty(col);",
+        ];
+        queries.extend(inserts.iter().map(|s| s.as_str()));
+        let file = create_sqlite_database(&queries);
+        let pager = create_pager(file.as_file().try_clone().unwrap()).unwrap();
+        let bctx = load_btree_context(file.as_file()).unwrap();
+        let table_page_id = find_table_page_id("example", file.path());
+        let index_page_id = find_index_page_id("index1", file.path());
+
+        let mut table_cursor = BtreeCursor::new(table_page_id, &pager, &bctx).unwrap();
+        let mut index_cursor = BtreeCursor::new(index_page_id, &pager, &bctx).unwrap();
+
+        // Move to the last cell after a seek in the middle as well.
+        table_cursor.table_move_to(500).unwrap();
+        index_cursor
+            .index_move_to(&[ValueCmp::new(&Value::Integer(500), &Collation::Binary)])
+            .unwrap();
+        for _ in 0..2 {
+            let payload = table_cursor.move_to_last().unwrap().unwrap();
+            let mut table_record = Record::parse(&payload).unwrap();
+            assert_eq!(table_record.get(0).unwrap(), Value::Integer(2999));
+            assert_eq!(table_cursor.parent_pages.len(), 2);
+            let (rowid, _) = table_cursor.get_table_payload().unwrap().unwrap();
+            assert_eq!(rowid, 3000);
+            for _ in 0..3 {
+                table_cursor.next().unwrap();
+                assert!(table_cursor.get_table_payload().unwrap().is_none());
+            }
+
+            let payload = index_cursor.move_to_last().unwrap().unwrap();
+            let mut index_record = Record::parse(&payload).unwrap();
+            assert_eq!(index_record.get(0).unwrap(), Value::Integer(2999));
+            assert_eq!(index_record.get(1).unwrap(), Value::Integer(3000));
+            for _ in 0..3 {
+                index_cursor.next().unwrap();
+                assert!(index_cursor.get_index_payload().unwrap().is_none());
+            }
+        }
+
+        // prev() iterates all the cells in reverse order from the last cell.
+        table_cursor.move_to_last().unwrap();
+        index_cursor.move_to_last().unwrap();
+        for i in (0..3000).rev() {
+            let (rowid, _) = table_cursor.get_table_payload().unwrap().unwrap();
+            assert_eq!(rowid, i + 1);
+            table_cursor.prev().unwrap();
+
+            let payload = index_cursor.get_index_payload().unwrap().unwrap();
+            let mut index_record = Record::parse(&payload).unwrap();
+            assert_eq!(index_record.get(0).unwrap(), Value::Integer(i));
+            index_cursor.prev().unwrap();
+        }
+        for _ in 0..3 {
+            assert!(table_cursor.get_table_payload().unwrap().is_none());
+            table_cursor.prev().unwrap();
+            assert!(index_cursor.get_index_payload().unwrap().is_none());
+            index_cursor.prev().unwrap();
+        }
+        table_cursor.next().unwrap();
+        assert!(table_cursor.get_table_payload().unwrap().is_none());
+        index_cursor.next().unwrap();
+        assert!(index_cursor.get_index_payload().unwrap().is_none());
+
+        // prev() and next() can be mixed.
+        table_cursor.table_move_to(1500).unwrap();
+        index_cursor
+            .index_move_to(&[ValueCmp::new(&Value::Integer(1500), &Collation::Binary)])
+            .unwrap();
+        for i in 0..1000 {
+            let (rowid, _) = table_cursor.get_table_payload().unwrap().unwrap();
+            assert_eq!(rowid, 1500 - i);
+            let payload = index_cursor.get_index_payload().unwrap().unwrap();
+            let mut index_record = Record::parse(&payload).unwrap();
+            assert_eq!(index_record.get(0).unwrap(), Value::Integer(1500 - i));
+            // Two steps back and one step forward.
+            table_cursor.prev().unwrap();
+            table_cursor.prev().unwrap();
+            table_cursor.next().unwrap();
+            index_cursor.prev().unwrap();
+            index_cursor.prev().unwrap();
+            index_cursor.next().unwrap();
+        }
+
+        table_cursor.move_to_first().unwrap();
+        let (rowid, _) = table_cursor.get_table_payload().unwrap().unwrap();
+        assert_eq!(rowid, 1);
+
+        let table_page_id = find_table_page_id("empty", file.path());
+        let index_page_id = find_index_page_id("index2", file.path());
+        let mut table_cursor = BtreeCursor::new(table_page_id, &pager, &bctx).unwrap();
+        assert!(table_cursor.move_to_last().unwrap().is_none());
+        table_cursor.next().unwrap();
+        assert!(table_cursor.get_table_payload().unwrap().is_none());
+        table_cursor.prev().unwrap();
+        assert!(table_cursor.get_table_payload().unwrap().is_none());
+        let mut index_cursor = BtreeCursor::new(index_page_id, &pager, &bctx).unwrap();
+        assert!(index_cursor.move_to_last().unwrap().is_none());
+        index_cursor.prev().unwrap();
+        assert!(index_cursor.get_index_payload().unwrap().is_none());
+
+        let mut table_cursor = BtreeCursor::new(table_page_id, &pager, &bctx).unwrap();
+        assert_eq!(
+            table_cursor.prev().unwrap_err().to_string(),
+            "cursor is not initialized"
+        );
+    }
+
+    #[test]
+    fn test_btree_cursor_concurrent_scan() {
+        let mut queries = vec!["PRAGMA page_size = 512;".to_string()];
+        for i in 1..=4 {
+            queries.push(format!("CREATE TABLE example{i}(col);"));
+            for _ in 0..500 * i {
+                queries.push(format!(
+                    "INSERT INTO example{i}(col) VALUES (zeroblob(100));"
+                ));
+            }
+        }
+        let file = create_sqlite_database(&queries.iter().map(|s| s.as_str()).collect::<Vec<_>>());
+        let pager = Arc::new(create_pager(file.as_file().try_clone().unwrap()).unwrap());
+        let bctx = Arc::new(load_btree_context(file.as_file()).unwrap());
+
+        // Two threads scan each table.
+        let handles = (1..=4)
+            .chain(1..=4)
+            .map(|i| {
+                let table_page_id = find_table_page_id(&format!("example{i}"), file.path());
+                let pager = pager.clone();
+                let bctx = bctx.clone();
+                std::thread::spawn(move || {
+                    let mut cursor = BtreeCursor::new(table_page_id, &pager, &bctx).unwrap();
+                    cursor.move_to_first().unwrap();
+                    let mut sum = 0;
+                    while let Some((rowid, _)) = cursor.get_table_payload().unwrap() {
+                        sum += rowid;
+                        cursor.next().unwrap();
+                    }
+                    (i, sum)
+                })
+            })
+            .collect::<Vec<_>>();
+        for handle in handles {
+            let (i, sum) = handle.join().unwrap();
+            let n_rows = 500 * i;
+            assert_eq!(sum, n_rows * (n_rows + 1) / 2);
+        }
+    }
+
+    #[test]
+    fn test_btree_cursor_peek_rowid() {
+        let mut queries = vec!["CREATE TABLE example(col);".to_string()];
+        // 4 rows with 1000 byte blob fill a leaf page.
+        for i in 1..=40 {
+            queries.push(format!(
+                "INSERT INTO example(rowid, col) VALUES ({i}, zeroblob(1000));"
+            ));
+        }
+        // Interior cells keep the keys of the deleted rows.
+        queries.push("DELETE FROM example WHERE rowid % 4 = 0;".to_string());
+        let file = create_sqlite_database(&queries.iter().map(|s| s.as_str()).collect::<Vec<_>>());
+        let pager = create_pager(file.as_file().try_clone().unwrap()).unwrap();
+        let bctx = load_btree_context(file.as_file()).unwrap();
+        let table_page_id = find_table_page_id("example", file.path());
+
+        let mut cursor = BtreeCursor::new(table_page_id, &pager, &bctx).unwrap();
+        assert!(cursor.peek_rowid().is_err());
+
+        cursor.move_to_first().unwrap();
+        let mut rowids = Vec::new();
+        while let Some(rowid) = cursor.peek_rowid().unwrap() {
+            // Peeking does not consume the cell.
+            assert_eq!(cursor.peek_rowid().unwrap(), Some(rowid));
+            let (key, _) = cursor.get_table_payload().unwrap().unwrap();
+            assert_eq!(key, rowid);
+            rowids.push(rowid);
+            cursor.next().unwrap();
+        }
+        assert_eq!(rowids, (1..=40).filter(|i| i % 4 != 0).collect::<Vec<_>>());
+        assert_eq!(cursor.peek_rowid().unwrap(), None);
+
+        // The cursor stops at the end of the first leaf page.
+        cursor.table_move_to(4).unwrap();
+        assert!(cursor.get_table_payload().unwrap().is_none());
+        assert_eq!(cursor.peek_rowid().unwrap(), Some(5));
+        let (key, _) = cursor.get_table_payload().unwrap().unwrap();
+        assert_eq!(key, 5);
+        cursor.next().unwrap();
+        assert_eq!(cursor.peek_rowid().unwrap(), Some(6));
+
+        cursor.table_move_to(40).unwrap();
+        assert_eq!(cursor.peek_rowid().unwrap(), None);
+        cursor.next().unwrap();
+        assert_eq!(cursor.peek_rowid().unwrap(), None);
+    }
+
+    #[test]
+    fn test_btree_cursor_large_rowid() {
+        let mut queries = vec!["CREATE TABLE example(col);".to_string()];
+        // Rowids in 9 bytes varint. Interior pages have them as keys too.
+        let mut rowids = vec![i64::MIN, -1, 0, i64::MAX];
+        for i in 0..40 {
+            rowids.push(9000000000000000000 + i * 1000);
+        }
+        for rowid in &rowids {
+            queries.push(format!(
+                "INSERT INTO example(rowid, col) VALUES ({rowid}, zeroblob(1000));"
+            ));
+        }
+        rowids.sort();
+        let file = create_sqlite_database(&queries.iter().map(|s| s.as_str()).collect::<Vec<_>>());
+        let pager = create_pager(file.as_file().try_clone().unwrap()).unwrap();
+        let bctx = load_btree_context(file.as_file()).unwrap();
+        let table_page_id = find_table_page_id("example", file.path());
+
+        let mut cursor = BtreeCursor::new(table_page_id, &pager, &bctx).unwrap();
+        cursor.move_to_first().unwrap();
+        let mut keys = Vec::new();
+        while let Some((key, _)) = cursor.get_table_payload().unwrap() {
+            keys.push(key);
+            cursor.next().unwrap();
+        }
+        assert_eq!(keys, rowids);
+
+        for rowid in rowids {
+            cursor.table_move_to(rowid).unwrap();
+            assert_eq!(cursor.peek_rowid().unwrap(), Some(rowid));
+        }
+        cursor.table_move_to(9000000000000000001).unwrap();
+        assert_eq!(cursor.peek_rowid().unwrap(), Some(9000000000000001000));
+    }
+
+    #[test]
+    fn test_btree_cursor_count_rows() {
+        let hex = buffer_to_hex(&[0; 500]);
+        let mut inserts = Vec::new();
+        for i in 0..3000 {
+            inserts.push(format!(
+                "INSERT INTO example(col,buf) VALUES ({},X'{}');",
+                i,
+                hex.as_str()
+            ));
+        }
+        let mut queries = vec![
+            "CREATE TABLE example(col,buf);",
+            "CREATE INDEX index1 ON example(buf);",
+            "CREATE TABLE empty(col);",
+            "CREATE INDEX index2 ON empty(col);",
+        ];
+        queries.extend(inserts.iter().map(|s| s.as_str()));
+        let file = create_sqlite_database(&queries);
+        let pager = create_pager(file.as_file().try_clone().unwrap()).unwrap();
+        let bctx = load_btree_context(file.as_file()).unwrap();
+
+        for (page_id, is_table) in [
+            (find_table_page_id("example", file.path()), true),
+            (find_index_page_id("index1", file.path()), false),
+            (find_table_page_id("empty", file.path()), true),
+            (find_index_page_id("index2", file.path()), false),
+        ] {
+            let mut cursor = BtreeCursor::new(page_id, &pager, &bctx).unwrap();
+            cursor.move_to_first().unwrap();
+            let mut n_rows = 0;
+            loop {
+                let has_row = if is_table {
+                    cursor.get_table_payload().unwrap().is_some()
+                } else {
+                    cursor.get_index_payload().unwrap().is_some()
+                };
+                if !has_row {
+                    break;
+                }
+                n_rows += 1;
+                cursor.next().unwrap();
+            }
+
+            assert_eq!(cursor.count_rows().unwrap(), n_rows, "{}", page_id);
+            // The cursor is reset.
+            assert!(cursor.get_table_payload().is_err());
+            cursor.move_to_first().unwrap();
+            assert_eq!(cursor.count_rows().unwrap(), n_rows, "{}", page_id);
+        }
+
+        let mut cursor =
+            BtreeCursor::new(find_table_page_id("example", file.path()), &pager, &bctx).unwrap();
+        assert_eq!(cursor.count_rows().unwrap(), 3000);
+        let mut cursor =
+            BtreeCursor::new(find_index_page_id("index1", file.path()), &pager, &bctx).unwrap();
+        assert_eq!(cursor.count_rows().unwrap(), 3000);
+    }
+
+    #[test]
+    fn test_overflow_payload_prefetch() {
+        let buf = (0..100000)
+            .map(|_| rand::random::<u8>())
+            .collect::<Vec<_>>();
+        let query = format!(
+            "INSERT INTO example(col) VALUES (X'{}');",
+            buffer_to_hex(&buf)
+        );
+        for (reserved, use_write_to) in [(0, false), (0, true), (255, false), (255, true)] {
+            let file = create_sqlite_database_with_reserved_bytes(
+                reserved,
+                &["CREATE TABLE example(col);", &query],
+            );
+            let bctx = load_btree_context(file.as_file()).unwrap();
+            assert_eq!(bctx.usable_size(), 4096 - reserved as u32);
+            let table_page_id = find_table_page_id("example", file.path());
+            let pager = create_pager(file.as_file().try_clone().unwrap()).unwrap();
+            let mut cursor = BtreeCursor::new(table_page_id, &pager, &bctx).unwrap();
+            cursor.move_to_first().unwrap();
+            let (_, payload) = cursor.get_table_payload().unwrap().unwrap();
+            let n_overflow_pages = (payload.size() as usize - payload.buf().len())
+                .div_ceil(bctx.usable_size() as usize - 4)
+                as u64;
+            assert!(n_overflow_pages > 10);
+            for i in 0..2 {
+                let stats = pager.stats();
+                let payload_buf = if use_write_to {
+                    let mut payload_buf = Vec::new();
+                    payload
+                        .write_to(0, payload.size(), &mut payload_buf)
+                        .unwrap();
+                    payload_buf
+                } else {
+                    payload.to_vec().unwrap()
+                };
+                assert_eq!(payload_buf[payload_buf.len() - 100000..], buf);
+
+                let new_stats = pager.stats();
+                if use_write_to {
+                    // write_to() loads the overflow pages one by one.
+                    assert_eq!(
+                        new_stats.pages_read - stats.pages_read,
+                        (1 - i) * n_overflow_pages
+                    );
+                    assert_eq!(
+                        new_stats.cache_misses - stats.cache_misses,
+                        (1 - i) * n_overflow_pages
+                    );
+                    assert_eq!(
+                        new_stats.cache_hits - stats.cache_hits,
+                        i * n_overflow_pages
+                    );
+                } else {
+                    // The contiguous overflow pages following the first overflow
+                    // page are read ahead without being cached.
+                    assert_eq!(
+                        new_stats.pages_read - stats.pages_read,
+                        n_overflow_pages - i
+                    );
+                    assert_eq!(new_stats.cache_misses - stats.cache_misses, 1 - i);
+                    assert_eq!(new_stats.cache_hits - stats.cache_hits, i);
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn test_overflow_payload_fragmented_chain() {
+        let blob = (0..100000)
+            .map(|_| rand::random::<u8>())
+            .collect::<Vec<_>>();
+        let query = format!(
+            "INSERT INTO example(rowid, col) VALUES (3, X'{}');",
+            buffer_to_hex(&blob)
+        );
+        // The overflow chain of the row 3 reuses the pages freed by the row 1,
+        // which are followed by the pages of the row 2.
+        let file = create_sqlite_database(&[
+            "CREATE TABLE example(col);",
+            "INSERT INTO example(rowid, col) VALUES (1, randomblob(30000));",
+            "INSERT INTO example(rowid, col) VALUES (2, randomblob(30000));",
+            "DELETE FROM example WHERE rowid = 1;",
+            &query,
+        ]);
+        let bctx = load_btree_context(file.as_file()).unwrap();
+        let table_page_id = find_table_page_id("example", file.path());
+        let pager = create_pager(file.as_file().try_clone().unwrap()).unwrap();
+        let mut cursor = BtreeCursor::new(table_page_id, &pager, &bctx).unwrap();
+        cursor.table_move_to(3).unwrap();
+        let (rowid, payload) = cursor.get_table_payload().unwrap().unwrap();
+        assert_eq!(rowid, 3);
+
+        let payload_buf = payload.to_vec().unwrap();
+        assert_eq!(payload_buf[payload_buf.len() - 100000..], blob);
+        let mut payload_buf = Vec::new();
+        payload
+            .write_to(0, payload.size(), &mut payload_buf)
+            .unwrap();
+        assert_eq!(payload_buf[payload_buf.len() - 100000..], blob);
     }
 
     #[test]
@@ -855,6 +2189,47 @@ mod tests {
         let result = unsafe { payload.load(10004, &mut payload_buf) };
         assert!(result.is_err());
 
+        assert_eq!(payload.load_vec(0, 10004).unwrap()[4..], buf);
+        let payload_buf = payload.to_vec().unwrap();
+        assert_eq!(payload_buf.len(), 10004);
+        assert_eq!(&payload_buf[..payload.buf().len()], payload.buf());
+        assert_eq!(payload_buf[4..], buf);
+        assert_eq!(payload.load_vec(3000, 100).unwrap(), buf[2996..3096]);
+        assert_eq!(payload.load_vec(9000, 1004).unwrap(), buf[8996..]);
+        assert_eq!(payload.load_vec(10004, 0).unwrap(), Vec::<u8>::new());
+        assert!(payload.load_vec(9000, 1005).is_err());
+        assert!(payload.load_vec(-1, 10).is_err());
+        assert!(payload.load_vec(0, -1).is_err());
+        assert!(payload.load_vec(i32::MAX, i32::MAX).is_err());
+
+        assert_eq!(payload.remaining(0), 10004);
+        assert_eq!(payload.remaining(-1), 10004);
+        assert_eq!(payload.remaining(3000), 7004);
+        assert_eq!(payload.remaining(10003), 1);
+        assert_eq!(payload.remaining(10004), 0);
+        assert_eq!(payload.remaining(i32::MAX), 0);
+        let mut payload_buf = vec![0; payload.remaining(9000) as usize];
+        let n = unsafe { payload.load(9000, &mut payload_buf) }.unwrap();
+        assert_eq!(n, payload_buf.len());
+        assert_eq!(payload_buf, buf[8996..]);
+
+        let mut written = Vec::new();
+        assert_eq!(payload.write_to(0, 10004, &mut written).unwrap(), 10004);
+        assert_eq!(written[..4], [0x04, 0x81, 0x9c, 0x2c]);
+        assert_eq!(written[4..], buf);
+
+        written.clear();
+        assert_eq!(payload.write_to(3000, 100, &mut written).unwrap(), 100);
+        assert_eq!(written, buf[2996..3096]);
+
+        written.clear();
+        assert_eq!(payload.write_to(104, 10000, &mut written).unwrap(), 9900);
+        assert_eq!(written, buf[100..]);
+
+        written.clear();
+        assert_eq!(payload.write_to(10004, 1, &mut written).unwrap(), 0);
+        assert!(payload.write_to(-1, 1, &mut written).is_err());
+
         let index_page_id = find_index_page_id("index1", file.path());
 
         let mut cursor = BtreeCursor::new(index_page_id, &pager, &bctx).unwrap();
@@ -1021,7 +2396,6 @@ mod tests {
             let mut record = Record::parse(payload.as_ref().unwrap()).unwrap();
             assert_eq!(record.get(0).unwrap(), Value::Integer(2 * i + 1));
             assert_eq!(record.get(1).unwrap(), Value::Integer(2 * i + 1));
-            drop(payload);
 
             cursor
                 .index_move_to(&[ValueCmp::new(
@@ -1046,7 +2420,6 @@ mod tests {
         // If there are multiple entries with the same key, one of the entries is
         // returned (not necessarily the first or last one).
         assert_eq!(record.get(1).unwrap(), Value::Integer(11));
-        drop(payload);
 
         for i in 10..13 {
             cursor
@@ -1073,7 +2446,6 @@ mod tests {
         let mut record = Record::parse(payload.as_ref().unwrap()).unwrap();
         assert_eq!(record.get(0).unwrap(), Value::Integer(11));
         assert_eq!(record.get(1).unwrap(), Value::Integer(14));
-        drop(payload);
 
         cursor
             .index_move_to(&[
@@ -1293,7 +2665,8 @@ mod tests {
             assert!(payload.is_some());
             let payload = payload.unwrap();
             let mut record = Record::parse(&payload).unwrap();
-            if let Value::Integer(rowid) = record.get(record.len() - 1).unwrap() {
+            let n_columns = record.len().unwrap();
+            if let Value::Integer(rowid) = record.get(n_columns - 1).unwrap() {
                 assert_eq!(rowid, expected, "{:?}", keys);
             } else {
                 panic!("unexpected payload: {:?}", keys);
@@ -1347,7 +2720,8 @@ mod tests {
             assert!(payload.is_some());
             let payload = payload.unwrap();
             let mut record = Record::parse(&payload).unwrap();
-            if let Value::Integer(rowid) = record.get(record.len() - 1).unwrap() {
+            let n_columns = record.len().unwrap();
+            if let Value::Integer(rowid) = record.get(n_columns - 1).unwrap() {
                 assert_eq!(rowid, expected[0], "{:?}", keys);
             } else {
                 panic!("unexpected payload: {:?}", keys);
@@ -1362,7 +2736,8 @@ mod tests {
             assert!(payload.is_some());
             let payload = payload.unwrap();
             let mut record = Record::parse(&payload).unwrap();
-            if let Value::Integer(rowid) = record.get(record.len() - 1).unwrap() {
+            let n_columns = record.len().unwrap();
+            if let Value::Integer(rowid) = record.get(n_columns - 1).unwrap() {
                 assert_eq!(rowid, expected[1], "{:?}", keys);
             } else {
                 panic!("unexpected payload: {:?}", keys);
@@ -1377,7 +2752,8 @@ mod tests {
             assert!(payload.is_some());
             let payload = payload.unwrap();
             let mut record = Record::parse(&payload).unwrap();
-            if let Value::Integer(rowid) = record.get(record.len() - 1).unwrap() {
+            let n_columns = record.len().unwrap();
+            if let Value::Integer(rowid) = record.get(n_columns - 1).unwrap() {
                 assert_eq!(rowid, expected[2], "{:?}", keys);
             } else {
                 panic!("unexpected payload: {:?}", keys);
@@ -1452,7 +2828,6 @@ mod tests {
             let mut record = Record::parse(payload.as_ref().unwrap()).unwrap();
             assert_eq!(record.get(0).unwrap(), Value::Integer(2 * i + 1));
             assert_eq!(record.get(2).unwrap(), Value::Integer(i));
-            drop(payload);
 
             // Reset the cursor.
             cursor.move_to_first().unwrap();
@@ -1468,7 +2843,6 @@ mod tests {
             let mut record = Record::parse(payload.as_ref().unwrap()).unwrap();
             assert_eq!(record.get(0).unwrap(), Value::Integer(2 * i + 1));
             assert_eq!(record.get(2).unwrap(), Value::Integer(i));
-            drop(payload);
 
             // Reset the cursor.
             cursor.move_to_first().unwrap();
@@ -0,0 +1,183 @@
+// Copyright 2023 Google LLC
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     https://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::fmt::Write;
+
+use crate::btree::get_cell_offset;
+use crate::btree::BtreePageHeader;
+use crate::pager::PageId;
+use crate::pager::Pager;
+
+/// Format the btree page header and the cell pointer array of the page.
+///
+/// This does not parse cells, so that it works for a corrupted page.
+pub fn dump_page(pager: &Pager, page_id: PageId, usable_size: u32) -> anyhow::Result<String> {
+    let page = pager.get_page(page_id)?;
+    let buffer = page.buffer();
+    let header = BtreePageHeader::from_page(&page, &buffer);
+    let mut out = String::new();
+
+    writeln!(out, "page: {}", page_id)?;
+    writeln!(out, "header offset: {}", page.header_offset)?;
+    let Some(kind) = header.page_type().kind() else {
+        writeln!(
+            out,
+            "page type: invalid (0x{:02x})",
+            buffer[page.header_offset]
+        )?;
+        return Ok(out);
+    };
+    writeln!(out, "page type: {:?}", kind)?;
+    writeln!(out, "first freeblock: {}", header.first_freeblock_offset())?;
+    writeln!(out, "cells: {}", header.n_cells())?;
+    writeln!(
+        out,
+        "cell content area: {}",
+        header.cell_content_area_offset()
+    )?;
+    writeln!(
+        out,
+        "fragmented free bytes: {}",
+        header.n_fragmented_free_bytes()
+    )?;
+    if !header.page_type().is_leaf() {
+        writeln!(out, "right page id: {}", header.right_page_id())?;
+    }
+
+    let max_cells = (buffer.len() - page.header_offset - header.header_size() as usize) >> 1;
+    let n_cells = std::cmp::min(header.n_cells() as usize, max_cells) as u16;
+    for cell_idx in 0..n_cells {
+        match get_cell_offset(&page, &buffer, cell_idx, header.header_size()) {
+            Ok(offset) if offset >= usable_size as usize => {
+                writeln!(
+                    out,
+                    "cell {}: {} (exceeds usable size {})",
+                    cell_idx, offset, usable_size
+                )?;
+            }
+            Ok(offset) => {
+                writeln!(out, "cell {}: {}", cell_idx, offset)?;
+            }
+            Err(e) => {
+                writeln!(out, "cell {}: {}", cell_idx, e)?;
+            }
+        }
+    }
+    if n_cells < header.n_cells() {
+        writeln!(out, "cell pointer array exceeds the page")?;
+    }
+    Ok(out)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use crate::test_utils::*;
+
+    #[test]
+    fn test_dump_page() {
+        let file = create_sqlite_database(&[
+            "PRAGMA page_size = 512;",
+            "CREATE TABLE example(col);",
+            "INSERT INTO example(col) VALUES (10);",
+            "INSERT INTO example(col) VALUES (20);",
+        ]);
+        let pager = create_pager(file.as_file().try_clone().unwrap()).unwrap();
+        let page_id = find_table_page_id("example", file.path());
+
+        let dump = dump_page(&pager, page_id, 512).unwrap();
+
+        // Each cell has 1 byte payload size, 1 byte rowid, 2 bytes record header and
+        // 1 byte value. Cells are allocated from the end of the page.
+        assert_eq!(
+            dump,
+            format!(
+                "page: {}
+header offset: 0
+page type: LeafTable
+first freeblock: 0
+cells: 2
+cell content area: 502
+fragmented free bytes: 0
+cell 0: 507
+cell 1: 502
+",
+                page_id
+            )
+        );
+
+        let dump = dump_page(&pager, 1, 512).unwrap();
+        assert!(dump.starts_with(
+            "page: 1
+header offset: 100
+page type: LeafTable
+first freeblock: 0
+cells: 1
+"
+        ));
+
+        assert!(dump_page(&pager, pager.num_pages() + 1, 512).is_err());
+    }
+
+    #[test]
+    fn test_dump_page_corrupted() {
+        let mut content = vec![0; 1024];
+        // Invalid page type.
+        content[512] = 0x01;
+        let pager = create_empty_pager(&content, 512);
+        assert_eq!(
+            dump_page(&pager, 2, 512).unwrap(),
+            "page: 2
+header offset: 0
+page type: invalid (0x01)
+"
+        );
+
+        // Leaf table page with 2 cells whose second cell exceeds the usable size.
+        content[512] = 0x0d;
+        content[512 + 4] = 2;
+        content[512 + 8..512 + 12].copy_from_slice(&[0x01, 0x00, 0x01, 0xf8]);
+        let pager = create_empty_pager(&content, 512);
+        assert_eq!(
+            dump_page(&pager, 2, 500).unwrap(),
+            "page: 2
+header offset: 0
+page type: LeafTable
+first freeblock: 0
+cells: 2
+cell content area: 65536
+fragmented free bytes: 0
+cell 0: 256
+cell 1: 504 (exceeds usable size 500)
+"
+        );
+
+        // The cell pointer array exceeds the page.
+        content[512 + 3] = 0xff;
+        let pager = create_empty_pager(&content, 512);
+        let dump = dump_page(&pager, 2, 512).unwrap();
+        assert!(dump.contains("cells: 65282\n"), "{}", dump);
+        assert!(
+            dump.ends_with(
+                "cell 250: cell offset out of range
+cell 251: cell offset out of range
+cell pointer array exceeds the page
+"
+            ),
+            "{}",
+            dump
+        );
+    }
+}
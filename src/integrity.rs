@@ -0,0 +1,439 @@
+// Copyright 2023 Google LLC
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     https://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::collections::HashSet;
+
+use crate::btree::get_cell_offset;
+use crate::btree::parse_btree_interior_cell_page_id;
+use crate::btree::parse_btree_leaf_table_cell;
+use crate::btree::BtreeContext;
+use crate::btree::BtreePageHeader;
+use crate::btree::IndexCellKeyParser;
+use crate::btree::OverflowPage;
+use crate::btree::PayloadInfo;
+use crate::btree::TableCellKeyParser;
+use crate::pager::PageId;
+use crate::pager::Pager;
+
+/// Walks btrees and collects the problems found in their pages.
+///
+/// This never fails on a corrupted page. The problem is recorded and the
+/// subtree under the page is skipped instead.
+pub struct IntegrityChecker<'a> {
+    pager: &'a Pager,
+    ctx: &'a BtreeContext,
+    /// Pages already referenced by a btree or an overflow chain.
+    visited: HashSet<PageId>,
+    problems: Vec<String>,
+}
+
+impl<'a> IntegrityChecker<'a> {
+    pub fn new(pager: &'a Pager, ctx: &'a BtreeContext) -> Self {
+        Self {
+            pager,
+            ctx,
+            visited: HashSet::new(),
+            problems: Vec::new(),
+        }
+    }
+
+    /// Record a problem which is not specific to a page.
+    pub fn add_problem(&mut self, problem: String) {
+        self.problems.push(problem);
+    }
+
+    /// Returns the problems found so far in the order of discovery.
+    pub fn into_problems(self) -> Vec<String> {
+        self.problems
+    }
+
+    /// Check all the pages of the btree.
+    ///
+    /// The btree is a table btree if is_table is Some(true), an index btree if
+    /// Some(false), and either if None, e.g. the btree of a WITHOUT ROWID table
+    /// is an index btree. All the pages in the btree must be the same kind
+    /// as the root page.
+    pub fn check_btree(&mut self, tree: &str, root_page_id: PageId, is_table: Option<bool>) {
+        let mut leaf_depth = None;
+        // Pages are checked in depth first order without recursion because
+        // a corrupted btree can be arbitrarily deep.
+        let mut stack = vec![(root_page_id, 0, is_table)];
+        while let Some((page_id, depth, is_table)) = stack.pop() {
+            let Some(page) = self.check_page(tree, page_id, depth, is_table, &mut leaf_depth)
+            else {
+                continue;
+            };
+            // Children are pushed in reverse to check them from the left.
+            for &child in page.children.iter().rev() {
+                stack.push((child, depth + 1, Some(page.is_table)));
+            }
+        }
+    }
+
+    fn check_page(
+        &mut self,
+        tree: &str,
+        page_id: PageId,
+        depth: usize,
+        is_table: Option<bool>,
+        leaf_depth: &mut Option<usize>,
+    ) -> Option<CheckedPage> {
+        if !self.visit(tree, page_id) {
+            return None;
+        }
+        let page = match self.pager.get_page(page_id) {
+            Ok(page) => page,
+            Err(e) => {
+                self.problems
+                    .push(format!("{tree} page {page_id}: failed to load: {e}"));
+                return None;
+            }
+        };
+        let buffer = page.buffer();
+        let header = BtreePageHeader::from_page(&page, &buffer);
+        if let Err(e) = header.validate(&page, &buffer) {
+            self.problems.push(format!("{tree} page {page_id}: {e}"));
+            return None;
+        }
+        let page_type = header.page_type();
+        if is_table.is_some_and(|is_table| is_table != page_type.is_table()) {
+            self.problems.push(format!(
+                "{tree} page {page_id}: unexpected page type {:?}",
+                page_type.kind().unwrap()
+            ));
+            return None;
+        }
+        let is_table = page_type.is_table();
+        let is_leaf = page_type.is_leaf();
+        if is_leaf {
+            match *leaf_depth {
+                Some(expected) if expected != depth => {
+                    self.problems.push(format!(
+                        "{tree} page {page_id}: leaf depth {depth} differs from {expected}"
+                    ));
+                }
+                Some(_) => {}
+                None => *leaf_depth = Some(depth),
+            }
+        }
+
+        let usable_size = self.ctx.usable_size() as usize;
+        let cell_pointers_end =
+            page.header_offset + header.header_size() as usize + 2 * header.n_cells() as usize;
+        let cell_content_area = header.cell_content_area_offset() as usize;
+        if cell_content_area < cell_pointers_end || cell_content_area > usable_size {
+            self.problems.push(format!(
+                "{tree} page {page_id}: cell content area {cell_content_area} out of range"
+            ));
+        }
+
+        let mut children = Vec::new();
+        let mut last_key = None;
+        for cell_idx in 0..header.n_cells() {
+            let offset = match get_cell_offset(&page, &buffer, cell_idx, header.header_size()) {
+                Ok(offset) => offset,
+                Err(e) => {
+                    self.problems
+                        .push(format!("{tree} page {page_id} cell {cell_idx}: {e}"));
+                    continue;
+                }
+            };
+            if offset < cell_pointers_end || offset >= usable_size {
+                self.problems.push(format!(
+                    "{tree} page {page_id} cell {cell_idx}: offset {offset} out of range"
+                ));
+                continue;
+            }
+
+            let result = if is_table {
+                if is_leaf {
+                    parse_btree_leaf_table_cell(self.ctx, &page, &buffer, cell_idx)
+                        .map(|(key, payload)| (key, Some(payload)))
+                } else {
+                    TableCellKeyParser::new(&page, &buffer)
+                        .get_cell_key(cell_idx)
+                        .map(|key| (key, None))
+                }
+                .map(|(key, payload)| (Some(key), payload))
+            } else {
+                IndexCellKeyParser::new(self.ctx, &page, &buffer)
+                    .get_cell_key(cell_idx)
+                    .map(|payload| (None, Some(payload)))
+            };
+            let (key, payload) = match result {
+                Ok(cell) => cell,
+                Err(e) => {
+                    self.problems
+                        .push(format!("{tree} page {page_id} cell {cell_idx}: {e}"));
+                    continue;
+                }
+            };
+
+            if let Some(key) = key {
+                if last_key.is_some_and(|last_key| key <= last_key) {
+                    self.problems.push(format!(
+                        "{tree} page {page_id} cell {cell_idx}: rowid {key} out of order"
+                    ));
+                }
+                last_key = Some(key);
+            }
+            if let Some(payload) = payload {
+                self.check_payload(tree, page_id, cell_idx, payload, usable_size);
+            }
+            if !is_leaf {
+                match parse_btree_interior_cell_page_id(&page, &buffer, cell_idx) {
+                    Ok(child) => children.push(child),
+                    Err(e) => {
+                        self.problems
+                            .push(format!("{tree} page {page_id} cell {cell_idx}: {e}"));
+                    }
+                }
+            }
+        }
+        if !is_leaf {
+            children.push(header.right_page_id());
+        }
+
+        Some(CheckedPage { is_table, children })
+    }
+
+    fn check_payload(
+        &mut self,
+        tree: &str,
+        page_id: PageId,
+        cell_idx: u16,
+        payload: PayloadInfo,
+        usable_size: usize,
+    ) {
+        if payload.local_range.end > usable_size {
+            self.problems.push(format!(
+                "{tree} page {page_id} cell {cell_idx}: payload exceeds the usable size"
+            ));
+        }
+        let mut overflow = payload.overflow;
+        while let Some(overflow_page) = overflow.take() {
+            let overflow_page_id = overflow_page.page_id();
+            if !self.visit(tree, overflow_page_id) {
+                return;
+            }
+            if let Err(e) = self
+                .parse_overflow(&overflow_page)
+                .map(|next| overflow = next)
+            {
+                self.problems.push(format!(
+                    "{tree} page {page_id} cell {cell_idx}: overflow page {overflow_page_id}: {e}"
+                ));
+                return;
+            }
+        }
+    }
+
+    fn parse_overflow(&self, overflow_page: &OverflowPage) -> anyhow::Result<Option<OverflowPage>> {
+        let page = self.pager.get_page(overflow_page.page_id())?;
+        let buffer = page.buffer();
+        let (_, next) = overflow_page
+            .parse(self.ctx, &buffer)
+            .map_err(|e| anyhow::anyhow!("{}", e))?;
+        Ok(next)
+    }
+
+    /// Returns false if the page is out of range or already visited.
+    fn visit(&mut self, tree: &str, page_id: PageId) -> bool {
+        if page_id == 0 || page_id > self.pager.page_count() {
+            self.problems
+                .push(format!("{tree}: page {page_id} out of range"));
+            false
+        } else if !self.visited.insert(page_id) {
+            self.problems.push(format!(
+                "{tree}: page {page_id} is referenced more than once"
+            ));
+            false
+        } else {
+            true
+        }
+    }
+}
+
+struct CheckedPage {
+    is_table: bool,
+    children: Vec<PageId>,
+}
+
+#[cfg(test)]
+mod tests {
+    use std::os::unix::fs::FileExt;
+
+    use super::*;
+    use crate::test_utils::*;
+
+    fn check_table(file: &tempfile::NamedTempFile, table: &str) -> Vec<String> {
+        let pager = create_pager(file.as_file().try_clone().unwrap()).unwrap();
+        let ctx = load_btree_context(file.as_file()).unwrap();
+        let mut checker = IntegrityChecker::new(&pager, &ctx);
+        checker.check_btree(table, find_table_page_id(table, file.path()), Some(true));
+        checker.into_problems()
+    }
+
+    #[test]
+    fn test_check_btree() {
+        let mut queries = vec![
+            "PRAGMA page_size = 512;",
+            "CREATE TABLE example(col);",
+            "CREATE INDEX index1 ON example(col);",
+        ];
+        queries.extend(
+            std::iter::repeat("INSERT INTO example(col) VALUES (randomblob(100));").take(100),
+        );
+        queries.push("INSERT INTO example(col) VALUES (randomblob(2000));");
+        let file = create_sqlite_database(&queries);
+        let pager = create_pager(file.as_file().try_clone().unwrap()).unwrap();
+        let ctx = load_btree_context(file.as_file()).unwrap();
+
+        let mut checker = IntegrityChecker::new(&pager, &ctx);
+        checker.check_btree(
+            "table",
+            find_table_page_id("example", file.path()),
+            Some(true),
+        );
+        checker.check_btree(
+            "index",
+            find_table_page_id("index1", file.path()),
+            Some(false),
+        );
+        assert_eq!(checker.visited.len() as u32, pager.num_pages() - 1);
+        assert_eq!(checker.into_problems(), Vec::<String>::new());
+
+        // The index btree is not a table btree.
+        let mut checker = IntegrityChecker::new(&pager, &ctx);
+        let page_id = find_table_page_id("index1", file.path());
+        checker.check_btree("index", page_id, Some(true));
+        assert_eq!(
+            checker.into_problems(),
+            vec![format!(
+                "index page {page_id}: unexpected page type InteriorIndex"
+            )]
+        );
+
+        // Pages shared by btrees.
+        let mut checker = IntegrityChecker::new(&pager, &ctx);
+        let page_id = find_table_page_id("example", file.path());
+        checker.check_btree("table1", page_id, None);
+        checker.check_btree("table2", page_id, None);
+        assert_eq!(
+            checker.into_problems(),
+            vec![format!(
+                "table2: page {page_id} is referenced more than once"
+            )]
+        );
+    }
+
+    #[test]
+    fn test_check_btree_invalid_cell_pointer() {
+        let file = create_sqlite_database(&[
+            "PRAGMA page_size = 512;",
+            "CREATE TABLE example(col);",
+            "INSERT INTO example(col) VALUES (1);",
+            "INSERT INTO example(col) VALUES (2);",
+        ]);
+        let page_id = find_table_page_id("example", file.path());
+        let offset = (page_id - 1) as u64 * 512;
+        // The cell pointer of the second cell points to the btree header.
+        file.as_file()
+            .write_all_at(&[0x00, 0x01], offset + 10)
+            .unwrap();
+
+        assert_eq!(
+            check_table(&file, "example"),
+            vec![format!(
+                "example page {page_id} cell 1: offset 1 out of range"
+            )]
+        );
+    }
+
+    #[test]
+    fn test_check_btree_rowid_order() {
+        let file = create_sqlite_database(&[
+            "PRAGMA page_size = 512;",
+            "CREATE TABLE example(col);",
+            "INSERT INTO example(col) VALUES (1);",
+            "INSERT INTO example(col) VALUES (2);",
+        ]);
+        let page_id = find_table_page_id("example", file.path());
+        let offset = (page_id - 1) as u64 * 512;
+        // Swap the cell pointers.
+        let mut cell_pointers = [0; 4];
+        file.as_file()
+            .read_exact_at(&mut cell_pointers, offset + 8)
+            .unwrap();
+        cell_pointers.rotate_left(2);
+        file.as_file()
+            .write_all_at(&cell_pointers, offset + 8)
+            .unwrap();
+
+        assert_eq!(
+            check_table(&file, "example"),
+            vec![format!(
+                "example page {page_id} cell 1: rowid 1 out of order"
+            )]
+        );
+    }
+
+    #[test]
+    fn test_check_btree_invalid_overflow() {
+        let file = create_sqlite_database(&[
+            "PRAGMA page_size = 512;",
+            "CREATE TABLE example(col);",
+            "INSERT INTO example(col) VALUES (randomblob(2000));",
+        ]);
+        let pager = create_pager(file.as_file().try_clone().unwrap()).unwrap();
+        let ctx = load_btree_context(file.as_file()).unwrap();
+        let page_id = find_table_page_id("example", file.path());
+        let page = pager.get_page(page_id).unwrap();
+        let buffer = page.buffer();
+        let (_, payload) = parse_btree_leaf_table_cell(&ctx, &page, &buffer, 0).unwrap();
+        let overflow_page_id = payload.overflow.unwrap().page_id();
+        drop(page);
+        assert_eq!(check_table(&file, "example"), Vec::<String>::new());
+
+        // The overflow page points to itself.
+        let offset = (overflow_page_id - 1) as u64 * 512;
+        file.as_file()
+            .write_all_at(&overflow_page_id.to_be_bytes(), offset)
+            .unwrap();
+        assert_eq!(
+            check_table(&file, "example"),
+            vec![format!(
+                "example: page {overflow_page_id} is referenced more than once"
+            )]
+        );
+
+        // The overflow page points to a page out of the file.
+        file.as_file()
+            .write_all_at(&1000_u32.to_be_bytes(), offset)
+            .unwrap();
+        assert_eq!(
+            check_table(&file, "example"),
+            vec!["example: page 1000 out of range".to_string()]
+        );
+
+        // The overflow chain terminates too early.
+        file.as_file().write_all_at(&[0; 4], offset).unwrap();
+        assert_eq!(
+            check_table(&file, "example"),
+            vec![format!(
+                "example page {page_id} cell 0: overflow page {overflow_page_id}: overflow payload does not have next page id"
+            )]
+        );
+    }
+}
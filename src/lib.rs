@@ -14,6 +14,8 @@
 
 mod btree;
 mod cursor;
+mod debug;
+mod integrity;
 mod pager;
 mod parser;
 mod record;
@@ -27,37 +29,63 @@ mod value;
 use std::cmp::Ordering;
 use std::fmt::Display;
 use std::fs::File;
+use std::io::Read;
+use std::io::Seek;
+use std::io::SeekFrom;
+use std::io::Write;
 use std::os::unix::fs::FileExt;
 use std::path::Path;
+use std::sync::Arc;
 
 use anyhow::bail;
 use anyhow::Context;
 
 use crate::btree::BtreeContext;
 use crate::cursor::BtreeCursor;
-use crate::cursor::BtreePayload;
-use crate::pager::PageId;
+pub use crate::cursor::BtreePayload;
+pub use crate::cursor::IndexRows;
+pub use crate::cursor::TableRow;
+pub use crate::cursor::TableRows;
+use crate::integrity::IntegrityChecker;
+pub use crate::pager::PageId;
 use crate::pager::Pager;
-use crate::parser::expect_no_more_token;
+pub use crate::pager::PagerStats;
+use crate::pager::CHECKSUM_SIZE;
+use crate::pager::ROOT_PAGE_ID;
 use crate::parser::expect_semicolon;
-use crate::parser::parse_select;
+use crate::parser::parse_complete;
+use crate::parser::parse_select_stmt;
+pub use crate::parser::parse_statement;
+pub use crate::parser::split_statements;
+use crate::parser::true_false_value;
 use crate::parser::BinaryOp;
 use crate::parser::CompareOp;
+pub use crate::parser::Delete;
 use crate::parser::Error as ParseError;
+use crate::parser::ExplainMode;
 use crate::parser::Expr;
-use crate::parser::Parser;
+pub use crate::parser::ParsedStatement;
 use crate::parser::ResultColumn;
 use crate::parser::UnaryOp;
+pub use crate::parser::Update;
 use crate::record::parse_record_header;
 use crate::record::Record;
 use crate::record::SerialType;
+pub use crate::record::SerialTypeKind;
 use crate::schema::calc_collation;
 use crate::schema::calc_type_affinity;
+pub use crate::schema::ColumnDefinition;
 use crate::schema::ColumnNumber;
+pub use crate::schema::IndexDefinition;
 use crate::schema::Schema;
+pub use crate::schema::SchemaEntry;
 use crate::schema::Table;
+pub use crate::schema::TableDefinition;
+pub use crate::utils::parse_varint;
+use crate::utils::CaseInsensitiveBytes;
 pub use crate::value::Buffer;
 use crate::value::Collation;
+use crate::value::TextEncoding;
 use crate::value::TypeAffinity;
 pub use crate::value::Value;
 use crate::value::ValueCmp;
@@ -66,6 +94,10 @@ use crate::value::DEFAULT_COLLATION;
 const SQLITE_MAX_PAGE_SIZE: u32 = 65536;
 pub const DATABASE_HEADER_SIZE: usize = 100;
 const MAGIC_HEADER: &[u8; 16] = b"SQLite format 3\0";
+/// The magic number at the start of a rollback journal header.
+///
+/// https://www.sqlite.org/fileformat2.html#the_rollback_journal
+const JOURNAL_MAGIC: &[u8; 8] = &[0xd9, 0xd5, 0x05, 0xf9, 0x20, 0xa1, 0x63, 0xd7];
 
 #[derive(Debug)]
 pub enum Error<'a> {
@@ -98,6 +130,37 @@ impl Display for Error<'_> {
 
 pub type Result<'a, T> = std::result::Result<T, Error<'a>>;
 
+/// The schema format number in the database header.
+///
+/// https://www.sqlite.org/fileformat2.html#schema_format_number
+#[derive(Debug, PartialEq, Eq, PartialOrd, Ord, Clone, Copy)]
+pub enum SchemaFormat {
+    /// The baseline format.
+    Format1 = 1,
+    /// Adds support for ALTER TABLE ... ADD COLUMN.
+    Format2 = 2,
+    /// Adds support for non-NULL default values of added columns.
+    Format3 = 3,
+    /// Respects DESC in index declarations and adds the serial types 8 and 9.
+    Format4 = 4,
+}
+
+impl SchemaFormat {
+    /// Convert the schema format number in the database header.
+    ///
+    /// 0 is treated as 1 as SQLite does. It is written to the database which
+    /// has no schema yet.
+    pub fn from_header(v: u32) -> Option<Self> {
+        match v {
+            0 | 1 => Some(Self::Format1),
+            2 => Some(Self::Format2),
+            3 => Some(Self::Format3),
+            4 => Some(Self::Format4),
+            _ => None,
+        }
+    }
+}
+
 pub struct DatabaseHeader<'a>(&'a [u8; DATABASE_HEADER_SIZE]);
 
 impl<'a> DatabaseHeader<'a> {
@@ -115,8 +178,9 @@ impl<'a> DatabaseHeader<'a> {
         (512..=SQLITE_MAX_PAGE_SIZE).contains(&pagesize) && (pagesize - 1) & pagesize == 0
     }
 
+    /// The usable size must be at least 480 bytes.
     pub fn validate_reserved(&self) -> bool {
-        self.pagesize() > self.reserved() as u32
+        self.usable_size() >= 480
     }
 
     pub fn pagesize(&self) -> u32 {
@@ -128,23 +192,164 @@ impl<'a> DatabaseHeader<'a> {
         self.0[20]
     }
 
+    pub fn change_counter(&self) -> u32 {
+        u32::from_be_bytes(self.0[24..28].try_into().unwrap())
+    }
+
+    /// The size of the database in pages.
+    ///
+    /// Returns None if the field is not valid. SQLite older than 3.7.0 leaves
+    /// it zero or stale, which is detected by the version-valid-for number
+    /// differing from the change counter.
+    pub fn database_size(&self) -> Option<u32> {
+        let database_size = u32::from_be_bytes(self.0[28..32].try_into().unwrap());
+        let version_valid_for = u32::from_be_bytes(self.0[92..96].try_into().unwrap());
+        (database_size != 0 && version_valid_for == self.change_counter()).then_some(database_size)
+    }
+
+    /// The page id of the first freelist trunk page, or 0 if the freelist is
+    /// empty.
+    pub fn freelist_trunk_page(&self) -> u32 {
+        u32::from_be_bytes(self.0[32..36].try_into().unwrap())
+    }
+
+    /// The total number of freelist pages including the trunk pages.
+    pub fn freelist_count(&self) -> u32 {
+        u32::from_be_bytes(self.0[36..40].try_into().unwrap())
+    }
+
+    /// The page size minus the bytes reserved at the end of each page.
     pub fn usable_size(&self) -> i32 {
         self.pagesize() as i32 - self.reserved() as i32
     }
+
+    /// Returns None if the schema format number is not supported.
+    pub fn schema_format(&self) -> Option<SchemaFormat> {
+        SchemaFormat::from_header(u32::from_be_bytes(self.0[44..48].try_into().unwrap()))
+    }
+
+    /// Returns None if the text encoding is not one of 1 (UTF-8), 2 (UTF-16le)
+    /// and 3 (UTF-16be).
+    pub fn text_encoding(&self) -> Option<TextEncoding> {
+        TextEncoding::from_header(u32::from_be_bytes(self.0[56..60].try_into().unwrap()))
+    }
+
+    /// The user version set by `PRAGMA user_version`.
+    pub fn user_version(&self) -> i32 {
+        i32::from_be_bytes(self.0[60..64].try_into().unwrap())
+    }
+
+    /// The application ID set by `PRAGMA application_id`.
+    pub fn application_id(&self) -> u32 {
+        u32::from_be_bytes(self.0[68..72].try_into().unwrap())
+    }
+}
+
+/// Fails if the rollback journal of the database is hot.
+///
+/// A hot journal holds the original pages of an incomplete transaction, so the
+/// database file may be partially committed. SQLite rolls it back on the next
+/// access, which this reader does not support. A journal which is empty or
+/// whose header is zeroed (journal_mode=TRUNCATE or PERSIST) belongs to a
+/// committed transaction.
+///
+/// The journal of a transaction in progress by another connection is detected
+/// as hot as well because the locks are not checked.
+fn check_hot_journal(filename: &Path) -> anyhow::Result<()> {
+    let mut journal_name = filename.as_os_str().to_owned();
+    journal_name.push("-journal");
+    let journal = match File::open(&journal_name) {
+        Ok(journal) => journal,
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(()),
+        Err(e) => return Err(e).context("open journal"),
+    };
+    let mut magic = [0; JOURNAL_MAGIC.len()];
+    match journal.read_exact_at(&mut magic, 0) {
+        Ok(()) => {}
+        Err(e) if e.kind() == std::io::ErrorKind::UnexpectedEof => return Ok(()),
+        Err(e) => return Err(e).context("read journal"),
+    }
+    if &magic == JOURNAL_MAGIC {
+        bail!("hot journal present; database may be inconsistent");
+    }
+    Ok(())
 }
 
+/// A connection to a database.
+///
+/// A clone of the connection shares the pager and its page cache with the
+/// original. Each clone can be moved to another thread to read the database
+/// concurrently.
 pub struct Connection {
-    pager: Pager,
+    pager: Arc<Pager>,
     btree_ctx: BtreeContext,
+    text_encoding: TextEncoding,
     schema: Option<Schema>,
 }
 
+impl Clone for Connection {
+    /// The clone loads the schema again on its first use.
+    fn clone(&self) -> Self {
+        Self {
+            pager: self.pager.clone(),
+            btree_ctx: BtreeContext::new(self.btree_ctx.usable_size() as i32),
+            text_encoding: self.text_encoding,
+            schema: None,
+        }
+    }
+}
+
 impl Connection {
     pub fn open(filename: &Path) -> anyhow::Result<Self> {
+        Self::open_with(filename, Pager::with_pagesize)
+    }
+
+    /// Open the database and read pages from the memory mapped file.
+    ///
+    /// # Safety
+    ///
+    /// Values and payloads borrowed from the connection point into the shared
+    /// mapping of the file. The file must not be modified or truncated by this
+    /// or any other process while the connection is alive. A write changes the
+    /// borrowed bytes under the reference, which is undefined behavior, and an
+    /// access to a truncated part of the mapping raises SIGBUS. The change
+    /// detection of [Self::enable_change_detection()] only reports a change
+    /// after the fact.
+    #[cfg(feature = "mmap")]
+    pub unsafe fn open_mmap(filename: &Path) -> anyhow::Result<Self> {
+        // SAFETY: the caller guarantees that the file is not modified or
+        // truncated while the connection is alive.
+        Self::open_with(filename, |file, pagesize| unsafe {
+            Pager::new_mmap(file, pagesize)
+        })
+    }
+
+    /// Open the database image read from the reader.
+    ///
+    /// e.g. a [std::io::Cursor] of a database image in memory.
+    pub fn open_reader(mut reader: impl Read + Seek + Send + 'static) -> anyhow::Result<Self> {
+        let mut buf = [0; DATABASE_HEADER_SIZE];
+        reader.seek(SeekFrom::Start(0))?;
+        reader.read_exact(&mut buf)?;
+        Self::from_header(&buf, |pagesize| Pager::from_reader(reader, pagesize))
+    }
+
+    fn open_with(
+        filename: &Path,
+        new_pager: impl FnOnce(File, usize) -> anyhow::Result<Pager>,
+    ) -> anyhow::Result<Self> {
+        check_hot_journal(filename)?;
         let file = File::open(filename)?;
         let mut buf = [0; DATABASE_HEADER_SIZE];
         file.read_exact_at(&mut buf, 0)?;
-        let header = DatabaseHeader::from(&buf);
+        Self::from_header(&buf, |pagesize| new_pager(file, pagesize))
+    }
+
+    fn from_header(
+        buf: &[u8; DATABASE_HEADER_SIZE],
+        new_pager: impl FnOnce(usize) -> anyhow::Result<Pager>,
+    ) -> anyhow::Result<Self> {
+        let header = DatabaseHeader::from(buf);
         if !header.validate_magic_header() {
             bail!("invalid magic header");
         } else if !header.validate_pagesize() {
@@ -152,48 +357,347 @@ impl Connection {
         } else if !header.validate_reserved() {
             bail!("invalid reserved");
         }
-        let pager = Pager::new(file, header.pagesize() as usize)?;
+        if header.schema_format().is_none() {
+            bail!("unsupported schema format");
+        }
+        let Some(text_encoding) = header.text_encoding() else {
+            bail!("invalid text encoding");
+        };
+        let pager = new_pager(header.pagesize() as usize)?;
         Ok(Self {
-            pager,
+            pager: Arc::new(pager),
             btree_ctx: BtreeContext::new(header.usable_size()),
+            text_encoding,
             schema: None,
         })
     }
 
-    pub fn prepare<'a>(&mut self, sql: &'a str) -> Result<'a, Statement> {
-        let input = sql.as_bytes();
-        let mut parser = Parser::new(input);
-        let select = parse_select(&mut parser)?;
-        expect_semicolon(&mut parser)?;
-        expect_no_more_token(&mut parser)?;
+    /// Verify the checksum of each page on loading it.
+    ///
+    /// The database must reserve 8 bytes at the tail of each page for the
+    /// checksum as the checksum VFS does. This fails if a clone of the
+    /// connection shares the pager.
+    /// https://www.sqlite.org/cksumvfs.html
+    pub fn enable_checksum_verification(&mut self) -> anyhow::Result<()> {
+        let page = self.pager.get_page(ROOT_PAGE_ID)?;
+        let buffer = page.buffer();
+        let header = DatabaseHeader::from(buffer[..DATABASE_HEADER_SIZE].try_into().unwrap());
+        if header.reserved() as usize != CHECKSUM_SIZE {
+            bail!(
+                "reserved bytes {} does not match checksum size",
+                header.reserved()
+            );
+        }
+        drop(page);
+        self.pager_mut()?.enable_checksum_verification();
+        // The schema may have been loaded from unverified pages.
+        self.schema = None;
+        Ok(())
+    }
+
+    /// The pager can be configured only if no clone shares it.
+    fn pager_mut(&mut self) -> anyhow::Result<&mut Pager> {
+        Arc::get_mut(&mut self.pager).context("pager is shared with cloned connections")
+    }
+
+    /// Iterate all the rows of the table.
+    ///
+    /// Values in each row are the record of the table btree except that the
+    /// INTEGER PRIMARY KEY column holds the rowid. Columns missing in the
+    /// record hold their default values. Integers in REAL columns are
+    /// converted to real values.
+    pub fn table_rows(&mut self, table_name: &str) -> anyhow::Result<TableRows<'_, '_>> {
+        self.load_schema()?;
+        let schema = self.schema.as_ref().unwrap();
+        let table = schema
+            .get_table(table_name.as_bytes())
+            .ok_or(anyhow::anyhow!("table not found: {:?}", table_name))?;
+        TableRows::new(
+            table.root_page_id,
+            &self.pager,
+            &self.btree_ctx,
+            self.text_encoding,
+            table.rowid_alias_column(),
+            table.default_values(),
+            table.type_affinities(),
+        )
+    }
 
+    /// Iterate all the entries of the index in index order.
+    ///
+    /// Each entry is the values of the indexed columns and the rowid of the
+    /// row in the table.
+    pub fn index_rows(&mut self, index_name: &str) -> anyhow::Result<IndexRows<'_, '_>> {
+        self.load_schema()?;
+        let schema = self.schema.as_ref().unwrap();
+        let index = schema
+            .get_index(index_name.as_bytes())
+            .ok_or(anyhow::anyhow!("index not found: {:?}", index_name))?;
+        IndexRows::new(
+            index.root_page_id,
+            &self.pager,
+            &self.btree_ctx,
+            self.text_encoding,
+            index.type_affinities.clone(),
+        )
+    }
+
+    /// Fail a scan if the database file is changed by another connection.
+    ///
+    /// A scan started or ended after a change fails, so that it never mixes
+    /// pages of different states. The change is detected by the file change
+    /// counter in the database header which SQLite increments on each
+    /// transaction. This fails if a clone of the connection shares the pager.
+    pub fn enable_change_detection(&mut self) -> anyhow::Result<()> {
+        self.pager_mut()?.enable_change_detection()?;
+        // The schema may have been loaded from another state.
+        self.schema = None;
+        Ok(())
+    }
+
+    /// Resolve the name of a table or an index to its root page id.
+    ///
+    /// Returns `None` if no such table or index exists.
+    pub fn root_page_of(&mut self, name: &str) -> anyhow::Result<Option<PageId>> {
+        Ok(self.load_schema()?.root_page_of(name.as_bytes()))
+    }
+
+    /// Count the rows of the table.
+    ///
+    /// This returns the same as `SELECT count(*) FROM <table>` but only walks
+    /// the btree pages without parsing any row.
+    pub fn count_rows(&mut self, table_name: &str) -> anyhow::Result<u64> {
+        self.load_schema()?;
+        let schema = self.schema.as_ref().unwrap();
+        let table = schema
+            .get_table(table_name.as_bytes())
+            .ok_or(anyhow::anyhow!("table not found: {:?}", table_name))?;
+        self.pager.check_unchanged()?;
+        let mut cursor = BtreeCursor::new(table.root_page_id, &self.pager, &self.btree_ctx)?;
+        let count = cursor.count_rows()?;
+        // The pages may be from different states.
+        self.pager.check_unchanged()?;
+        Ok(count)
+    }
+
+    /// Write the text or blob of the column in the row to the writer.
+    ///
+    /// The content is streamed page by page including the overflow pages, so
+    /// a large value is never loaded into memory at once. The text is written
+    /// as stored in the text encoding of the database.
+    ///
+    /// Returns the number of bytes written. Fails if the row does not exist or
+    /// the row does not store a text or a blob for the column.
+    pub fn write_column(
+        &mut self,
+        table_name: &str,
+        column_name: &str,
+        rowid: i64,
+        writer: &mut impl Write,
+    ) -> anyhow::Result<u64> {
+        self.load_schema()?;
+        let schema = self.schema.as_ref().unwrap();
+        let table = schema
+            .get_table(table_name.as_bytes())
+            .ok_or(anyhow::anyhow!("table not found: {:?}", table_name))?;
+        let column_idx = match table.get_column(column_name.as_bytes()) {
+            Some((ColumnNumber::Column(column_idx), _, _)) => column_idx,
+            Some((ColumnNumber::RowId, _, _)) => bail!("rowid is not text or blob"),
+            None => bail!("column not found: {:?}", column_name),
+        };
+        self.pager.check_unchanged()?;
+        let mut cursor = BtreeCursor::new(table.root_page_id, &self.pager, &self.btree_ctx)?;
+        cursor.table_move_to(rowid)?;
+        let payload = match cursor.get_table_payload()? {
+            Some((key, payload)) if key == rowid => payload,
+            _ => bail!("row not found: {}", rowid),
+        };
+        let mut record = Record::parse(&payload)?;
+        if column_idx >= record.len()? {
+            bail!("column is not stored in the row");
+        }
+        let n = record.read_column(column_idx, writer)?;
+        // The content may be from pages of different states.
+        self.pager.check_unchanged()?;
+        Ok(n)
+    }
+
+    /// Format the btree page header and the cell offsets of the page for
+    /// debugging.
+    pub fn dump_page(&self, page_id: PageId) -> anyhow::Result<String> {
+        debug::dump_page(&self.pager, page_id, self.btree_ctx.usable_size())
+    }
+
+    /// Walk all the btrees in the database and returns the problems found.
+    ///
+    /// This checks the cell pointers, the cell contents, the rowid order in
+    /// table btrees and the overflow chains, and that every page is referenced
+    /// once. Returns an empty list if no problem is found. A corrupted page does
+    /// not fail this but is reported as a problem.
+    pub fn integrity_check(&mut self) -> Vec<String> {
+        let entries = self.schema_entries();
+        let mut checker = IntegrityChecker::new(&self.pager, &self.btree_ctx);
+        checker.check_btree("table sqlite_schema", ROOT_PAGE_ID, Some(true));
+        let entries = match entries {
+            Ok(entries) => entries,
+            Err(e) => {
+                checker.add_problem(format!("failed to read sqlite_schema: {e}"));
+                return checker.into_problems();
+            }
+        };
+        for entry in entries {
+            let is_table = match entry.type_.as_slice() {
+                // The btree of a WITHOUT ROWID table is an index btree.
+                b"table" => None,
+                b"index" => Some(false),
+                _ => continue,
+            };
+            // Virtual tables have no btree.
+            if entry.root_page_id == 0 {
+                continue;
+            }
+            let tree = format!(
+                "{} {}",
+                String::from_utf8_lossy(&entry.type_),
+                String::from_utf8_lossy(&entry.name)
+            );
+            checker.check_btree(&tree, entry.root_page_id, is_table);
+        }
+        checker.into_problems()
+    }
+
+    /// Returns the ids of the unused pages in the freelist.
+    ///
+    /// The freelist trunk pages are included as well as the leaf pages.
+    pub fn freelist_pages(&self) -> anyhow::Result<Vec<PageId>> {
+        self.pager.freelist_pages()
+    }
+
+    /// Returns the page load counters of the connection.
+    pub fn pager_stats(&self) -> PagerStats {
+        self.pager.stats()
+    }
+
+    /// Returns all the rows of the sqlite_schema table.
+    pub fn schema_entries(&mut self) -> anyhow::Result<Vec<SchemaEntry>> {
+        let mut stmt = self.schema_statement();
+        let mut rows = stmt.execute()?;
+        let mut entries = Vec::new();
+        while let Some(row) = rows.next_row()? {
+            entries.push(SchemaEntry::parse(&row.parse()?)?);
+        }
+        Ok(entries)
+    }
+
+    /// Returns the row of the sqlite_schema table for the table, index, view
+    /// or trigger.
+    ///
+    /// Returns `None` if no such object exists. The name is case insensitive.
+    pub fn schema_entry(&mut self, name: &str) -> anyhow::Result<Option<SchemaEntry>> {
+        let name = CaseInsensitiveBytes::from(name.as_bytes());
+        Ok(self
+            .schema_entries()?
+            .into_iter()
+            .find(|entry| CaseInsensitiveBytes::from(entry.name.as_slice()) == name))
+    }
+
+    /// Scans all the columns of the sqlite_schema table.
+    fn schema_statement(&mut self) -> Statement<'_> {
+        let schema_table = Schema::schema_table();
+        let columns = schema_table
+            .get_all_columns()
+            .map(Expression::Column)
+            .collect::<Vec<_>>();
+        let column_names = schema_table
+            .columns
+            .iter()
+            .map(|column| Some(column.name.clone()))
+            .collect();
+        let plan = Plan::FullScan {
+            root: schema_table.root_page_id,
+        };
+        Statement::new(self, columns, column_names, None, plan, Vec::new())
+    }
+
+    fn load_schema(&mut self) -> anyhow::Result<&Schema> {
         if self.schema.is_none() {
-            let schema_table = Schema::schema_table();
-            let columns = schema_table
-                .get_all_columns()
-                .map(Expression::Column)
-                .collect::<Vec<_>>();
             self.schema = Some(Schema::generate(
-                Statement::new(self, schema_table.root_page_id, columns, None),
-                schema_table,
+                self.schema_statement(),
+                Schema::schema_table(),
             )?);
         }
-        let schema = self.schema.as_ref().unwrap();
-        let table_name = select.table_name.dequote();
+        Ok(self.schema.as_ref().unwrap())
+    }
+
+    /// Runs the SELECT statement and returns all the rows.
+    ///
+    /// The values are copied out of the pages. Use [Connection::prepare()] to
+    /// iterate over rows without loading all of them into memory.
+    pub fn query<'a>(&mut self, sql: &'a str) -> Result<'a, Vec<Vec<Value<'static>>>> {
+        let mut stmt = self.prepare(sql)?;
+        let rows = stmt.execute()?;
+        Ok(rows
+            .map(|row| row.map(|row| row.values))
+            .collect::<anyhow::Result<_>>()?)
+    }
+
+    pub fn prepare<'a>(&mut self, sql: &'a str) -> Result<'a, Statement<'_>> {
+        let select_stmt = parse_complete(sql.as_bytes(), |p| {
+            let select_stmt = parse_select_stmt(p)?;
+            expect_semicolon(p)?;
+            Ok(select_stmt)
+        })?;
+        if let Some(parameter) = select_stmt.parameters().first() {
+            let name = match parameter.name {
+                Some(name) => String::from_utf8_lossy(name).into_owned(),
+                None => format!("?{}", parameter.index),
+            };
+            return Err(anyhow::anyhow!("bind parameter {} is not supported yet", name).into());
+        } else if select_stmt.explain != ExplainMode::None {
+            return Err(anyhow::anyhow!("EXPLAIN is not supported yet").into());
+        } else if !select_stmt.compounds.is_empty() {
+            return Err(anyhow::anyhow!("compound select is not supported yet").into());
+        } else if !select_stmt.order_by.is_empty() {
+            return Err(anyhow::anyhow!("ORDER BY is not supported yet").into());
+        } else if select_stmt.limit.is_some() {
+            return Err(anyhow::anyhow!("LIMIT is not supported yet").into());
+        }
+        let select = select_stmt.select;
+        if !select.group_by.is_empty() || select.having.is_some() {
+            return Err(anyhow::anyhow!("GROUP BY is not supported yet").into());
+        }
+
+        if select.tables().count() > 1 {
+            return Err(anyhow::anyhow!("join is not supported yet").into());
+        }
+
+        let text_encoding = self.text_encoding;
+        let schema = self.load_schema()?;
+        let table_name = select.table.table_name.dequote();
         let table = schema.get_table(&table_name).ok_or(anyhow::anyhow!(
             "table not found: {:?}",
             std::str::from_utf8(&table_name).unwrap_or_default()
         ))?;
 
         let mut columns = Vec::new();
+        let mut column_names = Vec::new();
         for column in select.columns {
             match column {
                 ResultColumn::All => {
                     columns.extend(table.get_all_columns().map(Expression::Column));
+                    column_names
+                        .extend(table.columns.iter().map(|column| Some(column.name.clone())));
                 }
-                ResultColumn::Expr((expr, _alias)) => {
-                    // TODO: consider alias.
+                ResultColumn::Expr((expr, alias)) => {
+                    // TODO: Name expressions other than columns by their SQL text
+                    // as SQLite does.
+                    let name = match (alias, &expr) {
+                        (Some(alias), _) => Some(alias.dequote()),
+                        (None, Expr::Column(column_name)) => Some(column_name.dequote()),
+                        (None, _) => None,
+                    };
                     columns.push(Expression::from(expr, table)?);
+                    column_names.push(name);
                 }
                 ResultColumn::AllOfTable(_table_name) => {
                     todo!("ResultColumn::AllOfTable");
@@ -206,64 +710,16 @@ impl Connection {
             .map(|expr| Expression::from(expr, table))
             .transpose()?;
 
-        let index = if let Some(Expression::BinaryOperator {
-            operator: BinaryOp::Compare(CompareOp::Eq),
-            left,
-            right,
-        }) = &filter
-        {
-            if let Expression::Column((column_number, type_affinity, collation)) = left.as_ref() {
-                if let Expression::Const(const_value) = right.as_ref() {
-                    let mut next_index = table.indexes.as_ref();
-                    while let Some(index) = next_index {
-                        if index.columns[0] == *column_number {
-                            break;
-                        }
-                        next_index = index.next.as_ref();
-                    }
-                    if let Some(index) = next_index {
-                        let value = match type_affinity {
-                            TypeAffinity::Integer | TypeAffinity::Real | TypeAffinity::Numeric => {
-                                ConstantValue::copy_from(
-                                    const_value.as_value().apply_numeric_affinity(),
-                                )
-                            }
-                            TypeAffinity::Text => ConstantValue::copy_from(
-                                const_value.as_value().apply_text_affinity(),
-                            ),
-                            TypeAffinity::Blob => ConstantValue::copy_from(const_value.as_value()),
-                        };
-                        // TODO: Consider collation of constant value.
-                        Some(IndexInfo {
-                            page_id: index.root_page_id,
-                            keys: vec![(value, collation.clone())],
-                            n_extra: index.columns.len() - 1,
-                        })
-                    } else {
-                        None
-                    }
-                } else {
-                    None
-                }
-            } else {
-                None
-            }
-        } else {
-            None
-        };
-
-        let table_page_id = table.root_page_id;
-        if index.is_some() {
-            Ok(Statement::with_index(
-                self,
-                table_page_id,
-                columns,
-                filter,
-                index,
-            ))
-        } else {
-            Ok(Statement::new(self, table_page_id, columns, filter))
-        }
+        let plan = Plan::new(table, filter.as_ref(), text_encoding);
+        let default_values = table.default_values();
+        Ok(Statement::new(
+            self,
+            columns,
+            column_names,
+            filter,
+            plan,
+            default_values,
+        ))
     }
 }
 
@@ -286,7 +742,7 @@ impl ConstantValue {
         }
     }
 
-    fn as_value(&self) -> Value {
+    fn as_value(&self) -> Value<'_> {
         match self {
             Self::Integer(i) => Value::Integer(*i),
             Self::Real(f) => Value::Real(*f),
@@ -311,6 +767,41 @@ fn filter_expression_collation(
     }
 }
 
+/// Compare two non-null values after applying type conversions prior to
+/// comparison.
+///
+/// https://www.sqlite.org/datatype3.html#type_conversions_prior_to_comparison
+fn compare_values(
+    mut left_value: Value,
+    left_affinity: Option<TypeAffinity>,
+    mut right_value: Value,
+    right_affinity: Option<TypeAffinity>,
+    collation: &Collation,
+) -> Ordering {
+    match (left_affinity, right_affinity) {
+        (
+            Some(TypeAffinity::Integer) | Some(TypeAffinity::Real) | Some(TypeAffinity::Numeric),
+            Some(TypeAffinity::Text) | Some(TypeAffinity::Blob) | None,
+        ) => {
+            right_value = right_value.apply_numeric_affinity();
+        }
+        (
+            Some(TypeAffinity::Text) | Some(TypeAffinity::Blob) | None,
+            Some(TypeAffinity::Integer) | Some(TypeAffinity::Real) | Some(TypeAffinity::Numeric),
+        ) => {
+            left_value = left_value.apply_numeric_affinity();
+        }
+        (Some(TypeAffinity::Text), None) => {
+            right_value = right_value.apply_text_affinity();
+        }
+        (None, Some(TypeAffinity::Text)) => {
+            left_value = left_value.apply_text_affinity();
+        }
+        _ => {}
+    }
+    ValueCmp::new(&left_value, collation).compare(&right_value)
+}
+
 enum Expression {
     Column((ColumnNumber, TypeAffinity, Collation)),
     UnaryOperator {
@@ -369,29 +860,52 @@ impl Expression {
                 right: Box::new(Self::from(*right, table)?),
             }),
             Expr::Column(column_name) => {
-                let column_name = column_name.dequote();
-                table
-                    .get_column(&column_name)
-                    .map(Self::Column)
-                    .ok_or(anyhow::anyhow!(
+                let dequoted_name = column_name.dequote();
+                if let Some(column) = table.get_column(&dequoted_name) {
+                    Ok(Self::Column(column))
+                } else if let Some(v) = true_false_value(&column_name) {
+                    Ok(Self::Const(ConstantValue::Integer(v)))
+                } else {
+                    bail!(
                         "column not found: {}",
-                        std::str::from_utf8(&column_name).unwrap_or_default()
-                    ))
+                        std::str::from_utf8(&dequoted_name).unwrap_or_default()
+                    )
+                }
             }
+            Expr::QualifiedColumn { .. } => bail!("qualified column is not supported yet"),
             Expr::Cast { expr, type_name } => Ok(Self::Cast {
                 expr: Box::new(Self::from(*expr, table)?),
                 type_affinity: calc_type_affinity(&type_name),
             }),
+            Expr::Like { .. } => bail!("LIKE is not supported yet"),
+            Expr::Glob { .. } => bail!("GLOB is not supported yet"),
+            Expr::Regexp { .. } => bail!("REGEXP is not supported yet"),
+            Expr::In { .. } => bail!("IN is not supported yet"),
+            Expr::Between { .. } => bail!("BETWEEN is not supported yet"),
+            Expr::Aggregate { .. } => bail!("aggregate functions are not supported yet"),
+            Expr::Function { .. } => bail!("functions are not supported yet"),
+            Expr::Case { .. } => bail!("CASE is not supported yet"),
+            Expr::BindParameter(_) => bail!("bind parameters are not supported yet"),
+            Expr::CurrentTime(_) => bail!("current time is not supported yet"),
         }
     }
 
     fn execute<'a>(&'a self, row: &'a RowData) -> anyhow::Result<ExecutionResult<'a>> {
         match self {
-            Self::Column((idx, affinity, collation)) => Ok((
-                row.get_column_value(idx)?,
-                Some(*affinity),
-                Some((collation, CollateOrigin::Column)),
-            )),
+            Self::Column((idx, affinity, collation)) => {
+                let value = row.get_column_value(idx)?;
+                // Whole numbers in REAL columns may be stored as integers.
+                let value = if *affinity == TypeAffinity::Real {
+                    value.apply_real_affinity()
+                } else {
+                    value
+                };
+                Ok((
+                    value,
+                    Some(*affinity),
+                    Some((collation, CollateOrigin::Column)),
+                ))
+            }
             Self::UnaryOperator { operator, expr } => {
                 let (value, _, collation) = expr.execute(row)?;
                 let value = match operator {
@@ -408,6 +922,10 @@ impl Expression {
                         Value::Real(d) => Value::Real(-d),
                         Value::Text(_) | Value::Blob(_) => Value::Integer(0),
                     },
+                    UnaryOp::Not => match value.as_bool() {
+                        Some(b) => Value::Integer(!b as i64),
+                        None => Value::Null,
+                    },
                 };
                 Ok((value, None, filter_expression_collation(collation)))
             }
@@ -420,18 +938,45 @@ impl Expression {
                     Some((collation, CollateOrigin::Expression)),
                 ))
             }
+            Self::BinaryOperator {
+                operator: operator @ (BinaryOp::And | BinaryOp::Or),
+                left,
+                right,
+            } => {
+                let (left_value, _, _) = left.execute(row)?;
+                let (right_value, _, _) = right.execute(row)?;
+                let result = match (operator, left_value.as_bool(), right_value.as_bool()) {
+                    (BinaryOp::And, Some(false), _) | (BinaryOp::And, _, Some(false)) => {
+                        Some(false)
+                    }
+                    (BinaryOp::And, Some(true), Some(true)) => Some(true),
+                    (BinaryOp::Or, Some(true), _) | (BinaryOp::Or, _, Some(true)) => Some(true),
+                    (BinaryOp::Or, Some(false), Some(false)) => Some(false),
+                    // NULL if the other operand does not decide the result.
+                    _ => None,
+                };
+                let value = result.map_or(Value::Null, |result| Value::Integer(result as i64));
+                Ok((value, None, None))
+            }
             Self::BinaryOperator {
                 operator,
                 left,
                 right,
             } => {
-                let (mut left_value, left_affinity, left_collation) = left.execute(row)?;
-                let (mut right_value, right_affinity, right_collation) = right.execute(row)?;
+                let (left_value, left_affinity, left_collation) = left.execute(row)?;
+                let (right_value, right_affinity, right_collation) = right.execute(row)?;
 
                 // TODO: Confirm whether collation is preserved after NULL.
                 match (&left_value, &right_value) {
-                    (Value::Null, _) => return Ok((Value::Null, None, None)),
-                    (_, Value::Null) => return Ok((Value::Null, None, None)),
+                    (Value::Null, _) | (_, Value::Null) => {
+                        let both_null = left_value == Value::Null && right_value == Value::Null;
+                        let value = match operator {
+                            BinaryOp::Is => Value::Integer(both_null as i64),
+                            BinaryOp::IsNot => Value::Integer(!both_null as i64),
+                            _ => Value::Null,
+                        };
+                        return Ok((value, None, None));
+                    }
                     _ => {}
                 }
 
@@ -445,47 +990,26 @@ impl Expression {
                 let next_collation = filter_expression_collation(collation);
 
                 match operator {
-                    BinaryOp::Compare(compare_op) => {
-                        // Type Conversions Prior To Comparison
-                        match (left_affinity, right_affinity) {
-                            (
-                                Some(TypeAffinity::Integer)
-                                | Some(TypeAffinity::Real)
-                                | Some(TypeAffinity::Numeric),
-                                Some(TypeAffinity::Text) | Some(TypeAffinity::Blob) | None,
-                            ) => {
-                                right_value = right_value.apply_numeric_affinity();
-                            }
-                            (
-                                Some(TypeAffinity::Text) | Some(TypeAffinity::Blob) | None,
-                                Some(TypeAffinity::Integer)
-                                | Some(TypeAffinity::Real)
-                                | Some(TypeAffinity::Numeric),
-                            ) => {
-                                left_value = left_value.apply_numeric_affinity();
-                            }
-                            (Some(TypeAffinity::Text), None) => {
-                                right_value = right_value.apply_text_affinity();
+                    BinaryOp::Compare(_) | BinaryOp::Is | BinaryOp::IsNot => {
+                        let cmp = compare_values(
+                            left_value,
+                            left_affinity,
+                            right_value,
+                            right_affinity,
+                            collation.map(|(c, _)| c).unwrap_or(&DEFAULT_COLLATION),
+                        );
+                        let result = match operator {
+                            BinaryOp::Compare(CompareOp::Eq) | BinaryOp::Is => {
+                                cmp == Ordering::Equal
                             }
-                            (None, Some(TypeAffinity::Text)) => {
-                                left_value = left_value.apply_text_affinity();
+                            BinaryOp::Compare(CompareOp::Ne) | BinaryOp::IsNot => {
+                                cmp != Ordering::Equal
                             }
-                            _ => {}
-                        }
-
-                        let cmp = ValueCmp::new(
-                            &left_value,
-                            collation.map(|(c, _)| c).unwrap_or(&DEFAULT_COLLATION),
-                        )
-                        .compare(&right_value);
-
-                        let result = match compare_op {
-                            CompareOp::Eq => cmp == Ordering::Equal,
-                            CompareOp::Ne => cmp != Ordering::Equal,
-                            CompareOp::Lt => cmp == Ordering::Less,
-                            CompareOp::Le => cmp != Ordering::Greater,
-                            CompareOp::Gt => cmp == Ordering::Greater,
-                            CompareOp::Ge => cmp != Ordering::Less,
+                            BinaryOp::Compare(CompareOp::Lt) => cmp == Ordering::Less,
+                            BinaryOp::Compare(CompareOp::Le) => cmp != Ordering::Greater,
+                            BinaryOp::Compare(CompareOp::Gt) => cmp == Ordering::Greater,
+                            BinaryOp::Compare(CompareOp::Ge) => cmp != Ordering::Less,
+                            _ => unreachable!("not comparison"),
                         };
                         if result {
                             Ok((Value::Integer(1), None, next_collation))
@@ -509,6 +1033,22 @@ impl Expression {
                         buffer.extend(right.iter());
                         Ok((Value::Text(Buffer::Owned(buffer)), None, next_collation))
                     }
+                    BinaryOp::Add
+                    | BinaryOp::Sub
+                    | BinaryOp::Mul
+                    | BinaryOp::Div
+                    | BinaryOp::Mod => {
+                        let value = match operator {
+                            BinaryOp::Add => left_value.sqlite_add(&right_value),
+                            BinaryOp::Sub => left_value.sqlite_sub(&right_value),
+                            BinaryOp::Mul => left_value.sqlite_mul(&right_value),
+                            BinaryOp::Div => left_value.sqlite_div(&right_value),
+                            BinaryOp::Mod => left_value.sqlite_rem(&right_value),
+                            _ => unreachable!("not arithmetic"),
+                        };
+                        Ok((value, None, next_collation))
+                    }
+                    BinaryOp::And | BinaryOp::Or => unreachable!("logical operator"),
                 }
             }
             Self::Cast {
@@ -528,101 +1068,163 @@ impl Expression {
     }
 }
 
-struct IndexInfo {
-    page_id: PageId,
-    keys: Vec<(ConstantValue, Collation)>,
-    n_extra: usize,
+/// Execution plan of a SELECT statement.
+enum Plan {
+    /// Scan all rows in the table.
+    FullScan { root: PageId },
+    /// Move to the row of the rowid in the table.
+    RowIdSeek { root: PageId, rowid: i64 },
+    /// Seek the index by the keys and look up the table by the rowids in the
+    /// index.
+    IndexSeek {
+        index_root: PageId,
+        table_root: PageId,
+        keys: Vec<(ConstantValue, Collation)>,
+        /// The number of index columns not included in keys.
+        n_extra: usize,
+    },
+}
+
+impl Plan {
+    /// Choose a plan for the filter.
+    ///
+    /// This uses a rowid or an index if the filter is an equality between a
+    /// column and a constant value. Otherwise it falls back to a full scan.
+    fn new(table: &Table, filter: Option<&Expression>, text_encoding: TextEncoding) -> Self {
+        let full_scan = Self::FullScan {
+            root: table.root_page_id,
+        };
+        let Some(Expression::BinaryOperator {
+            operator: BinaryOp::Compare(CompareOp::Eq),
+            left,
+            right,
+        }) = filter
+        else {
+            return full_scan;
+        };
+        let ((column_number, type_affinity, collation), const_value) =
+            match (left.as_ref(), right.as_ref()) {
+                (Expression::Column(column), Expression::Const(const_value))
+                | (Expression::Const(const_value), Expression::Column(column)) => {
+                    (column, const_value)
+                }
+                _ => return full_scan,
+            };
+
+        if let ColumnNumber::RowId = column_number {
+            // The rowid and its alias column have integer affinity.
+            return match const_value.as_value().apply_numeric_affinity() {
+                Value::Integer(rowid) => Self::RowIdSeek {
+                    root: table.root_page_id,
+                    rowid,
+                },
+                _ => full_scan,
+            };
+        }
+
+        let mut next_index = table.indexes.as_ref();
+        while let Some(index) = next_index {
+            if index.columns[0] == *column_number {
+                break;
+            }
+            next_index = index.next.as_ref();
+        }
+        let Some(index) = next_index else {
+            return full_scan;
+        };
+        let value = match type_affinity {
+            TypeAffinity::Integer | TypeAffinity::Real | TypeAffinity::Numeric => {
+                ConstantValue::copy_from(const_value.as_value().apply_numeric_affinity())
+            }
+            TypeAffinity::Text => {
+                ConstantValue::copy_from(const_value.as_value().apply_text_affinity())
+            }
+            TypeAffinity::Blob => ConstantValue::copy_from(const_value.as_value()),
+        };
+        // Text keys in the index are in the text encoding of the database while
+        // the key is in UTF-8.
+        // TODO: Seek UTF-16 indexes with the key in the text encoding.
+        if text_encoding != TextEncoding::Utf8 && matches!(value.as_value(), Value::Text(_)) {
+            return full_scan;
+        }
+        // TODO: Consider collation of constant value.
+        Self::IndexSeek {
+            index_root: index.root_page_id,
+            table_root: table.root_page_id,
+            keys: vec![(value, collation.clone())],
+            n_extra: index.columns.len() - 1,
+        }
+    }
 }
 
 // TODO: make Connection non mut and support multiple statements.
 pub struct Statement<'conn> {
     conn: &'conn mut Connection,
-    table_page_id: PageId,
     columns: Vec<Expression>,
+    /// The names of the result columns. None if the column has no name.
+    column_names: Vec<Option<Vec<u8>>>,
     filter: Option<Expression>,
-    rowid: Option<i64>,
-    index: Option<IndexInfo>,
+    plan: Plan,
+    /// The default values of the columns missing in short records.
+    default_values: Vec<Option<Value<'static>>>,
 }
 
 impl<'conn> Statement<'conn> {
     pub(crate) fn new(
         conn: &'conn mut Connection,
-        table_page_id: PageId,
         columns: Vec<Expression>,
+        column_names: Vec<Option<Vec<u8>>>,
         filter: Option<Expression>,
+        plan: Plan,
+        default_values: Vec<Option<Value<'static>>>,
     ) -> Self {
-        let rowid = match &filter {
-            Some(Expression::BinaryOperator {
-                operator: BinaryOp::Compare(CompareOp::Eq),
-                left,
-                right,
-            }) => match (left.as_ref(), right.as_ref()) {
-                (
-                    Expression::Column((ColumnNumber::RowId, _, _)),
-                    Expression::Const(ConstantValue::Integer(value)),
-                ) => Some(*value),
-                (
-                    Expression::Const(ConstantValue::Integer(value)),
-                    Expression::Column((ColumnNumber::RowId, _, _)),
-                ) => Some(*value),
-                _ => None,
-            },
-            _ => None,
-        };
         Self {
             conn,
-            table_page_id,
             columns,
+            column_names,
             filter,
-            rowid,
-            index: None,
-        }
-    }
-
-    fn with_index(
-        conn: &'conn mut Connection,
-        table_page_id: PageId,
-        columns: Vec<Expression>,
-        filter: Option<Expression>,
-        index: Option<IndexInfo>,
-    ) -> Self {
-        Self {
-            conn,
-            table_page_id,
-            columns,
-            filter,
-            rowid: None,
-            index,
+            plan,
+            default_values,
         }
     }
 
     pub fn execute(&'conn mut self) -> anyhow::Result<Rows<'conn>> {
         // TODO: check schema version.
-        let mut cursor =
-            BtreeCursor::new(self.table_page_id, &self.conn.pager, &self.conn.btree_ctx)?;
-        let index_cursor = if let Some(rowid) = self.rowid {
-            cursor.table_move_to(rowid)?;
-            None
-        } else if let Some(index) = &self.index {
-            let mut index_cursor =
-                BtreeCursor::new(index.page_id, &self.conn.pager, &self.conn.btree_ctx)?;
-            // TODO: IndexInfo should hold ValueCmp instead of ConstantValue.
-            let tmp_keys = index
-                .keys
-                .iter()
-                .map(|(v, c)| (v.as_value(), c))
-                .collect::<Vec<_>>();
-            let mut keys = Vec::with_capacity(index.keys.len() + index.n_extra + 1);
-            keys.extend(tmp_keys.iter().map(|(v, c)| ValueCmp::new(v, c)));
-            // +1 for rowid
-            keys.extend(
-                (0..index.n_extra + 1).map(|_| ValueCmp::new(&Value::Null, &DEFAULT_COLLATION)),
-            );
-            index_cursor.index_move_to(&keys)?;
-            Some(index_cursor)
-        } else {
-            cursor.move_to_first()?;
-            None
+        self.conn.pager.check_unchanged()?;
+        let (cursor, index_cursor) = match &self.plan {
+            Plan::FullScan { root } => {
+                let mut cursor = BtreeCursor::new(*root, &self.conn.pager, &self.conn.btree_ctx)?;
+                cursor.move_to_first()?;
+                (cursor, None)
+            }
+            Plan::RowIdSeek { root, rowid } => {
+                let mut cursor = BtreeCursor::new(*root, &self.conn.pager, &self.conn.btree_ctx)?;
+                cursor.table_move_to(*rowid)?;
+                (cursor, None)
+            }
+            Plan::IndexSeek {
+                index_root,
+                table_root,
+                keys: index_keys,
+                n_extra,
+            } => {
+                let cursor = BtreeCursor::new(*table_root, &self.conn.pager, &self.conn.btree_ctx)?;
+                let mut index_cursor =
+                    BtreeCursor::new(*index_root, &self.conn.pager, &self.conn.btree_ctx)?;
+                // TODO: Plan::IndexSeek should hold ValueCmp instead of ConstantValue.
+                let tmp_keys = index_keys
+                    .iter()
+                    .map(|(v, c)| (v.as_value(), c))
+                    .collect::<Vec<_>>();
+                let mut keys = Vec::with_capacity(index_keys.len() + n_extra + 1);
+                keys.extend(tmp_keys.iter().map(|(v, c)| ValueCmp::new(v, c)));
+                // +1 for rowid
+                keys.extend(
+                    (0..n_extra + 1).map(|_| ValueCmp::new(&Value::Null, &DEFAULT_COLLATION)),
+                );
+                index_cursor.index_move_to(&keys)?;
+                (cursor, Some(index_cursor))
+            }
         };
         Ok(Rows {
             stmt: self,
@@ -657,6 +1259,8 @@ impl<'conn> Rows<'conn> {
                 Ok(true) => {}
                 Ok(false) => {
                     self.completed = true;
+                    // The rows may be from pages of different states.
+                    self.stmt.conn.pager.check_unchanged()?;
                     return Ok(None);
                 }
                 Err(e) => {
@@ -666,6 +1270,8 @@ impl<'conn> Rows<'conn> {
             }
 
             let Some((rowid, payload)) = self.cursor.get_table_payload()? else {
+                self.completed = true;
+                self.stmt.conn.pager.check_unchanged()?;
                 return Ok(None);
             };
 
@@ -678,7 +1284,9 @@ impl<'conn> Rows<'conn> {
             content_offset = headers[0].1;
             let last_header = &headers[headers.len() - 1];
             let content_size = last_header.1 + last_header.0.content_size() - content_offset;
-            assert!(content_offset + content_size <= payload.size());
+            if content_offset + content_size > payload.size() {
+                bail!("record content exceeds payload");
+            }
             use_local_buffer = payload.buf().len() >= (content_offset + content_size) as usize;
             if !use_local_buffer {
                 tmp_buf.resize(content_size as usize, 0);
@@ -690,6 +1298,8 @@ impl<'conn> Rows<'conn> {
 
             if let Some(filter) = &self.stmt.filter {
                 let data = RowData {
+                    text_encoding: self.stmt.conn.text_encoding,
+                    default_values: &self.stmt.default_values,
                     rowid,
                     payload,
                     tmp_buf,
@@ -699,6 +1309,8 @@ impl<'conn> Rows<'conn> {
                 };
                 let skip = matches!(filter.execute(&data)?.0, Value::Null | Value::Integer(0));
                 RowData {
+                    text_encoding: _,
+                    default_values: _,
                     rowid: _,
                     payload: _,
                     tmp_buf,
@@ -722,6 +1334,8 @@ impl<'conn> Rows<'conn> {
         Ok(Some(Row {
             stmt: self.stmt,
             data: RowData {
+                text_encoding: self.stmt.conn.text_encoding,
+                default_values: &self.stmt.default_values,
                 headers,
                 rowid,
                 payload,
@@ -732,10 +1346,28 @@ impl<'conn> Rows<'conn> {
         }))
     }
 
+    /// Returns the next row whose values are copied out of the pages.
+    ///
+    /// Unlike [Rows::next_row()], the row does not borrow the rows, so rows can
+    /// be collected. Returns None after all the rows or an error are returned.
+    pub fn next_owned(&mut self) -> Option<anyhow::Result<OwnedRow>> {
+        let result = match self.next_row() {
+            Ok(Some(row)) => row.parse().map(|columns| OwnedRow {
+                values: columns.values.into_iter().map(Value::into_owned).collect(),
+            }),
+            Ok(None) => return None,
+            Err(e) => Err(e),
+        };
+        if result.is_err() {
+            self.completed = true;
+        }
+        Some(result)
+    }
+
     fn move_next(&mut self) -> anyhow::Result<bool> {
         if self.is_first_row {
             self.is_first_row = false;
-        } else if self.stmt.rowid.is_some() {
+        } else if let Plan::RowIdSeek { .. } = self.stmt.plan {
             // Only one row is selected.
             return Ok(false);
         } else if let Some(index_cursor) = &mut self.index_cursor {
@@ -748,10 +1380,11 @@ impl<'conn> Rows<'conn> {
                 return Ok(false);
             };
             let mut record = Record::parse(&index_payload)?;
-            // self.stmt.index must be present if self.index_cursor is present.
-            assert!(self.stmt.index.is_some());
-            let keys = self.stmt.index.as_ref().unwrap().keys.as_slice();
-            if record.len() < keys.len() {
+            let Plan::IndexSeek { keys, .. } = &self.stmt.plan else {
+                unreachable!("index cursor without index plan");
+            };
+            let n_columns = record.len()?;
+            if n_columns < keys.len() {
                 bail!("index payload is too short");
             }
             for (i, (key, collation)) in keys.iter().enumerate() {
@@ -761,7 +1394,7 @@ impl<'conn> Rows<'conn> {
                     return Ok(false);
                 }
             }
-            let Value::Integer(rowid) = record.get(record.len() - 1)? else {
+            let Some(rowid) = record.get_integer(n_columns - 1)? else {
                 bail!("rowid in index is not integer");
             };
             self.cursor.table_move_to(rowid)?;
@@ -773,6 +1406,8 @@ impl<'conn> Rows<'conn> {
 const STATIC_NULL_VALUE: Value = Value::Null;
 
 struct RowData<'a> {
+    text_encoding: TextEncoding,
+    default_values: &'a [Option<Value<'static>>],
     rowid: i64,
     payload: BtreePayload<'a, 'a>,
     headers: Vec<(SerialType, i32)>,
@@ -782,7 +1417,7 @@ struct RowData<'a> {
 }
 
 impl<'a> RowData<'a> {
-    fn get_column_value(&self, column_idx: &ColumnNumber) -> anyhow::Result<Value> {
+    fn get_column_value(&self, column_idx: &ColumnNumber) -> anyhow::Result<Value<'_>> {
         match column_idx {
             ColumnNumber::Column(idx) => {
                 if let Some((serial_type, offset)) = self.headers.get(*idx) {
@@ -793,9 +1428,16 @@ impl<'a> RowData<'a> {
                     };
                     serial_type
                         .parse(&contents_buffer[(offset - self.content_offset) as usize..])
+                        .map(|v| v.decode_text(self.text_encoding))
                         .context("parse value")
                 } else {
-                    Ok(STATIC_NULL_VALUE)
+                    // The record can be shorter than the table if columns are
+                    // added by ALTER TABLE ADD COLUMN after the row is inserted.
+                    match self.default_values.get(*idx) {
+                        Some(Some(value)) => Ok(value.as_borrowed()),
+                        Some(None) => bail!("unsupported default value of missing column"),
+                        None => Ok(STATIC_NULL_VALUE),
+                    }
                 }
             }
             ColumnNumber::RowId => Ok(Value::Integer(self.rowid)),
@@ -803,11 +1445,25 @@ impl<'a> RowData<'a> {
     }
 }
 
+impl Iterator for Rows<'_> {
+    type Item = anyhow::Result<OwnedRow>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.next_owned()
+    }
+}
+
 pub struct Row<'a> {
     stmt: &'a Statement<'a>,
     data: RowData<'a>,
 }
 
+/// A row of the results of a statement which owns its values.
+#[derive(Debug, PartialEq)]
+pub struct OwnedRow {
+    pub values: Vec<Value<'static>>,
+}
+
 impl<'a> Row<'a> {
     pub fn parse(&self) -> anyhow::Result<Columns<'_>> {
         let mut columns = Vec::with_capacity(self.stmt.columns.len());
@@ -815,27 +1471,55 @@ impl<'a> Row<'a> {
             let (value, _, _) = expr.execute(&self.data)?;
             columns.push(value);
         }
-        Ok(Columns(columns))
+        Ok(Columns {
+            values: columns,
+            names: &self.stmt.column_names,
+        })
     }
 }
 
-pub struct Columns<'a>(Vec<Value<'a>>);
+pub struct Columns<'a> {
+    values: Vec<Value<'a>>,
+    names: &'a [Option<Vec<u8>>],
+}
 
 impl<'a> Columns<'a> {
     pub fn get(&self, i: usize) -> &Value<'a> {
-        self.0.get(i).unwrap_or(&STATIC_NULL_VALUE)
+        self.values.get(i).unwrap_or(&STATIC_NULL_VALUE)
+    }
+
+    /// Returns the value of the column with the name.
+    ///
+    /// The name is case insensitive. If multiple columns have the name, the
+    /// first one is returned. Returns None if no column has the name.
+    pub fn get_by_name(&self, name: &str) -> Option<&Value<'a>> {
+        let name = CaseInsensitiveBytes::from(name.as_bytes());
+        let i = self.names.iter().position(|column_name| {
+            column_name
+                .as_ref()
+                .is_some_and(|column_name| CaseInsensitiveBytes::from(column_name) == name)
+        })?;
+        Some(self.get(i))
+    }
+
+    /// Returns the name of the column.
+    ///
+    /// Returns None if the column has no name. e.g. an expression without an
+    /// alias.
+    pub fn name(&self, i: usize) -> Option<&[u8]> {
+        self.names.get(i)?.as_deref()
     }
 
     pub fn len(&self) -> usize {
-        self.0.len()
+        self.values.len()
     }
 
     pub fn is_empty(&self) -> bool {
-        self.0.is_empty()
+        self.values.is_empty()
     }
 
     pub fn iter(&self) -> impl Iterator<Item = &Value<'a>> {
-        self.0.iter()
+        self.values.iter()
     }
 }
 
@@ -881,5 +1565,232 @@ mod tests {
         assert_eq!(header.pagesize(), 4096);
         assert!(header.validate_pagesize());
         assert!(header.validate_reserved());
+        assert_eq!(header.usable_size(), 4096);
+    }
+
+    #[test]
+    fn usable_size() {
+        for (pagesize, reserved, usable_size, valid) in [
+            (1_u16, 0, 65536, true),
+            (1, 255, 65281, true),
+            (4096, 8, 4088, true),
+            (512, 32, 480, true),
+            (512, 33, 479, false),
+            (512, 255, 257, false),
+        ] {
+            let mut buf = [0_u8; DATABASE_HEADER_SIZE];
+            buf[16..18].copy_from_slice(&pagesize.to_be_bytes());
+            buf[20] = reserved;
+            let header = DatabaseHeader::from(&buf);
+
+            assert_eq!(header.usable_size(), usable_size);
+            assert_eq!(header.validate_reserved(), valid);
+        }
+    }
+
+    #[test]
+    fn schema_format_and_text_encoding() {
+        for (schema_format, expected) in [
+            (0_u32, Some(SchemaFormat::Format1)),
+            (1, Some(SchemaFormat::Format1)),
+            (2, Some(SchemaFormat::Format2)),
+            (3, Some(SchemaFormat::Format3)),
+            (4, Some(SchemaFormat::Format4)),
+            (5, None),
+            (0x01000000, None),
+        ] {
+            let mut buf = [0_u8; DATABASE_HEADER_SIZE];
+            buf[44..48].copy_from_slice(&schema_format.to_be_bytes());
+            assert_eq!(DatabaseHeader::from(&buf).schema_format(), expected);
+        }
+
+        for (text_encoding, expected) in [
+            (0_u32, None),
+            (1, Some(TextEncoding::Utf8)),
+            (2, Some(TextEncoding::Utf16le)),
+            (3, Some(TextEncoding::Utf16be)),
+            (4, None),
+            (0x01000000, None),
+        ] {
+            let mut buf = [0_u8; DATABASE_HEADER_SIZE];
+            buf[56..60].copy_from_slice(&text_encoding.to_be_bytes());
+            assert_eq!(DatabaseHeader::from(&buf).text_encoding(), expected);
+        }
+
+        let file = create_sqlite_database(&["CREATE TABLE example(col);"]);
+        let mut buf = [0_u8; DATABASE_HEADER_SIZE];
+        file.as_file().read_exact_at(&mut buf, 0).unwrap();
+        let header = DatabaseHeader::from(&buf);
+        assert_eq!(header.schema_format(), Some(SchemaFormat::Format4));
+        assert_eq!(header.text_encoding(), Some(TextEncoding::Utf8));
+
+        for (offset, value, msg) in [
+            (44, 5_u32, "unsupported schema format"),
+            (56, 4, "invalid text encoding"),
+        ] {
+            let mut content = std::fs::read(file.path()).unwrap();
+            content[offset..offset + 4].copy_from_slice(&value.to_be_bytes());
+            let e = Connection::open_reader(std::io::Cursor::new(content)).err();
+            assert_eq!(e.unwrap().to_string(), msg);
+        }
+    }
+
+    #[test]
+    fn user_version_and_application_id() {
+        let file = create_sqlite_database(&["CREATE TABLE example(col);"]);
+        let mut buf = [0_u8; DATABASE_HEADER_SIZE];
+        file.as_file().read_exact_at(&mut buf, 0).unwrap();
+        let header = DatabaseHeader::from(&buf);
+        assert_eq!(header.user_version(), 0);
+        assert_eq!(header.application_id(), 0);
+
+        for (user_version, application_id) in
+            [(1_i32, 0x12345678_u32), (-2, 0xffffffff), (i32::MAX, 1)]
+        {
+            let file = create_sqlite_database(&[
+                &format!("PRAGMA user_version = {user_version};"),
+                &format!("PRAGMA application_id = {};", application_id as i32),
+            ]);
+            file.as_file().read_exact_at(&mut buf, 0).unwrap();
+            let header = DatabaseHeader::from(&buf);
+            assert_eq!(header.user_version(), user_version);
+            assert_eq!(header.application_id(), application_id);
+        }
+    }
+
+    #[test]
+    fn enable_checksum_verification_without_reserved_bytes() {
+        let file = create_sqlite_database(&["CREATE TABLE example(col);"]);
+        let mut conn = Connection::open(file.path()).unwrap();
+
+        assert!(conn.enable_checksum_verification().is_err());
+        assert!(conn.prepare("SELECT * FROM example;").is_ok());
+    }
+
+    #[test]
+    fn plan_select() {
+        let file = create_sqlite_database(&[
+            "CREATE TABLE example(col1, col2, col3);",
+            "CREATE INDEX index1 ON example(col2, col3);",
+        ]);
+        let table_page_id = find_table_page_id("example", file.path());
+        let index_page_id = find_index_page_id("index1", file.path());
+        let mut conn = Connection::open(file.path()).unwrap();
+
+        for query in [
+            "SELECT * FROM example;",
+            "SELECT * FROM example WHERE col1 = 1;",
+            "SELECT * FROM example WHERE col3 = 1;",
+            "SELECT * FROM example WHERE col2 > 1;",
+            "SELECT * FROM example WHERE col2 = col1;",
+            "SELECT * FROM example WHERE rowid = 'a';",
+            "SELECT * FROM example WHERE rowid = 1.5;",
+            "SELECT * FROM example WHERE rowid = NULL;",
+        ] {
+            let stmt = conn.prepare(query).unwrap();
+            assert!(
+                matches!(stmt.plan, Plan::FullScan { root } if root == table_page_id),
+                "{query}"
+            );
+        }
+
+        for query in [
+            "SELECT * FROM example WHERE rowid = 10;",
+            "SELECT * FROM example WHERE 10 = rowid;",
+            "SELECT * FROM example WHERE rowid = '10';",
+        ] {
+            let stmt = conn.prepare(query).unwrap();
+            assert!(
+                matches!(stmt.plan, Plan::RowIdSeek { root, rowid: 10 } if root == table_page_id),
+                "{query}"
+            );
+        }
+
+        for query in [
+            "SELECT * FROM example WHERE col2 = 10;",
+            "SELECT * FROM example WHERE 10 = col2;",
+        ] {
+            let stmt = conn.prepare(query).unwrap();
+            let Plan::IndexSeek {
+                index_root,
+                table_root,
+                keys,
+                n_extra,
+            } = &stmt.plan
+            else {
+                panic!("{query}");
+            };
+            assert_eq!(*index_root, index_page_id);
+            assert_eq!(*table_root, table_page_id);
+            assert_eq!(keys.len(), 1);
+            assert_eq!(keys[0].0.as_value(), Value::Integer(10));
+            assert_eq!(*n_extra, 1);
+        }
+    }
+
+    #[test]
+    fn plan_select_utf16() {
+        let file = create_sqlite_database(&[
+            "PRAGMA encoding = 'UTF-16le';",
+            "CREATE TABLE example(col1, col2 TEXT);",
+            "CREATE INDEX index1 ON example(col1);",
+            "CREATE INDEX index2 ON example(col2);",
+        ]);
+        let table_page_id = find_table_page_id("example", file.path());
+        let index_page_id = find_index_page_id("index1", file.path());
+        let mut conn = Connection::open(file.path()).unwrap();
+
+        // Text keys can not seek indexes of a UTF-16 database.
+        for query in [
+            "SELECT * FROM example WHERE col1 = 'a';",
+            "SELECT * FROM example WHERE col2 = 10;",
+        ] {
+            let stmt = conn.prepare(query).unwrap();
+            assert!(
+                matches!(stmt.plan, Plan::FullScan { root } if root == table_page_id),
+                "{query}"
+            );
+        }
+
+        let stmt = conn
+            .prepare("SELECT * FROM example WHERE col1 = 10;")
+            .unwrap();
+        assert!(matches!(
+            stmt.plan,
+            Plan::IndexSeek { index_root, .. } if index_root == index_page_id
+        ));
+    }
+
+    #[test]
+    fn plan_select_rowid_alias() {
+        let file = create_sqlite_database(&[
+            "CREATE TABLE example(id INTEGER PRIMARY KEY, col);",
+            "CREATE TABLE example2(id INT PRIMARY KEY, col);",
+        ]);
+        let table_page_id = find_table_page_id("example", file.path());
+        let table2_page_id = find_table_page_id("example2", file.path());
+        let mut conn = Connection::open(file.path()).unwrap();
+
+        for query in [
+            "SELECT * FROM example WHERE id = 10;",
+            "SELECT * FROM example WHERE 10 = id;",
+            "SELECT * FROM example WHERE id = '10';",
+        ] {
+            let stmt = conn.prepare(query).unwrap();
+            assert!(
+                matches!(stmt.plan, Plan::RowIdSeek { root, rowid: 10 } if root == table_page_id),
+                "{query}"
+            );
+        }
+
+        // INT PRIMARY KEY is not an alias of the rowid.
+        let stmt = conn
+            .prepare("SELECT * FROM example2 WHERE id = 10;")
+            .unwrap();
+        assert!(!matches!(stmt.plan, Plan::RowIdSeek { .. }));
+        let stmt = conn
+            .prepare("SELECT * FROM example2 WHERE rowid = 10;")
+            .unwrap();
+        assert!(matches!(stmt.plan, Plan::RowIdSeek { root, rowid: 10 } if root == table2_page_id));
     }
 }
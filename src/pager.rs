@@ -12,101 +12,1134 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 
-use std::cell::Ref;
-use std::cell::RefCell;
-use std::collections::hash_map::Entry;
 use std::collections::HashMap;
 use std::fs::File;
+use std::io::Read;
+use std::io::Seek;
+use std::io::SeekFrom;
+#[cfg(feature = "mmap")]
+use std::ops::Deref;
+#[cfg(feature = "mmap")]
+use std::ops::Range;
+#[cfg(feature = "mmap")]
+use std::os::fd::AsRawFd;
 use std::os::unix::fs::FileExt;
-use std::rc::Rc;
+use std::sync::atomic::AtomicU64;
+use std::sync::atomic::Ordering;
+use std::sync::Arc;
+use std::sync::Mutex;
+use std::sync::RwLock;
 
 use anyhow::bail;
 
+use crate::DatabaseHeader;
 use crate::DATABASE_HEADER_SIZE;
 
 pub type PageId = u32;
-// The size of a page is more than 512.
-pub type PageBuffer<'a> = Ref<'a, Vec<u8>>;
 
 pub const ROOT_PAGE_ID: PageId = 1;
 
+/// The offset of the file change counter in the database header.
+const CHANGE_COUNTER_OFFSET: usize = 24;
+
+/// The size of the checksum at the tail of each page.
+///
+/// This is the reserved bytes used by the checksum VFS.
+/// https://www.sqlite.org/cksumvfs.html
+pub const CHECKSUM_SIZE: usize = 8;
+
+/// The offset of the page in the file.
+///
+/// This is computed in u64 because a database can be larger than 4GiB, which
+/// overflows usize on 32-bit targets.
+fn page_offset(id: PageId, pagesize: usize) -> u64 {
+    (id as u64 - 1) * pagesize as u64
+}
+
+/// A reader which pages can be loaded from.
+trait ReadSeek: Read + Seek + Send {}
+
+impl<T: Read + Seek + Send> ReadSeek for T {}
+
+enum Source {
+    File(File),
+    Reader(Mutex<Box<dyn ReadSeek>>),
+}
+
+impl Source {
+    fn read_exact_at(&self, buf: &mut [u8], offset: u64) -> std::io::Result<()> {
+        match self {
+            Self::File(file) => file.read_exact_at(buf, offset),
+            Self::Reader(reader) => {
+                let mut reader = reader.lock().unwrap();
+                reader.seek(SeekFrom::Start(offset))?;
+                reader.read_exact(buf)
+            }
+        }
+    }
+}
+
+/// Counters of page loads for profiling the page cache.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct PagerStats {
+    /// The number of pages read from the file or the reader.
+    ///
+    /// With a memory mapped file, every access to a page is counted as a read
+    /// and neither a cache hit nor a cache miss.
+    pub pages_read: u64,
+    /// The number of page accesses served from the page cache.
+    pub cache_hits: u64,
+    /// The number of page accesses not found in the page cache.
+    ///
+    /// This includes the accesses which fail to load the page.
+    pub cache_misses: u64,
+}
+
+/// Atomic counters of [PagerStats] updated by threads without a lock.
+#[derive(Default)]
+struct AtomicPagerStats {
+    pages_read: AtomicU64,
+    cache_hits: AtomicU64,
+    cache_misses: AtomicU64,
+}
+
+impl AtomicPagerStats {
+    fn load(&self) -> PagerStats {
+        PagerStats {
+            pages_read: self.pages_read.load(Ordering::Relaxed),
+            cache_hits: self.cache_hits.load(Ordering::Relaxed),
+            cache_misses: self.cache_misses.load(Ordering::Relaxed),
+        }
+    }
+}
+
+/// The pager can be shared by threads, e.g. with [Arc], to read pages
+/// concurrently.
 pub struct Pager {
-    file: File,
+    source: Source,
     n_pages: u32,
+    /// The size of the database in pages. This can be smaller than n_pages.
+    page_count: u32,
     cache: PageCache,
+    verify_checksum: bool,
+    /// The file change counter when the change detection is enabled.
+    expected_change_counter: Option<u32>,
+    stats: AtomicPagerStats,
+    #[cfg(feature = "mmap")]
+    mmap: Option<Arc<Mmap>>,
 }
 
 impl Pager {
-    pub fn new(file: File, pagesize: usize) -> anyhow::Result<Self> {
+    /// Creates a pager with the page size in the database header of the file.
+    pub fn new(file: File) -> anyhow::Result<Self> {
+        let mut buf = [0; DATABASE_HEADER_SIZE];
+        file.read_exact_at(&mut buf, 0)?;
+        let header = DatabaseHeader::from(&buf);
+        if !header.validate_pagesize() {
+            bail!("invalid pagesize");
+        }
+        Self::with_pagesize(file, header.pagesize() as usize)
+    }
+
+    /// Creates a pager with the explicit page size.
+    ///
+    /// This is for a file without the database header. e.g. a fragment of pages.
+    pub fn with_pagesize(file: File, pagesize: usize) -> anyhow::Result<Self> {
         let file_len = file.metadata()?.len();
-        assert!(file_len % pagesize as u64 == 0);
-        let n_pages = file_len / (pagesize as u64);
+        Self::with_source(Source::File(file), file_len, pagesize)
+    }
+
+    /// Creates a pager which reads pages from the reader.
+    ///
+    /// e.g. a [std::io::Cursor] of a database image in memory.
+    pub fn from_reader(
+        mut reader: impl Read + Seek + Send + 'static,
+        pagesize: usize,
+    ) -> anyhow::Result<Self> {
+        let len = reader.seek(SeekFrom::End(0))?;
+        Self::with_source(Source::Reader(Mutex::new(Box::new(reader))), len, pagesize)
+    }
+
+    fn with_source(source: Source, file_len: u64, pagesize: usize) -> anyhow::Result<Self> {
+        if file_len % pagesize as u64 != 0 {
+            bail!(
+                "file size {} is not a multiple of page size {}",
+                file_len,
+                pagesize
+            );
+        }
+        let n_pages = (file_len / (pagesize as u64)).try_into()?;
+        let mut page_count = n_pages;
+        if file_len >= DATABASE_HEADER_SIZE as u64 {
+            let mut buf = [0; DATABASE_HEADER_SIZE];
+            source.read_exact_at(&mut buf, 0)?;
+            let header = DatabaseHeader::from(&buf);
+            // A fragment of pages has no database header.
+            if let Some(database_size) = header
+                .database_size()
+                .filter(|_| header.validate_magic_header())
+            {
+                if database_size > n_pages {
+                    bail!(
+                        "database size {} exceeds the number of pages {} in the file",
+                        database_size,
+                        n_pages
+                    );
+                }
+                page_count = database_size;
+            }
+        }
         Ok(Self {
-            file,
+            source,
             cache: PageCache::new(pagesize),
-            n_pages: n_pages.try_into()?,
+            n_pages,
+            page_count,
+            verify_checksum: false,
+            expected_change_counter: None,
+            stats: AtomicPagerStats::default(),
+            #[cfg(feature = "mmap")]
+            mmap: None,
         })
     }
 
+    /// Creates a pager which reads pages from the memory mapped file.
+    ///
+    /// Pages are not copied into the cache.
+    ///
+    /// # Safety
+    ///
+    /// The file must not be modified or truncated while the pager or any page
+    /// loaded from it is alive. See [crate::Connection::open_mmap()].
+    #[cfg(feature = "mmap")]
+    pub unsafe fn new_mmap(file: File, pagesize: usize) -> anyhow::Result<Self> {
+        let mmap = Mmap::new(&file)?;
+        let mut pager = Self::with_pagesize(file, pagesize)?;
+        pager.mmap = Some(Arc::new(mmap));
+        Ok(pager)
+    }
+
+    /// Verify the checksum at the tail of each page on loading it.
+    ///
+    /// Pages which are already cached are dropped to be verified on the next
+    /// load.
+    pub fn enable_checksum_verification(&mut self) {
+        self.verify_checksum = true;
+        self.cache.clear();
+    }
+
+    /// Read the file change counter in the database header.
+    ///
+    /// This reads the file instead of the cached page so that changes by other
+    /// connections are visible. SQLite increments the counter on each
+    /// transaction which modifies the database.
+    pub fn change_counter(&self) -> anyhow::Result<u32> {
+        let mut buf = [0; 4];
+        #[cfg(feature = "mmap")]
+        if let Some(mmap) = &self.mmap {
+            buf.copy_from_slice(&mmap[CHANGE_COUNTER_OFFSET..CHANGE_COUNTER_OFFSET + 4]);
+            return Ok(u32::from_be_bytes(buf));
+        }
+        self.source
+            .read_exact_at(&mut buf, CHANGE_COUNTER_OFFSET as u64)?;
+        Ok(u32::from_be_bytes(buf))
+    }
+
+    /// Detect changes of the file by the change counter.
+    ///
+    /// The current change counter is taken as the consistent state. Cached
+    /// pages are dropped because they may be from another state.
+    pub fn enable_change_detection(&mut self) -> anyhow::Result<()> {
+        self.expected_change_counter = Some(self.change_counter()?);
+        self.cache.clear();
+        Ok(())
+    }
+
+    /// Fails if the change detection is enabled and the change counter differs
+    /// from the one when it is enabled.
+    pub fn check_unchanged(&self) -> anyhow::Result<()> {
+        let Some(expected) = self.expected_change_counter else {
+            return Ok(());
+        };
+        let actual = self.change_counter()?;
+        if actual != expected {
+            bail!(
+                "database file is changed: change counter {} -> {}",
+                expected,
+                actual
+            );
+        }
+        Ok(())
+    }
+
     pub fn get_page(&self, id: PageId) -> anyhow::Result<MemPage> {
         match id {
             0 => bail!("page id starts from 1"),
-            id if id > self.n_pages => bail!("page id exceeds file size"),
-            id => {
-                let (page, is_new) = self.cache.get_page(id);
-                if is_new {
-                    let mut buffer = page.borrow_mut();
-                    let offset = (id - 1) as usize * buffer.len();
-                    self.file.read_exact_at(&mut buffer, offset as u64)?;
+            id if id > self.n_pages => bail!(
+                "page id {} exceeds the number of pages {}",
+                id,
+                self.n_pages
+            ),
+            #[cfg(feature = "mmap")]
+            id if self.mmap.is_some() => {
+                let mmap = self.mmap.as_ref().unwrap();
+                let pagesize = self.cache.pagesize;
+                // The offset fits in usize because the page is in the mapped
+                // file.
+                let offset = page_offset(id, pagesize) as usize;
+                let range = offset..offset + pagesize;
+                self.stats.pages_read.fetch_add(1, Ordering::Relaxed);
+                if self.verify_checksum {
+                    verify_checksum(id, &mmap[range.clone()])?;
                 }
+                Ok(MemPage {
+                    page: PageData::Mmap {
+                        mmap: mmap.clone(),
+                        range,
+                    },
+                    header_offset: if id == 1 { DATABASE_HEADER_SIZE } else { 0 },
+                })
+            }
+            id => {
+                let page = if let Some(page) = self.cache.get(id) {
+                    self.stats.cache_hits.fetch_add(1, Ordering::Relaxed);
+                    page
+                } else {
+                    self.stats.cache_misses.fetch_add(1, Ordering::Relaxed);
+                    let mut buffer = vec![0; self.cache.pagesize];
+                    let offset = page_offset(id, buffer.len());
+                    self.source.read_exact_at(&mut buffer, offset)?;
+                    if self.verify_checksum {
+                        verify_checksum(id, &buffer)?;
+                    }
+                    self.stats.pages_read.fetch_add(1, Ordering::Relaxed);
+                    // Another thread may load the same page concurrently.
+                    // Either of the same contents is cached.
+                    self.cache.insert(id, buffer)
+                };
                 let header_offset = if id == 1 { DATABASE_HEADER_SIZE } else { 0 };
                 Ok(MemPage {
-                    page,
+                    page: PageData::Cache(page),
                     header_offset,
                 })
             }
         }
     }
 
-    // TODO: this is currently only used for testing.
-    #[allow(dead_code)]
+    /// Read the consecutive pages from the start page with a single read.
+    ///
+    /// This saves reads for pages known to be loaded in order, e.g. a
+    /// contiguous run of an overflow chain. The pages are returned in a single
+    /// buffer and are not cached. The read stops before the first page which is
+    /// already cached or beyond the file, and the buffer is truncated before the
+    /// first page failing the checksum verification, so it may hold fewer pages
+    /// than requested. Returns an empty buffer for a memory mapped file.
+    pub fn read_pages(&self, start: PageId, count: u32) -> anyhow::Result<Vec<u8>> {
+        #[cfg(feature = "mmap")]
+        if self.mmap.is_some() {
+            return Ok(Vec::new());
+        }
+        if start == 0 || start > self.n_pages {
+            return Ok(Vec::new());
+        }
+        let count = std::cmp::min(count, self.n_pages - start + 1);
+        let n_uncached = (start..start + count)
+            .take_while(|&id| !self.cache.contains(id))
+            .count();
+        if n_uncached == 0 {
+            return Ok(Vec::new());
+        }
+
+        let pagesize = self.cache.pagesize;
+        let mut buf = vec![0; n_uncached * pagesize];
+        let offset = page_offset(start, pagesize);
+        self.source.read_exact_at(&mut buf, offset)?;
+        self.stats
+            .pages_read
+            .fetch_add(n_uncached as u64, Ordering::Relaxed);
+        if self.verify_checksum {
+            let n_valid = (start..)
+                .zip(buf.chunks_exact(pagesize))
+                .take_while(|(id, content)| verify_checksum(*id, content).is_ok())
+                .count();
+            buf.truncate(n_valid * pagesize);
+        }
+        Ok(buf)
+    }
+
+    /// The size of a page in bytes.
+    pub fn pagesize(&self) -> usize {
+        self.cache.pagesize
+    }
+
+    /// The number of pages in the file.
     pub fn num_pages(&self) -> u32 {
         self.n_pages
     }
+
+    /// The number of pages in the database.
+    ///
+    /// This is the database size in the header. If the header field is not
+    /// valid, this falls back to the number of pages in the file.
+    pub fn page_count(&self) -> u32 {
+        self.page_count
+    }
+
+    /// Returns the ids of the pages in the freelist.
+    ///
+    /// The freelist is a chain of trunk pages from the database header, and
+    /// each trunk page lists free leaf pages. Trunk pages are not used either,
+    /// so they are included. Each trunk page is followed by its leaf pages.
+    ///
+    /// Fails if the number of pages does not match the freelist count in the
+    /// header, which also detects a cyclic chain.
+    pub fn freelist_pages(&self) -> anyhow::Result<Vec<PageId>> {
+        let (mut trunk_page_id, freelist_count, usable_size) = {
+            let page = self.get_page(ROOT_PAGE_ID)?;
+            let buffer = page.buffer();
+            let header = DatabaseHeader::from(buffer[..DATABASE_HEADER_SIZE].try_into()?);
+            (
+                header.freelist_trunk_page(),
+                header.freelist_count() as usize,
+                header.usable_size(),
+            )
+        };
+        // A trunk page has the next trunk page id, the number of leaf pages and
+        // the leaf page ids in 4 bytes each.
+        let max_leaves = (usable_size / 4 - 2).max(0) as usize;
+        let mut pages = Vec::new();
+        while trunk_page_id != 0 {
+            if pages.len() >= freelist_count {
+                bail!(
+                    "freelist is longer than the freelist count {}",
+                    freelist_count
+                );
+            }
+            if trunk_page_id > self.page_count {
+                bail!(
+                    "freelist trunk page {} exceeds the database size {}",
+                    trunk_page_id,
+                    self.page_count
+                );
+            }
+            pages.push(trunk_page_id);
+            let page = self.get_page(trunk_page_id)?;
+            let buffer = page.buffer();
+            let next_trunk_page_id = u32::from_be_bytes(buffer[0..4].try_into().unwrap());
+            let n_leaves = u32::from_be_bytes(buffer[4..8].try_into().unwrap()) as usize;
+            if n_leaves > max_leaves {
+                bail!(
+                    "freelist trunk page {} has too many leaf pages: {}",
+                    trunk_page_id,
+                    n_leaves
+                );
+            }
+            if pages.len() + n_leaves > freelist_count {
+                bail!(
+                    "freelist is longer than the freelist count {}",
+                    freelist_count
+                );
+            }
+            for leaf in buffer[8..8 + 4 * n_leaves].chunks_exact(4) {
+                let leaf_page_id = u32::from_be_bytes(leaf.try_into().unwrap());
+                if leaf_page_id == 0 || leaf_page_id > self.page_count {
+                    bail!(
+                        "freelist leaf page {} is out of the database size {}",
+                        leaf_page_id,
+                        self.page_count
+                    );
+                }
+                pages.push(leaf_page_id);
+            }
+            trunk_page_id = next_trunk_page_id;
+        }
+        if pages.len() != freelist_count {
+            bail!(
+                "freelist has {} pages but the freelist count is {}",
+                pages.len(),
+                freelist_count
+            );
+        }
+        Ok(pages)
+    }
+
+    pub fn stats(&self) -> PagerStats {
+        self.stats.load()
+    }
+}
+
+impl TryFrom<File> for Pager {
+    type Error = anyhow::Error;
+
+    fn try_from(file: File) -> anyhow::Result<Self> {
+        Self::new(file)
+    }
 }
 
 pub struct MemPage {
-    page: Rc<RefCell<Vec<u8>>>,
+    page: PageData,
     pub header_offset: usize,
 }
 
+enum PageData {
+    Cache(Arc<Vec<u8>>),
+    #[cfg(feature = "mmap")]
+    Mmap {
+        mmap: Arc<Mmap>,
+        range: Range<usize>,
+    },
+}
+
 impl MemPage {
-    pub fn buffer(&self) -> PageBuffer {
-        self.page.borrow()
+    pub fn buffer(&self) -> PageBuffer<'_> {
+        match &self.page {
+            PageData::Cache(page) => page,
+            #[cfg(feature = "mmap")]
+            PageData::Mmap { mmap, range } => &mmap[range.clone()],
+        }
+    }
+}
+
+/// The buffer of a page.
+///
+/// The size of a page is more than 512.
+pub type PageBuffer<'a> = &'a [u8];
+
+/// Read only memory mapping of a whole file.
+#[cfg(feature = "mmap")]
+struct Mmap {
+    ptr: *mut libc::c_void,
+    len: usize,
+}
+
+// SAFETY: the mapping is not tied to the thread which created it, and
+// munmap() on drop can be called from any thread.
+#[cfg(feature = "mmap")]
+unsafe impl Send for Mmap {}
+
+// SAFETY: the mapping is PROT_READ, so no thread writes to the memory through
+// it. The file can still be written by others, e.g. SQLite in another process or
+// another connection in this process, and MAP_SHARED makes the writes visible
+// in the mapping. Sharing the mapping between threads adds nothing to that
+// hazard, which exists even with a single thread. The caller of
+// Pager::new_mmap() guarantees that the file is neither modified nor truncated
+// while it is mapped.
+#[cfg(feature = "mmap")]
+unsafe impl Sync for Mmap {}
+
+#[cfg(feature = "mmap")]
+impl Mmap {
+    fn new(file: &File) -> anyhow::Result<Self> {
+        let len = file.metadata()?.len().try_into()?;
+        if len == 0 {
+            bail!("empty file can not be mapped");
+        }
+        // SAFETY: the file descriptor is valid and the mapping is read only.
+        let ptr = unsafe {
+            libc::mmap(
+                std::ptr::null_mut(),
+                len,
+                libc::PROT_READ,
+                libc::MAP_SHARED,
+                file.as_raw_fd(),
+                0,
+            )
+        };
+        if ptr == libc::MAP_FAILED {
+            return Err(std::io::Error::last_os_error().into());
+        }
+        Ok(Self { ptr, len })
     }
 }
 
+#[cfg(feature = "mmap")]
+impl Deref for Mmap {
+    type Target = [u8];
+
+    fn deref(&self) -> &Self::Target {
+        // SAFETY: ptr is valid for len bytes until the mapping is dropped.
+        unsafe { std::slice::from_raw_parts(self.ptr as *const u8, self.len) }
+    }
+}
+
+#[cfg(feature = "mmap")]
+impl Drop for Mmap {
+    fn drop(&mut self) {
+        // SAFETY: ptr and len are from the successful mmap().
+        unsafe {
+            libc::munmap(self.ptr, self.len);
+        }
+    }
+}
+
+/// Compute the checksum of the page in the same way as the checksum VFS.
+///
+/// The checksum is calculated over the page except the tail checksum.
+pub fn compute_checksum(buf: &[u8]) -> [u8; CHECKSUM_SIZE] {
+    let mut s1: u32 = 0;
+    let mut s2: u32 = 0;
+    for chunk in buf.chunks_exact(8) {
+        s1 = s1
+            .wrapping_add(u32::from_le_bytes(chunk[..4].try_into().unwrap()))
+            .wrapping_add(s2);
+        s2 = s2
+            .wrapping_add(u32::from_le_bytes(chunk[4..].try_into().unwrap()))
+            .wrapping_add(s1);
+    }
+    let mut checksum = [0; CHECKSUM_SIZE];
+    checksum[..4].copy_from_slice(&s1.to_le_bytes());
+    checksum[4..].copy_from_slice(&s2.to_le_bytes());
+    checksum
+}
+
+fn verify_checksum(id: PageId, page: &[u8]) -> anyhow::Result<()> {
+    let (content, expected) = page.split_at(page.len() - CHECKSUM_SIZE);
+    let actual = compute_checksum(content);
+    if expected != actual {
+        bail!(
+            "checksum mismatch: page {}, expected {:02x?}, actual {:02x?}",
+            id,
+            expected,
+            actual
+        );
+    }
+    Ok(())
+}
+
+/// The cache of loaded pages shared by threads.
+///
+/// Cached pages are immutable. Pages are loaded without the lock and inserted
+/// after that. Threads looking up cached pages share the read lock.
 struct PageCache {
-    map: RefCell<HashMap<PageId, Rc<RefCell<Vec<u8>>>>>,
+    map: RwLock<HashMap<PageId, Arc<Vec<u8>>>>,
     pagesize: usize,
 }
 
 impl PageCache {
     fn new(pagesize: usize) -> Self {
         Self {
-            map: RefCell::new(HashMap::new()),
+            map: RwLock::new(HashMap::new()),
             pagesize,
         }
     }
 
-    fn get_page(&self, id: PageId) -> (Rc<RefCell<Vec<u8>>>, bool) {
-        match self.map.borrow_mut().entry(id) {
-            Entry::Occupied(entry) => (entry.get().clone(), false),
-            Entry::Vacant(entry) => {
-                let page = Rc::new(RefCell::new(vec![0_u8; self.pagesize]));
-                entry.insert(page.clone());
-                (page, true)
+    fn get(&self, id: PageId) -> Option<Arc<Vec<u8>>> {
+        self.map.read().unwrap().get(&id).cloned()
+    }
+
+    /// Returns the cached page if exists. Otherwise caches the given page.
+    fn insert(&self, id: PageId, page: Vec<u8>) -> Arc<Vec<u8>> {
+        self.map
+            .write()
+            .unwrap()
+            .entry(id)
+            .or_insert_with(|| Arc::new(page))
+            .clone()
+    }
+
+    fn contains(&self, id: PageId) -> bool {
+        self.map.read().unwrap().contains_key(&id)
+    }
+
+    fn clear(&self) {
+        self.map.write().unwrap().clear();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use tempfile::NamedTempFile;
+
+    use crate::test_utils::*;
+
+    /// Create a database which reserves the checksum bytes in each page and fill
+    /// the checksums as the checksum VFS does.
+    fn create_checksum_database(queries: &[&str]) -> NamedTempFile {
+        let file = create_sqlite_database_with_reserved_bytes(CHECKSUM_SIZE as u8, queries);
+        let mut buf = std::fs::read(file.path()).unwrap();
+        let header = DatabaseHeader::from(buf[..DATABASE_HEADER_SIZE].try_into().unwrap());
+        let pagesize = header.pagesize() as usize;
+        assert_eq!(buf[20] as usize, CHECKSUM_SIZE);
+        for page in buf.chunks_exact_mut(pagesize) {
+            let checksum = compute_checksum(&page[..pagesize - CHECKSUM_SIZE]);
+            page[pagesize - CHECKSUM_SIZE..].copy_from_slice(&checksum);
+        }
+        file.as_file().write_all_at(&buf, 0).unwrap();
+        file
+    }
+
+    #[test]
+    fn test_pager_new() {
+        let file =
+            create_sqlite_database(&["PRAGMA page_size = 1024;", "CREATE TABLE example(col);"]);
+        let pager = Pager::new(file.as_file().try_clone().unwrap()).unwrap();
+        assert_eq!(pager.get_page(1).unwrap().buffer().len(), 1024);
+        assert_eq!(pager.num_pages(), 2);
+
+        let pager = Pager::try_from(file.as_file().try_clone().unwrap()).unwrap();
+        assert_eq!(pager.get_page(2).unwrap().buffer().len(), 1024);
+
+        // A fragment of pages without the database header.
+        let file = NamedTempFile::new().unwrap();
+        file.as_file().write_all_at(&[0; 1024], 0).unwrap();
+        let Err(e) = Pager::new(file.as_file().try_clone().unwrap()) else {
+            panic!("pager is created without the pagesize");
+        };
+        assert_eq!(e.to_string(), "invalid pagesize");
+        let pager = Pager::with_pagesize(file.as_file().try_clone().unwrap(), 512).unwrap();
+        assert_eq!(pager.num_pages(), 2);
+    }
+
+    #[test]
+    fn test_pager_page_count() {
+        let mut queries = vec!["PRAGMA page_size = 512;", "CREATE TABLE example(col);"];
+        queries.resize(2 + 10, "INSERT INTO example(col) VALUES (zeroblob(400));");
+        let file = create_sqlite_database(&queries);
+        let n_pages = (file.as_file().metadata().unwrap().len() / 512) as u32;
+        assert!(n_pages > 10);
+        let pager = Pager::new(file.as_file().try_clone().unwrap()).unwrap();
+        assert_eq!(pager.page_count(), n_pages);
+        assert_eq!(pager.num_pages(), n_pages);
+
+        // The file is larger than the database.
+        file.as_file()
+            .write_all_at(&[0; 512], n_pages as u64 * 512)
+            .unwrap();
+        let pager = Pager::new(file.as_file().try_clone().unwrap()).unwrap();
+        assert_eq!(pager.page_count(), n_pages);
+        assert_eq!(pager.num_pages(), n_pages + 1);
+
+        // Stale database size is ignored.
+        let mut buf = [0; DATABASE_HEADER_SIZE];
+        file.as_file().read_exact_at(&mut buf, 0).unwrap();
+        let change_counter = DatabaseHeader::from(&buf).change_counter();
+        file.as_file()
+            .write_all_at(&(change_counter + 1).to_be_bytes(), 24)
+            .unwrap();
+        let pager = Pager::new(file.as_file().try_clone().unwrap()).unwrap();
+        assert_eq!(pager.page_count(), n_pages + 1);
+
+        // Zero database size is ignored.
+        file.as_file()
+            .write_all_at(&change_counter.to_be_bytes(), 24)
+            .unwrap();
+        file.as_file().write_all_at(&[0; 4], 28).unwrap();
+        let pager = Pager::new(file.as_file().try_clone().unwrap()).unwrap();
+        assert_eq!(pager.page_count(), n_pages + 1);
+
+        // The database size exceeds the file.
+        file.as_file()
+            .write_all_at(&(n_pages + 2).to_be_bytes(), 28)
+            .unwrap();
+        let Err(e) = Pager::new(file.as_file().try_clone().unwrap()) else {
+            panic!("pager is created with the database size exceeding the file");
+        };
+        assert_eq!(
+            e.to_string(),
+            format!(
+                "database size {} exceeds the number of pages {} in the file",
+                n_pages + 2,
+                n_pages + 1
+            )
+        );
+    }
+
+    #[test]
+    fn test_pager_from_reader() {
+        let mut queries = vec!["CREATE TABLE example(col);"];
+        queries.resize(
+            1 + 100,
+            "INSERT INTO example(col) VALUES (randomblob(100));",
+        );
+        let file = create_sqlite_database(&queries);
+        let pager = create_pager(file.as_file().try_clone().unwrap()).unwrap();
+        let pagesize = pager.get_page(1).unwrap().buffer().len();
+        let content = std::fs::read(file.path()).unwrap();
+        let reader_pager = Pager::from_reader(std::io::Cursor::new(content), pagesize).unwrap();
+
+        assert_eq!(reader_pager.num_pages(), pager.num_pages());
+        // Load pages in the reverse order to seek backward.
+        for id in (1..=pager.num_pages()).rev() {
+            let page = pager.get_page(id).unwrap();
+            let reader_page = reader_pager.get_page(id).unwrap();
+            assert_eq!(reader_page.buffer(), page.buffer());
+            assert_eq!(reader_page.header_offset, page.header_offset);
+        }
+        assert!(reader_pager.get_page(pager.num_pages() + 1).is_err());
+
+        let content = vec![0; pagesize + 1];
+        assert!(Pager::from_reader(std::io::Cursor::new(content), pagesize).is_err());
+    }
+
+    #[test]
+    fn test_pager_large_offset() {
+        /// A reader of a huge file filled with zeros which records the offsets
+        /// of reads.
+        struct SparseReader {
+            len: u64,
+            pos: u64,
+            offsets: Arc<Mutex<Vec<u64>>>,
+        }
+
+        impl Read for SparseReader {
+            fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
+                self.offsets.lock().unwrap().push(self.pos);
+                let n = std::cmp::min(buf.len() as u64, self.len.saturating_sub(self.pos));
+                buf[..n as usize].fill(0);
+                self.pos += n;
+                Ok(n as usize)
+            }
+        }
+
+        impl Seek for SparseReader {
+            fn seek(&mut self, pos: SeekFrom) -> std::io::Result<u64> {
+                self.pos = match pos {
+                    SeekFrom::Start(offset) => offset,
+                    SeekFrom::End(offset) => self.len.checked_add_signed(offset).unwrap(),
+                    SeekFrom::Current(offset) => self.pos.checked_add_signed(offset).unwrap(),
+                };
+                Ok(self.pos)
+            }
+        }
+
+        let pagesize = 65536;
+        let offsets = Arc::new(Mutex::new(Vec::new()));
+        let reader = SparseReader {
+            len: PageId::MAX as u64 * pagesize as u64,
+            pos: 0,
+            offsets: offsets.clone(),
+        };
+        let pager = Pager::from_reader(reader, pagesize).unwrap();
+        assert_eq!(pager.num_pages(), PageId::MAX);
+
+        offsets.lock().unwrap().clear();
+        pager.get_page(PageId::MAX).unwrap();
+        assert_eq!(
+            offsets.lock().unwrap().as_slice(),
+            &[(PageId::MAX as u64 - 1) * pagesize as u64]
+        );
+
+        offsets.lock().unwrap().clear();
+        assert_eq!(pager.read_pages(0x10000, 2).unwrap().len(), 2 * pagesize);
+        assert_eq!(offsets.lock().unwrap().as_slice(), &[0xffff * 65536]);
+        assert_eq!(page_offset(0x10001, pagesize), 0x1_0000_0000);
+    }
+
+    #[test]
+    fn test_freelist_pages() {
+        let file = create_sqlite_database(&["CREATE TABLE example(col);"]);
+        let pager = create_pager(file.as_file().try_clone().unwrap()).unwrap();
+        assert!(pager.freelist_pages().unwrap().is_empty());
+
+        let mut queries = vec!["PRAGMA page_size = 512;", "CREATE TABLE example(col);"];
+        // A trunk page holds up to 126 leaf pages. The freelist has multiple
+        // trunk pages.
+        queries.resize(2 + 500, "INSERT INTO example(col) VALUES (zeroblob(400));");
+        queries.push("DELETE FROM example;");
+        let file = create_sqlite_database(&queries);
+        let conn = rusqlite::Connection::open(file.path()).unwrap();
+        let freelist_count: usize = conn
+            .query_row("PRAGMA freelist_count;", [], |row| row.get(0))
+            .unwrap();
+        drop(conn);
+        let pager = create_pager(file.as_file().try_clone().unwrap()).unwrap();
+        let pages = pager.freelist_pages().unwrap();
+        assert_eq!(pages.len(), freelist_count);
+        // All pages except sqlite_schema and the root page of the table.
+        let mut sorted_pages = pages.clone();
+        sorted_pages.sort();
+        assert_eq!(sorted_pages, (3..=pager.page_count()).collect::<Vec<_>>());
+
+        let content = std::fs::read(file.path()).unwrap();
+        let trunk_page_id = u32::from_be_bytes(content[32..36].try_into().unwrap());
+        assert_eq!(pages[0], trunk_page_id);
+        let trunk_offset = (trunk_page_id as usize - 1) * 512;
+        let n_pages = pages.len() as u32;
+        for (offset, value, msg) in [
+            (
+                36,
+                n_pages + 1,
+                format!(
+                    "freelist has {} pages but the freelist count is {}",
+                    n_pages,
+                    n_pages + 1
+                ),
+            ),
+            (
+                36,
+                n_pages - 1,
+                format!("freelist is longer than the freelist count {}", n_pages - 1),
+            ),
+            (
+                trunk_offset + 4,
+                127,
+                format!("freelist trunk page {trunk_page_id} has too many leaf pages: 127"),
+            ),
+            // The trunk page refers to itself.
+            (
+                trunk_offset,
+                trunk_page_id,
+                format!("freelist is longer than the freelist count {}", n_pages),
+            ),
+            (
+                trunk_offset + 8,
+                n_pages + 3,
+                format!(
+                    "freelist leaf page {} is out of the database size {}",
+                    n_pages + 3,
+                    n_pages + 2
+                ),
+            ),
+        ] {
+            let mut content = content.clone();
+            content[offset..offset + 4].copy_from_slice(&value.to_be_bytes());
+            let pager = create_empty_pager(&content, 512);
+            assert_eq!(
+                pager.freelist_pages().unwrap_err().to_string(),
+                msg,
+                "offset: {offset}"
+            );
+        }
+    }
+
+    #[test]
+    fn test_get_page_out_of_range() {
+        let file = create_sqlite_database(&["CREATE TABLE example(col);"]);
+        let pager = create_pager(file.as_file().try_clone().unwrap()).unwrap();
+        assert_eq!(pager.num_pages(), 2);
+
+        assert!(pager.get_page(1).is_ok());
+        assert!(pager.get_page(2).is_ok());
+        let Err(e) = pager.get_page(0) else {
+            panic!("page 0 is loaded");
+        };
+        assert_eq!(e.to_string(), "page id starts from 1");
+        let Err(e) = pager.get_page(3) else {
+            panic!("page 3 is loaded");
+        };
+        assert_eq!(e.to_string(), "page id 3 exceeds the number of pages 2");
+        assert!(pager.get_page(PageId::MAX).is_err());
+    }
+
+    #[test]
+    fn test_pager_stats() {
+        let file = create_sqlite_database(&["CREATE TABLE example(col);"]);
+        let pager = create_pager(file.as_file().try_clone().unwrap()).unwrap();
+        assert_eq!(pager.stats(), PagerStats::default());
+
+        pager.get_page(1).unwrap();
+        pager.get_page(2).unwrap();
+        pager.get_page(1).unwrap();
+        assert_eq!(
+            pager.stats(),
+            PagerStats {
+                pages_read: 2,
+                cache_hits: 1,
+                cache_misses: 2,
             }
+        );
+
+        // Out of range pages are not counted.
+        assert!(pager.get_page(3).is_err());
+        assert_eq!(pager.stats().cache_misses, 2);
+
+        // A page failing to load is a cache miss but not a read.
+        let mut pager = create_pager(file.as_file().try_clone().unwrap()).unwrap();
+        pager.enable_checksum_verification();
+        assert!(pager.get_page(1).is_err());
+        assert_eq!(
+            pager.stats(),
+            PagerStats {
+                pages_read: 0,
+                cache_hits: 0,
+                cache_misses: 1,
+            }
+        );
+    }
+
+    #[test]
+    fn test_read_pages() {
+        let mut queries = vec!["CREATE TABLE example(col);"];
+        queries.resize(
+            1 + 100,
+            "INSERT INTO example(col) VALUES (randomblob(100));",
+        );
+        let file = create_sqlite_database(&queries);
+        let pager = create_pager(file.as_file().try_clone().unwrap()).unwrap();
+        let pagesize = pager.pagesize();
+        let n_pages = pager.num_pages();
+        assert!(n_pages > 3);
+        let content = std::fs::read(file.path()).unwrap();
+
+        let pager = create_pager(file.as_file().try_clone().unwrap()).unwrap();
+        pager.get_page(3).unwrap();
+        // Stops before the cached page 3.
+        let buf = pager.read_pages(1, n_pages).unwrap();
+        assert_eq!(buf, &content[..2 * pagesize]);
+        assert_eq!(pager.stats().pages_read, 3);
+        // Stops at the end of the file.
+        let buf = pager.read_pages(4, n_pages).unwrap();
+        assert_eq!(buf, &content[3 * pagesize..]);
+        assert_eq!(pager.stats().pages_read, n_pages as u64);
+        // The pages are not cached.
+        let stats = pager.stats();
+        pager.get_page(1).unwrap();
+        pager.get_page(3).unwrap();
+        assert_eq!(
+            pager.stats(),
+            PagerStats {
+                pages_read: stats.pages_read + 1,
+                cache_hits: stats.cache_hits + 1,
+                cache_misses: stats.cache_misses + 1,
+            }
+        );
+
+        // Nothing to read.
+        let stats = pager.stats();
+        assert!(pager.read_pages(0, 10).unwrap().is_empty());
+        assert!(pager.read_pages(n_pages + 1, 10).unwrap().is_empty());
+        assert!(pager.read_pages(1, 0).unwrap().is_empty());
+        assert!(pager.read_pages(1, 1).unwrap().is_empty());
+        assert_eq!(pager.stats().pages_read, stats.pages_read);
+    }
+
+    #[test]
+    fn test_change_detection() {
+        let file = create_sqlite_database(&["CREATE TABLE example(col);"]);
+        let mut pager = create_pager(file.as_file().try_clone().unwrap()).unwrap();
+        let change_counter = pager.change_counter().unwrap();
+        assert!(pager.check_unchanged().is_ok());
+
+        let conn = rusqlite::Connection::open(file.path()).unwrap();
+        conn.execute("INSERT INTO example(col) VALUES (1);", [])
+            .unwrap();
+        assert_eq!(pager.change_counter().unwrap(), change_counter + 1);
+        // Disabled by default.
+        assert!(pager.check_unchanged().is_ok());
+
+        pager.enable_change_detection().unwrap();
+        assert!(pager.check_unchanged().is_ok());
+        conn.execute("INSERT INTO example(col) VALUES (2);", [])
+            .unwrap();
+        assert_eq!(
+            pager.check_unchanged().unwrap_err().to_string(),
+            format!(
+                "database file is changed: change counter {} -> {}",
+                change_counter + 1,
+                change_counter + 2
+            )
+        );
+    }
+
+    #[test]
+    fn test_new_with_partial_page() {
+        let file = create_sqlite_database(&["CREATE TABLE example(col);"]);
+        file.as_file().write_all_at(&[0], 4096 * 2).unwrap();
+        assert!(create_pager(file.as_file().try_clone().unwrap()).is_err());
+    }
+
+    #[test]
+    fn test_compute_checksum() {
+        assert_eq!(compute_checksum(&[0; 16]), [0; 8]);
+        let buf = [1, 0, 0, 0, 2, 0, 0, 0, 3, 0, 0, 0, 4, 0, 0, 0];
+        // s1 = 1, s2 = 2 + 1 = 3, s1 = 1 + 3 + 3 = 7, s2 = 3 + 4 + 7 = 14
+        assert_eq!(compute_checksum(&buf), [7, 0, 0, 0, 14, 0, 0, 0]);
+    }
+
+    #[test]
+    fn test_verify_checksum() {
+        let mut queries = vec!["CREATE TABLE example(col);"];
+        queries.resize(
+            1 + 100,
+            "INSERT INTO example(col) VALUES (randomblob(100));",
+        );
+        let file = create_checksum_database(&queries);
+        let mut pager = create_pager(file.as_file().try_clone().unwrap()).unwrap();
+        pager.enable_checksum_verification();
+        assert!(pager.num_pages() > 2);
+        for id in 1..=pager.num_pages() {
+            assert!(pager.get_page(id).is_ok());
         }
+
+        // Corrupt the page 2.
+        let pagesize = pager.get_page(1).unwrap().buffer().len();
+        file.as_file()
+            .write_all_at(&[0xff], pagesize as u64 + 10)
+            .unwrap();
+        let mut pager = create_pager(file.as_file().try_clone().unwrap()).unwrap();
+        assert!(pager.get_page(2).is_ok());
+        pager.enable_checksum_verification();
+        let Err(e) = pager.get_page(2) else {
+            panic!("checksum mismatch is not detected");
+        };
+        assert!(
+            e.to_string().starts_with("checksum mismatch: page 2,"),
+            "{e}"
+        );
+        // The corrupted page is not cached.
+        assert!(pager.get_page(2).is_err());
+        assert!(pager.get_page(1).is_ok());
+
+        // The pages read at once are truncated before the corrupted page.
+        let mut pager = create_pager(file.as_file().try_clone().unwrap()).unwrap();
+        pager.enable_checksum_verification();
+        assert_eq!(pager.read_pages(1, 3).unwrap().len(), pagesize);
+    }
+
+    #[cfg(feature = "mmap")]
+    #[test]
+    fn test_mmap_pager() {
+        let mut queries = vec!["CREATE TABLE example(col);"];
+        queries.resize(
+            1 + 100,
+            "INSERT INTO example(col) VALUES (randomblob(100));",
+        );
+        let file = create_sqlite_database(&queries);
+        let pager = create_pager(file.as_file().try_clone().unwrap()).unwrap();
+        let pagesize = pager.get_page(1).unwrap().buffer().len();
+        // SAFETY: the file is not modified while the pager is alive.
+        let mmap_pager =
+            unsafe { Pager::new_mmap(file.as_file().try_clone().unwrap(), pagesize) }.unwrap();
+
+        assert_eq!(mmap_pager.num_pages(), pager.num_pages());
+        for id in 1..=pager.num_pages() {
+            let page = pager.get_page(id).unwrap();
+            let mmap_page = mmap_pager.get_page(id).unwrap();
+            assert_eq!(mmap_page.buffer(), page.buffer());
+            assert_eq!(mmap_page.header_offset, page.header_offset);
+        }
+        assert!(mmap_pager.get_page(0).is_err());
+        assert!(mmap_pager.get_page(pager.num_pages() + 1).is_err());
+    }
+
+    #[cfg(feature = "mmap")]
+    #[test]
+    fn test_mmap_pager_verify_checksum() {
+        let file = create_checksum_database(&["CREATE TABLE example(col);"]);
+        let pagesize = create_pager(file.as_file().try_clone().unwrap())
+            .unwrap()
+            .get_page(1)
+            .unwrap()
+            .buffer()
+            .len();
+        file.as_file()
+            .write_all_at(&[0xff], pagesize as u64 + 10)
+            .unwrap();
+        // SAFETY: the file is not modified while the pager is alive.
+        let mut pager =
+            unsafe { Pager::new_mmap(file.as_file().try_clone().unwrap(), pagesize) }.unwrap();
+        pager.enable_checksum_verification();
+        assert!(pager.get_page(1).is_ok());
+        assert!(pager.get_page(2).is_err());
+        assert!(pager.get_page(1).is_ok());
+        assert_eq!(
+            pager.stats(),
+            PagerStats {
+                pages_read: 3,
+                cache_hits: 0,
+                cache_misses: 0,
+            }
+        );
     }
 }
@@ -17,7 +17,9 @@ use std::fmt::Display;
 use crate::token::get_token;
 use crate::token::Token;
 use crate::utils::parse_float;
+use crate::utils::parse_hex_integer;
 use crate::utils::parse_integer;
+use crate::utils::CaseInsensitiveBytes;
 use crate::utils::HexedBytes;
 use crate::utils::MaybeQuotedBytes;
 use crate::utils::ParseIntegerResult;
@@ -36,6 +38,11 @@ impl Error<'_> {
     pub fn cursor(&self) -> usize {
         self.cursor
     }
+
+    #[cfg(test)]
+    pub fn msg(&self) -> &'static str {
+        self.msg
+    }
 }
 
 impl Display for Error<'_> {
@@ -121,6 +128,25 @@ impl<'a> Parser<'a> {
         self.token.as_ref()
     }
 
+    /// Return the current token as a name if it is an identifier or a keyword
+    /// which can be a name.
+    ///
+    /// See [Token::is_fallback_keyword()].
+    fn peek_name(&self) -> Option<MaybeQuotedBytes<'a>> {
+        match self.token.as_ref()? {
+            Token::Identifier(id) => Some(*id),
+            token if token.is_fallback_keyword() => {
+                Some((&self.input[self.cursor..self.cursor + self.token_size]).into())
+            }
+            _ => None,
+        }
+    }
+
+    fn next_name(&mut self) -> Option<MaybeQuotedBytes<'a>> {
+        self.next();
+        self.peek_name()
+    }
+
     fn error(&self, msg: &'static str) -> Error<'a> {
         Error {
             input: self.input,
@@ -141,6 +167,13 @@ pub fn expect_semicolon<'a>(p: &mut Parser<'a>) -> Result<'a, ()> {
     Ok(())
 }
 
+/// Skip the next token if it is a semicolon.
+pub fn skip_semicolon(p: &mut Parser) {
+    if let Some(Token::Semicolon) = p.peek() {
+        p.next();
+    }
+}
+
 /// Assert that there is no token except spaces.
 ///
 /// Uses mutable [Parser] to unify the interface with other expect functions.
@@ -151,28 +184,117 @@ pub fn expect_no_more_token<'a>(p: &mut Parser<'a>) -> Result<'a, ()> {
     }
 }
 
+/// Parse the entire input with the parse function.
+///
+/// Fails with "unexpected token" if any token other than spaces and comments
+/// follows the parsed part, e.g. a semicolon.
+pub fn parse_complete<'a, T>(
+    input: &'a [u8],
+    parse: impl FnOnce(&mut Parser<'a>) -> Result<'a, T>,
+) -> Result<'a, T> {
+    let mut parser = Parser::new(input);
+    let parsed = parse(&mut parser)?;
+    expect_no_more_token(&mut parser)?;
+    Ok(parsed)
+}
+
+/// Split the sql into statements terminated by semicolons.
+///
+/// Each statement includes its terminating semicolon, if any, and has no
+/// leading or trailing spaces. Semicolons in string literals and quoted
+/// identifiers are not boundaries. Empty statements are skipped.
+pub fn split_statements(sql: &[u8]) -> Vec<&[u8]> {
+    let mut statements = Vec::new();
+    let mut cursor = 0;
+    // The start and the end of the statement except spaces.
+    let mut start = None;
+    let mut end = 0;
+    while let Some((n, token)) = get_token(&sql[cursor..]) {
+        match token {
+            Token::Space => {}
+            Token::Semicolon => {
+                if let Some(start) = start.take() {
+                    statements.push(&sql[start..cursor + n]);
+                }
+            }
+            _ => {
+                start.get_or_insert(cursor);
+                end = cursor + n;
+            }
+        }
+        cursor += n;
+    }
+    if let Some(start) = start {
+        statements.push(&sql[start..end]);
+    }
+    statements
+}
+
+/// A statement parsed by [parse_statement()].
+#[derive(Debug)]
+pub enum ParsedStatement<'a> {
+    CreateTable(CreateTable<'a>),
+    CreateIndex(CreateIndex<'a>),
+    AlterTable(AlterTable<'a>),
+    Select(Box<SelectStmt<'a>>),
+    Delete(Delete<'a>),
+    Update(Update<'a>),
+}
+
+/// Parse a single statement with an optional terminating semicolon.
+///
+/// This accepts each statement returned by [split_statements()], e.g. to
+/// replay the output of `.dump` statement by statement. Fails on any other
+/// kind of statement.
+pub fn parse_statement(sql: &[u8]) -> Result<'_, ParsedStatement<'_>> {
+    parse_complete(sql, |p| {
+        let statement = match p.peek() {
+            Some(Token::Create) => {
+                let mut lookahead = p.clone();
+                if let Some(Token::Table) = lookahead.next() {
+                    ParsedStatement::CreateTable(parse_create_table(p)?)
+                } else {
+                    ParsedStatement::CreateIndex(parse_create_index(p)?)
+                }
+            }
+            Some(Token::Alter) => ParsedStatement::AlterTable(parse_alter_table(p)?),
+            Some(Token::Delete) => ParsedStatement::Delete(parse_delete(p)?),
+            Some(Token::Update) => ParsedStatement::Update(parse_update(p)?),
+            _ => ParsedStatement::Select(Box::new(parse_select_stmt(p)?)),
+        };
+        skip_semicolon(p);
+        Ok(statement)
+    })
+}
+
 /// CREATE TABLE statement.
-#[derive(Debug, PartialEq, Eq)]
+#[derive(Debug, PartialEq)]
 pub struct CreateTable<'a> {
+    pub if_not_exists: bool,
+    pub schema_name: Option<MaybeQuotedBytes<'a>>,
     pub table_name: MaybeQuotedBytes<'a>,
     pub columns: Vec<ColumnDef<'a>>,
 }
 
 /// Constraint of a column in a table.
-#[derive(Debug, PartialEq, Eq)]
+#[derive(Debug, PartialEq)]
 pub enum ColumnConstraint<'a> {
     Collate(MaybeQuotedBytes<'a>),
     PrinaryKey,
+    /// `DEFAULT value`.
+    ///
+    /// The value is a literal, a signed literal, an identifier as a text or an
+    /// expression in parentheses.
+    Default(Expr<'a>),
 }
 
 /// https://www.sqlite.org/syntax/column-constraint.html
 fn parse_column_constraint<'a>(p: &mut Parser<'a>) -> Result<'a, Option<ColumnConstraint<'a>>> {
     match p.peek() {
         Some(Token::Collate) => {
-            let Some(Token::Identifier(collation)) = p.next() else {
+            let Some(collation) = p.next_name() else {
                 return Err(p.error("no collation name"));
             };
-            let collation = *collation;
             p.next();
             Ok(Some(ColumnConstraint::Collate(collation)))
         }
@@ -183,24 +305,106 @@ fn parse_column_constraint<'a>(p: &mut Parser<'a>) -> Result<'a, Option<ColumnCo
             p.next();
             Ok(Some(ColumnConstraint::PrinaryKey))
         }
+        Some(Token::Default) => {
+            p.next();
+            let expr = parse_column_default(p)?;
+            Ok(Some(ColumnConstraint::Default(expr)))
+        }
         _ => Ok(None),
     }
 }
 
+/// The value of TRUE or FALSE.
+///
+/// TRUE and FALSE are not keywords but names. They are 1 and 0 unless they are
+/// quoted or names of columns.
+pub fn true_false_value(name: &MaybeQuotedBytes) -> Option<i64> {
+    let name = CaseInsensitiveBytes::from(name.raw());
+    if name.equal_to_lower_bytes(b"true") {
+        Some(1)
+    } else if name.equal_to_lower_bytes(b"false") {
+        Some(0)
+    } else {
+        None
+    }
+}
+
+/// Parse the value of a DEFAULT column constraint.
+///
+/// Unlike the general expression, an expression other than a literal must be
+/// in parentheses, and an identifier is a text as SQLite does.
+fn parse_column_default<'a>(p: &mut Parser<'a>) -> Result<'a, Expr<'a>> {
+    let mut cloned_parser = p.clone();
+    if let Some(id) = p
+        .peek_name()
+        .filter(|_| !p.peek().is_some_and(Token::is_current_time))
+    {
+        p.next();
+        if let Some(v) = true_false_value(&id) {
+            return Ok(Expr::Integer(v));
+        }
+        return Ok(Expr::Text(id));
+    }
+    let literal_token = match cloned_parser.peek() {
+        Some(Token::LeftParen) => return parse_expr_primitive(p),
+        Some(Token::Plus) | Some(Token::Minus) => cloned_parser.next(),
+        token => token,
+    };
+    if !matches!(
+        literal_token,
+        Some(Token::Null)
+            | Some(Token::CurrentDate)
+            | Some(Token::CurrentTime)
+            | Some(Token::CurrentTimestamp)
+            | Some(Token::Integer(_))
+            | Some(Token::HexInteger(_))
+            | Some(Token::Float(_))
+            | Some(Token::String(_))
+            | Some(Token::Blob(_))
+    ) {
+        return Err(p.error("no default value"));
+    }
+    parse_expr_unary(p)
+}
+
 /// Definition of a column in a table.
-#[derive(Debug, PartialEq, Eq)]
+#[derive(Debug, PartialEq)]
 pub struct ColumnDef<'a> {
     pub name: MaybeQuotedBytes<'a>,
     pub type_name: Vec<MaybeQuotedBytes<'a>>,
     pub constraints: Vec<ColumnConstraint<'a>>,
 }
 
+/// https://www.sqlite.org/syntax/column-def.html
+fn parse_column_def<'a>(p: &mut Parser<'a>) -> Result<'a, ColumnDef<'a>> {
+    let Some(name) = p.peek_name() else {
+        return Err(p.error("no column name"));
+    };
+    p.next();
+
+    let type_name = parse_type_name(p)?;
+
+    let mut constraints = Vec::new();
+    while let Some(constraint) = parse_column_constraint(p)? {
+        constraints.push(constraint);
+    }
+
+    Ok(ColumnDef {
+        name,
+        type_name,
+        constraints,
+    })
+}
+
 /// https://www.sqlite.org/syntax/signed-number.html
 fn skip_signed_number<'a>(p: &mut Parser<'a>) -> Result<'a, ()> {
     if matches!(p.peek(), Some(Token::Plus) | Some(Token::Minus)) {
         p.next();
     }
-    if !matches!(p.peek(), Some(Token::Integer(_)) | Some(Token::Float(_))) {
+    if !matches!(
+        p.peek(),
+        Some(Token::Integer(_)) | Some(Token::HexInteger(_)) | Some(Token::Float(_))
+    ) {
         return Err(p.error("no signed number"));
     }
     p.next();
@@ -217,20 +421,21 @@ fn parse_type_name<'a>(p: &mut Parser<'a>) -> Result<'a, Vec<MaybeQuotedBytes<'a
         Some(Token::Null) => {
             type_name.push(NULL_BYTES.into());
         }
-        Some(Token::Identifier(id)) => {
-            type_name.push(*id);
-        }
-        _ => return Ok(Vec::new()),
+        _ => match p.peek_name() {
+            Some(id) => type_name.push(id),
+            None => return Ok(Vec::new()),
+        },
     };
 
     loop {
-        match p.next() {
+        if let Some(id) = p.next_name() {
+            type_name.push(id);
+            continue;
+        }
+        match p.peek() {
             Some(Token::Null) => {
                 type_name.push(NULL_BYTES.into());
             }
-            Some(Token::Identifier(id)) => {
-                type_name.push(*id);
-            }
             Some(Token::LeftParen) => {
                 p.next();
                 // Just check whether signed numbers are valid and move cursor without
@@ -268,38 +473,42 @@ pub fn parse_create_table<'a>(p: &mut Parser<'a>) -> Result<'a, CreateTable<'a>>
         return Err(p.error("no table"));
     };
 
-    let Some(Token::Identifier(table_name)) = p.next() else {
+    let if_not_exists = if let Some(Token::If) = p.next() {
+        let Some(Token::Not) = p.next() else {
+            return Err(p.error("no not after if"));
+        };
+        let Some(Token::Exists) = p.next() else {
+            return Err(p.error("no exists after if not"));
+        };
+        p.next();
+        true
+    } else {
+        false
+    };
+
+    let Some(name) = p.peek_name() else {
         return Err(p.error("no table_name"));
     };
-    let table_name = *table_name;
+    let (schema_name, table_name) = if let Some(Token::Dot) = p.next() {
+        let Some(table_name) = p.next_name() else {
+            return Err(p.error("no table_name after schema_name"));
+        };
+        p.next();
+        (Some(name), table_name)
+    } else {
+        (None, name)
+    };
 
-    let Some(Token::LeftParen) = p.next() else {
+    let Some(Token::LeftParen) = p.peek() else {
         return Err(p.error("no left paren"));
     };
 
     let mut columns = Vec::new();
     loop {
-        // Parse ColumnDef.
-        let Some(Token::Identifier(name)) = p.next() else {
-            return Err(p.error("no column name"));
-        };
-        let name = *name;
         p.next();
+        columns.push(parse_column_def(p)?);
 
-        let type_name = parse_type_name(p)?;
-
-        let mut constraints = Vec::new();
-        while let Some(constraint) = parse_column_constraint(p)? {
-            constraints.push(constraint);
-        }
-
-        columns.push(ColumnDef {
-            name,
-            type_name,
-            constraints,
-        });
-
-        // Parser contains a peekable token after parse_column_constraint().
+        // Parser contains a peekable token after parse_column_def().
         match p.peek() {
             Some(Token::Comma) => continue,
             Some(Token::RightParen) => break,
@@ -309,23 +518,36 @@ pub fn parse_create_table<'a>(p: &mut Parser<'a>) -> Result<'a, CreateTable<'a>>
     p.next();
 
     Ok(CreateTable {
+        if_not_exists,
+        schema_name,
         table_name,
         columns,
     })
 }
 
 /// CREATE INDEX statement.
-#[derive(Debug, PartialEq, Eq)]
+#[derive(Debug, PartialEq)]
 pub struct CreateIndex<'a> {
+    pub unique: bool,
     pub index_name: MaybeQuotedBytes<'a>,
     pub table_name: MaybeQuotedBytes<'a>,
     pub columns: Vec<IndexedColumn<'a>>,
+    /// The predicate of a partial index.
+    pub where_clause: Option<Expr<'a>>,
+}
+
+/// Sort order of a column in a index.
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub enum SortOrder {
+    Asc,
+    Desc,
 }
 
 /// Definition of a column in a index.
 #[derive(Debug, PartialEq, Eq)]
 pub struct IndexedColumn<'a> {
     pub name: MaybeQuotedBytes<'a>,
+    pub order: SortOrder,
 }
 
 /// Parse CREATE INDEX statement.
@@ -336,23 +558,28 @@ pub fn parse_create_index<'a>(p: &mut Parser<'a>) -> Result<'a, CreateIndex<'a>>
         return Err(p.error("no create"));
     };
 
-    let Some(Token::Index) = p.next() else {
+    let unique = if let Some(Token::Unique) = p.next() {
+        p.next();
+        true
+    } else {
+        false
+    };
+
+    let Some(Token::Index) = p.peek() else {
         return Err(p.error("no index"));
     };
 
-    let Some(Token::Identifier(index_name)) = p.next() else {
+    let Some(index_name) = p.next_name() else {
         return Err(p.error("no index_name"));
     };
-    let index_name = *index_name;
 
     let Some(Token::On) = p.next() else {
         return Err(p.error("no on"));
     };
 
-    let Some(Token::Identifier(table_name)) = p.next() else {
+    let Some(table_name) = p.next_name() else {
         return Err(p.error("no table_name"));
     };
-    let table_name = *table_name;
 
     let Some(Token::LeftParen) = p.next() else {
         return Err(p.error("no left paren"));
@@ -360,33 +587,184 @@ pub fn parse_create_index<'a>(p: &mut Parser<'a>) -> Result<'a, CreateIndex<'a>>
 
     let mut columns = Vec::new();
     loop {
-        let Some(Token::Identifier(name)) = p.next() else {
+        let Some(name) = p.next_name() else {
             return Err(p.error("no column name"));
         };
-        let name = *name;
 
-        columns.push(IndexedColumn { name });
+        let order = match p.next() {
+            Some(Token::Asc) => {
+                p.next();
+                SortOrder::Asc
+            }
+            Some(Token::Desc) => {
+                p.next();
+                SortOrder::Desc
+            }
+            _ => SortOrder::Asc,
+        };
+
+        columns.push(IndexedColumn { name, order });
 
-        match p.next() {
+        match p.peek() {
             Some(Token::Comma) => continue,
             Some(Token::RightParen) => break,
             _ => return Err(p.error("no right paren")),
         }
     }
-    p.next();
+
+    let where_clause = if let Some(Token::Where) = p.next() {
+        p.next();
+        Some(parse_expr(p)?)
+    } else {
+        None
+    };
 
     Ok(CreateIndex {
+        unique,
         index_name,
         table_name,
         columns,
+        where_clause,
+    })
+}
+
+/// ALTER TABLE statement.
+#[derive(Debug, PartialEq)]
+pub struct AlterTable<'a> {
+    pub schema_name: Option<MaybeQuotedBytes<'a>>,
+    pub table_name: MaybeQuotedBytes<'a>,
+    pub action: AlterTableAction<'a>,
+}
+
+#[derive(Debug, PartialEq)]
+pub enum AlterTableAction<'a> {
+    /// `RENAME TO new_table_name`.
+    RenameTo(MaybeQuotedBytes<'a>),
+    /// `RENAME [COLUMN] column_name TO new_column_name`.
+    RenameColumn {
+        column_name: MaybeQuotedBytes<'a>,
+        new_column_name: MaybeQuotedBytes<'a>,
+    },
+    /// `ADD [COLUMN] column_def`.
+    AddColumn(ColumnDef<'a>),
+}
+
+/// Parse ALTER TABLE statement.
+///
+/// https://www.sqlite.org/lang_altertable.html
+pub fn parse_alter_table<'a>(p: &mut Parser<'a>) -> Result<'a, AlterTable<'a>> {
+    let Some(Token::Alter) = p.peek() else {
+        return Err(p.error("no alter"));
+    };
+
+    let Some(Token::Table) = p.next() else {
+        return Err(p.error("no table"));
+    };
+
+    let Some(name) = p.next_name() else {
+        return Err(p.error("no table_name"));
+    };
+    let (schema_name, table_name) = if let Some(Token::Dot) = p.next() {
+        let Some(table_name) = p.next_name() else {
+            return Err(p.error("no table_name after schema_name"));
+        };
+        p.next();
+        (Some(name), table_name)
+    } else {
+        (None, name)
+    };
+
+    let action = match p.peek() {
+        Some(Token::Rename) => match p.next() {
+            Some(Token::To) => {
+                let Some(new_table_name) = p.next_name() else {
+                    return Err(p.error("no new table_name"));
+                };
+                p.next();
+                AlterTableAction::RenameTo(new_table_name)
+            }
+            token => {
+                if let Some(Token::Column) = token {
+                    p.next();
+                }
+                let Some(column_name) = p.peek_name() else {
+                    return Err(p.error("no column name"));
+                };
+                let Some(Token::To) = p.next() else {
+                    return Err(p.error("no to"));
+                };
+                let Some(new_column_name) = p.next_name() else {
+                    return Err(p.error("no new column name"));
+                };
+                p.next();
+                AlterTableAction::RenameColumn {
+                    column_name,
+                    new_column_name,
+                }
+            }
+        },
+        Some(Token::Add) => {
+            if let Some(Token::Column) = p.next() {
+                p.next();
+            }
+            AlterTableAction::AddColumn(parse_column_def(p)?)
+        }
+        _ => return Err(p.error("no rename or add")),
+    };
+
+    Ok(AlterTable {
+        schema_name,
+        table_name,
+        action,
     })
 }
 
+/// A table in the FROM clause.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct TableRef<'a> {
+    pub table_name: MaybeQuotedBytes<'a>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum JoinType {
+    /// `,`.
+    Cross,
+    /// `[INNER] JOIN`.
+    Inner,
+    /// `LEFT [OUTER] JOIN`.
+    Left,
+}
+
+/// A table joined to the tables on its left in the FROM clause.
+#[derive(Debug, PartialEq)]
+pub struct JoinClause<'a> {
+    pub join_type: JoinType,
+    pub table: TableRef<'a>,
+    /// The predicate of `ON`.
+    pub constraint: Option<Expr<'a>>,
+}
+
 #[derive(Debug)]
 pub struct Select<'a> {
-    pub table_name: MaybeQuotedBytes<'a>,
+    /// The first table in the FROM clause.
+    pub table: TableRef<'a>,
+    /// The tables joined to the first table in order, i.e. `a JOIN b JOIN c`
+    /// joins c to the result of `a JOIN b`.
+    pub joins: Vec<JoinClause<'a>>,
     pub columns: Vec<ResultColumn<'a>>,
     pub filter: Option<Expr<'a>>,
+    pub group_by: Vec<Expr<'a>>,
+    pub having: Option<Expr<'a>>,
+}
+
+impl<'a> Select<'a> {
+    /// Returns all the tables in the FROM clause in order.
+    ///
+    /// This is [Self::table] followed by the tables of [Self::joins], including
+    /// the tables after commas which are [JoinType::Cross] joins.
+    pub fn tables(&self) -> impl Iterator<Item = &TableRef<'a>> + '_ {
+        std::iter::once(&self.table).chain(self.joins.iter().map(|join| &join.table))
+    }
 }
 
 // Parse SELECT statement.
@@ -404,7 +782,9 @@ pub fn parse_select<'a>(p: &mut Parser<'a>) -> Result<'a, Select<'a>> {
     loop {
         match p.peek() {
             Some(Token::Comma) => {
-                p.next();
+                if let Some(Token::Comma | Token::From) | None = p.next() {
+                    return Err(p.error("no result column after comma"));
+                }
                 let result_column = parse_result_column(p)?;
                 columns.push(result_column);
             }
@@ -414,12 +794,25 @@ pub fn parse_select<'a>(p: &mut Parser<'a>) -> Result<'a, Select<'a>> {
             _ => return Err(p.error("no from")),
         }
     }
-    let Some(Token::Identifier(table_name)) = p.next() else {
-        return Err(p.error("no table_name"));
-    };
-    let table_name = *table_name;
+    p.next();
+    let table = parse_table_ref(p)?;
+    let mut joins = Vec::new();
+    while let Some(join_type) = parse_join_operator(p)? {
+        let table = parse_table_ref(p)?;
+        let constraint = if join_type != JoinType::Cross && p.peek() == Some(&Token::On) {
+            p.next();
+            Some(parse_expr(p)?)
+        } else {
+            None
+        };
+        joins.push(JoinClause {
+            join_type,
+            table,
+            constraint,
+        });
+    }
 
-    let filter = if let Some(Token::Where) = p.next() {
+    let filter = if let Some(Token::Where) = p.peek() {
         p.next();
         let expr = parse_expr(p)?;
         Some(expr)
@@ -427,1101 +820,4854 @@ pub fn parse_select<'a>(p: &mut Parser<'a>) -> Result<'a, Select<'a>> {
         None
     };
 
+    let group_by = if let Some(Token::Group) = p.peek() {
+        let Some(Token::By) = p.next() else {
+            return Err(p.error("no by after group"));
+        };
+        p.next();
+        let mut group_by = vec![parse_expr(p)?];
+        while let Some(Token::Comma) = p.peek() {
+            p.next();
+            group_by.push(parse_expr(p)?);
+        }
+        group_by
+    } else {
+        Vec::new()
+    };
+
+    let having = if let Some(Token::Having) = p.peek() {
+        if group_by.is_empty() {
+            return Err(p.error("having without group by"));
+        }
+        p.next();
+        Some(parse_expr(p)?)
+    } else {
+        None
+    };
+
     Ok(Select {
-        table_name,
+        table,
+        joins,
         columns,
         filter,
+        group_by,
+        having,
     })
 }
 
+/// The operator joining selects in a compound select.
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub enum CompoundOperator {
+    Union,
+    UnionAll,
+    Intersect,
+    Except,
+}
+
+/// Ordering term of ORDER BY clause.
+///
+/// https://www.sqlite.org/syntax/ordering-term.html
 #[derive(Debug, PartialEq)]
-pub enum ResultColumn<'a> {
-    All,
-    AllOfTable(MaybeQuotedBytes<'a>),
-    Expr((Expr<'a>, Option<MaybeQuotedBytes<'a>>)),
+pub struct OrderingTerm<'a> {
+    pub expr: Expr<'a>,
+    pub order: SortOrder,
 }
 
-/// Parse result column.
+/// LIMIT clause.
+#[derive(Debug, PartialEq)]
+pub struct Limit<'a> {
+    pub limit: Expr<'a>,
+    pub offset: Option<Expr<'a>>,
+    /// Whether the offset precedes the limit, i.e. `LIMIT <offset>, <limit>`.
+    pub is_offset_first: bool,
+}
+
+/// The EXPLAIN prefix of a statement.
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub enum ExplainMode {
+    None,
+    /// `EXPLAIN`
+    Explain,
+    /// `EXPLAIN QUERY PLAN`
+    QueryPlan,
+}
+
+/// A select or a compound select with ORDER BY and LIMIT.
 ///
-/// https://www.sqlite.org/syntax/result-column.html
-fn parse_result_column<'a>(p: &mut Parser<'a>) -> Result<'a, ResultColumn<'a>> {
-    match p.peek() {
-        Some(Token::Identifier(table_name)) => {
-            let table_name = *table_name;
-            let mut cloned_parser = p.clone();
-            if Some(&Token::Dot) == cloned_parser.next()
-                && Some(&Token::Asterisk) == cloned_parser.next()
-            {
-                cloned_parser.next();
-                *p = cloned_parser;
-                return Ok(ResultColumn::AllOfTable(table_name));
-            }
-            // Maybe schema_name.table_name.column_name. Fallback to expr
-            // parsing.
-        }
-        Some(Token::Asterisk) => {
-            p.next();
-            return Ok(ResultColumn::All);
+/// ORDER BY and LIMIT are applied to the whole compound select.
+#[derive(Debug)]
+pub struct SelectStmt<'a> {
+    pub explain: ExplainMode,
+    pub select: Select<'a>,
+    pub compounds: Vec<(CompoundOperator, Select<'a>)>,
+    pub order_by: Vec<OrderingTerm<'a>>,
+    pub limit: Option<Limit<'a>>,
+}
+
+impl<'a> SelectStmt<'a> {
+    /// Returns the bind parameters in the order of their first appearance.
+    ///
+    /// The parameters are deduplicated by their index, e.g. `:x` used twice is
+    /// one parameter. Like SQLite, `?` is numbered in the source order, so
+    /// `LIMIT ?, ?` binds the offset to `?1`.
+    pub fn parameters(&self) -> Vec<Parameter<'a>> {
+        let mut collector = ParameterCollector::default();
+        collector.visit_select(&self.select);
+        for (_, select) in self.compounds.iter() {
+            collector.visit_select(select);
         }
-        _ => {}
-    }
-    let expr = parse_expr(p)?;
-    match p.peek() {
-        Some(Token::Identifier(alias)) => {
-            let alias = *alias;
-            p.next();
-            Ok(ResultColumn::Expr((expr, Some(alias))))
+        for term in self.order_by.iter() {
+            collector.visit_expr(&term.expr);
         }
-        Some(Token::As) => {
-            let Some(Token::Identifier(alias)) = p.next() else {
-                return Err(p.error("no alias"));
+        if let Some(limit) = &self.limit {
+            let exprs = if limit.is_offset_first {
+                [limit.offset.as_ref(), Some(&limit.limit)]
+            } else {
+                [Some(&limit.limit), limit.offset.as_ref()]
             };
-            let alias = *alias;
-            p.next();
-            Ok(ResultColumn::Expr((expr, Some(alias))))
+            for expr in exprs.into_iter().flatten() {
+                collector.visit_expr(expr);
+            }
         }
-        _ => Ok(ResultColumn::Expr((expr, None))),
+        collector.parameters
     }
 }
 
-#[derive(Debug, PartialEq, Eq)]
-pub enum UnaryOp {
-    BitNot,
-    Minus,
+/// A bind parameter of a statement.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Parameter<'a> {
+    /// The index to bind a value, starting from 1.
+    pub index: u32,
+    /// The name including the prefix. None for `?` and `?NNN`.
+    pub name: Option<&'a [u8]>,
 }
 
-#[derive(Debug, PartialEq, Eq)]
-pub enum BinaryOp {
-    Compare(CompareOp),
-    Concat,
-    // TODO: BitOr
+/// Assigns indexes to bind parameters in the same way as SQLite.
+///
+/// `?` and a new name take the largest index so far plus one.
+#[derive(Default)]
+struct ParameterCollector<'a> {
+    parameters: Vec<Parameter<'a>>,
+    max_index: u32,
 }
 
-#[derive(Debug, PartialEq, Eq)]
-pub enum CompareOp {
-    /// Equal to
-    Eq,
-    /// Not equal to
-    Ne,
-    /// Greater than
-    Gt,
-    /// Greater than or equal to
-    Ge,
-    /// Less than
-    Lt,
-    /// Less than or equal to
-    Le,
-}
+impl<'a> ParameterCollector<'a> {
+    fn add(&mut self, parameter: &BindParameter<'a>) {
+        let (index, name) = match parameter {
+            BindParameter::Next => (self.max_index + 1, None),
+            BindParameter::Index(index) => (*index, None),
+            BindParameter::Name(name) => {
+                if self.parameters.iter().any(|p| p.name == Some(*name)) {
+                    return;
+                }
+                (self.max_index + 1, Some(*name))
+            }
+        };
+        self.max_index = self.max_index.max(index);
+        if !self.parameters.iter().any(|p| p.index == index) {
+            self.parameters.push(Parameter { index, name });
+        }
+    }
 
-#[derive(Debug, PartialEq)]
-pub enum Expr<'a> {
-    Column(MaybeQuotedBytes<'a>),
-    UnaryOperator {
-        operator: UnaryOp,
-        expr: Box<Expr<'a>>,
-    },
-    Collate {
-        expr: Box<Expr<'a>>,
-        collation_name: MaybeQuotedBytes<'a>,
-    },
-    BinaryOperator {
-        operator: BinaryOp,
-        left: Box<Expr<'a>>,
-        right: Box<Expr<'a>>,
-    },
-    Cast {
-        expr: Box<Expr<'a>>,
-        type_name: Vec<MaybeQuotedBytes<'a>>,
-    },
-    Null,
-    Integer(i64),
-    Real(f64),
-    Text(MaybeQuotedBytes<'a>),
-    Blob(HexedBytes<'a>),
-}
+    fn visit_select(&mut self, select: &Select<'a>) {
+        for column in select.columns.iter() {
+            if let ResultColumn::Expr((expr, _)) = column {
+                self.visit_expr(expr);
+            }
+        }
+        for join in select.joins.iter() {
+            if let Some(constraint) = &join.constraint {
+                self.visit_expr(constraint);
+            }
+        }
+        if let Some(filter) = &select.filter {
+            self.visit_expr(filter);
+        }
+        for expr in select.group_by.iter() {
+            self.visit_expr(expr);
+        }
+        if let Some(having) = &select.having {
+            self.visit_expr(having);
+        }
+    }
 
-/// Parse expression.
-///
-/// https://www.sqlite.org/syntax/expr.html
-fn parse_expr<'a>(p: &mut Parser<'a>) -> Result<'a, Expr<'a>> {
-    parse_expr_eq(p)
+    fn visit_expr(&mut self, expr: &Expr<'a>) {
+        match expr {
+            Expr::BindParameter(parameter) => self.add(parameter),
+            Expr::UnaryOperator { expr, .. }
+            | Expr::Collate { expr, .. }
+            | Expr::Cast { expr, .. } => self.visit_expr(expr),
+            Expr::BinaryOperator { left, right, .. } => {
+                self.visit_expr(left);
+                self.visit_expr(right);
+            }
+            Expr::Like {
+                left,
+                pattern,
+                escape,
+                ..
+            } => {
+                self.visit_expr(left);
+                self.visit_expr(pattern);
+                if let Some(escape) = escape {
+                    self.visit_expr(escape);
+                }
+            }
+            Expr::Glob { left, pattern, .. } | Expr::Regexp { left, pattern, .. } => {
+                self.visit_expr(left);
+                self.visit_expr(pattern);
+            }
+            Expr::Between {
+                expr, low, high, ..
+            } => {
+                self.visit_expr(expr);
+                self.visit_expr(low);
+                self.visit_expr(high);
+            }
+            Expr::In { expr, list, .. } => {
+                self.visit_expr(expr);
+                for expr in list.iter() {
+                    self.visit_expr(expr);
+                }
+            }
+            Expr::Aggregate { arg, .. } => {
+                if let AggArg::Expr(expr) = arg {
+                    self.visit_expr(expr);
+                }
+            }
+            Expr::Function { args, .. } => {
+                for expr in args.iter() {
+                    self.visit_expr(expr);
+                }
+            }
+            Expr::Case {
+                operand,
+                whens,
+                else_,
+            } => {
+                if let Some(operand) = operand {
+                    self.visit_expr(operand);
+                }
+                for (when, then) in whens.iter() {
+                    self.visit_expr(when);
+                    self.visit_expr(then);
+                }
+                if let Some(else_) = else_ {
+                    self.visit_expr(else_);
+                }
+            }
+            Expr::Column(_)
+            | Expr::QualifiedColumn { .. }
+            | Expr::Null
+            | Expr::Integer(_)
+            | Expr::Real(_)
+            | Expr::Text(_)
+            | Expr::Blob(_)
+            | Expr::CurrentTime(_) => {}
+        }
+    }
 }
 
-fn parse_expr_eq<'a>(p: &mut Parser<'a>) -> Result<'a, Expr<'a>> {
-    let mut expr = parse_expr_compare(p)?;
+// Parse SELECT statement which may be a compound select.
+//
+// https://www.sqlite.org/lang_select.html
+pub fn parse_select_stmt<'a>(p: &mut Parser<'a>) -> Result<'a, SelectStmt<'a>> {
+    let explain = parse_explain(p)?;
+    let select = parse_select(p)?;
+
+    let mut compounds = Vec::new();
     loop {
         let operator = match p.peek() {
-            Some(Token::Eq) => BinaryOp::Compare(CompareOp::Eq),
-            Some(Token::Ne) => BinaryOp::Compare(CompareOp::Ne),
+            Some(Token::Union) => {
+                if let Some(Token::All) = p.next() {
+                    p.next();
+                    CompoundOperator::UnionAll
+                } else {
+                    CompoundOperator::Union
+                }
+            }
+            Some(Token::Intersect) => {
+                p.next();
+                CompoundOperator::Intersect
+            }
+            Some(Token::Except) => {
+                p.next();
+                CompoundOperator::Except
+            }
             _ => break,
         };
-        p.next();
-        let right = parse_expr_compare(p)?;
-        expr = Expr::BinaryOperator {
-            operator,
-            left: Box::new(expr),
-            right: Box::new(right),
+        compounds.push((operator, parse_select(p)?));
+    }
+
+    let mut order_by = Vec::new();
+    if let Some(Token::Order) = p.peek() {
+        let Some(Token::By) = p.next() else {
+            return Err(p.error("no by after order"));
         };
+        loop {
+            p.next();
+            let expr = parse_expr(p)?;
+            let order = match p.peek() {
+                Some(Token::Asc) => {
+                    p.next();
+                    SortOrder::Asc
+                }
+                Some(Token::Desc) => {
+                    p.next();
+                    SortOrder::Desc
+                }
+                _ => SortOrder::Asc,
+            };
+            order_by.push(OrderingTerm { expr, order });
+            if p.peek() != Some(&Token::Comma) {
+                break;
+            }
+        }
     }
-    Ok(expr)
+
+    let limit = if let Some(Token::Limit) = p.peek() {
+        p.next();
+        let expr = parse_expr(p)?;
+        match p.peek() {
+            Some(Token::Offset) => {
+                p.next();
+                Some(Limit {
+                    limit: expr,
+                    offset: Some(parse_expr(p)?),
+                    is_offset_first: false,
+                })
+            }
+            // "LIMIT <offset>, <limit>"
+            Some(Token::Comma) => {
+                p.next();
+                Some(Limit {
+                    limit: parse_expr(p)?,
+                    offset: Some(expr),
+                    is_offset_first: true,
+                })
+            }
+            _ => Some(Limit {
+                limit: expr,
+                offset: None,
+                is_offset_first: false,
+            }),
+        }
+    } else {
+        None
+    };
+
+    Ok(SelectStmt {
+        explain,
+        select,
+        compounds,
+        order_by,
+        limit,
+    })
 }
 
-fn parse_expr_compare<'a>(p: &mut Parser<'a>) -> Result<'a, Expr<'a>> {
-    let mut expr = parse_expr_concat(p)?;
-    loop {
-        let operator = match p.peek() {
-            Some(Token::Gt) => BinaryOp::Compare(CompareOp::Gt),
-            Some(Token::Ge) => BinaryOp::Compare(CompareOp::Ge),
-            Some(Token::Lt) => BinaryOp::Compare(CompareOp::Lt),
-            Some(Token::Le) => BinaryOp::Compare(CompareOp::Le),
-            _ => break,
-        };
+/// Parse the optional `EXPLAIN` or `EXPLAIN QUERY PLAN` prefix.
+///
+/// https://www.sqlite.org/lang_explain.html
+fn parse_explain<'a>(p: &mut Parser<'a>) -> Result<'a, ExplainMode> {
+    let Some(Token::Explain) = p.peek() else {
+        return Ok(ExplainMode::None);
+    };
+    // QUERY and PLAN are not tokenized as keywords because they are common
+    // column names. Like EXPLAIN, they are names unless they follow EXPLAIN.
+    let is_keyword = |token: Option<&Token>, keyword: &[u8]| matches!(token, Some(Token::Identifier(id)) if id.raw().eq_ignore_ascii_case(keyword));
+    if is_keyword(p.next(), b"query") {
+        if !is_keyword(p.next(), b"plan") {
+            return Err(p.error("no plan after query"));
+        }
         p.next();
-        let right = parse_expr_concat(p)?;
-        expr = Expr::BinaryOperator {
-            operator,
-            left: Box::new(expr),
-            right: Box::new(right),
-        };
+        Ok(ExplainMode::QueryPlan)
+    } else {
+        Ok(ExplainMode::Explain)
     }
-    Ok(expr)
 }
 
-fn parse_expr_concat<'a>(p: &mut Parser<'a>) -> Result<'a, Expr<'a>> {
-    let mut expr = parse_expr_collate(p)?;
-    // TODO: -> ->> will be added in the future.
-    #[allow(clippy::while_let_loop)]
-    loop {
-        let operator = match p.peek() {
-            Some(Token::Concat) => BinaryOp::Concat,
-            _ => break,
-        };
+/// DELETE statement.
+#[derive(Debug, PartialEq)]
+pub struct Delete<'a> {
+    pub table_name: MaybeQuotedBytes<'a>,
+    pub filter: Option<Expr<'a>>,
+}
+
+/// Parse DELETE statement.
+///
+/// https://www.sqlite.org/lang_delete.html
+pub fn parse_delete<'a>(p: &mut Parser<'a>) -> Result<'a, Delete<'a>> {
+    let Some(Token::Delete) = p.peek() else {
+        return Err(p.error("no delete"));
+    };
+
+    let Some(Token::From) = p.next() else {
+        return Err(p.error("no from"));
+    };
+
+    let Some(table_name) = p.next_name() else {
+        return Err(p.error("no table_name"));
+    };
+
+    let filter = if let Some(Token::Where) = p.next() {
         p.next();
-        let right = parse_expr_collate(p)?;
-        expr = Expr::BinaryOperator {
-            operator,
-            left: Box::new(expr),
-            right: Box::new(right),
-        };
-    }
-    Ok(expr)
+        Some(parse_expr(p)?)
+    } else {
+        None
+    };
+
+    Ok(Delete { table_name, filter })
 }
 
-fn parse_expr_collate<'a>(p: &mut Parser<'a>) -> Result<'a, Expr<'a>> {
-    let expr = parse_expr_unary(p)?;
-    let mut collation = None;
-    while let Some(Token::Collate) = p.peek() {
-        let Some(Token::Identifier(collation_name)) = p.next() else {
-            return Err(p.error("no collation name"));
+/// UPDATE statement.
+#[derive(Debug, PartialEq)]
+pub struct Update<'a> {
+    pub table_name: MaybeQuotedBytes<'a>,
+    /// Pairs of the column name and the new value.
+    pub assignments: Vec<(MaybeQuotedBytes<'a>, Expr<'a>)>,
+    pub filter: Option<Expr<'a>>,
+}
+
+/// Parse UPDATE statement.
+///
+/// https://www.sqlite.org/lang_update.html
+pub fn parse_update<'a>(p: &mut Parser<'a>) -> Result<'a, Update<'a>> {
+    let Some(Token::Update) = p.peek() else {
+        return Err(p.error("no update"));
+    };
+
+    let Some(table_name) = p.next_name() else {
+        return Err(p.error("no table_name"));
+    };
+
+    let Some(Token::Set) = p.next() else {
+        return Err(p.error("no set"));
+    };
+
+    let mut assignments = Vec::new();
+    loop {
+        let Some(column_name) = p.next_name() else {
+            return Err(p.error("no column name"));
+        };
+        let Some(Token::Eq) = p.next() else {
+            return Err(p.error("no eq"));
         };
-        collation = Some(*collation_name);
         p.next();
+        assignments.push((column_name, parse_expr(p)?));
+        if p.peek() != Some(&Token::Comma) {
+            break;
+        }
     }
-    if let Some(collation_name) = collation {
-        Ok(Expr::Collate {
-            expr: Box::new(expr),
-            collation_name,
-        })
+
+    let filter = if let Some(Token::Where) = p.peek() {
+        p.next();
+        Some(parse_expr(p)?)
     } else {
-        Ok(expr)
-    }
+        None
+    };
+
+    Ok(Update {
+        table_name,
+        assignments,
+        filter,
+    })
 }
 
-fn parse_expr_unary<'a>(p: &mut Parser<'a>) -> Result<'a, Expr<'a>> {
-    match p.peek() {
-        Some(Token::Tilda) => {
+#[derive(Debug, PartialEq)]
+pub enum ResultColumn<'a> {
+    All,
+    AllOfTable(MaybeQuotedBytes<'a>),
+    Expr((Expr<'a>, Option<MaybeQuotedBytes<'a>>)),
+}
+
+/// Parse a table in the FROM clause.
+fn parse_table_ref<'a>(p: &mut Parser<'a>) -> Result<'a, TableRef<'a>> {
+    let Some(table_name) = p.peek_name() else {
+        return Err(p.error("no table_name"));
+    };
+    p.next();
+    Ok(TableRef { table_name })
+}
+
+/// Parse a join operator if exists.
+///
+/// https://www.sqlite.org/syntax/join-operator.html
+fn parse_join_operator<'a>(p: &mut Parser<'a>) -> Result<'a, Option<JoinType>> {
+    let join_type = match p.peek() {
+        Some(Token::Comma) => {
             p.next();
-            let expr = parse_expr_unary(p)?;
-            Ok(Expr::UnaryOperator {
-                operator: UnaryOp::BitNot,
-                expr: Box::new(expr),
-            })
+            return Ok(Some(JoinType::Cross));
         }
-        Some(Token::Plus) => {
+        Some(Token::Join) => JoinType::Inner,
+        Some(Token::Inner) => {
             p.next();
-            // Unary operator + is a no-op.
-            let expr = parse_expr_unary(p)?;
-            Ok(expr)
+            JoinType::Inner
         }
-        Some(Token::Minus) => match p.next() {
-            Some(Token::Integer(buf)) => {
-                let (valid, parsed_int) = parse_integer(buf);
-                assert!(valid);
-                let expr = match parsed_int {
-                    ParseIntegerResult::Integer(v) => Expr::Integer(-v),
-                    ParseIntegerResult::MaxPlusOne => Expr::Integer(i64::MIN),
-                    ParseIntegerResult::TooBig(_) => {
-                        let (valid, pure_integer, d) = parse_float(buf);
-                        assert!(valid);
-                        assert!(pure_integer);
-                        Expr::Real(-d)
-                    }
-                    ParseIntegerResult::Empty => {
-                        unreachable!("token integer must contain at least 1 digits only")
-                    }
-                };
-                p.next();
-                Ok(expr)
-            }
-            Some(Token::Float(buf)) => {
-                let (valid, pure_integer, d) = parse_float(buf);
-                assert!(valid);
-                assert!(!pure_integer);
+        Some(Token::Left) => {
+            if let Some(Token::Outer) = p.next() {
                 p.next();
-                Ok(Expr::Real(-d))
             }
-            _ => {
-                let expr = parse_expr_unary(p)?;
-                Ok(Expr::UnaryOperator {
-                    operator: UnaryOp::Minus,
-                    expr: Box::new(expr),
-                })
-            }
-        },
-        _ => parse_expr_primitive(p),
-    }
+            JoinType::Left
+        }
+        _ => return Ok(None),
+    };
+    let Some(Token::Join) = p.peek() else {
+        return Err(p.error("no join"));
+    };
+    p.next();
+    Ok(Some(join_type))
 }
 
-fn parse_expr_primitive<'a>(p: &mut Parser<'a>) -> Result<'a, Expr<'a>> {
-    let expr = match p.peek() {
-        Some(Token::Identifier(id)) => Expr::Column(*id),
-        Some(Token::Cast) => {
-            let Some(Token::LeftParen) = p.next() else {
-                return Err(p.error("no cast left paren"));
+/// Parse result column.
+///
+/// https://www.sqlite.org/syntax/result-column.html
+fn parse_result_column<'a>(p: &mut Parser<'a>) -> Result<'a, ResultColumn<'a>> {
+    if let Some(table_name) = p.peek_name() {
+        let mut cloned_parser = p.clone();
+        if Some(&Token::Dot) == cloned_parser.next()
+            && Some(&Token::Asterisk) == cloned_parser.next()
+        {
+            cloned_parser.next();
+            *p = cloned_parser;
+            return Ok(ResultColumn::AllOfTable(table_name));
+        }
+        // Maybe table_name.column_name. Fallback to expr parsing.
+    }
+    match p.peek() {
+        Some(Token::Comma | Token::From) | None => {
+            return Err(p.error("no result column"));
+        }
+        Some(Token::Asterisk) => {
+            p.next();
+            return Ok(ResultColumn::All);
+        }
+        _ => {}
+    }
+    let expr = parse_expr(p)?;
+    // Like SQLite, a join keyword is not a bare alias though it is a name.
+    if let Some(alias) = p
+        .peek_name()
+        .filter(|_| !p.peek().is_some_and(Token::is_join_keyword))
+    {
+        p.next();
+        return Ok(ResultColumn::Expr((expr, Some(alias))));
+    }
+    match p.peek() {
+        Some(Token::As) => {
+            let Some(alias) = p.next_name() else {
+                return Err(p.error("no alias"));
             };
             p.next();
+            Ok(ResultColumn::Expr((expr, Some(alias))))
+        }
+        _ => Ok(ResultColumn::Expr((expr, None))),
+    }
+}
 
-            let expr = parse_expr(p)?;
+#[derive(Debug, PartialEq, Eq)]
+pub enum UnaryOp {
+    BitNot,
+    Minus,
+    Not,
+}
 
-            let Some(Token::As) = p.peek() else {
-                return Err(p.error("no cast as"));
-            };
-            p.next();
+#[derive(Debug, PartialEq, Eq)]
+pub enum BinaryOp {
+    Compare(CompareOp),
+    /// `left || right`.
+    ///
+    /// Both operands are converted to text before concatenation. This binds
+    /// tighter than any other binary operator, i.e. `a || b * c` is
+    /// `(a || b) * c`.
+    Concat,
+    Add,
+    Sub,
+    Mul,
+    Div,
+    Mod,
+    /// Null-safe equal to. `NULL IS NULL` is true.
+    Is,
+    /// Null-safe not equal to. `NULL IS NOT NULL` is false.
+    IsNot,
+    /// Logical AND. `NULL AND 0` is false.
+    And,
+    /// Logical OR. `NULL OR 1` is true.
+    Or,
+    // TODO: BitOr
+}
 
-            let type_name = parse_type_name(p)?;
+#[derive(Debug, PartialEq, Eq)]
+pub enum CompareOp {
+    /// Equal to
+    Eq,
+    /// Not equal to
+    Ne,
+    /// Greater than
+    Gt,
+    /// Greater than or equal to
+    Ge,
+    /// Less than
+    Lt,
+    /// Less than or equal to
+    Le,
+}
 
-            let Some(Token::RightParen) = p.peek() else {
-                return Err(p.error("no cast right paren"));
-            };
+/// Aggregate function.
+///
+/// https://www.sqlite.org/lang_aggfunc.html
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub enum AggFunc {
+    Count,
+    Sum,
+    Avg,
+    Min,
+    Max,
+}
 
-            Expr::Cast {
-                expr: Box::new(expr),
-                type_name,
-            }
-        }
-        Some(Token::Null) => Expr::Null,
-        Some(Token::Integer(buf)) => {
-            let (valid, parsed_int) = parse_integer(buf);
-            assert!(valid);
-            match parsed_int {
-                ParseIntegerResult::Integer(v) => Expr::Integer(v),
-                ParseIntegerResult::MaxPlusOne | ParseIntegerResult::TooBig(_) => {
-                    let (valid, pure_integer, d) = parse_float(buf);
-                    assert!(valid);
-                    assert!(pure_integer);
-                    Expr::Real(d)
-                }
-                ParseIntegerResult::Empty => {
-                    unreachable!("token integer must contain at least 1 digits only")
-                }
-            }
-        }
-        Some(Token::Float(buf)) => {
-            let (valid, pure_integer, d) = parse_float(buf);
-            assert!(valid);
-            assert!(!pure_integer);
-            Expr::Real(d)
+impl AggFunc {
+    fn from_name(name: &[u8]) -> Option<Self> {
+        let name = CaseInsensitiveBytes::from(name);
+        if name.equal_to_lower_bytes(b"count") {
+            Some(Self::Count)
+        } else if name.equal_to_lower_bytes(b"sum") {
+            Some(Self::Sum)
+        } else if name.equal_to_lower_bytes(b"avg") {
+            Some(Self::Avg)
+        } else if name.equal_to_lower_bytes(b"min") {
+            Some(Self::Min)
+        } else if name.equal_to_lower_bytes(b"max") {
+            Some(Self::Max)
+        } else {
+            None
         }
-        Some(Token::String(text)) => Expr::Text(*text),
-        Some(Token::Blob(hex)) => Expr::Blob(*hex),
-        _ => return Err(p.error("no expr")),
-    };
-    p.next();
+    }
+}
 
-    Ok(expr)
+/// Argument of an aggregate function.
+#[derive(Debug, PartialEq)]
+pub enum AggArg<'a> {
+    /// `*` of `count(*)`.
+    Star,
+    Expr(Box<Expr<'a>>),
 }
 
-#[cfg(test)]
-mod tests {
-    use super::*;
+#[derive(Debug, PartialEq)]
+pub enum Expr<'a> {
+    Column(MaybeQuotedBytes<'a>),
+    /// `table_name.column_name`.
+    QualifiedColumn {
+        table_name: MaybeQuotedBytes<'a>,
+        column_name: MaybeQuotedBytes<'a>,
+    },
+    UnaryOperator {
+        operator: UnaryOp,
+        expr: Box<Expr<'a>>,
+    },
+    Collate {
+        expr: Box<Expr<'a>>,
+        collation_name: MaybeQuotedBytes<'a>,
+    },
+    BinaryOperator {
+        operator: BinaryOp,
+        left: Box<Expr<'a>>,
+        right: Box<Expr<'a>>,
+    },
+    Cast {
+        expr: Box<Expr<'a>>,
+        type_name: Vec<MaybeQuotedBytes<'a>>,
+    },
+    /// `left [NOT] LIKE pattern [ESCAPE escape]`.
+    ///
+    /// In the pattern, `%` matches any sequence of zero or more characters and
+    /// `_` matches any single character. Upper case ASCII characters match
+    /// their lower case ones and vice versa. A character following the escape
+    /// character matches itself even if it is `%`, `_` or the escape
+    /// character.
+    Like {
+        left: Box<Expr<'a>>,
+        pattern: Box<Expr<'a>>,
+        escape: Option<Box<Expr<'a>>>,
+        negated: bool,
+    },
+    /// `left [NOT] GLOB pattern`.
+    ///
+    /// Unlike LIKE, GLOB is case sensitive and uses the Unix file globbing
+    /// syntax. GLOB does not take an ESCAPE clause.
+    Glob {
+        left: Box<Expr<'a>>,
+        pattern: Box<Expr<'a>>,
+        negated: bool,
+    },
+    /// `left [NOT] REGEXP pattern`.
+    ///
+    /// SQLite has no default implementation of REGEXP. It is only usable when
+    /// a `regexp()` function is registered.
+    Regexp {
+        left: Box<Expr<'a>>,
+        pattern: Box<Expr<'a>>,
+        negated: bool,
+    },
+    /// `expr [NOT] BETWEEN low AND high`.
+    Between {
+        expr: Box<Expr<'a>>,
+        low: Box<Expr<'a>>,
+        high: Box<Expr<'a>>,
+        negated: bool,
+    },
+    /// `expr [NOT] IN (list)`.
+    In {
+        expr: Box<Expr<'a>>,
+        list: Vec<Expr<'a>>,
+        negated: bool,
+    },
+    /// `func([DISTINCT] arg)` or `count(*)`.
+    Aggregate {
+        func: AggFunc,
+        arg: AggArg<'a>,
+        distinct: bool,
+    },
+    /// `name([DISTINCT] args...)`. Any function call other than the
+    /// single-argument aggregate functions.
+    Function {
+        name: MaybeQuotedBytes<'a>,
+        args: Vec<Expr<'a>>,
+        distinct: bool,
+    },
+    /// `CASE [operand] WHEN when THEN then ... [ELSE else] END`.
+    ///
+    /// Without the operand, the result of the first WHEN expression which is
+    /// true is selected. With the operand, the first WHEN expression equal to
+    /// the operand is selected instead.
+    Case {
+        operand: Option<Box<Expr<'a>>>,
+        whens: Vec<(Expr<'a>, Expr<'a>)>,
+        else_: Option<Box<Expr<'a>>>,
+    },
+    Null,
+    Integer(i64),
+    Real(f64),
+    Text(MaybeQuotedBytes<'a>),
+    Blob(HexedBytes<'a>),
+    BindParameter(BindParameter<'a>),
+    /// `CURRENT_DATE`, `CURRENT_TIME` or `CURRENT_TIMESTAMP`.
+    ///
+    /// The value is the current date and/or time in UTC at the start of the
+    /// statement.
+    CurrentTime(CurrentTimeKind),
+}
 
-    macro_rules! assert_parser {
-        ($parse_fn:ident, $input:expr, $size:expr, $expected:expr) => {
-            let mut parser = Parser::new($input);
-            let r = $parse_fn(&mut parser);
-            assert!(r.is_ok());
-            assert_eq!(r.unwrap(), $expected);
-            assert_eq!(parser.n_consumed(), $size);
-        };
-    }
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CurrentTimeKind {
+    /// `CURRENT_DATE`. "YYYY-MM-DD".
+    Date,
+    /// `CURRENT_TIME`. "HH:MM:SS".
+    Time,
+    /// `CURRENT_TIMESTAMP`. "YYYY-MM-DD HH:MM:SS".
+    Timestamp,
+}
 
-    #[test]
-    fn test_expect_semicolon() {
-        assert_parser!(expect_semicolon, b";  ", 3, ());
-        assert_parser!(expect_semicolon, b";  a  ", 3, ());
-        assert_parser!(expect_semicolon, b"   ;  ", 6, ());
+/// The largest index of a bind parameter. This is the default of
+/// SQLITE_MAX_VARIABLE_NUMBER.
+const MAX_BIND_PARAMETER_INDEX: u32 = 32766;
 
-        let mut parser = Parser::new(b"     ");
-        let r = expect_semicolon(&mut parser);
-        assert!(r.is_err());
-        assert_eq!(r.unwrap_err().cursor(), 5);
+/// A placeholder of a value which is bound on execution.
+///
+/// https://www.sqlite.org/lang_expr.html#varparam
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BindParameter<'a> {
+    /// `?`. The index is one more than the largest index of the parameters
+    /// preceding it.
+    Next,
+    /// `?NNN`.
+    Index(u32),
+    /// `:AAAA`, `@AAAA` or `$AAAA`. This includes the prefix because the
+    /// parameters with different prefixes are different.
+    Name(&'a [u8]),
+}
 
-        let mut parser = Parser::new(b"");
-        let r = expect_semicolon(&mut parser);
-        assert!(r.is_err());
-        assert_eq!(r.unwrap_err().cursor(), 0);
+/// Parse expression.
+///
+/// https://www.sqlite.org/syntax/expr.html
+fn parse_expr<'a>(p: &mut Parser<'a>) -> Result<'a, Expr<'a>> {
+    parse_expr_or(p)
+}
+
+fn parse_expr_or<'a>(p: &mut Parser<'a>) -> Result<'a, Expr<'a>> {
+    let mut expr = parse_expr_and(p)?;
+    while let Some(Token::Or) = p.peek() {
+        p.next();
+        let right = parse_expr_and(p)?;
+        expr = Expr::BinaryOperator {
+            operator: BinaryOp::Or,
+            left: Box::new(expr),
+            right: Box::new(right),
+        };
     }
+    Ok(expr)
+}
 
-    #[test]
-    fn test_expect_no_more_token() {
-        assert_parser!(expect_no_more_token, b"", 0, ());
-        assert_parser!(expect_no_more_token, b"    ", 4, ());
+fn parse_expr_and<'a>(p: &mut Parser<'a>) -> Result<'a, Expr<'a>> {
+    let mut expr = parse_expr_not(p)?;
+    while let Some(Token::And) = p.peek() {
+        p.next();
+        let right = parse_expr_not(p)?;
+        expr = Expr::BinaryOperator {
+            operator: BinaryOp::And,
+            left: Box::new(expr),
+            right: Box::new(right),
+        };
+    }
+    Ok(expr)
+}
 
-        let mut parser = Parser::new(b"    ;  ");
-        let r = expect_no_more_token(&mut parser);
-        assert!(r.is_err());
-        assert_eq!(r.unwrap_err().cursor(), 4);
+fn parse_expr_not<'a>(p: &mut Parser<'a>) -> Result<'a, Expr<'a>> {
+    if let Some(Token::Not) = p.peek() {
+        p.next();
+        let expr = parse_expr_not(p)?;
+        Ok(Expr::UnaryOperator {
+            operator: UnaryOp::Not,
+            expr: Box::new(expr),
+        })
+    } else {
+        parse_expr_eq(p)
     }
+}
 
-    #[test]
-    fn test_parse_create_table() {
-        let input = b"create table foo (id integer primary key, name text, real real, \"blob\" blob, `empty` null,[no_type])";
-        let mut parser = Parser::new(input);
-        let create_table = parse_create_table(&mut parser).unwrap();
-        assert_eq!(parser.n_consumed(), input.len());
-        assert_eq!(create_table.table_name, b"foo".as_slice().into());
-        assert_eq!(
-            create_table.columns,
-            vec![
-                ColumnDef {
-                    name: b"id".as_slice().into(),
-                    type_name: vec![b"integer".as_slice().into()],
-                    constraints: vec![ColumnConstraint::PrinaryKey],
-                },
-                ColumnDef {
-                    name: b"name".as_slice().into(),
-                    type_name: vec![b"text".as_slice().into()],
-                    constraints: vec![],
-                },
-                ColumnDef {
-                    name: b"real".as_slice().into(),
-                    type_name: vec![b"real".as_slice().into()],
-                    constraints: vec![],
-                },
-                ColumnDef {
-                    name: b"\"blob\"".as_slice().into(),
-                    type_name: vec![b"blob".as_slice().into()],
-                    constraints: vec![],
-                },
-                ColumnDef {
-                    name: b"`empty`".as_slice().into(),
+fn parse_expr_eq<'a>(p: &mut Parser<'a>) -> Result<'a, Expr<'a>> {
+    let mut expr = parse_expr_compare(p)?;
+    loop {
+        let operator = match p.peek() {
+            Some(Token::Eq) => BinaryOp::Compare(CompareOp::Eq),
+            Some(Token::Ne) => BinaryOp::Compare(CompareOp::Ne),
+            Some(Token::Is) => {
+                let mut cloned_parser = p.clone();
+                if let Some(Token::Not) = cloned_parser.next() {
+                    // Skip IS. NOT is skipped below.
+                    *p = cloned_parser;
+                    BinaryOp::IsNot
+                } else {
+                    BinaryOp::Is
+                }
+            }
+            Some(Token::Not) | Some(Token::Like) | Some(Token::Glob) | Some(Token::Regexp)
+            | Some(Token::In) | Some(Token::Between) => {
+                let negated = p.peek() == Some(&Token::Not);
+                if negated {
+                    p.next();
+                }
+                expr = match p.peek() {
+                    Some(Token::Like) => parse_like(p, expr, negated)?,
+                    Some(Token::Glob) => parse_glob(p, expr, negated)?,
+                    Some(Token::Regexp) => parse_regexp(p, expr, negated)?,
+                    Some(Token::In) => parse_in(p, expr, negated)?,
+                    Some(Token::Between) => parse_between(p, expr, negated)?,
+                    _ => return Err(p.error("no like, glob, regexp, in or between after not")),
+                };
+                continue;
+            }
+            _ => break,
+        };
+        p.next();
+        let right = parse_expr_compare(p)?;
+        expr = Expr::BinaryOperator {
+            operator,
+            left: Box::new(expr),
+            right: Box::new(right),
+        };
+    }
+    Ok(expr)
+}
+
+/// Parse the rest of LIKE expression. The parser must point to LIKE.
+fn parse_like<'a>(p: &mut Parser<'a>, left: Expr<'a>, negated: bool) -> Result<'a, Expr<'a>> {
+    assert_eq!(p.peek(), Some(&Token::Like));
+    p.next();
+    let pattern = parse_expr_compare(p)?;
+    let escape = if let Some(Token::Escape) = p.peek() {
+        p.next();
+        Some(Box::new(parse_expr_compare(p)?))
+    } else {
+        None
+    };
+    Ok(Expr::Like {
+        left: Box::new(left),
+        pattern: Box::new(pattern),
+        escape,
+        negated,
+    })
+}
+
+/// Parse the rest of GLOB expression. The parser must point to GLOB.
+fn parse_glob<'a>(p: &mut Parser<'a>, left: Expr<'a>, negated: bool) -> Result<'a, Expr<'a>> {
+    assert_eq!(p.peek(), Some(&Token::Glob));
+    p.next();
+    let pattern = parse_expr_compare(p)?;
+    Ok(Expr::Glob {
+        left: Box::new(left),
+        pattern: Box::new(pattern),
+        negated,
+    })
+}
+
+/// Parse the rest of REGEXP expression. The parser must point to REGEXP.
+fn parse_regexp<'a>(p: &mut Parser<'a>, left: Expr<'a>, negated: bool) -> Result<'a, Expr<'a>> {
+    assert_eq!(p.peek(), Some(&Token::Regexp));
+    p.next();
+    let pattern = parse_expr_compare(p)?;
+    Ok(Expr::Regexp {
+        left: Box::new(left),
+        pattern: Box::new(pattern),
+        negated,
+    })
+}
+
+/// Parse the rest of BETWEEN expression. The parser must point to BETWEEN.
+///
+/// The AND between low and high is consumed here and is not a logical
+/// operator.
+fn parse_between<'a>(p: &mut Parser<'a>, expr: Expr<'a>, negated: bool) -> Result<'a, Expr<'a>> {
+    assert_eq!(p.peek(), Some(&Token::Between));
+    p.next();
+    let low = parse_expr_compare(p)?;
+    let Some(Token::And) = p.peek() else {
+        return Err(p.error("no and after between"));
+    };
+    p.next();
+    let high = parse_expr_compare(p)?;
+    Ok(Expr::Between {
+        expr: Box::new(expr),
+        low: Box::new(low),
+        high: Box::new(high),
+        negated,
+    })
+}
+
+/// Parse the rest of IN expression. The parser must point to IN.
+fn parse_in<'a>(p: &mut Parser<'a>, expr: Expr<'a>, negated: bool) -> Result<'a, Expr<'a>> {
+    assert_eq!(p.peek(), Some(&Token::In));
+    let Some(Token::LeftParen) = p.next() else {
+        return Err(p.error("no in left paren"));
+    };
+    p.next();
+
+    // An empty list is not allowed.
+    let mut list = vec![parse_expr(p)?];
+    while let Some(Token::Comma) = p.peek() {
+        p.next();
+        list.push(parse_expr(p)?);
+    }
+
+    let Some(Token::RightParen) = p.peek() else {
+        return Err(p.error("no in right paren"));
+    };
+    p.next();
+
+    Ok(Expr::In {
+        expr: Box::new(expr),
+        list,
+        negated,
+    })
+}
+
+fn parse_expr_compare<'a>(p: &mut Parser<'a>) -> Result<'a, Expr<'a>> {
+    let mut expr = parse_expr_add(p)?;
+    loop {
+        let operator = match p.peek() {
+            Some(Token::Gt) => BinaryOp::Compare(CompareOp::Gt),
+            Some(Token::Ge) => BinaryOp::Compare(CompareOp::Ge),
+            Some(Token::Lt) => BinaryOp::Compare(CompareOp::Lt),
+            Some(Token::Le) => BinaryOp::Compare(CompareOp::Le),
+            _ => break,
+        };
+        p.next();
+        let right = parse_expr_add(p)?;
+        expr = Expr::BinaryOperator {
+            operator,
+            left: Box::new(expr),
+            right: Box::new(right),
+        };
+    }
+    Ok(expr)
+}
+
+fn parse_expr_add<'a>(p: &mut Parser<'a>) -> Result<'a, Expr<'a>> {
+    let mut expr = parse_expr_mul(p)?;
+    loop {
+        let operator = match p.peek() {
+            Some(Token::Plus) => BinaryOp::Add,
+            Some(Token::Minus) => BinaryOp::Sub,
+            _ => break,
+        };
+        p.next();
+        let right = parse_expr_mul(p)?;
+        expr = Expr::BinaryOperator {
+            operator,
+            left: Box::new(expr),
+            right: Box::new(right),
+        };
+    }
+    Ok(expr)
+}
+
+fn parse_expr_mul<'a>(p: &mut Parser<'a>) -> Result<'a, Expr<'a>> {
+    let mut expr = parse_expr_concat(p)?;
+    loop {
+        let operator = match p.peek() {
+            Some(Token::Asterisk) => BinaryOp::Mul,
+            Some(Token::Slash) => BinaryOp::Div,
+            Some(Token::Percent) => BinaryOp::Mod,
+            _ => break,
+        };
+        p.next();
+        let right = parse_expr_concat(p)?;
+        expr = Expr::BinaryOperator {
+            operator,
+            left: Box::new(expr),
+            right: Box::new(right),
+        };
+    }
+    Ok(expr)
+}
+
+fn parse_expr_concat<'a>(p: &mut Parser<'a>) -> Result<'a, Expr<'a>> {
+    let mut expr = parse_expr_collate(p)?;
+    // TODO: -> ->> will be added in the future.
+    #[allow(clippy::while_let_loop)]
+    loop {
+        let operator = match p.peek() {
+            Some(Token::Concat) => BinaryOp::Concat,
+            _ => break,
+        };
+        p.next();
+        let right = parse_expr_collate(p)?;
+        expr = Expr::BinaryOperator {
+            operator,
+            left: Box::new(expr),
+            right: Box::new(right),
+        };
+    }
+    Ok(expr)
+}
+
+fn parse_expr_collate<'a>(p: &mut Parser<'a>) -> Result<'a, Expr<'a>> {
+    let expr = parse_expr_unary(p)?;
+    let mut collation = None;
+    while let Some(Token::Collate) = p.peek() {
+        let Some(collation_name) = p.next_name() else {
+            return Err(p.error("no collation name"));
+        };
+        collation = Some(collation_name);
+        p.next();
+    }
+    if let Some(collation_name) = collation {
+        Ok(Expr::Collate {
+            expr: Box::new(expr),
+            collation_name,
+        })
+    } else {
+        Ok(expr)
+    }
+}
+
+fn parse_expr_unary<'a>(p: &mut Parser<'a>) -> Result<'a, Expr<'a>> {
+    match p.peek() {
+        Some(Token::Tilda) => {
+            p.next();
+            let expr = parse_expr_unary(p)?;
+            Ok(Expr::UnaryOperator {
+                operator: UnaryOp::BitNot,
+                expr: Box::new(expr),
+            })
+        }
+        Some(Token::Plus) => {
+            p.next();
+            // Unary operator + is a no-op.
+            let expr = parse_expr_unary(p)?;
+            Ok(expr)
+        }
+        Some(Token::Minus) => match p.next() {
+            Some(Token::Integer(buf)) => {
+                let (valid, parsed_int) = parse_integer(buf);
+                assert!(valid);
+                let expr = match parsed_int {
+                    ParseIntegerResult::Integer(v) => Expr::Integer(-v),
+                    ParseIntegerResult::MaxPlusOne => Expr::Integer(i64::MIN),
+                    ParseIntegerResult::TooBig(_) => {
+                        let (valid, pure_integer, d) = parse_float(buf);
+                        assert!(valid);
+                        assert!(pure_integer);
+                        Expr::Real(-d)
+                    }
+                    ParseIntegerResult::Empty => {
+                        unreachable!("token integer must contain at least 1 digits only")
+                    }
+                };
+                p.next();
+                Ok(expr)
+            }
+            Some(Token::Float(buf)) => {
+                let (valid, pure_integer, d) = parse_float(buf);
+                assert!(valid);
+                assert!(!pure_integer);
+                p.next();
+                Ok(Expr::Real(-d))
+            }
+            _ => {
+                let expr = parse_expr_unary(p)?;
+                Ok(Expr::UnaryOperator {
+                    operator: UnaryOp::Minus,
+                    expr: Box::new(expr),
+                })
+            }
+        },
+        _ => parse_expr_primitive(p),
+    }
+}
+
+fn parse_expr_primitive<'a>(p: &mut Parser<'a>) -> Result<'a, Expr<'a>> {
+    if let Some(name) = p
+        .peek_name()
+        .filter(|_| !p.peek().is_some_and(Token::is_current_time))
+    {
+        return parse_expr_name(p, name);
+    }
+    let expr = match p.peek() {
+        Some(Token::Cast) => {
+            let Some(Token::LeftParen) = p.next() else {
+                return Err(p.error("no cast left paren"));
+            };
+            p.next();
+
+            let expr = parse_expr(p)?;
+
+            let Some(Token::As) = p.peek() else {
+                return Err(p.error("no cast as"));
+            };
+            p.next();
+
+            let type_name = parse_type_name(p)?;
+            if type_name.is_empty() {
+                return Err(p.error("no cast type name"));
+            }
+
+            let Some(Token::RightParen) = p.peek() else {
+                return Err(p.error("no cast right paren"));
+            };
+
+            Expr::Cast {
+                expr: Box::new(expr),
+                type_name,
+            }
+        }
+        Some(Token::LeftParen) => {
+            p.next();
+            let expr = parse_expr(p)?;
+            let Some(Token::RightParen) = p.peek() else {
+                return Err(p.error("no right paren"));
+            };
+            expr
+        }
+        Some(Token::Case) => return parse_case(p),
+        Some(Token::Null) => Expr::Null,
+        Some(Token::CurrentDate) => Expr::CurrentTime(CurrentTimeKind::Date),
+        Some(Token::CurrentTime) => Expr::CurrentTime(CurrentTimeKind::Time),
+        Some(Token::CurrentTimestamp) => Expr::CurrentTime(CurrentTimeKind::Timestamp),
+        Some(Token::Integer(buf)) => {
+            let (valid, parsed_int) = parse_integer(buf);
+            assert!(valid);
+            match parsed_int {
+                ParseIntegerResult::Integer(v) => Expr::Integer(v),
+                ParseIntegerResult::MaxPlusOne | ParseIntegerResult::TooBig(_) => {
+                    let (valid, pure_integer, d) = parse_float(buf);
+                    assert!(valid);
+                    assert!(pure_integer);
+                    Expr::Real(d)
+                }
+                ParseIntegerResult::Empty => {
+                    unreachable!("token integer must contain at least 1 digits only")
+                }
+            }
+        }
+        Some(Token::HexInteger(buf)) => {
+            let Some(v) = parse_hex_integer(buf) else {
+                return Err(p.error("hex literal too big"));
+            };
+            Expr::Integer(v)
+        }
+        Some(Token::Float(buf)) => {
+            let (valid, pure_integer, d) = parse_float(buf);
+            assert!(valid);
+            assert!(!pure_integer);
+            Expr::Real(d)
+        }
+        Some(Token::String(text)) => Expr::Text(*text),
+        Some(Token::Blob(hex)) => Expr::Blob(*hex),
+        Some(Token::BindParameter(buf)) => {
+            let buf = *buf;
+            let parameter = match buf {
+                b"?" => BindParameter::Next,
+                [b'?', digits @ ..] => {
+                    let index = std::str::from_utf8(digits)
+                        .ok()
+                        .and_then(|digits| digits.parse::<u32>().ok())
+                        .filter(|index| (1..=MAX_BIND_PARAMETER_INDEX).contains(index));
+                    let Some(index) = index else {
+                        return Err(p.error("bind parameter index out of range"));
+                    };
+                    BindParameter::Index(index)
+                }
+                name => BindParameter::Name(name),
+            };
+            Expr::BindParameter(parameter)
+        }
+        _ => return Err(p.error("no expr")),
+    };
+    p.next();
+
+    Ok(expr)
+}
+
+/// Parse a column, a qualified column, a function or an aggregate. The parser
+/// must point to the name.
+fn parse_expr_name<'a>(p: &mut Parser<'a>, name: MaybeQuotedBytes<'a>) -> Result<'a, Expr<'a>> {
+    let mut cloned_parser = p.clone();
+    if let Some(Token::LeftParen) = cloned_parser.next() {
+        *p = cloned_parser;
+        // A quoted name is also an aggregate, e.g. `"count"(*)`.
+        if let Some(func) = AggFunc::from_name(&name.dequote()) {
+            return parse_aggregate(p, name, func);
+        }
+        return parse_function(p, name);
+    }
+    let mut cloned_parser = p.clone();
+    let expr = if let Some(Token::Dot) = cloned_parser.next() {
+        *p = cloned_parser;
+        let Some(column_name) = p.next_name() else {
+            return Err(p.error("no column name after dot"));
+        };
+        Expr::QualifiedColumn {
+            table_name: name,
+            column_name,
+        }
+    } else {
+        Expr::Column(name)
+    };
+    p.next();
+    Ok(expr)
+}
+
+/// Parse the CASE expression. The parser must point to CASE.
+///
+/// https://www.sqlite.org/lang_expr.html#the_case_expression
+fn parse_case<'a>(p: &mut Parser<'a>) -> Result<'a, Expr<'a>> {
+    let operand = match p.next() {
+        Some(Token::When) => None,
+        _ => Some(Box::new(parse_expr(p)?)),
+    };
+    let mut whens = Vec::new();
+    while let Some(Token::When) = p.peek() {
+        p.next();
+        let when = parse_expr(p)?;
+        let Some(Token::Then) = p.peek() else {
+            return Err(p.error("no then in case"));
+        };
+        p.next();
+        let then = parse_expr(p)?;
+        whens.push((when, then));
+    }
+    if whens.is_empty() {
+        return Err(p.error("no when in case"));
+    }
+    let else_ = if let Some(Token::Else) = p.peek() {
+        p.next();
+        Some(Box::new(parse_expr(p)?))
+    } else {
+        None
+    };
+    let Some(Token::End) = p.peek() else {
+        return Err(p.error("no end of case"));
+    };
+    p.next();
+    Ok(Expr::Case {
+        operand,
+        whens,
+        else_,
+    })
+}
+
+/// Parse the arguments of an aggregate function. The parser must point to
+/// the left paren.
+///
+/// min() and max() with multiple arguments are scalar functions and parsed
+/// as [Expr::Function].
+fn parse_aggregate<'a>(
+    p: &mut Parser<'a>,
+    name: MaybeQuotedBytes<'a>,
+    func: AggFunc,
+) -> Result<'a, Expr<'a>> {
+    assert_eq!(p.peek(), Some(&Token::LeftParen));
+    p.next();
+    let distinct = parse_distinct(p);
+    let arg = match p.peek() {
+        Some(Token::Asterisk) if func == AggFunc::Count && !distinct => {
+            p.next();
+            AggArg::Star
+        }
+        _ => {
+            let expr = parse_expr(p)?;
+            if !distinct && matches!(p.peek(), Some(Token::Comma)) {
+                let args = parse_function_args(p, vec![expr])?;
+                return Ok(Expr::Function {
+                    name,
+                    args,
+                    distinct,
+                });
+            }
+            AggArg::Expr(Box::new(expr))
+        }
+    };
+    let Some(Token::RightParen) = p.peek() else {
+        return Err(p.error("no aggregate right paren"));
+    };
+    p.next();
+    Ok(Expr::Aggregate {
+        func,
+        arg,
+        distinct,
+    })
+}
+
+/// Parse a function call. The parser must point to the left paren.
+fn parse_function<'a>(p: &mut Parser<'a>, name: MaybeQuotedBytes<'a>) -> Result<'a, Expr<'a>> {
+    assert_eq!(p.peek(), Some(&Token::LeftParen));
+    p.next();
+    let distinct = parse_distinct(p);
+    let args = if !distinct && matches!(p.peek(), Some(Token::RightParen)) {
+        p.next();
+        Vec::new()
+    } else {
+        let expr = parse_expr(p)?;
+        parse_function_args(p, vec![expr])?
+    };
+    Ok(Expr::Function {
+        name,
+        args,
+        distinct,
+    })
+}
+
+/// Consume the optional DISTINCT keyword.
+fn parse_distinct(p: &mut Parser) -> bool {
+    if let Some(Token::Distinct) = p.peek() {
+        p.next();
+        true
+    } else {
+        false
+    }
+}
+
+/// Parse the remaining comma-separated arguments and the right paren.
+fn parse_function_args<'a>(
+    p: &mut Parser<'a>,
+    mut args: Vec<Expr<'a>>,
+) -> Result<'a, Vec<Expr<'a>>> {
+    while let Some(Token::Comma) = p.peek() {
+        p.next();
+        args.push(parse_expr(p)?);
+    }
+    let Some(Token::RightParen) = p.peek() else {
+        return Err(p.error("no function right paren"));
+    };
+    p.next();
+    Ok(args)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    macro_rules! assert_parser {
+        ($parse_fn:ident, $input:expr, $size:expr, $expected:expr) => {
+            let mut parser = Parser::new($input);
+            let r = $parse_fn(&mut parser);
+            assert!(r.is_ok());
+            assert_eq!(r.unwrap(), $expected);
+            assert_eq!(parser.n_consumed(), $size);
+        };
+    }
+
+    #[test]
+    fn test_expect_semicolon() {
+        assert_parser!(expect_semicolon, b";  ", 3, ());
+        assert_parser!(expect_semicolon, b";  a  ", 3, ());
+        assert_parser!(expect_semicolon, b"   ;  ", 6, ());
+
+        let mut parser = Parser::new(b"     ");
+        let r = expect_semicolon(&mut parser);
+        assert!(r.is_err());
+        assert_eq!(r.unwrap_err().cursor(), 5);
+
+        let mut parser = Parser::new(b"");
+        let r = expect_semicolon(&mut parser);
+        assert!(r.is_err());
+        assert_eq!(r.unwrap_err().cursor(), 0);
+    }
+
+    #[test]
+    fn test_skip_semicolon() {
+        let mut parser = Parser::new(b"  ;  ");
+        skip_semicolon(&mut parser);
+        assert_eq!(parser.n_consumed(), 5);
+
+        let mut parser = Parser::new(b"  a;");
+        skip_semicolon(&mut parser);
+        assert_eq!(parser.n_consumed(), 2);
+    }
+
+    #[test]
+    fn test_expect_no_more_token() {
+        assert_parser!(expect_no_more_token, b"", 0, ());
+        assert_parser!(expect_no_more_token, b"    ", 4, ());
+
+        let mut parser = Parser::new(b"    ;  ");
+        let r = expect_no_more_token(&mut parser);
+        assert!(r.is_err());
+        assert_eq!(r.unwrap_err().cursor(), 4);
+    }
+
+    #[test]
+    fn test_parse_complete() {
+        let create_table = parse_complete(b"create table foo (id)", parse_create_table).unwrap();
+        assert_eq!(create_table.table_name, b"foo".as_slice().into());
+        let create_table =
+            parse_complete(b"create table foo (id) -- comment", parse_create_table).unwrap();
+        assert_eq!(create_table.table_name, b"foo".as_slice().into());
+
+        let r = parse_complete(b"create table Foo (Id, Name)abc ", parse_create_table);
+        let err = r.unwrap_err();
+        assert_eq!(err.msg, "unexpected token");
+        assert_eq!(err.cursor(), 27);
+
+        let r = parse_complete(b"create table foo (id);", parse_create_table);
+        let err = r.unwrap_err();
+        assert_eq!(err.msg, "unexpected token");
+        assert_eq!(err.cursor(), 21);
+
+        let r = parse_complete(b"create table foo", parse_create_table);
+        assert_ne!(r.unwrap_err().msg, "unexpected token");
+    }
+
+    #[test]
+    fn test_split_statements() {
+        assert_eq!(
+            split_statements(b"CREATE TABLE a(b); SELECT * FROM a;"),
+            vec![b"CREATE TABLE a(b);".as_slice(), b"SELECT * FROM a;"]
+        );
+        assert_eq!(
+            split_statements(b"  SELECT 1  ;\n\n SELECT 2  "),
+            vec![b"SELECT 1  ;".as_slice(), b"SELECT 2"]
+        );
+        assert_eq!(
+            split_statements(b"SELECT ';', \"a;b\", `c;d`, [e;f] FROM a; SELECT 1;"),
+            vec![
+                b"SELECT ';', \"a;b\", `c;d`, [e;f] FROM a;".as_slice(),
+                b"SELECT 1;"
+            ]
+        );
+        assert_eq!(
+            split_statements(b";; SELECT 1;  ;"),
+            vec![b"SELECT 1;".as_slice()]
+        );
+        assert!(split_statements(b"").is_empty());
+        assert!(split_statements(b"   ").is_empty());
+        assert!(split_statements(b" ; ; ").is_empty());
+        assert_eq!(
+            split_statements(b"-- a; b\nSELECT 1 /* ; */; SELECT 2; -- c;"),
+            vec![b"SELECT 1 /* ; */;".as_slice(), b"SELECT 2;"]
+        );
+
+        for sql in split_statements(b"CREATE TABLE a(b); CREATE TABLE c(d);") {
+            let mut parser = Parser::new(sql);
+            parse_create_table(&mut parser).unwrap();
+            expect_semicolon(&mut parser).unwrap();
+            expect_no_more_token(&mut parser).unwrap();
+        }
+    }
+
+    #[test]
+    fn test_parse_statement() {
+        let statements = split_statements(
+            b"CREATE TABLE a(b, c); CREATE INDEX i ON a(b); CREATE UNIQUE INDEX j ON a(c);
+            ALTER TABLE a ADD COLUMN d; SELECT * FROM a; DELETE FROM a WHERE b = 1;
+            UPDATE a SET c = 2",
+        );
+        let parsed = statements
+            .iter()
+            .map(|sql| parse_statement(sql).unwrap())
+            .collect::<Vec<_>>();
+        assert_eq!(parsed.len(), 7);
+        assert!(matches!(&parsed[0], ParsedStatement::CreateTable(c) if c.columns.len() == 2));
+        assert!(matches!(&parsed[1], ParsedStatement::CreateIndex(c) if !c.unique));
+        assert!(matches!(&parsed[2], ParsedStatement::CreateIndex(c) if c.unique));
+        assert!(matches!(&parsed[3], ParsedStatement::AlterTable(_)));
+        assert!(matches!(&parsed[4], ParsedStatement::Select(_)));
+        assert!(
+            matches!(&parsed[5], ParsedStatement::Delete(d) if d.table_name == b"a".as_slice().into() && d.filter.is_some())
+        );
+        assert!(
+            matches!(&parsed[6], ParsedStatement::Update(u) if u.assignments.len() == 1 && u.filter.is_none())
+        );
+
+        assert_eq!(
+            parse_statement(b"DELETE FROM a; DELETE FROM b;")
+                .unwrap_err()
+                .msg(),
+            "unexpected token"
+        );
+        assert!(parse_statement(b"INSERT INTO a VALUES (1);").is_err());
+        assert!(parse_statement(b"CREATE VIEW v AS SELECT 1;").is_err());
+        assert!(parse_statement(b"").is_err());
+    }
+
+    #[test]
+    fn test_parse_create_table() {
+        let input = b"create table foo (id integer primary key, name text, real real, \"blob\" blob, `empty` null,[no_type])";
+        let mut parser = Parser::new(input);
+        let create_table = parse_create_table(&mut parser).unwrap();
+        assert_eq!(parser.n_consumed(), input.len());
+        assert!(!create_table.if_not_exists);
+        assert!(create_table.schema_name.is_none());
+        assert_eq!(create_table.table_name, b"foo".as_slice().into());
+        assert_eq!(
+            create_table.columns,
+            vec![
+                ColumnDef {
+                    name: b"id".as_slice().into(),
+                    type_name: vec![b"integer".as_slice().into()],
+                    constraints: vec![ColumnConstraint::PrinaryKey],
+                },
+                ColumnDef {
+                    name: b"name".as_slice().into(),
+                    type_name: vec![b"text".as_slice().into()],
+                    constraints: vec![],
+                },
+                ColumnDef {
+                    name: b"real".as_slice().into(),
+                    type_name: vec![b"real".as_slice().into()],
+                    constraints: vec![],
+                },
+                ColumnDef {
+                    name: b"\"blob\"".as_slice().into(),
+                    type_name: vec![b"blob".as_slice().into()],
+                    constraints: vec![],
+                },
+                ColumnDef {
+                    name: b"`empty`".as_slice().into(),
                     type_name: vec![b"null".as_slice().into()],
                     constraints: vec![],
                 },
-                ColumnDef {
-                    name: b"no_type".as_slice().into(),
-                    type_name: vec![],
-                    constraints: vec![],
+                ColumnDef {
+                    name: b"no_type".as_slice().into(),
+                    type_name: vec![],
+                    constraints: vec![],
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn test_parse_create_table_with_comments() {
+        let input = b"create table foo ( -- the table\n  id integer /* rowid */ primary key,\n  /* name, */ -- name text,\n  value text -- trailing\n)";
+        let mut parser = Parser::new(input);
+        let create_table = parse_create_table(&mut parser).unwrap();
+        assert_eq!(parser.n_consumed(), input.len());
+        assert_eq!(create_table.table_name, b"foo".as_slice().into());
+        assert_eq!(
+            create_table.columns,
+            vec![
+                ColumnDef {
+                    name: b"id".as_slice().into(),
+                    type_name: vec![b"integer".as_slice().into()],
+                    constraints: vec![ColumnConstraint::PrinaryKey],
+                },
+                ColumnDef {
+                    name: b"value".as_slice().into(),
+                    type_name: vec![b"text".as_slice().into()],
+                    constraints: vec![],
+                },
+            ]
+        );
+
+        let mut parser = Parser::new(b"create table foo (id /* unterminated)");
+        assert!(parse_create_table(&mut parser).is_err());
+    }
+
+    #[test]
+    fn test_parse_create_table_if_not_exists_and_schema_name() {
+        for (input, if_not_exists, schema_name) in [
+            (b"create table foo (id)".as_slice(), false, None),
+            (b"create table if not exists foo (id)", true, None),
+            (
+                b"create table main.foo (id)",
+                false,
+                Some(b"main".as_slice().into()),
+            ),
+            (
+                b"CREATE TABLE IF NOT EXISTS \"main\" . foo (id)",
+                true,
+                Some(b"\"main\"".as_slice().into()),
+            ),
+        ] {
+            let mut parser = Parser::new(input);
+            let create_table = parse_create_table(&mut parser).unwrap();
+            assert_eq!(parser.n_consumed(), input.len(), "{:?}", input);
+            assert_eq!(create_table.if_not_exists, if_not_exists, "{:?}", input);
+            assert_eq!(create_table.schema_name, schema_name, "{:?}", input);
+            assert_eq!(
+                create_table.table_name,
+                b"foo".as_slice().into(),
+                "{:?}",
+                input
+            );
+            assert_eq!(
+                create_table.columns,
+                vec![ColumnDef {
+                    name: b"id".as_slice().into(),
+                    type_name: vec![],
+                    constraints: vec![],
+                }],
+                "{:?}",
+                input
+            );
+        }
+
+        for (input, cursor, msg) in [
+            (
+                b"create table if foo (id)".as_slice(),
+                16,
+                "no not after if",
+            ),
+            (
+                b"create table if not foo (id)",
+                20,
+                "no exists after if not",
+            ),
+            (b"create table if not exists (id)", 27, "no table_name"),
+            (
+                b"create table main. (id)",
+                19,
+                "no table_name after schema_name",
+            ),
+            (b"create table main.foo.bar (id)", 21, "no left paren"),
+        ] {
+            let e = parse_create_table(&mut Parser::new(input)).unwrap_err();
+            assert_eq!(e.cursor(), cursor, "{:?}", input);
+            assert_eq!(e.msg(), msg, "{:?}", input);
+        }
+    }
+
+    #[test]
+    fn test_parse_create_table_type_name() {
+        let input = b"create table foo (col1 type type primary key, col2 Varint(10), col3 [Float](+10), col4 \"test\"(-10.0), col5 null(0), col6 `blob```(1,+2))";
+        let mut parser = Parser::new(input);
+        let create_table = parse_create_table(&mut parser).unwrap();
+        assert_eq!(parser.n_consumed(), input.len());
+        assert_eq!(create_table.table_name, b"foo".as_slice().into());
+        assert_eq!(create_table.columns.len(), 6);
+        assert_eq!(
+            create_table.columns[0].type_name,
+            vec![b"type".as_slice().into(), b"type".as_slice().into()]
+        );
+        assert_eq!(
+            create_table.columns[1].type_name,
+            vec![b"Varint".as_slice().into()]
+        );
+        assert_eq!(
+            create_table.columns[2].type_name,
+            vec![b"Float".as_slice().into()]
+        );
+        assert_eq!(
+            create_table.columns[3].type_name,
+            vec![b"\"test\"".as_slice().into()]
+        );
+        assert_eq!(
+            create_table.columns[4].type_name,
+            vec![b"null".as_slice().into()]
+        );
+        assert_eq!(
+            create_table.columns[5].type_name,
+            vec![b"`blob```".as_slice().into()]
+        );
+    }
+
+    #[test]
+    fn test_parse_column_def() {
+        let input = b"col1 unsigned big int(10, -2) primary key collate nocase, col2";
+        let mut parser = Parser::new(input);
+        let column_def = parse_column_def(&mut parser).unwrap();
+        assert_eq!(parser.n_consumed(), input.len() - 6);
+        assert_eq!(
+            column_def,
+            ColumnDef {
+                name: b"col1".as_slice().into(),
+                type_name: vec![
+                    b"unsigned".as_slice().into(),
+                    b"big".as_slice().into(),
+                    b"int".as_slice().into()
+                ],
+                constraints: vec![
+                    ColumnConstraint::PrinaryKey,
+                    ColumnConstraint::Collate(b"nocase".as_slice().into())
+                ],
+            }
+        );
+        assert_eq!(parser.peek(), Some(&Token::Comma));
+
+        let input = b"\"col 1\"";
+        let mut parser = Parser::new(input);
+        let column_def = parse_column_def(&mut parser).unwrap();
+        assert_eq!(parser.n_consumed(), input.len());
+        assert_eq!(column_def.name, b"\"col 1\"".as_slice().into());
+        assert!(column_def.type_name.is_empty());
+        assert!(column_def.constraints.is_empty());
+
+        // no column name.
+        let r = parse_column_def(&mut Parser::new(b"(col1)"));
+        assert!(r.is_err());
+        assert_eq!(r.unwrap_err().msg(), "no column name");
+        // unclosed type name arguments.
+        let r = parse_column_def(&mut Parser::new(b"col1 varchar(10"));
+        assert!(r.is_err());
+        assert_eq!(r.unwrap_err().cursor(), 15);
+    }
+
+    #[test]
+    fn test_parse_create_table_type_name_with_arguments() {
+        let input = b"create table t (name varchar(255) collate nocase, price decimal(10, 2) primary key, id integer)";
+        let mut parser = Parser::new(input);
+        let create_table = parse_create_table(&mut parser).unwrap();
+        assert_eq!(parser.n_consumed(), input.len());
+        assert_eq!(create_table.columns.len(), 3);
+        assert_eq!(
+            create_table.columns[0].type_name,
+            vec![b"varchar".as_slice().into()]
+        );
+        assert_eq!(
+            create_table.columns[0].constraints,
+            vec![ColumnConstraint::Collate(b"nocase".as_slice().into())]
+        );
+        assert_eq!(
+            create_table.columns[1].type_name,
+            vec![b"decimal".as_slice().into()]
+        );
+        assert_eq!(
+            create_table.columns[1].constraints,
+            vec![ColumnConstraint::PrinaryKey]
+        );
+        assert_eq!(
+            create_table.columns[2].type_name,
+            vec![b"integer".as_slice().into()]
+        );
+    }
+
+    #[test]
+    fn test_parse_create_table_constraints() {
+        let input = b"create table foo (col1 type type collate binary primary key collate nocase, col2 collate rtrim, col3 collate \"RTRIM\")";
+        let mut parser = Parser::new(input);
+        let create_table = parse_create_table(&mut parser).unwrap();
+        assert_eq!(parser.n_consumed(), input.len());
+        assert_eq!(
+            create_table.columns[0].constraints,
+            vec![
+                ColumnConstraint::Collate(b"binary".as_slice().into()),
+                ColumnConstraint::PrinaryKey,
+                ColumnConstraint::Collate(b"nocase".as_slice().into())
+            ]
+        );
+        assert_eq!(
+            create_table.columns[1].constraints,
+            vec![ColumnConstraint::Collate(b"rtrim".as_slice().into()),]
+        );
+        assert_eq!(
+            create_table.columns[2].constraints,
+            vec![ColumnConstraint::Collate(b"\"RTRIM\"".as_slice().into()),]
+        );
+    }
+
+    #[test]
+    fn test_parse_create_table_default() {
+        let input = b"create table foo (col1 default 1 collate nocase, col2 default -2.5, col3 default 'abc', col4 default (1 + 2), col5 default null)";
+        let mut parser = Parser::new(input);
+        let create_table = parse_create_table(&mut parser).unwrap();
+        assert_eq!(parser.n_consumed(), input.len());
+        assert_eq!(
+            create_table.columns[0].constraints,
+            vec![
+                ColumnConstraint::Default(Expr::Integer(1)),
+                ColumnConstraint::Collate(b"nocase".as_slice().into())
+            ]
+        );
+        assert_eq!(
+            create_table.columns[1].constraints,
+            vec![ColumnConstraint::Default(Expr::Real(-2.5))]
+        );
+        assert_eq!(
+            create_table.columns[2].constraints,
+            vec![ColumnConstraint::Default(Expr::Text(
+                b"'abc'".as_slice().into()
+            ))]
+        );
+        assert_eq!(
+            create_table.columns[3].constraints,
+            vec![ColumnConstraint::Default(Expr::BinaryOperator {
+                operator: BinaryOp::Add,
+                left: Box::new(Expr::Integer(1)),
+                right: Box::new(Expr::Integer(2)),
+            })]
+        );
+        assert_eq!(
+            create_table.columns[4].constraints,
+            vec![ColumnConstraint::Default(Expr::Null)]
+        );
+
+        // no default value.
+        let r = parse_create_table(&mut Parser::new(b"create table foo (id default)"));
+        assert!(r.is_err());
+    }
+
+    #[test]
+    fn test_parse_column_default() {
+        assert_parser!(
+            parse_column_default,
+            b"(strftime('%s', 'now'))",
+            23,
+            Expr::Function {
+                name: b"strftime".as_slice().into(),
+                args: vec![
+                    Expr::Text(b"'%s'".as_slice().into()),
+                    Expr::Text(b"'now'".as_slice().into()),
+                ],
+                distinct: false,
+            }
+        );
+        assert_parser!(
+            parse_column_default,
+            b"current_timestamp",
+            17,
+            Expr::CurrentTime(CurrentTimeKind::Timestamp)
+        );
+        assert_parser!(
+            parse_column_default,
+            b"CURRENT_DATE",
+            12,
+            Expr::CurrentTime(CurrentTimeKind::Date)
+        );
+        assert_parser!(parse_column_default, b"true", 4, Expr::Integer(1));
+        assert_parser!(parse_column_default, b"False", 5, Expr::Integer(0));
+        assert_parser!(
+            parse_column_default,
+            b"\"true\"",
+            6,
+            Expr::Text(b"\"true\"".as_slice().into())
+        );
+        assert_parser!(parse_column_default, b"+10", 3, Expr::Integer(10));
+        assert_parser!(parse_column_default, b"- 10", 4, Expr::Integer(-10));
+        assert_parser!(
+            parse_column_default,
+            b"-0x10",
+            5,
+            Expr::UnaryOperator {
+                operator: UnaryOp::Minus,
+                expr: Box::new(Expr::Integer(16))
+            }
+        );
+        assert_parser!(parse_column_default, b"-1.5e1", 6, Expr::Real(-15.0));
+        assert_parser!(
+            parse_column_default,
+            b"x'0102'",
+            7,
+            Expr::Blob(b"0102".as_slice().into())
+        );
+        assert_parser!(
+            parse_column_default,
+            b"-'abc'",
+            6,
+            Expr::UnaryOperator {
+                operator: UnaryOp::Minus,
+                expr: Box::new(Expr::Text(b"'abc'".as_slice().into()))
+            }
+        );
+        // An identifier is a text.
+        assert_parser!(
+            parse_column_default,
+            b"abc",
+            3,
+            Expr::Text(b"abc".as_slice().into())
+        );
+        assert_parser!(
+            parse_column_default,
+            b"\"abc\"",
+            5,
+            Expr::Text(b"\"abc\"".as_slice().into())
+        );
+        // Only the literal is the default value.
+        assert_parser!(parse_column_default, b"1 + 2", 2, Expr::Integer(1));
+
+        for input in [
+            b"".as_slice(),
+            b"-abc",
+            b"--1",
+            b"?",
+            b"cast(1 as text)",
+            b"-(1)",
+            b",",
+        ] {
+            let r = parse_column_default(&mut Parser::new(input));
+            assert_eq!(r.unwrap_err().msg(), "no default value", "{:?}", input);
+        }
+        let r = parse_column_default(&mut Parser::new(b"(1"));
+        assert_eq!(r.unwrap_err().msg(), "no right paren");
+
+        // A function call must be in parentheses.
+        let r = parse_create_table(&mut Parser::new(b"create table foo (id default abs(1))"));
+        assert!(r.is_err());
+    }
+
+    #[test]
+    fn test_parse_create_table_with_extra() {
+        let input = b"create table Foo (Id, Name)abc ";
+        let mut parser = Parser::new(input);
+        let create_table = parse_create_table(&mut parser).unwrap();
+        assert_eq!(parser.n_consumed(), input.len() - 4);
+        assert_eq!(create_table.table_name, b"Foo".as_slice().into());
+        assert_eq!(
+            create_table.columns,
+            vec![
+                ColumnDef {
+                    name: b"Id".as_slice().into(),
+                    type_name: Vec::new(),
+                    constraints: vec![],
+                },
+                ColumnDef {
+                    name: b"Name".as_slice().into(),
+                    type_name: Vec::new(),
+                    constraints: vec![],
+                }
+            ]
+        );
+    }
+
+    #[test]
+    fn test_parse_create_table_fail() {
+        // no column def.
+        let r = parse_create_table(&mut Parser::new(b"create table foo ()"));
+        assert!(r.is_err());
+        assert_eq!(r.unwrap_err().cursor(), 18);
+        // no right paren.
+        let r = parse_create_table(&mut Parser::new(b"create table foo (id, name "));
+        assert!(r.is_err());
+        assert_eq!(r.unwrap_err().cursor(), 27);
+        // primary without key.
+        let r = parse_create_table(&mut Parser::new(b"create table foo (id primary, name)"));
+        assert!(r.is_err());
+        assert_eq!(r.unwrap_err().cursor(), 28);
+        // key without primary.
+        let r = parse_create_table(&mut Parser::new(b"create table foo (id key, name)"));
+        assert!(r.is_err());
+        assert_eq!(r.unwrap_err().cursor(), 21);
+        // no signed number in type name arguments.
+        let r = parse_create_table(&mut Parser::new(b"create table foo (id varchar(), name)"));
+        assert!(r.is_err());
+        assert_eq!(r.unwrap_err().cursor(), 29);
+        let r = parse_create_table(&mut Parser::new(
+            b"create table foo (id decimal(10,), name)",
+        ));
+        assert!(r.is_err());
+        assert_eq!(r.unwrap_err().cursor(), 32);
+        // no right paren in type name arguments.
+        let r = parse_create_table(&mut Parser::new(
+            b"create table foo (id decimal(10 2), name)",
+        ));
+        assert!(r.is_err());
+        assert_eq!(r.unwrap_err().cursor(), 32);
+    }
+
+    #[test]
+    fn test_parse_create_index() {
+        let input = b"create index foo on bar (col1, col2,col3)";
+        let mut parser = Parser::new(input);
+        let create_index = parse_create_index(&mut parser).unwrap();
+        assert_eq!(parser.n_consumed(), input.len());
+        assert_eq!(create_index.index_name, b"foo".as_slice().into());
+        assert_eq!(create_index.table_name, b"bar".as_slice().into());
+        assert_eq!(
+            create_index.columns,
+            vec![
+                IndexedColumn {
+                    name: b"col1".as_slice().into(),
+                    order: SortOrder::Asc,
+                },
+                IndexedColumn {
+                    name: b"col2".as_slice().into(),
+                    order: SortOrder::Asc,
+                },
+                IndexedColumn {
+                    name: b"col3".as_slice().into(),
+                    order: SortOrder::Asc,
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn test_parse_create_index_with_extra() {
+        let input = b"create index fOo on bAR (Col1,cOL2)abc ";
+        let mut parser = Parser::new(input);
+        let create_index = parse_create_index(&mut parser).unwrap();
+        assert_eq!(parser.n_consumed(), input.len() - 4);
+        assert_eq!(create_index.index_name, b"fOo".as_slice().into());
+        assert_eq!(create_index.table_name, b"bAR".as_slice().into());
+        assert_eq!(
+            create_index.columns,
+            vec![
+                IndexedColumn {
+                    name: b"Col1".as_slice().into(),
+                    order: SortOrder::Asc,
+                },
+                IndexedColumn {
+                    name: b"cOL2".as_slice().into(),
+                    order: SortOrder::Asc,
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn test_parse_create_index_fail() {
+        // no right paren.
+        let r = parse_create_index(&mut Parser::new(b"create index foo on bar (id, name "));
+        assert!(r.is_err());
+        assert_eq!(r.unwrap_err().cursor(), 34);
+
+        // no index.
+        let r = parse_create_index(&mut Parser::new(b"create unique foo on bar (id)"));
+        assert!(r.is_err());
+        assert_eq!(r.unwrap_err().cursor(), 14);
+
+        // invalid order.
+        let r = parse_create_index(&mut Parser::new(b"create index foo on bar (id asc desc)"));
+        assert!(r.is_err());
+        assert_eq!(r.unwrap_err().cursor(), 32);
+
+        // no where expression.
+        let r = parse_create_index(&mut Parser::new(b"create index foo on bar (id) where"));
+        assert!(r.is_err());
+        assert_eq!(r.unwrap_err().cursor(), 34);
+    }
+
+    #[test]
+    fn test_parse_create_index_unique_order_where() {
+        let input = b"create unique index foo on bar (col1 desc, col2 ASC, col3) where col1 > 10";
+        let mut parser = Parser::new(input);
+        let create_index = parse_create_index(&mut parser).unwrap();
+        assert_eq!(parser.n_consumed(), input.len());
+        assert!(create_index.unique);
+        assert_eq!(create_index.index_name, b"foo".as_slice().into());
+        assert_eq!(create_index.table_name, b"bar".as_slice().into());
+        assert_eq!(
+            create_index.columns,
+            vec![
+                IndexedColumn {
+                    name: b"col1".as_slice().into(),
+                    order: SortOrder::Desc,
+                },
+                IndexedColumn {
+                    name: b"col2".as_slice().into(),
+                    order: SortOrder::Asc,
+                },
+                IndexedColumn {
+                    name: b"col3".as_slice().into(),
+                    order: SortOrder::Asc,
+                },
+            ]
+        );
+        assert_eq!(
+            create_index.where_clause,
+            Some(Expr::BinaryOperator {
+                operator: BinaryOp::Compare(CompareOp::Gt),
+                left: Box::new(Expr::Column(b"col1".as_slice().into())),
+                right: Box::new(Expr::Integer(10)),
+            })
+        );
+
+        let input = b"create index foo on bar (col1)";
+        let create_index = parse_create_index(&mut Parser::new(input)).unwrap();
+        assert!(!create_index.unique);
+        assert!(create_index.where_clause.is_none());
+
+        let input = b"create index foo on bar (desc desc, asc)";
+        let create_index = parse_create_index(&mut Parser::new(input)).unwrap();
+        assert_eq!(
+            create_index.columns,
+            vec![
+                IndexedColumn {
+                    name: b"desc".as_slice().into(),
+                    order: SortOrder::Desc,
+                },
+                IndexedColumn {
+                    name: b"asc".as_slice().into(),
+                    order: SortOrder::Asc,
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn test_parse_alter_table_add_column() {
+        let input = b"alter table foo add column col1 integer default 10 collate nocase";
+        let mut parser = Parser::new(input);
+        let alter_table = parse_alter_table(&mut parser).unwrap();
+        assert_eq!(parser.n_consumed(), input.len());
+        assert_eq!(
+            alter_table,
+            AlterTable {
+                schema_name: None,
+                table_name: b"foo".as_slice().into(),
+                action: AlterTableAction::AddColumn(ColumnDef {
+                    name: b"col1".as_slice().into(),
+                    type_name: vec![b"integer".as_slice().into()],
+                    constraints: vec![
+                        ColumnConstraint::Default(Expr::Integer(10)),
+                        ColumnConstraint::Collate(b"nocase".as_slice().into())
+                    ],
+                }),
+            }
+        );
+
+        // COLUMN is optional.
+        let input = b"alter table main.foo add col1";
+        let mut parser = Parser::new(input);
+        let alter_table = parse_alter_table(&mut parser).unwrap();
+        assert_eq!(parser.n_consumed(), input.len());
+        assert_eq!(alter_table.schema_name, Some(b"main".as_slice().into()));
+        assert_eq!(alter_table.table_name, b"foo".as_slice().into());
+        assert_eq!(
+            alter_table.action,
+            AlterTableAction::AddColumn(ColumnDef {
+                name: b"col1".as_slice().into(),
+                type_name: Vec::new(),
+                constraints: Vec::new(),
+            })
+        );
+    }
+
+    #[test]
+    fn test_parse_alter_table_rename() {
+        let input = b"alter table foo rename to bar";
+        let mut parser = Parser::new(input);
+        let alter_table = parse_alter_table(&mut parser).unwrap();
+        assert_eq!(parser.n_consumed(), input.len());
+        assert_eq!(alter_table.table_name, b"foo".as_slice().into());
+        assert_eq!(
+            alter_table.action,
+            AlterTableAction::RenameTo(b"bar".as_slice().into())
+        );
+
+        for input in [
+            b"alter table foo rename column col1 to col2".as_slice(),
+            b"alter table foo rename col1 to col2".as_slice(),
+        ] {
+            let mut parser = Parser::new(input);
+            let alter_table = parse_alter_table(&mut parser).unwrap();
+            assert_eq!(parser.n_consumed(), input.len());
+            assert_eq!(
+                alter_table.action,
+                AlterTableAction::RenameColumn {
+                    column_name: b"col1".as_slice().into(),
+                    new_column_name: b"col2".as_slice().into(),
+                }
+            );
+        }
+    }
+
+    #[test]
+    fn test_parse_alter_table_keywords_as_names() {
+        let input = b"alter table rename rename column column to rename";
+        let mut parser = Parser::new(input);
+        let alter_table = parse_alter_table(&mut parser).unwrap();
+        assert_eq!(parser.n_consumed(), input.len());
+        assert_eq!(alter_table.table_name, b"rename".as_slice().into());
+        assert_eq!(
+            alter_table.action,
+            AlterTableAction::RenameColumn {
+                column_name: b"column".as_slice().into(),
+                new_column_name: b"rename".as_slice().into(),
+            }
+        );
+
+        let input = b"alter table foo add column column";
+        let mut parser = Parser::new(input);
+        let alter_table = parse_alter_table(&mut parser).unwrap();
+        assert_eq!(parser.n_consumed(), input.len());
+        assert_eq!(
+            alter_table.action,
+            AlterTableAction::AddColumn(ColumnDef {
+                name: b"column".as_slice().into(),
+                type_name: Vec::new(),
+                constraints: Vec::new(),
+            })
+        );
+    }
+
+    #[test]
+    fn test_parse_alter_table_fail() {
+        // no action.
+        let r = parse_alter_table(&mut Parser::new(b"alter table foo"));
+        assert!(r.is_err());
+        assert_eq!(r.unwrap_err().cursor(), 15);
+
+        // no column def.
+        let r = parse_alter_table(&mut Parser::new(b"alter table foo add column"));
+        assert!(r.is_err());
+        assert_eq!(r.unwrap_err().cursor(), 26);
+
+        // no new table name.
+        let r = parse_alter_table(&mut Parser::new(b"alter table foo rename to"));
+        assert!(r.is_err());
+        assert_eq!(r.unwrap_err().cursor(), 25);
+
+        // no to.
+        let r = parse_alter_table(&mut Parser::new(b"alter table foo rename col1 col2"));
+        assert!(r.is_err());
+        assert_eq!(r.unwrap_err().cursor(), 28);
+
+        // drop column is not supported.
+        let r = parse_alter_table(&mut Parser::new(b"alter table foo drop col1"));
+        assert!(r.is_err());
+        assert_eq!(r.unwrap_err().msg(), "no rename or add");
+    }
+
+    #[test]
+    fn test_parse_select_all() {
+        let input = b"select * from foo";
+        let mut parser = Parser::new(input);
+        let select = parse_select(&mut parser).unwrap();
+        assert_eq!(parser.n_consumed(), input.len());
+        assert_eq!(select.table.table_name, b"foo".as_slice().into());
+        assert!(select.joins.is_empty());
+        assert_eq!(select.columns, vec![ResultColumn::All]);
+    }
+
+    #[test]
+    fn test_parse_select_columns() {
+        let input = b"select id,name,*,col as col2, col3 col4, 10, 'text' as col5, col = 11, col2 < col3 as col6 from foo";
+        let mut parser = Parser::new(input);
+        let select = parse_select(&mut parser).unwrap();
+        assert_eq!(parser.n_consumed(), input.len());
+        assert_eq!(select.table.table_name, b"foo".as_slice().into());
+        assert!(select.joins.is_empty());
+        assert_eq!(
+            select.columns,
+            vec![
+                ResultColumn::Expr((Expr::Column(b"id".as_slice().into()), None)),
+                ResultColumn::Expr((Expr::Column(b"name".as_slice().into()), None)),
+                ResultColumn::All,
+                ResultColumn::Expr((
+                    Expr::Column(b"col".as_slice().into()),
+                    Some(b"col2".as_slice().into())
+                )),
+                ResultColumn::Expr((
+                    Expr::Column(b"col3".as_slice().into()),
+                    Some(b"col4".as_slice().into())
+                )),
+                ResultColumn::Expr((Expr::Integer(10), None)),
+                ResultColumn::Expr((
+                    Expr::Text(b"'text'".as_slice().into()),
+                    Some(b"col5".as_slice().into())
+                )),
+                ResultColumn::Expr((
+                    Expr::BinaryOperator {
+                        operator: BinaryOp::Compare(CompareOp::Eq),
+                        left: Box::new(Expr::Column(b"col".as_slice().into())),
+                        right: Box::new(Expr::Integer(11)),
+                    },
+                    None
+                )),
+                ResultColumn::Expr((
+                    Expr::BinaryOperator {
+                        operator: BinaryOp::Compare(CompareOp::Lt),
+                        left: Box::new(Expr::Column(b"col2".as_slice().into())),
+                        right: Box::new(Expr::Column(b"col3".as_slice().into())),
+                    },
+                    Some(b"col6".as_slice().into())
+                ))
+            ]
+        );
+    }
+
+    #[test]
+    fn test_parse_select_table_all() {
+        let input = b"select bar.* from foo";
+        let mut parser = Parser::new(input);
+        let select = parse_select(&mut parser).unwrap();
+        assert_eq!(parser.n_consumed(), input.len());
+        assert_eq!(select.table.table_name, b"foo".as_slice().into());
+        assert!(select.joins.is_empty());
+        assert_eq!(
+            select.columns,
+            vec![ResultColumn::AllOfTable(b"bar".as_slice().into()),]
+        );
+    }
+
+    #[test]
+    fn test_parse_select_multiple_tables() {
+        let input = b"select * from a, b where a.id = b.aid";
+        let mut parser = Parser::new(input);
+        let select = parse_select(&mut parser).unwrap();
+        assert_eq!(parser.n_consumed(), input.len());
+        assert_eq!(select.table.table_name, b"a".as_slice().into());
+        assert_eq!(select.joins[0].table.table_name, b"b".as_slice().into());
+        assert_eq!(select.joins[0].join_type, JoinType::Cross);
+        assert!(select.joins[0].constraint.is_none());
+        assert_eq!(select.tables().count(), 2);
+        assert_eq!(
+            select.filter.unwrap(),
+            Expr::BinaryOperator {
+                operator: BinaryOp::Compare(CompareOp::Eq),
+                left: Box::new(Expr::QualifiedColumn {
+                    table_name: b"a".as_slice().into(),
+                    column_name: b"id".as_slice().into(),
+                }),
+                right: Box::new(Expr::QualifiedColumn {
+                    table_name: b"b".as_slice().into(),
+                    column_name: b"aid".as_slice().into(),
+                }),
+            }
+        );
+
+        let input = b"select a.col, \"b\".\"col\" from a,b,c";
+        let mut parser = Parser::new(input);
+        let select = parse_select(&mut parser).unwrap();
+        assert_eq!(parser.n_consumed(), input.len());
+        assert_eq!(select.joins.len(), 2);
+        assert_eq!(
+            select
+                .tables()
+                .map(|table| table.table_name)
+                .collect::<Vec<_>>(),
+            vec![
+                b"a".as_slice().into(),
+                b"b".as_slice().into(),
+                b"c".as_slice().into()
+            ]
+        );
+        assert_eq!(
+            select.columns,
+            vec![
+                ResultColumn::Expr((
+                    Expr::QualifiedColumn {
+                        table_name: b"a".as_slice().into(),
+                        column_name: b"col".as_slice().into(),
+                    },
+                    None
+                )),
+                ResultColumn::Expr((
+                    Expr::QualifiedColumn {
+                        table_name: b"\"b\"".as_slice().into(),
+                        column_name: b"\"col\"".as_slice().into(),
+                    },
+                    None
+                )),
+            ]
+        );
+
+        // no column name after dot.
+        let r = parse_select(&mut Parser::new(b"select a. from a"));
+        assert!(r.is_err());
+        assert_eq!(r.unwrap_err().cursor(), 10);
+        // no table after comma.
+        let r = parse_select(&mut Parser::new(b"select * from a, where x = 1"));
+        assert!(r.is_err());
+        assert_eq!(r.unwrap_err().cursor(), 17);
+        let r = parse_select(&mut Parser::new(b"select * from a,"));
+        assert!(r.is_err());
+        assert_eq!(r.unwrap_err().cursor(), 16);
+    }
+
+    #[test]
+    fn test_parse_select_join() {
+        let a_id_eq_b_aid = Expr::BinaryOperator {
+            operator: BinaryOp::Compare(CompareOp::Eq),
+            left: Box::new(Expr::QualifiedColumn {
+                table_name: b"a".as_slice().into(),
+                column_name: b"id".as_slice().into(),
+            }),
+            right: Box::new(Expr::QualifiedColumn {
+                table_name: b"b".as_slice().into(),
+                column_name: b"aid".as_slice().into(),
+            }),
+        };
+        for (input, join_type) in [
+            (
+                b"select * from a join b on a.id = b.aid".as_slice(),
+                JoinType::Inner,
+            ),
+            (
+                b"select * from a INNER JOIN b ON a.id = b.aid",
+                JoinType::Inner,
+            ),
+            (
+                b"select * from a left join b on a.id = b.aid",
+                JoinType::Left,
+            ),
+            (
+                b"select * from a LEFT OUTER JOIN b ON a.id = b.aid",
+                JoinType::Left,
+            ),
+        ] {
+            let mut parser = Parser::new(input);
+            let select = parse_select(&mut parser).unwrap();
+            assert_eq!(parser.n_consumed(), input.len(), "{:?}", input);
+            assert_eq!(select.table.table_name, b"a".as_slice().into());
+            assert_eq!(select.joins.len(), 1, "{:?}", input);
+            assert_eq!(select.joins[0].join_type, join_type, "{:?}", input);
+            assert_eq!(
+                select.joins[0].table,
+                TableRef {
+                    table_name: b"b".as_slice().into()
+                }
+            );
+            assert_eq!(select.joins[0].constraint.as_ref(), Some(&a_id_eq_b_aid));
+        }
+
+        let input = b"select * from a join b on a.id = b.aid left join c, d join e where x = 1";
+        let mut parser = Parser::new(input);
+        let select = parse_select(&mut parser).unwrap();
+        assert_eq!(parser.n_consumed(), input.len());
+        assert_eq!(
+            select
+                .joins
+                .iter()
+                .map(|join| (
+                    join.join_type,
+                    join.table.table_name,
+                    join.constraint.is_some()
+                ))
+                .collect::<Vec<_>>(),
+            vec![
+                (JoinType::Inner, b"b".as_slice().into(), true),
+                (JoinType::Left, b"c".as_slice().into(), false),
+                (JoinType::Cross, b"d".as_slice().into(), false),
+                (JoinType::Inner, b"e".as_slice().into(), false),
+            ]
+        );
+        assert!(select.filter.is_some());
+
+        // no join after inner.
+        let r = parse_select(&mut Parser::new(b"select * from a inner b"));
+        assert!(r.is_err());
+        assert_eq!(r.unwrap_err().cursor(), 22);
+        // no join after left outer.
+        let r = parse_select(&mut Parser::new(b"select * from a left outer b"));
+        assert!(r.is_err());
+        assert_eq!(r.unwrap_err().cursor(), 27);
+        // no table after join.
+        let r = parse_select(&mut Parser::new(b"select * from a join on x"));
+        assert!(r.is_err());
+        assert_eq!(r.unwrap_err().cursor(), 21);
+        // no on expression.
+        let r = parse_select(&mut Parser::new(b"select * from a join b on"));
+        assert!(r.is_err());
+        assert_eq!(r.unwrap_err().cursor(), 25);
+    }
+
+    #[test]
+    fn test_parse_select_where() {
+        let input = b"select * from foo where id = 5";
+        let mut parser = Parser::new(input);
+        let select = parse_select(&mut parser).unwrap();
+        assert_eq!(parser.n_consumed(), input.len());
+        assert_eq!(select.table.table_name, b"foo".as_slice().into());
+        assert!(select.joins.is_empty());
+        assert_eq!(select.columns, vec![ResultColumn::All,]);
+        assert!(select.filter.is_some());
+        assert_eq!(
+            select.filter.unwrap(),
+            Expr::BinaryOperator {
+                operator: BinaryOp::Compare(CompareOp::Eq),
+                left: Box::new(Expr::Column(b"id".as_slice().into())),
+                right: Box::new(Expr::Integer(5)),
+            }
+        );
+
+        for input in [
+            b"select * from foo where a != 1".as_slice(),
+            b"select * from foo where a <> 1",
+            b"select * from foo where a!=1",
+            b"select * from foo where a<>1",
+        ] {
+            let mut parser = Parser::new(input);
+            let select = parse_select(&mut parser).unwrap();
+            assert_eq!(parser.n_consumed(), input.len());
+            assert_eq!(
+                select.filter.unwrap(),
+                Expr::BinaryOperator {
+                    operator: BinaryOp::Compare(CompareOp::Ne),
+                    left: Box::new(Expr::Column(b"a".as_slice().into())),
+                    right: Box::new(Expr::Integer(1)),
+                },
+                "{:?}",
+                input
+            );
+        }
+    }
+
+    #[test]
+    fn test_parse_select_aggregate() {
+        let input = b"select count(*), COUNT(col), max(col) as m, sum(a) + avg(b) from foo";
+        let mut parser = Parser::new(input);
+        let select = parse_select(&mut parser).unwrap();
+        assert_eq!(parser.n_consumed(), input.len());
+        assert_eq!(select.table.table_name, b"foo".as_slice().into());
+        assert!(select.joins.is_empty());
+        assert_eq!(
+            select.columns,
+            vec![
+                ResultColumn::Expr((
+                    Expr::Aggregate {
+                        func: AggFunc::Count,
+                        arg: AggArg::Star,
+                        distinct: false,
+                    },
+                    None
+                )),
+                ResultColumn::Expr((
+                    Expr::Aggregate {
+                        func: AggFunc::Count,
+                        arg: AggArg::Expr(Box::new(Expr::Column(b"col".as_slice().into()))),
+                        distinct: false,
+                    },
+                    None
+                )),
+                ResultColumn::Expr((
+                    Expr::Aggregate {
+                        func: AggFunc::Max,
+                        arg: AggArg::Expr(Box::new(Expr::Column(b"col".as_slice().into()))),
+                        distinct: false,
+                    },
+                    Some(b"m".as_slice().into())
+                )),
+                ResultColumn::Expr((
+                    Expr::BinaryOperator {
+                        operator: BinaryOp::Add,
+                        left: Box::new(Expr::Aggregate {
+                            func: AggFunc::Sum,
+                            arg: AggArg::Expr(Box::new(Expr::Column(b"a".as_slice().into()))),
+                            distinct: false,
+                        }),
+                        right: Box::new(Expr::Aggregate {
+                            func: AggFunc::Avg,
+                            arg: AggArg::Expr(Box::new(Expr::Column(b"b".as_slice().into()))),
+                            distinct: false,
+                        }),
+                    },
+                    None
+                )),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_parse_select_group_by() {
+        let input = b"select col1, count(*) from foo where col2 = 1 group by col1, col2 || col3 having count(*) > 1";
+        let mut parser = Parser::new(input);
+        let select = parse_select(&mut parser).unwrap();
+        assert_eq!(parser.n_consumed(), input.len());
+        assert!(select.filter.is_some());
+        assert_eq!(
+            select.group_by,
+            vec![
+                Expr::Column(b"col1".as_slice().into()),
+                Expr::BinaryOperator {
+                    operator: BinaryOp::Concat,
+                    left: Box::new(Expr::Column(b"col2".as_slice().into())),
+                    right: Box::new(Expr::Column(b"col3".as_slice().into())),
                 },
             ]
         );
+        assert_eq!(
+            select.having,
+            Some(Expr::BinaryOperator {
+                operator: BinaryOp::Compare(CompareOp::Gt),
+                left: Box::new(Expr::Aggregate {
+                    func: AggFunc::Count,
+                    arg: AggArg::Star,
+                    distinct: false,
+                }),
+                right: Box::new(Expr::Integer(1)),
+            })
+        );
+
+        let input = b"select col1 from foo GROUP BY col1";
+        let mut parser = Parser::new(input);
+        let select = parse_select(&mut parser).unwrap();
+        assert_eq!(parser.n_consumed(), input.len());
+        assert!(select.filter.is_none());
+        assert_eq!(
+            select.group_by,
+            vec![Expr::Column(b"col1".as_slice().into())]
+        );
+        assert!(select.having.is_none());
+
+        let input = b"select by from foo group by by";
+        let mut parser = Parser::new(input);
+        let select = parse_select(&mut parser).unwrap();
+        assert_eq!(parser.n_consumed(), input.len());
+        assert_eq!(select.group_by, vec![Expr::Column(b"by".as_slice().into())]);
+
+        let input = b"select col1 from foo";
+        let mut parser = Parser::new(input);
+        let select = parse_select(&mut parser).unwrap();
+        assert!(select.group_by.is_empty());
+        assert!(select.having.is_none());
+
+        // no by.
+        let r = parse_select(&mut Parser::new(b"select col1 from foo group col1"));
+        assert!(r.is_err());
+        assert_eq!(r.unwrap_err().cursor(), 27);
+        // no expr.
+        let r = parse_select(&mut Parser::new(b"select col1 from foo group by"));
+        assert!(r.is_err());
+        assert_eq!(r.unwrap_err().cursor(), 29);
+        // having without group by.
+        let r = parse_select(&mut Parser::new(
+            b"select col1 from foo having count(*) > 1",
+        ));
+        assert!(r.is_err());
+        assert_eq!(r.unwrap_err().cursor(), 21);
+    }
+
+    #[test]
+    fn test_parse_select_stmt() {
+        let input = b"select col1 from foo";
+        let mut parser = Parser::new(input);
+        let select_stmt = parse_select_stmt(&mut parser).unwrap();
+        assert_eq!(parser.n_consumed(), input.len());
+        assert_eq!(
+            select_stmt.select.table.table_name,
+            b"foo".as_slice().into()
+        );
+        assert_eq!(select_stmt.explain, ExplainMode::None);
+        assert!(select_stmt.compounds.is_empty());
+        assert!(select_stmt.order_by.is_empty());
+        assert!(select_stmt.limit.is_none());
+
+        let input = b"select col1 from foo union select col2 from bar union all select col3 from baz where col3 = 1 intersect select * from qux except select col4 from quux;";
+        let mut parser = Parser::new(input);
+        let select_stmt = parse_select_stmt(&mut parser).unwrap();
+        assert_eq!(parser.n_consumed(), input.len() - 1);
+        assert_eq!(
+            select_stmt.select.table.table_name,
+            b"foo".as_slice().into()
+        );
+        assert_eq!(
+            select_stmt
+                .compounds
+                .iter()
+                .map(|(operator, select)| (*operator, select.table.table_name))
+                .collect::<Vec<_>>(),
+            vec![
+                (CompoundOperator::Union, b"bar".as_slice().into()),
+                (CompoundOperator::UnionAll, b"baz".as_slice().into()),
+                (CompoundOperator::Intersect, b"qux".as_slice().into()),
+                (CompoundOperator::Except, b"quux".as_slice().into()),
+            ]
+        );
+        assert!(select_stmt.compounds[1].1.filter.is_some());
+        assert_eq!(select_stmt.compounds[2].1.columns, vec![ResultColumn::All]);
+        assert!(select_stmt.order_by.is_empty());
+        assert!(select_stmt.limit.is_none());
+
+        // ORDER BY and LIMIT are applied to the whole compound select.
+        let input = b"select col1 from foo union all select col2 from bar order by col1 desc, 2 asc, col3 limit 10 offset 5";
+        let mut parser = Parser::new(input);
+        let select_stmt = parse_select_stmt(&mut parser).unwrap();
+        assert_eq!(parser.n_consumed(), input.len());
+        assert_eq!(select_stmt.compounds.len(), 1);
+        assert_eq!(
+            select_stmt.order_by,
+            vec![
+                OrderingTerm {
+                    expr: Expr::Column(b"col1".as_slice().into()),
+                    order: SortOrder::Desc,
+                },
+                OrderingTerm {
+                    expr: Expr::Integer(2),
+                    order: SortOrder::Asc,
+                },
+                OrderingTerm {
+                    expr: Expr::Column(b"col3".as_slice().into()),
+                    order: SortOrder::Asc,
+                },
+            ]
+        );
+        assert_eq!(
+            select_stmt.limit,
+            Some(Limit {
+                limit: Expr::Integer(10),
+                offset: Some(Expr::Integer(5)),
+                is_offset_first: false,
+            })
+        );
+
+        let input = b"select asc from foo order by asc desc, desc";
+        let mut parser = Parser::new(input);
+        let select_stmt = parse_select_stmt(&mut parser).unwrap();
+        assert_eq!(parser.n_consumed(), input.len());
+        assert_eq!(
+            select_stmt.order_by,
+            vec![
+                OrderingTerm {
+                    expr: Expr::Column(b"asc".as_slice().into()),
+                    order: SortOrder::Desc,
+                },
+                OrderingTerm {
+                    expr: Expr::Column(b"desc".as_slice().into()),
+                    order: SortOrder::Asc,
+                },
+            ]
+        );
+
+        let input = b"select col1 from foo limit 5, 10";
+        let mut parser = Parser::new(input);
+        let select_stmt = parse_select_stmt(&mut parser).unwrap();
+        assert_eq!(parser.n_consumed(), input.len());
+        assert!(select_stmt.order_by.is_empty());
+        assert_eq!(
+            select_stmt.limit,
+            Some(Limit {
+                limit: Expr::Integer(10),
+                offset: Some(Expr::Integer(5)),
+                is_offset_first: true,
+            })
+        );
+
+        // "offset" is a name unless it follows the limit.
+        let input = b"select offset from offset limit offset offset offset";
+        let mut parser = Parser::new(input);
+        let select_stmt = parse_select_stmt(&mut parser).unwrap();
+        assert_eq!(parser.n_consumed(), input.len());
+        assert_eq!(
+            select_stmt.limit,
+            Some(Limit {
+                limit: Expr::Column(b"offset".as_slice().into()),
+                offset: Some(Expr::Column(b"offset".as_slice().into())),
+                is_offset_first: false,
+            })
+        );
+
+        let input = b"select col1 from foo limit 10";
+        let mut parser = Parser::new(input);
+        let select_stmt = parse_select_stmt(&mut parser).unwrap();
+        assert_eq!(parser.n_consumed(), input.len());
+        assert_eq!(
+            select_stmt.limit,
+            Some(Limit {
+                limit: Expr::Integer(10),
+                offset: None,
+                is_offset_first: false,
+            })
+        );
+    }
+
+    #[test]
+    fn test_parse_select_stmt_explain() {
+        let input = b"EXPLAIN select col1 from foo";
+        let mut parser = Parser::new(input);
+        let select_stmt = parse_select_stmt(&mut parser).unwrap();
+        assert_eq!(parser.n_consumed(), input.len());
+        assert_eq!(select_stmt.explain, ExplainMode::Explain);
+        assert_eq!(
+            select_stmt.select.table.table_name,
+            b"foo".as_slice().into()
+        );
+
+        let input = b"explain Query PLAN select col1 from foo union select col2 from bar";
+        let mut parser = Parser::new(input);
+        let select_stmt = parse_select_stmt(&mut parser).unwrap();
+        assert_eq!(parser.n_consumed(), input.len());
+        assert_eq!(select_stmt.explain, ExplainMode::QueryPlan);
+        assert_eq!(select_stmt.compounds.len(), 1);
+
+        // explain, query and plan are valid names.
+        let input = b"select query, plan, explain from explain";
+        let mut parser = Parser::new(input);
+        let select_stmt = parse_select_stmt(&mut parser).unwrap();
+        assert_eq!(parser.n_consumed(), input.len());
+        assert_eq!(select_stmt.explain, ExplainMode::None);
+
+        // no plan after query.
+        let r = parse_select_stmt(&mut Parser::new(b"explain query select col1 from foo"));
+        let e = r.unwrap_err();
+        assert_eq!(e.cursor(), 14);
+        assert_eq!(e.msg(), "no plan after query");
+        // "query" is quoted.
+        let r = parse_select_stmt(&mut Parser::new(b"explain \"query\" plan select 1"));
+        assert_eq!(r.unwrap_err().msg(), "no select");
+    }
+
+    #[test]
+    fn test_parse_select_stmt_fail() {
+        // no select after union.
+        let r = parse_select_stmt(&mut Parser::new(b"select col1 from foo union"));
+        let e = r.unwrap_err();
+        assert_eq!(e.cursor(), 26);
+        assert_eq!(e.msg(), "no select");
+        let r = parse_select_stmt(&mut Parser::new(b"select col1 from foo union all from bar"));
+        let e = r.unwrap_err();
+        assert_eq!(e.cursor(), 31);
+        assert_eq!(e.msg(), "no select");
+        // no by after order.
+        let r = parse_select_stmt(&mut Parser::new(b"select col1 from foo order col1"));
+        let e = r.unwrap_err();
+        assert_eq!(e.cursor(), 27);
+        assert_eq!(e.msg(), "no by after order");
+        // no expr after order by.
+        let r = parse_select_stmt(&mut Parser::new(b"select col1 from foo order by"));
+        assert_eq!(r.unwrap_err().cursor(), 29);
+        let r = parse_select_stmt(&mut Parser::new(b"select col1 from foo order by col1,"));
+        assert_eq!(r.unwrap_err().cursor(), 35);
+        // no expr after limit.
+        let r = parse_select_stmt(&mut Parser::new(b"select col1 from foo limit"));
+        assert_eq!(r.unwrap_err().cursor(), 26);
+        let r = parse_select_stmt(&mut Parser::new(b"select col1 from foo limit 1 offset"));
+        assert_eq!(r.unwrap_err().cursor(), 35);
+    }
+
+    #[test]
+    fn test_parse_delete() {
+        let input = b"delete from foo";
+        let mut parser = Parser::new(input);
+        let delete = parse_delete(&mut parser).unwrap();
+        assert_eq!(parser.n_consumed(), input.len());
+        assert_eq!(
+            delete,
+            Delete {
+                table_name: b"foo".as_slice().into(),
+                filter: None,
+            }
+        );
+
+        let input = b"DELETE FROM \"foo\" WHERE id = 1;";
+        let mut parser = Parser::new(input);
+        let delete = parse_delete(&mut parser).unwrap();
+        assert_eq!(parser.n_consumed(), input.len() - 1);
+        assert_eq!(
+            delete,
+            Delete {
+                table_name: b"\"foo\"".as_slice().into(),
+                filter: Some(Expr::BinaryOperator {
+                    operator: BinaryOp::Compare(CompareOp::Eq),
+                    left: Box::new(Expr::Column(b"id".as_slice().into())),
+                    right: Box::new(Expr::Integer(1)),
+                }),
+            }
+        );
+
+        for (input, cursor, msg) in [
+            (b"select * from foo".as_slice(), 0, "no delete"),
+            (b"delete foo", 7, "no from"),
+            (b"delete from where id = 1", 12, "no table_name"),
+        ] {
+            let e = parse_delete(&mut Parser::new(input)).unwrap_err();
+            assert_eq!(e.cursor(), cursor, "{:?}", input);
+            assert_eq!(e.msg(), msg, "{:?}", input);
+        }
+        // no expr after where.
+        let r = parse_delete(&mut Parser::new(b"delete from foo where"));
+        assert_eq!(r.unwrap_err().cursor(), 21);
+    }
+
+    #[test]
+    fn test_parse_update() {
+        let input = b"update foo set col1 = 1";
+        let mut parser = Parser::new(input);
+        let update = parse_update(&mut parser).unwrap();
+        assert_eq!(parser.n_consumed(), input.len());
+        assert_eq!(
+            update,
+            Update {
+                table_name: b"foo".as_slice().into(),
+                assignments: vec![(b"col1".as_slice().into(), Expr::Integer(1))],
+                filter: None,
+            }
+        );
+
+        let input = b"UPDATE foo SET col1 = col1 + 1, \"col2\"='a' WHERE id = 1;";
+        let mut parser = Parser::new(input);
+        let update = parse_update(&mut parser).unwrap();
+        assert_eq!(parser.n_consumed(), input.len() - 1);
+        assert_eq!(
+            update,
+            Update {
+                table_name: b"foo".as_slice().into(),
+                assignments: vec![
+                    (
+                        b"col1".as_slice().into(),
+                        Expr::BinaryOperator {
+                            operator: BinaryOp::Add,
+                            left: Box::new(Expr::Column(b"col1".as_slice().into())),
+                            right: Box::new(Expr::Integer(1)),
+                        }
+                    ),
+                    (
+                        b"\"col2\"".as_slice().into(),
+                        Expr::Text(b"'a'".as_slice().into())
+                    ),
+                ],
+                filter: Some(Expr::BinaryOperator {
+                    operator: BinaryOp::Compare(CompareOp::Eq),
+                    left: Box::new(Expr::Column(b"id".as_slice().into())),
+                    right: Box::new(Expr::Integer(1)),
+                }),
+            }
+        );
+
+        for (input, cursor, msg) in [
+            (b"delete from foo".as_slice(), 0, "no update"),
+            (b"update set col1 = 1", 7, "no table_name"),
+            (b"update foo col1 = 1", 11, "no set"),
+            (b"update foo set = 1", 15, "no column name"),
+            (b"update foo set col1 1", 20, "no eq"),
+            (b"update foo set col1 = 1,", 24, "no column name"),
+            (
+                b"update foo set col1 = 1, where id = 1",
+                25,
+                "no column name",
+            ),
+        ] {
+            let e = parse_update(&mut Parser::new(input)).unwrap_err();
+            assert_eq!(e.cursor(), cursor, "{:?}", input);
+            assert_eq!(e.msg(), msg, "{:?}", input);
+        }
+        // no expr after eq.
+        let r = parse_update(&mut Parser::new(b"update foo set col1 ="));
+        assert_eq!(r.unwrap_err().cursor(), 21);
+    }
+
+    #[test]
+    fn test_parse_select_fail() {
+        // a join keyword is not a bare alias.
+        for input in [b"select col left from foo".as_slice(), b"select col outer"] {
+            let r = parse_select(&mut Parser::new(input));
+            assert_eq!(r.unwrap_err().cursor(), 11);
+        }
+        let input = b"select col as inner from foo";
+        let mut parser = Parser::new(input);
+        let select = parse_select(&mut parser).unwrap();
+        assert_eq!(parser.n_consumed(), input.len());
+        assert_eq!(
+            select.columns,
+            vec![ResultColumn::Expr((
+                Expr::Column(b"col".as_slice().into()),
+                Some(b"inner".as_slice().into())
+            ))]
+        );
+        // no expr after comma.
+        let r = parse_select(&mut Parser::new(b"select col, from foo"));
+        assert!(r.is_err());
+        let e = r.unwrap_err();
+        assert_eq!(e.cursor(), 12);
+        assert_eq!(e.msg(), "no result column after comma");
+        let r = parse_select(&mut Parser::new(b"select col,, col2 from foo"));
+        let e = r.unwrap_err();
+        assert_eq!(e.cursor(), 11);
+        assert_eq!(e.msg(), "no result column after comma");
+        let r = parse_select(&mut Parser::new(b"select col,"));
+        assert_eq!(r.unwrap_err().msg(), "no result column after comma");
+        // no result column.
+        for (input, cursor) in [
+            (b"select from foo".as_slice(), 7),
+            (b"select , col from foo", 7),
+            (b"select", 6),
+        ] {
+            let r = parse_select(&mut Parser::new(input));
+            let e = r.unwrap_err();
+            assert_eq!(e.cursor(), cursor, "{:?}", input);
+            assert_eq!(e.msg(), "no result column", "{:?}", input);
+        }
+        // no from.
+        let r = parse_select(&mut Parser::new(b"select col1 col2 col3 from foo"));
+        let e = r.unwrap_err();
+        assert_eq!(e.cursor(), 17);
+        assert_eq!(e.msg(), "no from");
+        // no table name.
+        let r = parse_select(&mut Parser::new(b"select col from ;"));
+        assert!(r.is_err());
+        assert_eq!(r.unwrap_err().cursor(), 16);
+    }
+
+    #[test]
+    fn test_parse_expr_literal_value() {
+        // Parse null
+        assert_parser!(parse_expr, b"null", 4, Expr::Null);
+
+        // Parse integer
+        assert_parser!(parse_expr, b"123456789a", 9, Expr::Integer(123456789));
+        assert_parser!(parse_expr, b"00123456789a", 11, Expr::Integer(123456789));
+        assert_parser!(parse_expr, b"00000000000000000001a", 20, Expr::Integer(1));
+        assert_parser!(
+            parse_expr,
+            b"9223372036854775807",
+            19,
+            Expr::Integer(9223372036854775807)
+        );
+        assert_parser!(
+            parse_expr,
+            b"000000000000000000009223372036854775807",
+            39,
+            Expr::Integer(9223372036854775807)
+        );
+        // integer -> float fallback
+        assert_parser!(
+            parse_expr,
+            b"9223372036854775808",
+            19,
+            Expr::Real(9223372036854775808.0)
+        );
+        assert_parser!(
+            parse_expr,
+            b"9999999999999999999",
+            19,
+            Expr::Real(9999999999999999999.0)
+        );
+        assert_parser!(
+            parse_expr,
+            b"99999999999999999999a",
+            20,
+            Expr::Real(99999999999999999999.0)
+        );
+
+        // Parse hexadecimal integer
+        assert_parser!(parse_expr, b"0xFF", 4, Expr::Integer(255));
+        assert_parser!(parse_expr, b"0x0", 3, Expr::Integer(0));
+        assert_parser!(
+            parse_expr,
+            b"0x7fffffffffffffff",
+            18,
+            Expr::Integer(i64::MAX)
+        );
+        assert_parser!(parse_expr, b"0xffffffffffffffff", 18, Expr::Integer(-1));
+        assert_parser!(
+            parse_expr,
+            b"-0x1",
+            4,
+            Expr::UnaryOperator {
+                operator: UnaryOp::Minus,
+                expr: Box::new(Expr::Integer(1))
+            }
+        );
+        let r = parse_expr(&mut Parser::new(b"0x10000000000000000"));
+        assert!(r.is_err());
+        assert_eq!(r.unwrap_err().cursor(), 0);
+
+        // Parse float
+        assert_parser!(parse_expr, b".1", 2, Expr::Real(0.1));
+        assert_parser!(parse_expr, b"5.", 2, Expr::Real(5.0));
+        assert_parser!(parse_expr, b"1e3", 3, Expr::Real(1000.0));
+        assert_parser!(parse_expr, b"1.", 2, Expr::Real(1.0));
+        assert_parser!(parse_expr, b"1.01", 4, Expr::Real(1.01));
+        assert_parser!(parse_expr, b"1e1", 3, Expr::Real(10.0));
+        assert_parser!(parse_expr, b"1e-1", 4, Expr::Real(0.1));
+
+        // Parse string
+        assert_parser!(
+            parse_expr,
+            b"'hello'",
+            7,
+            Expr::Text(b"'hello'".as_slice().into())
+        );
+        assert_parser!(
+            parse_expr,
+            b"'hel''lo'",
+            9,
+            Expr::Text(b"'hel''lo'".as_slice().into())
+        );
+
+        // Parse blob
+        assert_parser!(
+            parse_expr,
+            b"x'0123456789abcdef'",
+            19,
+            Expr::Blob(b"0123456789abcdef".as_slice().into())
+        );
+        assert_parser!(
+            parse_expr,
+            b"X'0123456789abcdef'",
+            19,
+            Expr::Blob(b"0123456789abcdef".as_slice().into())
+        );
+
+        // TRUE and FALSE are names which may be columns.
+        assert_parser!(
+            parse_expr,
+            b"true",
+            4,
+            Expr::Column(b"true".as_slice().into())
+        );
+        assert_parser!(
+            parse_expr,
+            b"-FALSE",
+            6,
+            Expr::UnaryOperator {
+                operator: UnaryOp::Minus,
+                expr: Box::new(Expr::Column(b"FALSE".as_slice().into()))
+            }
+        );
+
+        // Parse current time
+        assert_parser!(
+            parse_expr,
+            b"current_date",
+            12,
+            Expr::CurrentTime(CurrentTimeKind::Date)
+        );
+        assert_parser!(
+            parse_expr,
+            b"Current_Time",
+            12,
+            Expr::CurrentTime(CurrentTimeKind::Time)
+        );
+        assert_parser!(
+            parse_expr,
+            b"CURRENT_TIMESTAMP",
+            17,
+            Expr::CurrentTime(CurrentTimeKind::Timestamp)
+        );
+        assert_parser!(
+            parse_expr,
+            b"current_timestamps",
+            18,
+            Expr::Column(b"current_timestamps".as_slice().into())
+        );
+    }
+
+    #[test]
+    fn test_parse_expr_blob_fail() {
+        // Odd number of hex digits.
+        let r = parse_expr(&mut Parser::new(b"x'00F'"));
+        assert!(r.is_err());
+        assert_eq!(r.unwrap_err().cursor(), 0);
+        // Non-hex character.
+        let r = parse_expr(&mut Parser::new(b"col = X'0G'"));
+        assert!(r.is_err());
+        assert_eq!(r.unwrap_err().cursor(), 6);
+    }
+
+    #[test]
+    fn test_parse_expr_column() {
+        assert_parser!(
+            parse_expr,
+            b"foo",
+            3,
+            Expr::Column(b"foo".as_slice().into())
+        );
+        assert_parser!(
+            parse_expr,
+            b"\"foo\"",
+            5,
+            Expr::Column(b"\"foo\"".as_slice().into())
+        );
+    }
+
+    #[test]
+    fn test_parse_expr_aggregate() {
+        assert_parser!(
+            parse_expr,
+            b"count ( * )",
+            11,
+            Expr::Aggregate {
+                func: AggFunc::Count,
+                arg: AggArg::Star,
+                distinct: false,
+            }
+        );
+        assert_parser!(
+            parse_expr,
+            b"\"count\"(*)",
+            10,
+            Expr::Aggregate {
+                func: AggFunc::Count,
+                arg: AggArg::Star,
+                distinct: false,
+            }
+        );
+        assert_parser!(
+            parse_expr,
+            b"[Sum](a)",
+            8,
+            Expr::Aggregate {
+                func: AggFunc::Sum,
+                arg: AggArg::Expr(Box::new(Expr::Column(b"a".as_slice().into()))),
+                distinct: false,
+            }
+        );
+        assert_parser!(
+            parse_expr,
+            b"Min(a + 1)",
+            10,
+            Expr::Aggregate {
+                func: AggFunc::Min,
+                arg: AggArg::Expr(Box::new(Expr::BinaryOperator {
+                    operator: BinaryOp::Add,
+                    left: Box::new(Expr::Column(b"a".as_slice().into())),
+                    right: Box::new(Expr::Integer(1)),
+                })),
+                distinct: false,
+            }
+        );
+        assert_parser!(
+            parse_expr,
+            b"count(*) > 1",
+            12,
+            Expr::BinaryOperator {
+                operator: BinaryOp::Compare(CompareOp::Gt),
+                left: Box::new(Expr::Aggregate {
+                    func: AggFunc::Count,
+                    arg: AggArg::Star,
+                    distinct: false,
+                }),
+                right: Box::new(Expr::Integer(1)),
+            }
+        );
+        // Without parens, it is a column.
+        assert_parser!(
+            parse_expr,
+            b"count",
+            5,
+            Expr::Column(b"count".as_slice().into())
+        );
+        assert_parser!(
+            parse_expr,
+            b"\"count\"",
+            7,
+            Expr::Column(b"\"count\"".as_slice().into())
+        );
+
+        // * is only for count.
+        let r = parse_expr(&mut Parser::new(b"sum(*)"));
+        assert!(r.is_err());
+        assert_eq!(r.unwrap_err().cursor(), 4);
+        // no right paren.
+        let r = parse_expr(&mut Parser::new(b"count(*"));
+        assert!(r.is_err());
+        assert_eq!(r.unwrap_err().cursor(), 7);
+        // count(DISTINCT *) is invalid.
+        let r = parse_expr(&mut Parser::new(b"count(distinct *)"));
+        assert!(r.is_err());
+        assert_eq!(r.unwrap_err().cursor(), 15);
+
+        assert_parser!(
+            parse_expr,
+            b"count(DISTINCT col)",
+            19,
+            Expr::Aggregate {
+                func: AggFunc::Count,
+                arg: AggArg::Expr(Box::new(Expr::Column(b"col".as_slice().into()))),
+                distinct: true,
+            }
+        );
+        // max() with multiple arguments is a scalar function.
+        assert_parser!(
+            parse_expr,
+            b"max(a, b)",
+            9,
+            Expr::Function {
+                name: b"max".as_slice().into(),
+                args: vec![
+                    Expr::Column(b"a".as_slice().into()),
+                    Expr::Column(b"b".as_slice().into()),
+                ],
+                distinct: false,
+            }
+        );
+    }
+
+    #[test]
+    fn test_parse_expr_function() {
+        assert_parser!(
+            parse_expr,
+            b"length(name)",
+            12,
+            Expr::Function {
+                name: b"length".as_slice().into(),
+                args: vec![Expr::Column(b"name".as_slice().into())],
+                distinct: false,
+            }
+        );
+        assert_parser!(
+            parse_expr,
+            b"random ( )",
+            10,
+            Expr::Function {
+                name: b"random".as_slice().into(),
+                args: Vec::new(),
+                distinct: false,
+            }
+        );
+        assert_parser!(
+            parse_expr,
+            b"coalesce(lower(a), length(b || 'x'), 1) = 2",
+            43,
+            Expr::BinaryOperator {
+                operator: BinaryOp::Compare(CompareOp::Eq),
+                left: Box::new(Expr::Function {
+                    name: b"coalesce".as_slice().into(),
+                    args: vec![
+                        Expr::Function {
+                            name: b"lower".as_slice().into(),
+                            args: vec![Expr::Column(b"a".as_slice().into())],
+                            distinct: false,
+                        },
+                        Expr::Function {
+                            name: b"length".as_slice().into(),
+                            args: vec![Expr::BinaryOperator {
+                                operator: BinaryOp::Concat,
+                                left: Box::new(Expr::Column(b"b".as_slice().into())),
+                                right: Box::new(Expr::Text(b"'x'".as_slice().into())),
+                            }],
+                            distinct: false,
+                        },
+                        Expr::Integer(1),
+                    ],
+                    distinct: false,
+                }),
+                right: Box::new(Expr::Integer(2)),
+            }
+        );
+        assert_parser!(
+            parse_expr,
+            b"group_concat(DISTINCT name)",
+            27,
+            Expr::Function {
+                name: b"group_concat".as_slice().into(),
+                args: vec![Expr::Column(b"name".as_slice().into())],
+                distinct: true,
+            }
+        );
+
+        // no right paren.
+        let r = parse_expr(&mut Parser::new(b"lower(a"));
+        assert!(r.is_err());
+        assert_eq!(r.unwrap_err().msg(), "no function right paren");
+        // trailing comma.
+        let r = parse_expr(&mut Parser::new(b"lower(a,)"));
+        assert!(r.is_err());
+        assert_eq!(r.unwrap_err().cursor(), 8);
+        // DISTINCT without arguments.
+        let r = parse_expr(&mut Parser::new(b"f(distinct)"));
+        assert!(r.is_err());
+        assert_eq!(r.unwrap_err().cursor(), 10);
+    }
+
+    #[test]
+    fn test_parse_expr_case() {
+        assert_parser!(
+            parse_expr,
+            b"case when x > 0 then 'pos' else 'neg' end",
+            41,
+            Expr::Case {
+                operand: None,
+                whens: vec![(
+                    Expr::BinaryOperator {
+                        operator: BinaryOp::Compare(CompareOp::Gt),
+                        left: Box::new(Expr::Column(b"x".as_slice().into())),
+                        right: Box::new(Expr::Integer(0)),
+                    },
+                    Expr::Text(b"'pos'".as_slice().into())
+                )],
+                else_: Some(Box::new(Expr::Text(b"'neg'".as_slice().into()))),
+            }
+        );
+        assert_parser!(
+            parse_expr,
+            b"CASE x WHEN 1 THEN 'one' WHEN 2 THEN 'two' END",
+            46,
+            Expr::Case {
+                operand: Some(Box::new(Expr::Column(b"x".as_slice().into()))),
+                whens: vec![
+                    (Expr::Integer(1), Expr::Text(b"'one'".as_slice().into())),
+                    (Expr::Integer(2), Expr::Text(b"'two'".as_slice().into())),
+                ],
+                else_: None,
+            }
+        );
+        // CASE is a primitive expression.
+        assert_parser!(
+            parse_expr,
+            b"case x + 1 when y then 1 end + 2",
+            32,
+            Expr::BinaryOperator {
+                operator: BinaryOp::Add,
+                left: Box::new(Expr::Case {
+                    operand: Some(Box::new(Expr::BinaryOperator {
+                        operator: BinaryOp::Add,
+                        left: Box::new(Expr::Column(b"x".as_slice().into())),
+                        right: Box::new(Expr::Integer(1)),
+                    })),
+                    whens: vec![(Expr::Column(b"y".as_slice().into()), Expr::Integer(1))],
+                    else_: None,
+                }),
+                right: Box::new(Expr::Integer(2)),
+            }
+        );
+        assert_parser!(
+            parse_expr,
+            b"case when a then case when b then 1 end end",
+            43,
+            Expr::Case {
+                operand: None,
+                whens: vec![(
+                    Expr::Column(b"a".as_slice().into()),
+                    Expr::Case {
+                        operand: None,
+                        whens: vec![(Expr::Column(b"b".as_slice().into()), Expr::Integer(1))],
+                        else_: None,
+                    }
+                )],
+                else_: None,
+            }
+        );
+
+        let r = parse_expr(&mut Parser::new(b"case when x then 1 else 2"));
+        assert_eq!(r.unwrap_err().msg(), "no end of case");
+        let r = parse_expr(&mut Parser::new(b"case when x then 1"));
+        assert_eq!(r.unwrap_err().msg(), "no end of case");
+        let r = parse_expr(&mut Parser::new(b"case when x 1 end"));
+        assert_eq!(r.unwrap_err().msg(), "no then in case");
+        let r = parse_expr(&mut Parser::new(b"case x else 1 end"));
+        assert_eq!(r.unwrap_err().msg(), "no when in case");
+        // end is the operand.
+        let r = parse_expr(&mut Parser::new(b"case end"));
+        assert_eq!(r.unwrap_err().msg(), "no when in case");
+
+        // END is a name unless it terminates the case.
+        assert_parser!(
+            parse_expr,
+            b"case end when end then end else end end",
+            39,
+            Expr::Case {
+                operand: Some(Box::new(Expr::Column(b"end".as_slice().into()))),
+                whens: vec![(
+                    Expr::Column(b"end".as_slice().into()),
+                    Expr::Column(b"end".as_slice().into())
+                )],
+                else_: Some(Box::new(Expr::Column(b"end".as_slice().into()))),
+            }
+        );
+    }
+
+    #[test]
+    fn test_parse_expr_cast() {
+        assert_parser!(
+            parse_expr,
+            b"cast(100as text)",
+            16,
+            Expr::Cast {
+                expr: Box::new(Expr::Integer(100)),
+                type_name: vec![b"text".as_slice().into()],
+            }
+        );
+        assert_parser!(
+            parse_expr,
+            b"cast ( '100' as integer)",
+            24,
+            Expr::Cast {
+                expr: Box::new(Expr::Text(b"'100'".as_slice().into())),
+                type_name: vec![b"integer".as_slice().into()],
+            }
+        );
+        assert_parser!(
+            parse_expr,
+            b"cast (col = 100 as integer)",
+            27,
+            Expr::Cast {
+                expr: Box::new(Expr::BinaryOperator {
+                    operator: BinaryOp::Compare(CompareOp::Eq),
+                    left: Box::new(Expr::Column(b"col".as_slice().into())),
+                    right: Box::new(Expr::Integer(100))
+                }),
+                type_name: vec![b"integer".as_slice().into()],
+            }
+        );
+        assert_parser!(
+            parse_expr,
+            b"CAST(col AS unsigned big int)",
+            29,
+            Expr::Cast {
+                expr: Box::new(Expr::Column(b"col".as_slice().into())),
+                type_name: vec![
+                    b"unsigned".as_slice().into(),
+                    b"big".as_slice().into(),
+                    b"int".as_slice().into()
+                ],
+            }
+        );
+        assert_parser!(
+            parse_expr,
+            b"CAST(col AS varchar(255))",
+            25,
+            Expr::Cast {
+                expr: Box::new(Expr::Column(b"col".as_slice().into())),
+                type_name: vec![b"varchar".as_slice().into()],
+            }
+        );
+
+        let r = parse_expr(&mut Parser::new(b"cast 1 as integer)"));
+        assert_eq!(r.unwrap_err().msg(), "no cast left paren");
+        let r = parse_expr(&mut Parser::new(b"cast(1 integer)"));
+        assert_eq!(r.unwrap_err().msg(), "no cast as");
+        let r = parse_expr(&mut Parser::new(b"cast(1 as)"));
+        let e = r.unwrap_err();
+        assert_eq!(e.msg(), "no cast type name");
+        assert_eq!(e.cursor(), 9);
+        let r = parse_expr(&mut Parser::new(b"cast(1 as integer"));
+        assert_eq!(r.unwrap_err().msg(), "no cast right paren");
     }
 
     #[test]
-    fn test_parse_create_table_type_name() {
-        let input = b"create table foo (col1 type type primary key, col2 Varint(10), col3 [Float](+10), col4 \"test\"(-10.0), col5 null(0), col6 `blob```(1,+2))";
-        let mut parser = Parser::new(input);
-        let create_table = parse_create_table(&mut parser).unwrap();
-        assert_eq!(parser.n_consumed(), input.len());
-        assert_eq!(create_table.table_name, b"foo".as_slice().into());
-        assert_eq!(create_table.columns.len(), 6);
-        assert_eq!(
-            create_table.columns[0].type_name,
-            vec![b"type".as_slice().into(), b"type".as_slice().into()]
+    fn test_parse_expr_unary_operator() {
+        assert_parser!(
+            parse_expr,
+            b"+foo",
+            4,
+            Expr::Column(b"foo".as_slice().into())
         );
-        assert_eq!(
-            create_table.columns[1].type_name,
-            vec![b"Varint".as_slice().into()]
+        assert_parser!(
+            parse_expr,
+            b"-foo",
+            4,
+            Expr::UnaryOperator {
+                operator: UnaryOp::Minus,
+                expr: Box::new(Expr::Column(b"foo".as_slice().into()))
+            }
         );
-        assert_eq!(
-            create_table.columns[2].type_name,
-            vec![b"Float".as_slice().into()]
+        assert_parser!(
+            parse_expr,
+            b"~foo",
+            4,
+            Expr::UnaryOperator {
+                operator: UnaryOp::BitNot,
+                expr: Box::new(Expr::Column(b"foo".as_slice().into()))
+            }
         );
-        assert_eq!(
-            create_table.columns[3].type_name,
-            vec![b"\"test\"".as_slice().into()]
+        assert_parser!(
+            parse_expr,
+            b"~~foo",
+            5,
+            Expr::UnaryOperator {
+                operator: UnaryOp::BitNot,
+                expr: Box::new(Expr::UnaryOperator {
+                    operator: UnaryOp::BitNot,
+                    expr: Box::new(Expr::Column(b"foo".as_slice().into()))
+                })
+            }
         );
-        assert_eq!(
-            create_table.columns[4].type_name,
-            vec![b"null".as_slice().into()]
+        assert_parser!(
+            parse_expr,
+            b"-+-+-foo",
+            8,
+            Expr::UnaryOperator {
+                operator: UnaryOp::Minus,
+                expr: Box::new(Expr::UnaryOperator {
+                    operator: UnaryOp::Minus,
+                    expr: Box::new(Expr::UnaryOperator {
+                        operator: UnaryOp::Minus,
+                        expr: Box::new(Expr::Column(b"foo".as_slice().into()))
+                    })
+                })
+            }
         );
-        assert_eq!(
-            create_table.columns[5].type_name,
-            vec![b"`blob```".as_slice().into()]
+        assert_parser!(parse_expr, b"+123", 4, Expr::Integer(123));
+        assert_parser!(parse_expr, b"+ 123", 5, Expr::Integer(123));
+        assert_parser!(parse_expr, b"-123", 4, Expr::Integer(-123));
+        assert_parser!(parse_expr, b"- 123", 5, Expr::Integer(-123));
+        assert_parser!(
+            parse_expr,
+            b"-9223372036854775808",
+            20,
+            Expr::Integer(-9223372036854775808)
+        );
+        assert_parser!(
+            parse_expr,
+            b"-+-123",
+            6,
+            Expr::UnaryOperator {
+                operator: UnaryOp::Minus,
+                expr: Box::new(Expr::Integer(-123))
+            }
+        );
+        assert_parser!(parse_expr, b"+123.4", 6, Expr::Real(123.4));
+        assert_parser!(parse_expr, b"-123.4", 6, Expr::Real(-123.4));
+        assert_parser!(
+            parse_expr,
+            b"-+-123.4",
+            8,
+            Expr::UnaryOperator {
+                operator: UnaryOp::Minus,
+                expr: Box::new(Expr::Real(-123.4))
+            }
+        );
+        assert_parser!(
+            parse_expr,
+            b"+'abc'",
+            6,
+            Expr::Text(b"'abc'".as_slice().into())
+        );
+        assert_parser!(
+            parse_expr,
+            b"-'abc'",
+            6,
+            Expr::UnaryOperator {
+                operator: UnaryOp::Minus,
+                expr: Box::new(Expr::Text(b"'abc'".as_slice().into()))
+            }
+        );
+        assert_parser!(
+            parse_expr,
+            b"-abc",
+            4,
+            Expr::UnaryOperator {
+                operator: UnaryOp::Minus,
+                expr: Box::new(Expr::Column(b"abc".as_slice().into()))
+            }
+        );
+        assert_parser!(
+            parse_expr,
+            b"~-abc",
+            5,
+            Expr::UnaryOperator {
+                operator: UnaryOp::BitNot,
+                expr: Box::new(Expr::UnaryOperator {
+                    operator: UnaryOp::Minus,
+                    expr: Box::new(Expr::Column(b"abc".as_slice().into()))
+                })
+            }
+        );
+    }
+
+    #[test]
+    fn test_parse_expr_negative_literal() {
+        assert_parser!(
+            parse_expr,
+            b"balance = -5",
+            12,
+            Expr::BinaryOperator {
+                operator: BinaryOp::Compare(CompareOp::Eq),
+                left: Box::new(Expr::Column(b"balance".as_slice().into())),
+                right: Box::new(Expr::Integer(-5)),
+            }
+        );
+        assert_parser!(
+            parse_expr,
+            b"balance > -3 + 1",
+            16,
+            Expr::BinaryOperator {
+                operator: BinaryOp::Compare(CompareOp::Gt),
+                left: Box::new(Expr::Column(b"balance".as_slice().into())),
+                right: Box::new(Expr::BinaryOperator {
+                    operator: BinaryOp::Add,
+                    left: Box::new(Expr::Integer(-3)),
+                    right: Box::new(Expr::Integer(1)),
+                }),
+            }
+        );
+        // A minus after an operand is a binary subtraction.
+        assert_parser!(
+            parse_expr,
+            b"5 - 3",
+            5,
+            Expr::BinaryOperator {
+                operator: BinaryOp::Sub,
+                left: Box::new(Expr::Integer(5)),
+                right: Box::new(Expr::Integer(3)),
+            }
+        );
+        assert_parser!(
+            parse_expr,
+            b"5 -3",
+            4,
+            Expr::BinaryOperator {
+                operator: BinaryOp::Sub,
+                left: Box::new(Expr::Integer(5)),
+                right: Box::new(Expr::Integer(3)),
+            }
+        );
+        assert_parser!(
+            parse_expr,
+            b"5 - -3",
+            6,
+            Expr::BinaryOperator {
+                operator: BinaryOp::Sub,
+                left: Box::new(Expr::Integer(5)),
+                right: Box::new(Expr::Integer(-3)),
+            }
+        );
+        assert_parser!(
+            parse_expr,
+            b"5 + +3",
+            6,
+            Expr::BinaryOperator {
+                operator: BinaryOp::Add,
+                left: Box::new(Expr::Integer(5)),
+                right: Box::new(Expr::Integer(3)),
+            }
+        );
+    }
+
+    #[test]
+    fn test_parse_expr_collate() {
+        assert_parser!(
+            parse_expr,
+            b"abc COLLATE binary",
+            18,
+            Expr::Collate {
+                expr: Box::new(Expr::Column(b"abc".as_slice().into())),
+                collation_name: b"binary".as_slice().into()
+            }
+        );
+        assert_parser!(
+            parse_expr,
+            b"'abc' COLLATE \"nocase\"",
+            22,
+            Expr::Collate {
+                expr: Box::new(Expr::Text(b"'abc'".as_slice().into())),
+                collation_name: b"\"nocase\"".as_slice().into()
+            }
+        );
+        assert_parser!(
+            parse_expr,
+            b"-abc COLLATE rtrim",
+            18,
+            Expr::Collate {
+                expr: Box::new(Expr::UnaryOperator {
+                    operator: UnaryOp::Minus,
+                    expr: Box::new(Expr::Column(b"abc".as_slice().into()))
+                }),
+                collation_name: b"rtrim".as_slice().into()
+            }
+        );
+        assert_parser!(
+            parse_expr,
+            b"abc COLLATE binary COLLATE nocase COLLATE rtrim",
+            47,
+            Expr::Collate {
+                expr: Box::new(Expr::Column(b"abc".as_slice().into())),
+                collation_name: b"rtrim".as_slice().into()
+            }
+        );
+    }
+
+    #[test]
+    fn test_parse_expr_bind_parameter() {
+        assert_parser!(
+            parse_expr,
+            b"id = ?",
+            6,
+            Expr::BinaryOperator {
+                operator: BinaryOp::Compare(CompareOp::Eq),
+                left: Box::new(Expr::Column(b"id".as_slice().into())),
+                right: Box::new(Expr::BindParameter(BindParameter::Next)),
+            }
+        );
+        assert_parser!(
+            parse_expr,
+            b"?2 + ?32766",
+            11,
+            Expr::BinaryOperator {
+                operator: BinaryOp::Add,
+                left: Box::new(Expr::BindParameter(BindParameter::Index(2))),
+                right: Box::new(Expr::BindParameter(BindParameter::Index(32766))),
+            }
+        );
+        for name in [b":name".as_slice(), b"@name", b"$name"] {
+            let mut parser = Parser::new(name);
+            assert_eq!(
+                parse_expr(&mut parser).unwrap(),
+                Expr::BindParameter(BindParameter::Name(name))
+            );
+            assert_eq!(parser.n_consumed(), name.len());
+        }
+        assert_parser!(
+            parse_expr,
+            b"name IN (:a, ?)",
+            15,
+            Expr::In {
+                expr: Box::new(Expr::Column(b"name".as_slice().into())),
+                list: vec![
+                    Expr::BindParameter(BindParameter::Name(b":a")),
+                    Expr::BindParameter(BindParameter::Next),
+                ],
+                negated: false,
+            }
+        );
+        assert_parser!(
+            parse_expr,
+            b"id BETWEEN ?1 AND $b",
+            20,
+            Expr::Between {
+                expr: Box::new(Expr::Column(b"id".as_slice().into())),
+                low: Box::new(Expr::BindParameter(BindParameter::Index(1))),
+                high: Box::new(Expr::BindParameter(BindParameter::Name(b"$b"))),
+                negated: false,
+            }
         );
+
+        for input in [b"?0".as_slice(), b"?32767", b"?99999999999"] {
+            let r = parse_expr(&mut Parser::new(input));
+            assert!(r.is_err());
+            let e = r.unwrap_err();
+            assert_eq!(e.msg(), "bind parameter index out of range");
+            assert_eq!(e.cursor(), 0);
+        }
+        // An empty name is illegal.
+        assert!(parse_expr(&mut Parser::new(b":")).is_err());
     }
 
     #[test]
-    fn test_parse_create_table_constraints() {
-        let input = b"create table foo (col1 type type collate binary primary key collate nocase, col2 collate rtrim, col3 collate \"RTRIM\")";
+    fn test_select_stmt_parameters() {
+        let input = b"select ?, :x + ?5 from foo join bar on a = $y where (b = :x) = (c in (?, @z, ?5)) group by ?2 having ? order by :x limit ? offset :w";
         let mut parser = Parser::new(input);
-        let create_table = parse_create_table(&mut parser).unwrap();
+        let select_stmt = parse_select_stmt(&mut parser).unwrap();
         assert_eq!(parser.n_consumed(), input.len());
         assert_eq!(
-            create_table.columns[0].constraints,
+            select_stmt.parameters(),
             vec![
-                ColumnConstraint::Collate(b"binary".as_slice().into()),
-                ColumnConstraint::PrinaryKey,
-                ColumnConstraint::Collate(b"nocase".as_slice().into())
+                Parameter {
+                    index: 1,
+                    name: None
+                },
+                Parameter {
+                    index: 2,
+                    name: Some(b":x")
+                },
+                Parameter {
+                    index: 5,
+                    name: None
+                },
+                Parameter {
+                    index: 6,
+                    name: Some(b"$y")
+                },
+                Parameter {
+                    index: 7,
+                    name: None
+                },
+                Parameter {
+                    index: 8,
+                    name: Some(b"@z")
+                },
+                Parameter {
+                    index: 9,
+                    name: None
+                },
+                Parameter {
+                    index: 10,
+                    name: None
+                },
+                Parameter {
+                    index: 11,
+                    name: Some(b":w")
+                },
             ]
         );
-        assert_eq!(
-            create_table.columns[1].constraints,
-            vec![ColumnConstraint::Collate(b"rtrim".as_slice().into()),]
-        );
-        assert_eq!(
-            create_table.columns[2].constraints,
-            vec![ColumnConstraint::Collate(b"\"RTRIM\"".as_slice().into()),]
-        );
-    }
 
-    #[test]
-    fn test_parse_create_table_with_extra() {
-        let input = b"create table Foo (Id, Name)abc ";
+        let input = b"select * from foo union select ?3 from bar where :a = ?1";
         let mut parser = Parser::new(input);
-        let create_table = parse_create_table(&mut parser).unwrap();
-        assert_eq!(parser.n_consumed(), input.len() - 4);
-        assert_eq!(create_table.table_name, b"Foo".as_slice().into());
+        let select_stmt = parse_select_stmt(&mut parser).unwrap();
+        assert_eq!(parser.n_consumed(), input.len());
         assert_eq!(
-            create_table.columns,
+            select_stmt.parameters(),
             vec![
-                ColumnDef {
-                    name: b"Id".as_slice().into(),
-                    type_name: Vec::new(),
-                    constraints: vec![],
+                Parameter {
+                    index: 3,
+                    name: None
+                },
+                Parameter {
+                    index: 4,
+                    name: Some(b":a")
+                },
+                Parameter {
+                    index: 1,
+                    name: None
                 },
-                ColumnDef {
-                    name: b"Name".as_slice().into(),
-                    type_name: Vec::new(),
-                    constraints: vec![],
-                }
             ]
         );
-    }
-
-    #[test]
-    fn test_parse_create_table_fail() {
-        // no column def.
-        let r = parse_create_table(&mut Parser::new(b"create table foo ()"));
-        assert!(r.is_err());
-        assert_eq!(r.unwrap_err().cursor(), 18);
-        // no right paren.
-        let r = parse_create_table(&mut Parser::new(b"create table foo (id, name "));
-        assert!(r.is_err());
-        assert_eq!(r.unwrap_err().cursor(), 27);
-        // primary without key.
-        let r = parse_create_table(&mut Parser::new(b"create table foo (id primary, name)"));
-        assert!(r.is_err());
-        assert_eq!(r.unwrap_err().cursor(), 28);
-        // key without primary.
-        let r = parse_create_table(&mut Parser::new(b"create table foo (id key, name)"));
-        assert!(r.is_err());
-        assert_eq!(r.unwrap_err().cursor(), 21);
-    }
 
-    #[test]
-    fn test_parse_create_index() {
-        let input = b"create index foo on bar (col1, col2,col3)";
-        let mut parser = Parser::new(input);
-        let create_index = parse_create_index(&mut parser).unwrap();
-        assert_eq!(parser.n_consumed(), input.len());
-        assert_eq!(create_index.index_name, b"foo".as_slice().into());
-        assert_eq!(create_index.table_name, b"bar".as_slice().into());
+        // "limit <offset>, <limit>" binds the offset first.
+        let input = b"select ? from foo limit :offset, :limit";
+        let select_stmt = parse_select_stmt(&mut Parser::new(input)).unwrap();
         assert_eq!(
-            create_index.columns,
+            select_stmt.parameters(),
             vec![
-                IndexedColumn {
-                    name: b"col1".as_slice().into()
+                Parameter {
+                    index: 1,
+                    name: None
                 },
-                IndexedColumn {
-                    name: b"col2".as_slice().into()
+                Parameter {
+                    index: 2,
+                    name: Some(b":offset")
                 },
-                IndexedColumn {
-                    name: b"col3".as_slice().into()
+                Parameter {
+                    index: 3,
+                    name: Some(b":limit")
                 },
             ]
         );
-    }
 
-    #[test]
-    fn test_parse_create_index_with_extra() {
-        let input = b"create index fOo on bAR (Col1,cOL2)abc ";
-        let mut parser = Parser::new(input);
-        let create_index = parse_create_index(&mut parser).unwrap();
-        assert_eq!(parser.n_consumed(), input.len() - 4);
-        assert_eq!(create_index.index_name, b"fOo".as_slice().into());
-        assert_eq!(create_index.table_name, b"bAR".as_slice().into());
+        let input = b"select case ?2 when :a then ? else ?1 end from foo";
+        let select_stmt = parse_select_stmt(&mut Parser::new(input)).unwrap();
         assert_eq!(
-            create_index.columns,
+            select_stmt.parameters(),
             vec![
-                IndexedColumn {
-                    name: b"Col1".as_slice().into()
+                Parameter {
+                    index: 2,
+                    name: None
                 },
-                IndexedColumn {
-                    name: b"cOL2".as_slice().into()
+                Parameter {
+                    index: 3,
+                    name: Some(b":a")
+                },
+                Parameter {
+                    index: 4,
+                    name: None
+                },
+                Parameter {
+                    index: 1,
+                    name: None
                 },
             ]
         );
-    }
-
-    #[test]
-    fn test_parse_create_index_fail() {
-        // no right paren.
-        let r = parse_create_index(&mut Parser::new(b"create index foo on bar (id, name "));
-        assert!(r.is_err());
-        assert_eq!(r.unwrap_err().cursor(), 34);
-    }
 
-    #[test]
-    fn test_parse_select_all() {
         let input = b"select * from foo";
-        let mut parser = Parser::new(input);
-        let select = parse_select(&mut parser).unwrap();
-        assert_eq!(parser.n_consumed(), input.len());
-        assert_eq!(select.table_name, b"foo".as_slice().into());
-        assert_eq!(select.columns, vec![ResultColumn::All]);
+        let select_stmt = parse_select_stmt(&mut Parser::new(input)).unwrap();
+        assert!(select_stmt.parameters().is_empty());
     }
 
     #[test]
-    fn test_parse_select_columns() {
-        let input = b"select id,name,*,col as col2, col3 col4, 10, 'text' as col5, col = 11, col2 < col3 as col6 from foo";
-        let mut parser = Parser::new(input);
-        let select = parse_select(&mut parser).unwrap();
-        assert_eq!(parser.n_consumed(), input.len());
-        assert_eq!(select.table_name, b"foo".as_slice().into());
-        assert_eq!(
-            select.columns,
-            vec![
-                ResultColumn::Expr((Expr::Column(b"id".as_slice().into()), None)),
-                ResultColumn::Expr((Expr::Column(b"name".as_slice().into()), None)),
-                ResultColumn::All,
-                ResultColumn::Expr((
-                    Expr::Column(b"col".as_slice().into()),
-                    Some(b"col2".as_slice().into())
-                )),
-                ResultColumn::Expr((
-                    Expr::Column(b"col3".as_slice().into()),
-                    Some(b"col4".as_slice().into())
-                )),
-                ResultColumn::Expr((Expr::Integer(10), None)),
-                ResultColumn::Expr((
-                    Expr::Text(b"'text'".as_slice().into()),
-                    Some(b"col5".as_slice().into())
-                )),
-                ResultColumn::Expr((
-                    Expr::BinaryOperator {
-                        operator: BinaryOp::Compare(CompareOp::Eq),
-                        left: Box::new(Expr::Column(b"col".as_slice().into())),
-                        right: Box::new(Expr::Integer(11)),
-                    },
-                    None
-                )),
-                ResultColumn::Expr((
-                    Expr::BinaryOperator {
-                        operator: BinaryOp::Compare(CompareOp::Lt),
-                        left: Box::new(Expr::Column(b"col2".as_slice().into())),
-                        right: Box::new(Expr::Column(b"col3".as_slice().into())),
-                    },
-                    Some(b"col6".as_slice().into())
-                ))
-            ]
+    fn test_parse_expr_concat() {
+        assert_parser!(
+            parse_expr,
+            b"'abc' || 2",
+            10,
+            Expr::BinaryOperator {
+                operator: BinaryOp::Concat,
+                left: Box::new(Expr::Text(b"'abc'".as_slice().into())),
+                right: Box::new(Expr::Integer(2)),
+            }
         );
-    }
-
-    #[test]
-    fn test_parse_select_table_all() {
-        let input = b"select bar.* from foo";
-        let mut parser = Parser::new(input);
-        let select = parse_select(&mut parser).unwrap();
-        assert_eq!(parser.n_consumed(), input.len());
-        assert_eq!(select.table_name, b"foo".as_slice().into());
-        assert_eq!(
-            select.columns,
-            vec![ResultColumn::AllOfTable(b"bar".as_slice().into()),]
+        assert_parser!(
+            parse_expr,
+            b"-1 || 2 || -abc",
+            15,
+            Expr::BinaryOperator {
+                operator: BinaryOp::Concat,
+                left: Box::new(Expr::BinaryOperator {
+                    operator: BinaryOp::Concat,
+                    left: Box::new(Expr::Integer(-1)),
+                    right: Box::new(Expr::Integer(2)),
+                }),
+                right: Box::new(Expr::UnaryOperator {
+                    operator: UnaryOp::Minus,
+                    expr: Box::new(Expr::Column(b"abc".as_slice().into()))
+                }),
+            }
         );
-    }
-
-    #[test]
-    fn test_parse_select_where() {
-        let input = b"select * from foo where id = 5";
-        let mut parser = Parser::new(input);
-        let select = parse_select(&mut parser).unwrap();
-        assert_eq!(parser.n_consumed(), input.len());
-        assert_eq!(select.table_name, b"foo".as_slice().into());
-        assert_eq!(select.columns, vec![ResultColumn::All,]);
-        assert!(select.filter.is_some());
-        assert_eq!(
-            select.filter.unwrap(),
+        assert_parser!(
+            parse_expr,
+            b"first || ' ' || last",
+            20,
             Expr::BinaryOperator {
-                operator: BinaryOp::Compare(CompareOp::Eq),
-                left: Box::new(Expr::Column(b"id".as_slice().into())),
-                right: Box::new(Expr::Integer(5)),
+                operator: BinaryOp::Concat,
+                left: Box::new(Expr::BinaryOperator {
+                    operator: BinaryOp::Concat,
+                    left: Box::new(Expr::Column(b"first".as_slice().into())),
+                    right: Box::new(Expr::Text(b"' '".as_slice().into())),
+                }),
+                right: Box::new(Expr::Column(b"last".as_slice().into())),
+            }
+        );
+        assert_parser!(
+            parse_expr,
+            b"1 + 2 || 3 * 4",
+            14,
+            Expr::BinaryOperator {
+                operator: BinaryOp::Add,
+                left: Box::new(Expr::Integer(1)),
+                right: Box::new(Expr::BinaryOperator {
+                    operator: BinaryOp::Mul,
+                    left: Box::new(Expr::BinaryOperator {
+                        operator: BinaryOp::Concat,
+                        left: Box::new(Expr::Integer(2)),
+                        right: Box::new(Expr::Integer(3)),
+                    }),
+                    right: Box::new(Expr::Integer(4)),
+                }),
             }
         );
     }
 
     #[test]
-    fn test_parse_select_fail() {
-        // no expr after comma.
-        let r = parse_select(&mut Parser::new(b"select col, from foo"));
-        assert!(r.is_err());
-        assert_eq!(r.unwrap_err().cursor(), 12);
-        // no table name.
-        let r = parse_select(&mut Parser::new(b"select col from ;"));
-        assert!(r.is_err());
-        assert_eq!(r.unwrap_err().cursor(), 16);
-    }
-
-    #[test]
-    fn test_parse_expr_literal_value() {
-        // Parse null
-        assert_parser!(parse_expr, b"null", 4, Expr::Null);
-
-        // Parse integer
-        assert_parser!(parse_expr, b"123456789a", 9, Expr::Integer(123456789));
-        assert_parser!(parse_expr, b"00123456789a", 11, Expr::Integer(123456789));
-        assert_parser!(parse_expr, b"00000000000000000001a", 20, Expr::Integer(1));
+    fn test_parse_expr_arithmetic() {
         assert_parser!(
             parse_expr,
-            b"9223372036854775807",
-            19,
-            Expr::Integer(9223372036854775807)
+            b"1 + 2 * 3",
+            9,
+            Expr::BinaryOperator {
+                operator: BinaryOp::Add,
+                left: Box::new(Expr::Integer(1)),
+                right: Box::new(Expr::BinaryOperator {
+                    operator: BinaryOp::Mul,
+                    left: Box::new(Expr::Integer(2)),
+                    right: Box::new(Expr::Integer(3)),
+                }),
+            }
+        );
+        assert_parser!(
+            parse_expr,
+            b"1 * 2 - 3 / 4 % 5",
+            17,
+            Expr::BinaryOperator {
+                operator: BinaryOp::Sub,
+                left: Box::new(Expr::BinaryOperator {
+                    operator: BinaryOp::Mul,
+                    left: Box::new(Expr::Integer(1)),
+                    right: Box::new(Expr::Integer(2)),
+                }),
+                right: Box::new(Expr::BinaryOperator {
+                    operator: BinaryOp::Mod,
+                    left: Box::new(Expr::BinaryOperator {
+                        operator: BinaryOp::Div,
+                        left: Box::new(Expr::Integer(3)),
+                        right: Box::new(Expr::Integer(4)),
+                    }),
+                    right: Box::new(Expr::Integer(5)),
+                }),
+            }
         );
+        // Binary minus and unary minus.
         assert_parser!(
             parse_expr,
-            b"000000000000000000009223372036854775807",
-            39,
-            Expr::Integer(9223372036854775807)
+            b"1-5 - -5",
+            8,
+            Expr::BinaryOperator {
+                operator: BinaryOp::Sub,
+                left: Box::new(Expr::BinaryOperator {
+                    operator: BinaryOp::Sub,
+                    left: Box::new(Expr::Integer(1)),
+                    right: Box::new(Expr::Integer(5)),
+                }),
+                right: Box::new(Expr::Integer(-5)),
+            }
         );
-        // integer -> float fallback
         assert_parser!(
             parse_expr,
-            b"9223372036854775808",
-            19,
-            Expr::Real(9223372036854775808.0)
+            b"-a + +b",
+            7,
+            Expr::BinaryOperator {
+                operator: BinaryOp::Add,
+                left: Box::new(Expr::UnaryOperator {
+                    operator: UnaryOp::Minus,
+                    expr: Box::new(Expr::Column(b"a".as_slice().into())),
+                }),
+                right: Box::new(Expr::Column(b"b".as_slice().into())),
+            }
         );
         assert_parser!(
             parse_expr,
-            b"9999999999999999999",
-            19,
-            Expr::Real(9999999999999999999.0)
+            b"price * quantity > 100",
+            22,
+            Expr::BinaryOperator {
+                operator: BinaryOp::Compare(CompareOp::Gt),
+                left: Box::new(Expr::BinaryOperator {
+                    operator: BinaryOp::Mul,
+                    left: Box::new(Expr::Column(b"price".as_slice().into())),
+                    right: Box::new(Expr::Column(b"quantity".as_slice().into())),
+                }),
+                right: Box::new(Expr::Integer(100)),
+            }
         );
         assert_parser!(
             parse_expr,
-            b"99999999999999999999a",
-            20,
-            Expr::Real(99999999999999999999.0)
+            b"1 * 2 || 3",
+            10,
+            Expr::BinaryOperator {
+                operator: BinaryOp::Mul,
+                left: Box::new(Expr::Integer(1)),
+                right: Box::new(Expr::BinaryOperator {
+                    operator: BinaryOp::Concat,
+                    left: Box::new(Expr::Integer(2)),
+                    right: Box::new(Expr::Integer(3)),
+                }),
+            }
         );
 
-        // Parse float
-        assert_parser!(parse_expr, b".1", 2, Expr::Real(0.1));
-        assert_parser!(parse_expr, b"1.", 2, Expr::Real(1.0));
-        assert_parser!(parse_expr, b"1.01", 4, Expr::Real(1.01));
-        assert_parser!(parse_expr, b"1e1", 3, Expr::Real(10.0));
-        assert_parser!(parse_expr, b"1e-1", 4, Expr::Real(0.1));
+        // no right expression.
+        let r = parse_expr(&mut Parser::new(b"1 +"));
+        assert!(r.is_err());
+        assert_eq!(r.unwrap_err().cursor(), 3);
+    }
 
-        // Parse string
+    #[test]
+    fn test_parse_expr_compare() {
         assert_parser!(
             parse_expr,
-            b"'hello'",
+            b"-1 < 2",
+            6,
+            Expr::BinaryOperator {
+                operator: BinaryOp::Compare(CompareOp::Lt),
+                left: Box::new(Expr::Integer(-1)),
+                right: Box::new(Expr::Integer(2)),
+            }
+        );
+        assert_parser!(
+            parse_expr,
+            b"-1 <= 2",
             7,
-            Expr::Text(b"'hello'".as_slice().into())
+            Expr::BinaryOperator {
+                operator: BinaryOp::Compare(CompareOp::Le),
+                left: Box::new(Expr::Integer(-1)),
+                right: Box::new(Expr::Integer(2)),
+            }
         );
         assert_parser!(
             parse_expr,
-            b"'hel''lo'",
-            9,
-            Expr::Text(b"'hel''lo'".as_slice().into())
+            b"-1 > 2",
+            6,
+            Expr::BinaryOperator {
+                operator: BinaryOp::Compare(CompareOp::Gt),
+                left: Box::new(Expr::Integer(-1)),
+                right: Box::new(Expr::Integer(2)),
+            }
         );
-
-        // Parse blob
         assert_parser!(
             parse_expr,
-            b"x'0123456789abcdef'",
-            19,
-            Expr::Blob(b"0123456789abcdef".as_slice().into())
+            b"-1 >= 2",
+            7,
+            Expr::BinaryOperator {
+                operator: BinaryOp::Compare(CompareOp::Ge),
+                left: Box::new(Expr::Integer(-1)),
+                right: Box::new(Expr::Integer(2)),
+            }
         );
         assert_parser!(
             parse_expr,
-            b"X'0123456789abcdef'",
-            19,
-            Expr::Blob(b"0123456789abcdef".as_slice().into())
+            b"-1 >= 2 >= -abc",
+            15,
+            Expr::BinaryOperator {
+                operator: BinaryOp::Compare(CompareOp::Ge),
+                left: Box::new(Expr::BinaryOperator {
+                    operator: BinaryOp::Compare(CompareOp::Ge),
+                    left: Box::new(Expr::Integer(-1)),
+                    right: Box::new(Expr::Integer(2)),
+                }),
+                right: Box::new(Expr::UnaryOperator {
+                    operator: UnaryOp::Minus,
+                    expr: Box::new(Expr::Column(b"abc".as_slice().into()))
+                }),
+            }
         );
     }
 
     #[test]
-    fn test_parse_expr_column() {
+    fn test_parse_expr_eq() {
         assert_parser!(
             parse_expr,
-            b"foo",
-            3,
-            Expr::Column(b"foo".as_slice().into())
+            b"-1 = 2",
+            6,
+            Expr::BinaryOperator {
+                operator: BinaryOp::Compare(CompareOp::Eq),
+                left: Box::new(Expr::Integer(-1)),
+                right: Box::new(Expr::Integer(2)),
+            }
         );
         assert_parser!(
             parse_expr,
-            b"\"foo\"",
-            5,
-            Expr::Column(b"\"foo\"".as_slice().into())
+            b"-1 == 2",
+            7,
+            Expr::BinaryOperator {
+                operator: BinaryOp::Compare(CompareOp::Eq),
+                left: Box::new(Expr::Integer(-1)),
+                right: Box::new(Expr::Integer(2)),
+            }
         );
-    }
-
-    #[test]
-    fn test_parse_expr_cast() {
         assert_parser!(
             parse_expr,
-            b"cast(100as text)",
-            16,
-            Expr::Cast {
-                expr: Box::new(Expr::Integer(100)),
-                type_name: vec![b"text".as_slice().into()],
+            b"-1 != 2",
+            7,
+            Expr::BinaryOperator {
+                operator: BinaryOp::Compare(CompareOp::Ne),
+                left: Box::new(Expr::Integer(-1)),
+                right: Box::new(Expr::Integer(2)),
             }
         );
         assert_parser!(
             parse_expr,
-            b"cast ( '100' as integer)",
-            24,
-            Expr::Cast {
-                expr: Box::new(Expr::Text(b"'100'".as_slice().into())),
-                type_name: vec![b"integer".as_slice().into()],
+            b"-1 <> 2",
+            7,
+            Expr::BinaryOperator {
+                operator: BinaryOp::Compare(CompareOp::Ne),
+                left: Box::new(Expr::Integer(-1)),
+                right: Box::new(Expr::Integer(2)),
             }
         );
         assert_parser!(
             parse_expr,
-            b"cast (col = 100 as integer)",
-            27,
-            Expr::Cast {
-                expr: Box::new(Expr::BinaryOperator {
+            b"1 = 2 = 3",
+            9,
+            Expr::BinaryOperator {
+                operator: BinaryOp::Compare(CompareOp::Eq),
+                left: Box::new(Expr::BinaryOperator {
                     operator: BinaryOp::Compare(CompareOp::Eq),
-                    left: Box::new(Expr::Column(b"col".as_slice().into())),
-                    right: Box::new(Expr::Integer(100))
+                    left: Box::new(Expr::Integer(1)),
+                    right: Box::new(Expr::Integer(2)),
                 }),
-                type_name: vec![b"integer".as_slice().into()],
+                right: Box::new(Expr::Integer(3)),
             }
         );
-    }
-
-    #[test]
-    fn test_parse_expr_unary_operator() {
         assert_parser!(
             parse_expr,
-            b"+foo",
-            4,
-            Expr::Column(b"foo".as_slice().into())
+            b"t1.id = t2.id",
+            13,
+            Expr::BinaryOperator {
+                operator: BinaryOp::Compare(CompareOp::Eq),
+                left: Box::new(Expr::QualifiedColumn {
+                    table_name: b"t1".as_slice().into(),
+                    column_name: b"id".as_slice().into(),
+                }),
+                right: Box::new(Expr::QualifiedColumn {
+                    table_name: b"t2".as_slice().into(),
+                    column_name: b"id".as_slice().into(),
+                }),
+            }
         );
+        // Equality operators are left-associative as SQLite does.
         assert_parser!(
             parse_expr,
-            b"-foo",
-            4,
-            Expr::UnaryOperator {
-                operator: UnaryOp::Minus,
-                expr: Box::new(Expr::Column(b"foo".as_slice().into()))
+            b"a = b != c",
+            10,
+            Expr::BinaryOperator {
+                operator: BinaryOp::Compare(CompareOp::Ne),
+                left: Box::new(Expr::BinaryOperator {
+                    operator: BinaryOp::Compare(CompareOp::Eq),
+                    left: Box::new(Expr::Column(b"a".as_slice().into())),
+                    right: Box::new(Expr::Column(b"b".as_slice().into())),
+                }),
+                right: Box::new(Expr::Column(b"c".as_slice().into())),
             }
         );
+        // Comparison operators bind tighter than equality operators.
         assert_parser!(
             parse_expr,
-            b"~foo",
-            4,
+            b"a = b < c",
+            9,
+            Expr::BinaryOperator {
+                operator: BinaryOp::Compare(CompareOp::Eq),
+                left: Box::new(Expr::Column(b"a".as_slice().into())),
+                right: Box::new(Expr::BinaryOperator {
+                    operator: BinaryOp::Compare(CompareOp::Lt),
+                    left: Box::new(Expr::Column(b"b".as_slice().into())),
+                    right: Box::new(Expr::Column(b"c".as_slice().into())),
+                }),
+            }
+        );
+    }
+
+    #[test]
+    fn test_parse_expr_not() {
+        assert_parser!(
+            parse_expr,
+            b"not col",
+            7,
             Expr::UnaryOperator {
-                operator: UnaryOp::BitNot,
-                expr: Box::new(Expr::Column(b"foo".as_slice().into()))
+                operator: UnaryOp::Not,
+                expr: Box::new(Expr::Column(b"col".as_slice().into())),
             }
         );
         assert_parser!(
             parse_expr,
-            b"~~foo",
-            5,
+            b"NOT not (a = 1)",
+            15,
             Expr::UnaryOperator {
-                operator: UnaryOp::BitNot,
+                operator: UnaryOp::Not,
                 expr: Box::new(Expr::UnaryOperator {
-                    operator: UnaryOp::BitNot,
-                    expr: Box::new(Expr::Column(b"foo".as_slice().into()))
-                })
+                    operator: UnaryOp::Not,
+                    expr: Box::new(Expr::BinaryOperator {
+                        operator: BinaryOp::Compare(CompareOp::Eq),
+                        left: Box::new(Expr::Column(b"a".as_slice().into())),
+                        right: Box::new(Expr::Integer(1)),
+                    }),
+                }),
             }
         );
+        // NOT binds looser than comparisons.
         assert_parser!(
             parse_expr,
-            b"-+-+-foo",
-            8,
+            b"not a = 1",
+            9,
             Expr::UnaryOperator {
-                operator: UnaryOp::Minus,
-                expr: Box::new(Expr::UnaryOperator {
-                    operator: UnaryOp::Minus,
-                    expr: Box::new(Expr::UnaryOperator {
-                        operator: UnaryOp::Minus,
-                        expr: Box::new(Expr::Column(b"foo".as_slice().into()))
-                    })
-                })
+                operator: UnaryOp::Not,
+                expr: Box::new(Expr::BinaryOperator {
+                    operator: BinaryOp::Compare(CompareOp::Eq),
+                    left: Box::new(Expr::Column(b"a".as_slice().into())),
+                    right: Box::new(Expr::Integer(1)),
+                }),
             }
         );
-        assert_parser!(parse_expr, b"+123", 4, Expr::Integer(123));
-        assert_parser!(parse_expr, b"+ 123", 5, Expr::Integer(123));
-        assert_parser!(parse_expr, b"-123", 4, Expr::Integer(-123));
-        assert_parser!(parse_expr, b"- 123", 5, Expr::Integer(-123));
         assert_parser!(
             parse_expr,
-            b"-9223372036854775808",
-            20,
-            Expr::Integer(-9223372036854775808)
+            b"not a is not null",
+            17,
+            Expr::UnaryOperator {
+                operator: UnaryOp::Not,
+                expr: Box::new(Expr::BinaryOperator {
+                    operator: BinaryOp::IsNot,
+                    left: Box::new(Expr::Column(b"a".as_slice().into())),
+                    right: Box::new(Expr::Null),
+                }),
+            }
         );
         assert_parser!(
             parse_expr,
-            b"-+-123",
-            6,
+            b"not a not in (1)",
+            16,
             Expr::UnaryOperator {
-                operator: UnaryOp::Minus,
-                expr: Box::new(Expr::Integer(-123))
+                operator: UnaryOp::Not,
+                expr: Box::new(Expr::In {
+                    expr: Box::new(Expr::Column(b"a".as_slice().into())),
+                    list: vec![Expr::Integer(1)],
+                    negated: true,
+                }),
             }
         );
-        assert_parser!(parse_expr, b"+123.4", 6, Expr::Real(123.4));
-        assert_parser!(parse_expr, b"-123.4", 6, Expr::Real(-123.4));
         assert_parser!(
             parse_expr,
-            b"-+-123.4",
-            8,
+            b"not a not like 'b'",
+            18,
             Expr::UnaryOperator {
-                operator: UnaryOp::Minus,
-                expr: Box::new(Expr::Real(-123.4))
+                operator: UnaryOp::Not,
+                expr: Box::new(Expr::Like {
+                    left: Box::new(Expr::Column(b"a".as_slice().into())),
+                    pattern: Box::new(Expr::Text(b"'b'".as_slice().into())),
+                    escape: None,
+                    negated: true,
+                }),
             }
         );
+
+        let mut parser = Parser::new(b"not");
+        let r = parse_expr(&mut parser);
+        assert!(r.is_err());
+        assert_eq!(r.unwrap_err().cursor(), 3);
+
+        let mut parser = Parser::new(b"not (a = 1");
+        let r = parse_expr(&mut parser);
+        assert!(r.is_err());
+        assert_eq!(r.unwrap_err().cursor(), 10);
+    }
+
+    #[test]
+    fn test_parse_expr_is() {
         assert_parser!(
             parse_expr,
-            b"+'abc'",
-            6,
-            Expr::Text(b"'abc'".as_slice().into())
+            b"col is null",
+            11,
+            Expr::BinaryOperator {
+                operator: BinaryOp::Is,
+                left: Box::new(Expr::Column(b"col".as_slice().into())),
+                right: Box::new(Expr::Null),
+            }
         );
         assert_parser!(
             parse_expr,
-            b"-'abc'",
-            6,
-            Expr::UnaryOperator {
-                operator: UnaryOp::Minus,
-                expr: Box::new(Expr::Text(b"'abc'".as_slice().into()))
+            b"col IS NOT NULL",
+            15,
+            Expr::BinaryOperator {
+                operator: BinaryOp::IsNot,
+                left: Box::new(Expr::Column(b"col".as_slice().into())),
+                right: Box::new(Expr::Null),
             }
         );
         assert_parser!(
             parse_expr,
-            b"-abc",
-            4,
-            Expr::UnaryOperator {
-                operator: UnaryOp::Minus,
-                expr: Box::new(Expr::Column(b"abc".as_slice().into()))
+            b"1 < 2 is not 3 || 4",
+            19,
+            Expr::BinaryOperator {
+                operator: BinaryOp::IsNot,
+                left: Box::new(Expr::BinaryOperator {
+                    operator: BinaryOp::Compare(CompareOp::Lt),
+                    left: Box::new(Expr::Integer(1)),
+                    right: Box::new(Expr::Integer(2)),
+                }),
+                right: Box::new(Expr::BinaryOperator {
+                    operator: BinaryOp::Concat,
+                    left: Box::new(Expr::Integer(3)),
+                    right: Box::new(Expr::Integer(4)),
+                }),
             }
         );
         assert_parser!(
             parse_expr,
-            b"~-abc",
-            5,
-            Expr::UnaryOperator {
-                operator: UnaryOp::BitNot,
-                expr: Box::new(Expr::UnaryOperator {
-                    operator: UnaryOp::Minus,
-                    expr: Box::new(Expr::Column(b"abc".as_slice().into()))
-                })
+            b"1 is 2 = 3",
+            10,
+            Expr::BinaryOperator {
+                operator: BinaryOp::Compare(CompareOp::Eq),
+                left: Box::new(Expr::BinaryOperator {
+                    operator: BinaryOp::Is,
+                    left: Box::new(Expr::Integer(1)),
+                    right: Box::new(Expr::Integer(2)),
+                }),
+                right: Box::new(Expr::Integer(3)),
             }
         );
+
+        // no right expression.
+        let r = parse_expr(&mut Parser::new(b"col is not"));
+        assert!(r.is_err());
+        assert_eq!(r.unwrap_err().cursor(), 10);
     }
 
     #[test]
-    fn test_parse_expr_collate() {
+    fn test_parse_expr_like() {
         assert_parser!(
             parse_expr,
-            b"abc COLLATE binary",
-            18,
-            Expr::Collate {
-                expr: Box::new(Expr::Column(b"abc".as_slice().into())),
-                collation_name: b"binary".as_slice().into()
+            b"name like 'a%'",
+            14,
+            Expr::Like {
+                left: Box::new(Expr::Column(b"name".as_slice().into())),
+                pattern: Box::new(Expr::Text(b"'a%'".as_slice().into())),
+                escape: None,
+                negated: false,
             }
         );
         assert_parser!(
             parse_expr,
-            b"'abc' COLLATE \"nocase\"",
-            22,
-            Expr::Collate {
-                expr: Box::new(Expr::Text(b"'abc'".as_slice().into())),
-                collation_name: b"\"nocase\"".as_slice().into()
+            b"name NOT LIKE 'a\\%%' ESCAPE '\\'",
+            31,
+            Expr::Like {
+                left: Box::new(Expr::Column(b"name".as_slice().into())),
+                pattern: Box::new(Expr::Text(b"'a\\%%'".as_slice().into())),
+                escape: Some(Box::new(Expr::Text(b"'\\'".as_slice().into()))),
+                negated: true,
             }
         );
         assert_parser!(
             parse_expr,
-            b"-abc COLLATE rtrim",
-            18,
-            Expr::Collate {
-                expr: Box::new(Expr::UnaryOperator {
-                    operator: UnaryOp::Minus,
-                    expr: Box::new(Expr::Column(b"abc".as_slice().into()))
+            b"a || b like c || d = 1",
+            22,
+            Expr::BinaryOperator {
+                operator: BinaryOp::Compare(CompareOp::Eq),
+                left: Box::new(Expr::Like {
+                    left: Box::new(Expr::BinaryOperator {
+                        operator: BinaryOp::Concat,
+                        left: Box::new(Expr::Column(b"a".as_slice().into())),
+                        right: Box::new(Expr::Column(b"b".as_slice().into())),
+                    }),
+                    pattern: Box::new(Expr::BinaryOperator {
+                        operator: BinaryOp::Concat,
+                        left: Box::new(Expr::Column(b"c".as_slice().into())),
+                        right: Box::new(Expr::Column(b"d".as_slice().into())),
+                    }),
+                    escape: None,
+                    negated: false,
                 }),
-                collation_name: b"rtrim".as_slice().into()
+                right: Box::new(Expr::Integer(1)),
             }
         );
+
+        // LIKE is a name where an expression is expected.
         assert_parser!(
             parse_expr,
-            b"abc COLLATE binary COLLATE nocase COLLATE rtrim",
-            47,
-            Expr::Collate {
-                expr: Box::new(Expr::Column(b"abc".as_slice().into())),
-                collation_name: b"rtrim".as_slice().into()
+            b"like(like, 'a%') LIKE like",
+            26,
+            Expr::Like {
+                left: Box::new(Expr::Function {
+                    name: b"like".as_slice().into(),
+                    args: vec![
+                        Expr::Column(b"like".as_slice().into()),
+                        Expr::Text(b"'a%'".as_slice().into())
+                    ],
+                    distinct: false,
+                }),
+                pattern: Box::new(Expr::Column(b"like".as_slice().into())),
+                escape: None,
+                negated: false,
             }
         );
+
+        // no like or in after not.
+        let r = parse_expr(&mut Parser::new(b"name not 'a%'"));
+        assert!(r.is_err());
+        assert_eq!(r.unwrap_err().cursor(), 9);
+        // no escape expression.
+        let r = parse_expr(&mut Parser::new(b"name like 'a%' escape"));
+        assert!(r.is_err());
+        assert_eq!(r.unwrap_err().cursor(), 21);
     }
 
     #[test]
-    fn test_parse_expr_concat() {
+    fn test_parse_fallback_keywords() {
+        for keyword in [
+            "like", "LIKE", "by", "asc", "desc", "offset", "if", "glob", "regexp", "inner", "left",
+            "outer", "column", "rename", "explain", "end",
+        ] {
+            let name = MaybeQuotedBytes::from(keyword.as_bytes());
+
+            // Like SQLite, "create table if" starts "if not exists".
+            let table_name = if keyword == "if" {
+                format!("\"{keyword}\"")
+            } else {
+                keyword.to_string()
+            };
+            let input =
+                format!("create table {table_name} ({keyword} {keyword}, col default {keyword})");
+            let mut parser = Parser::new(input.as_bytes());
+            let create_table = parse_create_table(&mut parser).unwrap();
+            assert_eq!(parser.n_consumed(), input.len(), "{keyword}");
+            assert_eq!(
+                create_table.table_name,
+                table_name.as_bytes().into(),
+                "{keyword}"
+            );
+            assert_eq!(
+                create_table.columns,
+                vec![
+                    ColumnDef {
+                        name,
+                        type_name: vec![name],
+                        constraints: vec![],
+                    },
+                    ColumnDef {
+                        name: b"col".as_slice().into(),
+                        type_name: vec![],
+                        constraints: vec![ColumnConstraint::Default(Expr::Text(name))],
+                    },
+                ],
+                "{keyword}"
+            );
+
+            let input = format!("create index {keyword} on {keyword} ({keyword})");
+            let mut parser = Parser::new(input.as_bytes());
+            let create_index = parse_create_index(&mut parser).unwrap();
+            assert_eq!(parser.n_consumed(), input.len(), "{keyword}");
+            assert_eq!(create_index.index_name, name, "{keyword}");
+            assert_eq!(create_index.table_name, name, "{keyword}");
+            assert_eq!(
+                create_index.columns,
+                vec![IndexedColumn {
+                    name,
+                    order: SortOrder::Asc,
+                }],
+                "{keyword}"
+            );
+
+            let input = format!(
+                "select {keyword}.*, {keyword}.{keyword}, {keyword}({keyword}) as {keyword} from {keyword}"
+            );
+            let mut parser = Parser::new(input.as_bytes());
+            let select = parse_select(&mut parser).unwrap();
+            assert_eq!(parser.n_consumed(), input.len(), "{keyword}");
+            assert_eq!(select.table.table_name, name, "{keyword}");
+            assert_eq!(
+                select.columns,
+                vec![
+                    ResultColumn::AllOfTable(name),
+                    ResultColumn::Expr((
+                        Expr::QualifiedColumn {
+                            table_name: name,
+                            column_name: name,
+                        },
+                        None
+                    )),
+                    ResultColumn::Expr((
+                        Expr::Function {
+                            name,
+                            args: vec![Expr::Column(name)],
+                            distinct: false,
+                        },
+                        Some(name)
+                    )),
+                ],
+                "{keyword}"
+            );
+        }
+    }
+
+    #[test]
+    fn test_parse_expr_glob_regexp() {
         assert_parser!(
             parse_expr,
-            b"'abc' || 2",
-            10,
-            Expr::BinaryOperator {
-                operator: BinaryOp::Concat,
-                left: Box::new(Expr::Text(b"'abc'".as_slice().into())),
-                right: Box::new(Expr::Integer(2)),
+            b"name glob 'a*'",
+            14,
+            Expr::Glob {
+                left: Box::new(Expr::Column(b"name".as_slice().into())),
+                pattern: Box::new(Expr::Text(b"'a*'".as_slice().into())),
+                negated: false,
             }
         );
         assert_parser!(
             parse_expr,
-            b"-1 || 2 || -abc",
-            15,
+            b"name NOT GLOB 'a?' = 0",
+            22,
             Expr::BinaryOperator {
-                operator: BinaryOp::Concat,
-                left: Box::new(Expr::BinaryOperator {
-                    operator: BinaryOp::Concat,
-                    left: Box::new(Expr::Integer(-1)),
-                    right: Box::new(Expr::Integer(2)),
+                operator: BinaryOp::Compare(CompareOp::Eq),
+                left: Box::new(Expr::Glob {
+                    left: Box::new(Expr::Column(b"name".as_slice().into())),
+                    pattern: Box::new(Expr::Text(b"'a?'".as_slice().into())),
+                    negated: true,
                 }),
-                right: Box::new(Expr::UnaryOperator {
-                    operator: UnaryOp::Minus,
-                    expr: Box::new(Expr::Column(b"abc".as_slice().into()))
+                right: Box::new(Expr::Integer(0)),
+            }
+        );
+        assert_parser!(
+            parse_expr,
+            b"name regexp '^a' || 'b'",
+            23,
+            Expr::Regexp {
+                left: Box::new(Expr::Column(b"name".as_slice().into())),
+                pattern: Box::new(Expr::BinaryOperator {
+                    operator: BinaryOp::Concat,
+                    left: Box::new(Expr::Text(b"'^a'".as_slice().into())),
+                    right: Box::new(Expr::Text(b"'b'".as_slice().into())),
                 }),
+                negated: false,
             }
         );
-    }
-
-    #[test]
-    fn test_parse_expr_compare() {
         assert_parser!(
             parse_expr,
-            b"-1 < 2",
-            6,
-            Expr::BinaryOperator {
-                operator: BinaryOp::Compare(CompareOp::Lt),
-                left: Box::new(Expr::Integer(-1)),
-                right: Box::new(Expr::Integer(2)),
+            b"name not regexp 'a'",
+            19,
+            Expr::Regexp {
+                left: Box::new(Expr::Column(b"name".as_slice().into())),
+                pattern: Box::new(Expr::Text(b"'a'".as_slice().into())),
+                negated: true,
             }
         );
+
+        // GLOB and REGEXP are names where an expression is expected.
         assert_parser!(
             parse_expr,
-            b"-1 <= 2",
-            7,
-            Expr::BinaryOperator {
-                operator: BinaryOp::Compare(CompareOp::Le),
-                left: Box::new(Expr::Integer(-1)),
-                right: Box::new(Expr::Integer(2)),
+            b"glob(glob, 'a*') GLOB regexp",
+            28,
+            Expr::Glob {
+                left: Box::new(Expr::Function {
+                    name: b"glob".as_slice().into(),
+                    args: vec![
+                        Expr::Column(b"glob".as_slice().into()),
+                        Expr::Text(b"'a*'".as_slice().into())
+                    ],
+                    distinct: false,
+                }),
+                pattern: Box::new(Expr::Column(b"regexp".as_slice().into())),
+                negated: false,
             }
         );
+
+        // no pattern.
+        let r = parse_expr(&mut Parser::new(b"name glob"));
+        assert!(r.is_err());
+        assert_eq!(r.unwrap_err().cursor(), 9);
+        let r = parse_expr(&mut Parser::new(b"name not regexp"));
+        assert!(r.is_err());
+        assert_eq!(r.unwrap_err().cursor(), 15);
+    }
+
+    #[test]
+    fn test_parse_expr_in() {
         assert_parser!(
             parse_expr,
-            b"-1 > 2",
-            6,
-            Expr::BinaryOperator {
-                operator: BinaryOp::Compare(CompareOp::Gt),
-                left: Box::new(Expr::Integer(-1)),
-                right: Box::new(Expr::Integer(2)),
+            b"id in (1, 2,3)",
+            14,
+            Expr::In {
+                expr: Box::new(Expr::Column(b"id".as_slice().into())),
+                list: vec![Expr::Integer(1), Expr::Integer(2), Expr::Integer(3)],
+                negated: false,
             }
         );
         assert_parser!(
             parse_expr,
-            b"-1 >= 2",
-            7,
-            Expr::BinaryOperator {
-                operator: BinaryOp::Compare(CompareOp::Ge),
-                left: Box::new(Expr::Integer(-1)),
-                right: Box::new(Expr::Integer(2)),
+            b"id NOT IN ('a' || 'b', id = 1)",
+            30,
+            Expr::In {
+                expr: Box::new(Expr::Column(b"id".as_slice().into())),
+                list: vec![
+                    Expr::BinaryOperator {
+                        operator: BinaryOp::Concat,
+                        left: Box::new(Expr::Text(b"'a'".as_slice().into())),
+                        right: Box::new(Expr::Text(b"'b'".as_slice().into())),
+                    },
+                    Expr::BinaryOperator {
+                        operator: BinaryOp::Compare(CompareOp::Eq),
+                        left: Box::new(Expr::Column(b"id".as_slice().into())),
+                        right: Box::new(Expr::Integer(1)),
+                    }
+                ],
+                negated: true,
             }
         );
         assert_parser!(
             parse_expr,
-            b"-1 >= 2 >= -abc",
-            15,
+            b"1 in (2) = 0",
+            12,
             Expr::BinaryOperator {
-                operator: BinaryOp::Compare(CompareOp::Ge),
-                left: Box::new(Expr::BinaryOperator {
-                    operator: BinaryOp::Compare(CompareOp::Ge),
-                    left: Box::new(Expr::Integer(-1)),
-                    right: Box::new(Expr::Integer(2)),
-                }),
-                right: Box::new(Expr::UnaryOperator {
-                    operator: UnaryOp::Minus,
-                    expr: Box::new(Expr::Column(b"abc".as_slice().into()))
+                operator: BinaryOp::Compare(CompareOp::Eq),
+                left: Box::new(Expr::In {
+                    expr: Box::new(Expr::Integer(1)),
+                    list: vec![Expr::Integer(2)],
+                    negated: false,
                 }),
+                right: Box::new(Expr::Integer(0)),
             }
         );
+
+        // empty list.
+        let r = parse_expr(&mut Parser::new(b"id in ()"));
+        assert!(r.is_err());
+        assert_eq!(r.unwrap_err().cursor(), 7);
+        // no left paren.
+        let r = parse_expr(&mut Parser::new(b"id in 1"));
+        assert!(r.is_err());
+        assert_eq!(r.unwrap_err().cursor(), 6);
+        // no right paren.
+        let r = parse_expr(&mut Parser::new(b"id not in (1, 2"));
+        assert!(r.is_err());
+        assert_eq!(r.unwrap_err().cursor(), 15);
     }
 
     #[test]
-    fn test_parse_expr_eq() {
+    fn test_parse_expr_and_or() {
         assert_parser!(
             parse_expr,
-            b"-1 = 2",
-            6,
+            b"not a = 1 and b = 2",
+            19,
             Expr::BinaryOperator {
-                operator: BinaryOp::Compare(CompareOp::Eq),
-                left: Box::new(Expr::Integer(-1)),
-                right: Box::new(Expr::Integer(2)),
+                operator: BinaryOp::And,
+                left: Box::new(Expr::UnaryOperator {
+                    operator: UnaryOp::Not,
+                    expr: Box::new(Expr::BinaryOperator {
+                        operator: BinaryOp::Compare(CompareOp::Eq),
+                        left: Box::new(Expr::Column(b"a".as_slice().into())),
+                        right: Box::new(Expr::Integer(1)),
+                    }),
+                }),
+                right: Box::new(Expr::BinaryOperator {
+                    operator: BinaryOp::Compare(CompareOp::Eq),
+                    left: Box::new(Expr::Column(b"b".as_slice().into())),
+                    right: Box::new(Expr::Integer(2)),
+                }),
             }
         );
+        // AND binds tighter than OR.
         assert_parser!(
             parse_expr,
-            b"-1 == 2",
-            7,
+            b"a OR b AND c OR d",
+            17,
             Expr::BinaryOperator {
-                operator: BinaryOp::Compare(CompareOp::Eq),
-                left: Box::new(Expr::Integer(-1)),
-                right: Box::new(Expr::Integer(2)),
+                operator: BinaryOp::Or,
+                left: Box::new(Expr::BinaryOperator {
+                    operator: BinaryOp::Or,
+                    left: Box::new(Expr::Column(b"a".as_slice().into())),
+                    right: Box::new(Expr::BinaryOperator {
+                        operator: BinaryOp::And,
+                        left: Box::new(Expr::Column(b"b".as_slice().into())),
+                        right: Box::new(Expr::Column(b"c".as_slice().into())),
+                    }),
+                }),
+                right: Box::new(Expr::Column(b"d".as_slice().into())),
             }
         );
+        // The AND of BETWEEN is not a logical operator.
         assert_parser!(
             parse_expr,
-            b"-1 != 2",
-            7,
+            b"a between 1 and 2 and b",
+            23,
             Expr::BinaryOperator {
-                operator: BinaryOp::Compare(CompareOp::Ne),
-                left: Box::new(Expr::Integer(-1)),
-                right: Box::new(Expr::Integer(2)),
+                operator: BinaryOp::And,
+                left: Box::new(Expr::Between {
+                    expr: Box::new(Expr::Column(b"a".as_slice().into())),
+                    low: Box::new(Expr::Integer(1)),
+                    high: Box::new(Expr::Integer(2)),
+                    negated: false,
+                }),
+                right: Box::new(Expr::Column(b"b".as_slice().into())),
             }
         );
+
+        // no right operand.
+        let r = parse_expr(&mut Parser::new(b"a and"));
+        assert!(r.is_err());
+        assert_eq!(r.unwrap_err().cursor(), 5);
+        let r = parse_expr(&mut Parser::new(b"a or or b"));
+        assert!(r.is_err());
+        assert_eq!(r.unwrap_err().cursor(), 5);
+    }
+
+    #[test]
+    fn test_parse_expr_between() {
         assert_parser!(
             parse_expr,
-            b"-1 <> 2",
-            7,
-            Expr::BinaryOperator {
-                operator: BinaryOp::Compare(CompareOp::Ne),
-                left: Box::new(Expr::Integer(-1)),
-                right: Box::new(Expr::Integer(2)),
+            b"age between 18 and 65",
+            21,
+            Expr::Between {
+                expr: Box::new(Expr::Column(b"age".as_slice().into())),
+                low: Box::new(Expr::Integer(18)),
+                high: Box::new(Expr::Integer(65)),
+                negated: false,
             }
         );
         assert_parser!(
             parse_expr,
-            b"1 = 2 = 3",
-            9,
-            Expr::BinaryOperator {
-                operator: BinaryOp::Compare(CompareOp::Eq),
-                left: Box::new(Expr::BinaryOperator {
-                    operator: BinaryOp::Compare(CompareOp::Eq),
+            b"age NOT BETWEEN 1 + 2 AND age < 3",
+            33,
+            Expr::Between {
+                expr: Box::new(Expr::Column(b"age".as_slice().into())),
+                low: Box::new(Expr::BinaryOperator {
+                    operator: BinaryOp::Add,
                     left: Box::new(Expr::Integer(1)),
                     right: Box::new(Expr::Integer(2)),
                 }),
-                right: Box::new(Expr::Integer(3)),
+                high: Box::new(Expr::BinaryOperator {
+                    operator: BinaryOp::Compare(CompareOp::Lt),
+                    left: Box::new(Expr::Column(b"age".as_slice().into())),
+                    right: Box::new(Expr::Integer(3)),
+                }),
+                negated: true,
+            }
+        );
+        assert_parser!(
+            parse_expr,
+            b"1 between 0 and 2 = 1",
+            21,
+            Expr::BinaryOperator {
+                operator: BinaryOp::Compare(CompareOp::Eq),
+                left: Box::new(Expr::Between {
+                    expr: Box::new(Expr::Integer(1)),
+                    low: Box::new(Expr::Integer(0)),
+                    high: Box::new(Expr::Integer(2)),
+                    negated: false,
+                }),
+                right: Box::new(Expr::Integer(1)),
             }
         );
+
+        // no and.
+        let r = parse_expr(&mut Parser::new(b"age between 18"));
+        assert_eq!(r.unwrap_err().msg(), "no and after between");
+        let r = parse_expr(&mut Parser::new(b"age not between 18 or 65"));
+        assert_eq!(r.unwrap_err().cursor(), 19);
+        // no high.
+        let r = parse_expr(&mut Parser::new(b"age between 18 and"));
+        assert_eq!(r.unwrap_err().cursor(), 18);
     }
 
     #[test]
@@ -13,6 +13,7 @@
 // limitations under the License.
 
 use std::cmp::Ordering;
+use std::io::Write;
 
 use anyhow::bail;
 use anyhow::Context;
@@ -25,7 +26,9 @@ use crate::value::ValueCmp;
 
 pub fn compare_record(keys: &[ValueCmp<'_>], payload: &BtreePayload) -> anyhow::Result<Ordering> {
     let mut record = Record::parse(payload)?;
-    if record.len() < keys.len() {
+    // Parse only the header of the columns to compare.
+    record.parse_header(keys.len())?;
+    if record.header.len() < keys.len() {
         bail!("keys is more than index columns");
     }
     for (i, key) in keys.iter().enumerate() {
@@ -40,26 +43,56 @@ pub fn compare_record(keys: &[ValueCmp<'_>], payload: &BtreePayload) -> anyhow::
 
 pub struct SerialType(u32);
 
+/// The type of the content described by a [SerialType].
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub enum SerialTypeKind {
+    Null,
+    /// Big-endian integer with the number of bytes.
+    Integer(u8),
+    Real,
+    /// Integer 0 without content.
+    Zero,
+    /// Integer 1 without content.
+    One,
+    /// Blob with the number of bytes.
+    Blob(u32),
+    /// Text with the number of bytes.
+    Text(u32),
+}
+
 impl SerialType {
-    pub fn content_size(&self) -> i32 {
-        // TODO: use pre-calculated table for first 128 serial types.
+    pub fn kind(&self) -> SerialTypeKind {
         match self.0 {
-            n if n <= 4 => n as i32,
-            5 => 6,
-            6 | 7 => 8,
-            8 | 9 => 0,
+            0 => SerialTypeKind::Null,
+            n if n <= 4 => SerialTypeKind::Integer(n as u8),
+            5 => SerialTypeKind::Integer(6),
+            6 => SerialTypeKind::Integer(8),
+            7 => SerialTypeKind::Real,
+            8 => SerialTypeKind::Zero,
+            9 => SerialTypeKind::One,
             10 | 11 => {
                 unimplemented!("reserved record is not implemented");
             }
-            n => ((n - 12) >> 1) as i32,
+            n if n & 1 == 0 => SerialTypeKind::Blob((n - 12) >> 1),
+            n => SerialTypeKind::Text((n - 13) >> 1),
+        }
+    }
+
+    pub fn content_size(&self) -> i32 {
+        // TODO: use pre-calculated table for first 128 serial types.
+        match self.kind() {
+            SerialTypeKind::Null | SerialTypeKind::Zero | SerialTypeKind::One => 0,
+            SerialTypeKind::Integer(n) => n as i32,
+            SerialTypeKind::Real => 8,
+            SerialTypeKind::Blob(n) | SerialTypeKind::Text(n) => n as i32,
         }
     }
 
     pub fn parse<'a>(&self, buf: &'a [u8]) -> anyhow::Result<Value<'a>> {
         let v = match self.0 {
             0 => Value::Null,
-            1 => Value::Integer(i8::from_be_bytes(buf[..1].try_into()?) as i64),
-            2 => Value::Integer(i16::from_be_bytes(buf[..2].try_into()?) as i64),
+            1 => Value::Integer(i8::from_be_bytes(fixed_bytes(buf)?) as i64),
+            2 => Value::Integer(i16::from_be_bytes(fixed_bytes(buf)?) as i64),
             // TODO: use std::mem::transmute.
             3 => {
                 if buf.len() < 3 {
@@ -69,7 +102,7 @@ impl SerialType {
                     ((buf[0] as i64) << 56 | (buf[1] as i64) << 48 | (buf[2] as i64) << 40) >> 40,
                 )
             }
-            4 => Value::Integer(i32::from_be_bytes(buf[..4].try_into()?) as i64),
+            4 => Value::Integer(i32::from_be_bytes(fixed_bytes(buf)?) as i64),
             // TODO: use std::mem::transmute.
             5 => {
                 if buf.len() < 6 {
@@ -85,9 +118,9 @@ impl SerialType {
                         >> 16,
                 )
             }
-            6 => Value::Integer(i64::from_be_bytes(buf[..8].try_into()?)),
+            6 => Value::Integer(i64::from_be_bytes(fixed_bytes(buf)?)),
             7 => {
-                let f = f64::from_be_bytes(buf[..8].try_into()?);
+                let f = f64::from_be_bytes(fixed_bytes(buf)?);
                 if f.is_nan() {
                     Value::Null
                 } else {
@@ -120,33 +153,62 @@ impl SerialType {
     }
 }
 
+/// Returns the first N bytes of the buffer.
+fn fixed_bytes<const N: usize>(buf: &[u8]) -> anyhow::Result<[u8; N]> {
+    match buf.get(..N) {
+        Some(bytes) => Ok(bytes.try_into().unwrap()),
+        None => bail!("buffer size {} does not match integer {}", buf.len(), N),
+    }
+}
+
+/// A record of a btree payload.
+///
+/// The record header is parsed lazily. Accessing a column parses the serial
+/// types only up to the column, so reading the first few columns of a wide
+/// record does not walk the whole header.
 pub struct Record<'payload> {
     payload: &'payload BtreePayload<'payload, 'payload>,
+    header_parser: RecordHeaderParser,
     header: Vec<(SerialType, i32)>,
     tmp_buf: Vec<u8>,
 }
 
 impl<'payload> Record<'payload> {
     pub fn parse(payload: &'payload BtreePayload<'payload, 'payload>) -> anyhow::Result<Self> {
-        let header = parse_record_header(payload)?;
+        let header_parser = RecordHeaderParser::new(payload)?;
         Ok(Self {
             payload,
-            header,
+            header_parser,
+            header: Vec::new(),
             tmp_buf: Vec::new(),
         })
     }
 
-    pub fn len(&self) -> usize {
-        self.header.len()
+    /// The number of columns in the record.
+    ///
+    /// This parses the whole record header.
+    pub fn len(&mut self) -> anyhow::Result<usize> {
+        self.parse_header(usize::MAX)?;
+        Ok(self.header.len())
+    }
+
+    /// Returns the types of the columns without parsing their contents.
+    pub fn serial_types(&mut self) -> anyhow::Result<impl Iterator<Item = SerialTypeKind> + '_> {
+        self.parse_header(usize::MAX)?;
+        Ok(self
+            .header
+            .iter()
+            .map(|(serial_type, _)| serial_type.kind()))
     }
 
     pub fn get(&mut self, i: usize) -> anyhow::Result<Value<'_>> {
-        let Some((serial_type, offset)) = &self.header.get(i) else {
-            bail!("index out of range");
-        };
-        let offset = *offset;
+        let (serial_type, offset) = self.column(i)?;
         let content_size = serial_type.content_size() as usize;
-        let buf = if offset as usize + content_size > self.payload.buf().len() {
+        let buf = if content_size == 0 {
+            // The offset of a zero-sized content may be the end of the payload
+            // which is not loadable.
+            &[]
+        } else if offset as usize + content_size > self.payload.buf().len() {
             self.tmp_buf.resize(content_size, 0);
             // SAFETY: tmp_buf is not from MemPage.
             let n = unsafe { self.payload.load(offset, &mut self.tmp_buf)? };
@@ -159,47 +221,258 @@ impl<'payload> Record<'payload> {
         };
         serial_type.parse(buf)
     }
+
+    /// Returns the value of the column which owns its buffer.
+    ///
+    /// [Record::get()] borrows the text or blob from the local payload without
+    /// copying, but the content in overflow pages is copied into the buffer of
+    /// the record first. This loads the content into the returned value
+    /// directly instead, and does not borrow the record mutably.
+    pub fn get_owned(&mut self, i: usize) -> anyhow::Result<Value<'static>> {
+        let (serial_type, offset) = self.column(i)?;
+        Ok(load_value(self.payload, &serial_type, offset)?.into_owned())
+    }
+
+    /// Write the content of the text or blob column to the writer.
+    ///
+    /// This is useful for a large text or blob in overflow pages because the
+    /// content is not loaded into memory at once. Fails if the column is not
+    /// a text or a blob.
+    ///
+    /// Returns the number of bytes written.
+    pub fn read_column(&mut self, i: usize, writer: &mut impl Write) -> anyhow::Result<u64> {
+        let (serial_type, offset) = self.column(i)?;
+        if !matches!(
+            serial_type.kind(),
+            SerialTypeKind::Text(_) | SerialTypeKind::Blob(_)
+        ) {
+            bail!("column is not text or blob");
+        }
+        let content_size = serial_type.content_size();
+        let n = self.payload.write_to(offset, content_size, writer)?;
+        if n != content_size as u64 {
+            bail!("failed to write the whole column content");
+        }
+        Ok(n)
+    }
+
+    /// Returns the integer value of the column.
+    ///
+    /// Returns None if the column is not an integer. Unlike [Record::get()],
+    /// this does not allocate even if the content is in overflow pages.
+    pub fn get_integer(&mut self, i: usize) -> anyhow::Result<Option<i64>> {
+        let (serial_type, offset) = self.column(i)?;
+        if !matches!(serial_type.0, 1..=6 | 8 | 9) {
+            return Ok(None);
+        }
+        let offset = offset as usize;
+        let content_size = serial_type.content_size() as usize;
+        let mut tmp_buf = [0; 8];
+        let buf = if content_size == 0 {
+            &[]
+        } else if offset + content_size > self.payload.buf().len() {
+            // SAFETY: tmp_buf is not from MemPage.
+            let n = unsafe {
+                self.payload
+                    .load(offset as i32, &mut tmp_buf[..content_size])?
+            };
+            if n != content_size {
+                bail!("failed to load integer from payload");
+            }
+            &tmp_buf[..content_size]
+        } else {
+            &self.payload.buf()[offset..offset + content_size]
+        };
+        match serial_type.parse(buf)? {
+            Value::Integer(v) => Ok(Some(v)),
+            _ => unreachable!("serial type {} is integer", serial_type.0),
+        }
+    }
+
+    /// Returns the serial type and the content offset of the column.
+    fn column(&mut self, i: usize) -> anyhow::Result<(SerialType, i32)> {
+        self.parse_header(i.saturating_add(1))?;
+        let Some((serial_type, offset)) = self.header.get(i) else {
+            bail!("index out of range");
+        };
+        Ok((SerialType(serial_type.0), *offset))
+    }
+
+    /// Parse the record header up to the first n_columns columns.
+    fn parse_header(&mut self, n_columns: usize) -> anyhow::Result<()> {
+        if self.header.len() < n_columns {
+            self.header_parser
+                .parse(self.payload, n_columns, &mut self.header)?;
+        }
+        Ok(())
+    }
 }
 
-/// Parse record header and return a list of serial types and content offsets.
+/// Parse the value of the column at the offset of the payload.
 ///
-/// TODO: support partial parsing.
-pub fn parse_record_header(payload: &BtreePayload) -> anyhow::Result<Vec<(SerialType, i32)>> {
-    let local_buf = payload.buf();
-    let (header_size, consumed) = parse_varint(local_buf).context("parse record header size")?;
-    let header_size: i32 = header_size.try_into().context("header size is too large")?;
-    let mut header_offset = consumed as i32;
-    let mut content_offset = header_size;
-
-    let mut buf_loaded;
-    let buf = if local_buf.len() > header_size as usize {
-        buf_loaded = vec![0; header_size as usize];
-        // SAFETY: buf_loaded does not overlap with payload.
-        let n = unsafe { payload.load(0, &mut buf_loaded) }.context("load record header")?;
-        if n != header_size as usize {
+/// The value borrows the local payload. Only the content in overflow pages is
+/// loaded into an owned buffer.
+fn load_value<'a>(
+    payload: &'a BtreePayload,
+    serial_type: &SerialType,
+    offset: i32,
+) -> anyhow::Result<Value<'a>> {
+    let content_size = serial_type.content_size() as usize;
+    if content_size == 0 || offset as usize + content_size <= payload.buf().len() {
+        let buf = if content_size == 0 {
+            &[]
+        } else {
+            &payload.buf()[offset as usize..offset as usize + content_size]
+        };
+        return serial_type.parse(buf);
+    }
+    let buf = payload.load_vec(offset, content_size as i32)?;
+    Ok(match serial_type.parse(&buf)? {
+        Value::Text(_) => Value::Text(Buffer::Owned(buf)),
+        Value::Blob(_) => Value::Blob(Buffer::Owned(buf)),
+        value => value.into_owned(),
+    })
+}
+
+/// Extracts the same set of columns from many records.
+///
+/// The record header is parsed only up to the last projected column, and the
+/// buffer of the parsed header is reused across records.
+pub struct RecordProjector {
+    columns: Vec<usize>,
+    header: Vec<(SerialType, i32)>,
+}
+
+impl RecordProjector {
+    pub fn new(columns: Vec<usize>) -> Self {
+        Self {
+            columns,
+            header: Vec::new(),
+        }
+    }
+
+    /// Fill the values of the projected columns of the record converted by the
+    /// function.
+    ///
+    /// The length of values must be the same as the number of the projected
+    /// columns. The function takes the index of the column and its value,
+    /// which is None if the column is beyond the record.
+    pub fn project<'a, V>(
+        &mut self,
+        payload: &'a BtreePayload,
+        values: &mut [V],
+        mut f: impl FnMut(usize, Option<Value<'a>>) -> anyhow::Result<V>,
+    ) -> anyhow::Result<()> {
+        if values.len() != self.columns.len() {
             bail!(
-                "record header size {} does not match with loaded size {}",
-                header_size,
-                n
+                "values length {} does not match the number of columns {}",
+                values.len(),
+                self.columns.len()
             );
         }
-        &buf_loaded
-    } else {
-        local_buf
-    };
+        let n_columns = self.columns.iter().max().map_or(0, |&i| i + 1);
+        parse_record_header_into(payload, n_columns, &mut self.header)?;
+        for (value, &i) in values.iter_mut().zip(self.columns.iter()) {
+            let loaded = match self.header.get(i) {
+                Some((serial_type, offset)) => Some(load_value(payload, serial_type, *offset)?),
+                None => None,
+            };
+            *value = f(i, loaded)?;
+        }
+        Ok(())
+    }
+}
 
+/// Parse record header and return a list of serial types and content offsets.
+pub fn parse_record_header(payload: &BtreePayload) -> anyhow::Result<Vec<(SerialType, i32)>> {
     let mut parsed = Vec::new();
-    while header_offset < header_size {
-        let (serial_type, consumed) =
-            parse_varint(&buf[header_offset as usize..]).context("parse serial type")?;
-        let serial_type = SerialType(serial_type.try_into().context("serial type is too large")?);
-        let content_size = serial_type.content_size();
-        parsed.push((serial_type, content_offset));
-        header_offset += consumed as i32;
-        content_offset += content_size;
+    parse_record_header_into(payload, usize::MAX, &mut parsed)?;
+    Ok(parsed)
+}
+
+/// Parse the first n_columns columns of record header into the cleared vec.
+fn parse_record_header_into(
+    payload: &BtreePayload,
+    n_columns: usize,
+    parsed: &mut Vec<(SerialType, i32)>,
+) -> anyhow::Result<()> {
+    parsed.clear();
+    RecordHeaderParser::new(payload)?.parse(payload, n_columns, parsed)
+}
+
+/// Parses the serial types of a record header incrementally.
+struct RecordHeaderParser {
+    header_size: i32,
+    /// The offset of the next serial type in the header.
+    header_offset: i32,
+    /// The offset of the content of the next column.
+    content_offset: i32,
+    /// The header loaded from overflow pages if it does not fit in the local
+    /// payload.
+    loaded_header: Option<Vec<u8>>,
+}
+
+impl RecordHeaderParser {
+    fn new(payload: &BtreePayload) -> anyhow::Result<Self> {
+        let local_buf = payload.buf();
+        let (header_size, consumed) =
+            parse_varint(local_buf).context("parse record header size")?;
+        let header_size: i32 = header_size.try_into().context("header size is too large")?;
+        if header_size < consumed as i32 {
+            bail!("invalid record header size: {}", header_size);
+        } else if header_size > payload.size() {
+            bail!(
+                "record header length exceeds payload: {} > {}",
+                header_size,
+                payload.size()
+            );
+        }
+        let loaded_header = if local_buf.len() < header_size as usize {
+            Some(
+                payload
+                    .load_vec(0, header_size)
+                    .context("load record header")?,
+            )
+        } else {
+            None
+        };
+        Ok(Self {
+            header_size,
+            header_offset: consumed as i32,
+            content_offset: header_size,
+            loaded_header,
+        })
     }
 
-    Ok(parsed)
+    /// Parse the serial types following the parsed ones until parsed has
+    /// n_columns columns or the header ends.
+    fn parse(
+        &mut self,
+        payload: &BtreePayload,
+        n_columns: usize,
+        parsed: &mut Vec<(SerialType, i32)>,
+    ) -> anyhow::Result<()> {
+        let buf = self.loaded_header.as_deref().unwrap_or(payload.buf());
+        while self.header_offset < self.header_size && parsed.len() < n_columns {
+            let (serial_type, consumed) =
+                parse_varint(&buf[self.header_offset as usize..]).context("parse serial type")?;
+            let serial_type =
+                SerialType(serial_type.try_into().context("serial type is too large")?);
+            if serial_type.0 == 10 || serial_type.0 == 11 {
+                bail!("reserved serial type: {}", serial_type.0);
+            }
+            let content_size = serial_type.content_size();
+            let next_content_offset = self
+                .content_offset
+                .checked_add(content_size)
+                .filter(|&offset| offset <= payload.size())
+                .context("record content exceeds payload")?;
+            parsed.push((serial_type, self.content_offset));
+            self.header_offset += consumed as i32;
+            self.content_offset = next_content_offset;
+        }
+        Ok(())
+    }
 }
 
 #[cfg(test)]
@@ -207,8 +480,10 @@ mod tests {
 
     use super::*;
 
+    use crate::btree::BtreeContext;
     use crate::cursor::BtreeCursor;
     use crate::test_utils::*;
+    use crate::value::Collation;
 
     #[test]
     fn test_parse_record() {
@@ -253,11 +528,19 @@ mod tests {
 
         let (_, payload) = cursor.get_table_payload().unwrap().unwrap();
         let mut record = Record::parse(&payload).unwrap();
+        assert_eq!(
+            record.serial_types().unwrap().collect::<Vec<_>>(),
+            vec![
+                SerialTypeKind::Null,
+                SerialTypeKind::One,
+                SerialTypeKind::Null,
+                SerialTypeKind::Zero
+            ]
+        );
         assert_eq!(record.get(0).unwrap(), Value::Null);
         assert_eq!(record.get(1).unwrap(), Value::Integer(1));
         assert_eq!(record.get(2).unwrap(), Value::Null);
         assert_eq!(record.get(3).unwrap(), Value::Integer(0));
-        drop(payload);
 
         cursor.next().unwrap();
         let (_, payload) = cursor.get_table_payload().unwrap().unwrap();
@@ -266,7 +549,6 @@ mod tests {
         assert_eq!(record.get(1).unwrap(), Value::Integer(i8::MIN as i64));
         assert_eq!(record.get(2).unwrap(), Value::Integer(i16::MAX as i64));
         assert_eq!(record.get(3).unwrap(), Value::Integer(i16::MIN as i64));
-        drop(payload);
 
         cursor.next().unwrap();
         let (_, payload) = cursor.get_table_payload().unwrap().unwrap();
@@ -275,26 +557,51 @@ mod tests {
         assert_eq!(record.get(1).unwrap(), Value::Integer(-(ONE << 23)));
         assert_eq!(record.get(2).unwrap(), Value::Integer(i32::MAX as i64));
         assert_eq!(record.get(3).unwrap(), Value::Integer(i32::MIN as i64));
-        drop(payload);
 
         cursor.next().unwrap();
         let (_, payload) = cursor.get_table_payload().unwrap().unwrap();
         let mut record = Record::parse(&payload).unwrap();
+        assert_eq!(
+            record.serial_types().unwrap().collect::<Vec<_>>(),
+            vec![
+                SerialTypeKind::Integer(6),
+                SerialTypeKind::Integer(6),
+                SerialTypeKind::Integer(8),
+                SerialTypeKind::Integer(8)
+            ]
+        );
         assert_eq!(record.get(0).unwrap(), Value::Integer((ONE << 47) - 1));
         assert_eq!(record.get(1).unwrap(), Value::Integer(-(ONE << 47)));
         assert_eq!(record.get(2).unwrap(), Value::Integer(i64::MAX));
         assert_eq!(record.get(3).unwrap(), Value::Integer(i64::MIN));
-        drop(payload);
+        assert_eq!(record.get_integer(0).unwrap(), Some((ONE << 47) - 1));
+        assert_eq!(record.get_integer(1).unwrap(), Some(-(ONE << 47)));
+        assert_eq!(record.get_integer(2).unwrap(), Some(i64::MAX));
+        assert_eq!(record.get_integer(3).unwrap(), Some(i64::MIN));
 
         cursor.next().unwrap();
         let (_, payload) = cursor.get_table_payload().unwrap().unwrap();
         let mut record = Record::parse(&payload).unwrap();
+        assert_eq!(
+            record.serial_types().unwrap().collect::<Vec<_>>(),
+            vec![
+                SerialTypeKind::Zero,
+                SerialTypeKind::One,
+                SerialTypeKind::Text(5),
+                SerialTypeKind::Blob(8)
+            ]
+        );
         assert_eq!(record.get(0).unwrap(), Value::Integer(0));
         assert_eq!(record.get(1).unwrap(), Value::Integer(1));
         assert_eq!(
             record.get(2).unwrap(),
             Value::Text(b"hello".as_slice().into())
         );
+        assert_eq!(record.get_integer(0).unwrap(), Some(0));
+        assert_eq!(record.get_integer(1).unwrap(), Some(1));
+        assert_eq!(record.get_integer(2).unwrap(), None);
+        assert_eq!(record.get_integer(3).unwrap(), None);
+        assert!(record.get_integer(4).is_err());
         assert_eq!(
             record.get(3).unwrap(),
             Value::Blob(
@@ -303,12 +610,307 @@ mod tests {
                     .into()
             )
         );
-        drop(payload);
 
         cursor.next().unwrap();
         let (_, payload) = cursor.get_table_payload().unwrap().unwrap();
         let mut record = Record::parse(&payload).unwrap();
+        assert_eq!(
+            record.serial_types().unwrap().collect::<Vec<_>>(),
+            vec![
+                SerialTypeKind::Real,
+                SerialTypeKind::Null,
+                SerialTypeKind::Null,
+                SerialTypeKind::Null
+            ]
+        );
         assert_eq!(record.get(0).unwrap(), Value::Real(0.5));
+        assert_eq!(record.get_integer(0).unwrap(), None);
+        assert_eq!(record.get_integer(1).unwrap(), None);
+    }
+
+    #[test]
+    fn test_parse_record_header_in_overflow_page() {
+        let columns = (0..1000).map(|i| format!("c{i}")).collect::<Vec<_>>();
+        let create_table = format!("CREATE TABLE example({});", columns.join(","));
+        let file = create_sqlite_database(&[
+            "PRAGMA page_size = 512;",
+            &create_table,
+            "INSERT INTO example(c999) VALUES (1);",
+        ]);
+        let pager = create_pager(file.as_file().try_clone().unwrap()).unwrap();
+        let bctx = load_btree_context(file.as_file()).unwrap();
+        let table_page_id = find_table_page_id("example", file.path());
+
+        let mut cursor = BtreeCursor::new(table_page_id, &pager, &bctx).unwrap();
+        cursor.move_to_first().unwrap();
+        let (_, payload) = cursor.get_table_payload().unwrap().unwrap();
+        assert!((payload.buf().len() as i32) < payload.size());
+        let mut record = Record::parse(&payload).unwrap();
+        assert_eq!(record.len().unwrap(), 1000);
+        assert_eq!(record.get(0).unwrap(), Value::Null);
+        assert_eq!(record.get(999).unwrap(), Value::Integer(1));
+        assert_eq!(record.get_integer(998).unwrap(), None);
+        assert_eq!(record.get_integer(999).unwrap(), Some(1));
+    }
+
+    #[test]
+    fn test_parse_record_header_exceeds_payload() {
+        const PAGESIZE: usize = 512;
+        let mut content = vec![0; 2 * PAGESIZE];
+        let page = &mut content[PAGESIZE..];
+        // Leaf table page with a cell at offset 500.
+        page[0] = 0x0d;
+        page[4] = 1;
+        page[8..10].copy_from_slice(&500_u16.to_be_bytes());
+        // 3 bytes payload with rowid 1, whose record header claims 5 bytes.
+        page[500..505].copy_from_slice(&[0x03, 0x01, 0x05, 0x01, 0x01]);
+        let pager = create_empty_pager(&content, PAGESIZE);
+        let bctx = BtreeContext::new(PAGESIZE as i32);
+
+        let mut cursor = BtreeCursor::new(2, &pager, &bctx).unwrap();
+        cursor.move_to_first().unwrap();
+        let (_, payload) = cursor.get_table_payload().unwrap().unwrap();
+        assert_eq!(
+            Record::parse(&payload).err().unwrap().to_string(),
+            "record header length exceeds payload: 5 > 3"
+        );
+
+        // The record header size is smaller than its own varint.
+        let page = &mut content[PAGESIZE..];
+        page[502] = 0x00;
+        let pager = create_empty_pager(&content, PAGESIZE);
+        let mut cursor = BtreeCursor::new(2, &pager, &bctx).unwrap();
+        cursor.move_to_first().unwrap();
+        let (_, payload) = cursor.get_table_payload().unwrap().unwrap();
+        assert_eq!(
+            Record::parse(&payload).err().unwrap().to_string(),
+            "invalid record header size: 0"
+        );
+    }
+
+    #[test]
+    fn test_record_9_bytes_serial_type() {
+        const PAGESIZE: usize = 512;
+        let mut content = vec![0; 2 * PAGESIZE];
+        let page = &mut content[PAGESIZE..];
+        // Leaf table page with a cell at offset 480.
+        page[0] = 0x0d;
+        page[4] = 1;
+        page[8..10].copy_from_slice(&480_u16.to_be_bytes());
+        // 11 bytes payload with rowid 1. The serial type 1 is in a 9 bytes
+        // varint.
+        page[480..493].copy_from_slice(&[
+            0x0b, 0x01, 0x0a, 0x80, 0x80, 0x80, 0x80, 0x80, 0x80, 0x80, 0x80, 0x01, 0x2a,
+        ]);
+        let pager = create_empty_pager(&content, PAGESIZE);
+        let bctx = BtreeContext::new(PAGESIZE as i32);
+
+        let mut cursor = BtreeCursor::new(2, &pager, &bctx).unwrap();
+        cursor.move_to_first().unwrap();
+        let (_, payload) = cursor.get_table_payload().unwrap().unwrap();
+        let mut record = Record::parse(&payload).unwrap();
+        assert_eq!(record.len().unwrap(), 1);
+        assert_eq!(record.get(0).unwrap(), Value::Integer(42));
+
+        // The serial type overflows in the 9 bytes varint.
+        let page = &mut content[PAGESIZE..];
+        page[483] = 0x81;
+        page[491] = 0xff;
+        let pager = create_empty_pager(&content, PAGESIZE);
+        let mut cursor = BtreeCursor::new(2, &pager, &bctx).unwrap();
+        cursor.move_to_first().unwrap();
+        let (_, payload) = cursor.get_table_payload().unwrap().unwrap();
+        let mut record = Record::parse(&payload).unwrap();
+        assert_eq!(
+            record.get(0).err().unwrap().to_string(),
+            "serial type is too large"
+        );
+    }
+
+    #[test]
+    fn test_record_lazy_header() {
+        const PAGESIZE: usize = 512;
+        let mut content = vec![0; 2 * PAGESIZE];
+        let page = &mut content[PAGESIZE..];
+        // Leaf table page with a cell at offset 500.
+        page[0] = 0x0d;
+        page[4] = 1;
+        page[8..10].copy_from_slice(&500_u16.to_be_bytes());
+        // 6 bytes payload with rowid 1. The third serial type is reserved.
+        page[500..508].copy_from_slice(&[0x06, 0x01, 0x04, 0x01, 0x01, 0x0a, 0x05, 0x06]);
+        let pager = create_empty_pager(&content, PAGESIZE);
+        let bctx = BtreeContext::new(PAGESIZE as i32);
+
+        let mut cursor = BtreeCursor::new(2, &pager, &bctx).unwrap();
+        cursor.move_to_first().unwrap();
+        let (_, payload) = cursor.get_table_payload().unwrap().unwrap();
+        let mut record = Record::parse(&payload).unwrap();
+        // Columns before the reserved serial type are accessible.
+        assert_eq!(record.get(1).unwrap(), Value::Integer(6));
+        assert_eq!(record.get_integer(0).unwrap(), Some(5));
+        assert_eq!(record.header.len(), 2);
+        assert_eq!(
+            record.get(2).err().unwrap().to_string(),
+            "reserved serial type: 10"
+        );
+        assert!(record.len().is_err());
+        assert_eq!(record.get_owned(0).unwrap(), Value::Integer(5));
+        assert_eq!(
+            compare_record(
+                &[ValueCmp::new(&Value::Integer(5), &Collation::Binary)],
+                &payload
+            )
+            .unwrap(),
+            Ordering::Equal
+        );
+    }
+
+    #[test]
+    fn test_record_projector() {
+        let blob_query = format!(
+            "INSERT INTO example(col1, col2, col3, col4) VALUES (2, X'{}', 'world', 3.5);",
+            "FF".repeat(2000)
+        );
+        let file = create_sqlite_database(&[
+            "PRAGMA page_size = 512;",
+            "CREATE TABLE example(col1, col2, col3, col4);",
+            "INSERT INTO example(col1, col2, col3, col4) VALUES (1, 'hello', null, 10);",
+            &blob_query,
+            "INSERT INTO example(col1) VALUES (3);",
+        ]);
+        let pager = create_pager(file.as_file().try_clone().unwrap()).unwrap();
+        let bctx = load_btree_context(file.as_file()).unwrap();
+        let table_page_id = find_table_page_id("example", file.path());
+
+        let mut projector = RecordProjector::new(vec![3, 1, 0, 5]);
+        let mut cursor = BtreeCursor::new(table_page_id, &pager, &bctx).unwrap();
+        cursor.move_to_first().unwrap();
+        let mut rows = Vec::new();
+        while let Some((_, payload)) = cursor.get_table_payload().unwrap() {
+            let mut values = [Value::Null, Value::Null, Value::Null, Value::Null];
+            projector
+                .project(&payload, &mut values, |_, v| {
+                    Ok(v.map_or(Value::Null, Value::into_owned))
+                })
+                .unwrap();
+            rows.push(values);
+            cursor.next().unwrap();
+        }
+        assert_eq!(
+            rows,
+            vec![
+                [
+                    Value::Integer(10),
+                    Value::Text(b"hello".as_slice().into()),
+                    Value::Integer(1),
+                    Value::Null,
+                ],
+                [
+                    Value::Real(3.5),
+                    Value::Blob(vec![0xff; 2000].into()),
+                    Value::Integer(2),
+                    Value::Null,
+                ],
+                [Value::Null, Value::Null, Value::Integer(3), Value::Null],
+            ]
+        );
+
+        cursor.move_to_first().unwrap();
+        let (_, payload) = cursor.get_table_payload().unwrap().unwrap();
+        let mut values = [Value::Null, Value::Null, Value::Null];
+        assert!(projector
+            .project(&payload, &mut values, |_, v| Ok(v.unwrap_or(Value::Null)))
+            .is_err());
+
+        let mut projector = RecordProjector::new(Vec::new());
+        assert!(projector
+            .project(&payload, &mut [], |_, v| Ok(v.unwrap_or(Value::Null)))
+            .is_ok());
+        // The function takes the index of the column.
+        let mut projector = RecordProjector::new(vec![2, 7]);
+        let mut values = [(0, false), (0, false)];
+        projector
+            .project(&payload, &mut values, |i, v| Ok((i, v.is_some())))
+            .unwrap();
+        assert_eq!(values, [(2, true), (7, false)]);
+    }
+
+    #[test]
+    fn test_read_column() {
+        let text = "abcdefghij".repeat(1000);
+        let insert = format!("INSERT INTO example VALUES (1, '{text}', X'1234', NULL, 1.5);");
+        let file = create_sqlite_database(&["CREATE TABLE example(a, b, c, d, e);", &insert]);
+        let pager = create_pager(file.as_file().try_clone().unwrap()).unwrap();
+        let bctx = load_btree_context(file.as_file()).unwrap();
+        let table_page_id = find_table_page_id("example", file.path());
+
+        let mut cursor = BtreeCursor::new(table_page_id, &pager, &bctx).unwrap();
+        cursor.move_to_first().unwrap();
+        let (_, payload) = cursor.get_table_payload().unwrap().unwrap();
+        assert!((payload.buf().len() as i32) < payload.size());
+        let mut record = Record::parse(&payload).unwrap();
+
+        let mut buf = Vec::new();
+        assert_eq!(record.read_column(1, &mut buf).unwrap(), 10000);
+        assert_eq!(buf, text.as_bytes());
+
+        let mut buf = Vec::new();
+        assert_eq!(record.read_column(2, &mut buf).unwrap(), 2);
+        assert_eq!(buf, [0x12, 0x34]);
+
+        for i in [0, 3, 4, 5] {
+            let mut buf = Vec::new();
+            assert!(record.read_column(i, &mut buf).is_err(), "column {i}");
+            assert!(buf.is_empty());
+        }
+    }
+
+    #[test]
+    fn test_get_owned() {
+        let text = "abcdefghij".repeat(1000);
+        let insert = format!("INSERT INTO example VALUES (1, X'1234', NULL, 1.5, '{text}');");
+        let file = create_sqlite_database(&["CREATE TABLE example(a, b, c, d, e);", &insert]);
+        let pager = create_pager(file.as_file().try_clone().unwrap()).unwrap();
+        let bctx = load_btree_context(file.as_file()).unwrap();
+        let table_page_id = find_table_page_id("example", file.path());
+
+        let mut cursor = BtreeCursor::new(table_page_id, &pager, &bctx).unwrap();
+        cursor.move_to_first().unwrap();
+        let (_, payload) = cursor.get_table_payload().unwrap().unwrap();
+        assert!((payload.buf().len() as i32) < payload.size());
+        let mut record = Record::parse(&payload).unwrap();
+
+        // The blob in the local payload is borrowed.
+        assert!(matches!(
+            record.get(1).unwrap(),
+            Value::Blob(Buffer::Ref(_))
+        ));
+
+        let values = (0..record.len().unwrap())
+            .map(|i| record.get_owned(i).unwrap())
+            .collect::<Vec<_>>();
+        assert_eq!(
+            values,
+            vec![
+                Value::Integer(1),
+                Value::Blob([0x12, 0x34].as_slice().into()),
+                Value::Null,
+                Value::Real(1.5),
+                Value::Text(text.as_bytes().into()),
+            ]
+        );
+        assert!(matches!(values[4], Value::Text(Buffer::Owned(_))));
+        assert_eq!(record.get(4).unwrap(), values[4]);
+        assert!(record.get_owned(5).is_err());
+    }
+
+    #[test]
+    fn test_parse_short_buffer() {
+        for serial_type in [1, 2, 3, 4, 5, 6, 7, 14, 17] {
+            let size = SerialType(serial_type).content_size() as usize;
+            assert!(SerialType(serial_type).parse(&[0; 8][..size - 1]).is_err());
+            assert!(SerialType(serial_type).parse(&[0; 8][..size]).is_ok());
+        }
     }
 
     #[test]
@@ -14,19 +14,26 @@
 
 use std::collections::HashMap;
 use std::iter::Iterator;
-use std::rc::Rc;
+use std::sync::Arc;
 
 use anyhow::bail;
 use anyhow::Context;
 
-pub use crate::btree::*;
 use crate::pager::PageId;
 use crate::pager::ROOT_PAGE_ID;
-use crate::parser::expect_no_more_token;
+use crate::parser::parse_alter_table;
+use crate::parser::parse_complete;
 use crate::parser::parse_create_index;
 use crate::parser::parse_create_table;
+use crate::parser::skip_semicolon;
+use crate::parser::AlterTableAction;
 use crate::parser::ColumnConstraint;
-use crate::parser::Parser;
+use crate::parser::ColumnDef;
+use crate::parser::CreateIndex;
+use crate::parser::CreateTable;
+use crate::parser::Expr;
+use crate::parser::SortOrder;
+use crate::parser::UnaryOp;
 use crate::utils::upper_to_lower;
 use crate::utils::CaseInsensitiveBytes;
 use crate::utils::MaybeQuotedBytes;
@@ -38,6 +45,16 @@ use crate::value::DEFAULT_COLLATION;
 use crate::Columns;
 use crate::Statement;
 
+/// Whether the name is reserved for the internal use of SQLite.
+///
+/// SQLite rejects user objects whose name begins with "sqlite_" (case
+/// insensitive). e.g. sqlite_sequence, sqlite_stat1 and the
+/// sqlite_autoindex_* indexes of UNIQUE and PRIMARY KEY constraints.
+fn is_internal_name(name: &[u8]) -> bool {
+    const PREFIX: &[u8] = b"sqlite_";
+    name.len() >= PREFIX.len() && name[..PREFIX.len()].eq_ignore_ascii_case(PREFIX)
+}
+
 struct SchemaRecord<'a> {
     type_: &'a [u8],
     name: &'a [u8],
@@ -80,11 +97,223 @@ impl<'a> SchemaRecord<'a> {
     }
 }
 
+/// The definition of a table in its CREATE TABLE statement.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TableDefinition {
+    /// The dequoted name of the table.
+    pub name: Vec<u8>,
+    pub columns: Vec<ColumnDefinition>,
+}
+
+impl From<&CreateTable<'_>> for TableDefinition {
+    fn from(create_table: &CreateTable) -> Self {
+        Self {
+            name: create_table.table_name.dequote(),
+            columns: create_table
+                .columns
+                .iter()
+                .map(ColumnDefinition::from)
+                .collect(),
+        }
+    }
+}
+
+impl TableDefinition {
+    /// Apply an ALTER TABLE statement to the definition.
+    ///
+    /// This reconstructs the effective schema of an altered table from the
+    /// statements of a dump. The trailing semicolon is optional.
+    pub fn apply_alter_table(&mut self, sql: &[u8]) -> anyhow::Result<()> {
+        let alter_table = parse_complete(sql, |p| {
+            let alter_table = parse_alter_table(p)?;
+            skip_semicolon(p);
+            Ok(alter_table)
+        })
+        .map_err(|e| anyhow::anyhow!("parse alter table sql: {}", e))?;
+        let table_name = alter_table.table_name.dequote();
+        if CaseInsensitiveBytes::from(&table_name) != CaseInsensitiveBytes::from(&self.name) {
+            bail!(
+                "alter table {} is not for {}",
+                String::from_utf8_lossy(&table_name),
+                String::from_utf8_lossy(&self.name)
+            );
+        }
+        match alter_table.action {
+            AlterTableAction::RenameTo(new_table_name) => {
+                self.name = new_table_name.dequote();
+            }
+            AlterTableAction::RenameColumn {
+                column_name,
+                new_column_name,
+            } => {
+                let column_name = column_name.dequote();
+                let new_column_name = new_column_name.dequote();
+                self.ensure_no_column(&new_column_name)?;
+                let Some(column) = self.columns.iter_mut().find(|column| {
+                    CaseInsensitiveBytes::from(&column.name)
+                        == CaseInsensitiveBytes::from(&column_name)
+                }) else {
+                    bail!("no such column: {}", String::from_utf8_lossy(&column_name));
+                };
+                column.name = new_column_name;
+            }
+            AlterTableAction::AddColumn(column_def) => {
+                let column = ColumnDefinition::from(&column_def);
+                self.ensure_no_column(&column.name)?;
+                self.columns.push(column);
+            }
+        }
+        Ok(())
+    }
+
+    fn ensure_no_column(&self, name: &[u8]) -> anyhow::Result<()> {
+        if self.columns.iter().any(|column| {
+            CaseInsensitiveBytes::from(&column.name) == CaseInsensitiveBytes::from(name)
+        }) {
+            bail!("duplicate column name: {}", String::from_utf8_lossy(name));
+        }
+        Ok(())
+    }
+}
+
+/// The definition of a column in a CREATE TABLE statement.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ColumnDefinition {
+    /// The dequoted name of the column.
+    pub name: Vec<u8>,
+    /// The declared type, e.g. `UNSIGNED BIG INT`. Empty if omitted.
+    pub type_name: Vec<u8>,
+    /// The collation name of the COLLATE constraint.
+    pub collation: Option<Vec<u8>>,
+    /// Whether the column has the PRIMARY KEY constraint.
+    pub primary_key: bool,
+}
+
+impl From<&ColumnDef<'_>> for ColumnDefinition {
+    fn from(column: &ColumnDef) -> Self {
+        let mut collation = None;
+        let mut primary_key = false;
+        for constraint in column.constraints.iter() {
+            match constraint {
+                ColumnConstraint::Collate(name) => collation = Some(name.dequote()),
+                ColumnConstraint::PrinaryKey => primary_key = true,
+                ColumnConstraint::Default(_) => {}
+            }
+        }
+        Self {
+            name: column.name.dequote(),
+            type_name: column
+                .type_name
+                .iter()
+                .map(|name| name.dequote())
+                .collect::<Vec<_>>()
+                .join(b" ".as_slice()),
+            collation,
+            primary_key,
+        }
+    }
+}
+
+/// The definition of an index in its CREATE INDEX statement.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct IndexDefinition {
+    /// The dequoted name of the index.
+    pub name: Vec<u8>,
+    /// The dequoted name of the indexed table.
+    pub table_name: Vec<u8>,
+    pub unique: bool,
+    /// The dequoted names of the indexed columns in order.
+    pub columns: Vec<Vec<u8>>,
+}
+
+impl From<&CreateIndex<'_>> for IndexDefinition {
+    fn from(create_index: &CreateIndex) -> Self {
+        Self {
+            name: create_index.index_name.dequote(),
+            table_name: create_index.table_name.dequote(),
+            unique: create_index.unique,
+            columns: create_index
+                .columns
+                .iter()
+                .map(|column| column.name.dequote())
+                .collect(),
+        }
+    }
+}
+
+/// A row of the sqlite_schema table.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SchemaEntry {
+    /// "table", "index", "view" or "trigger".
+    pub type_: Vec<u8>,
+    pub name: Vec<u8>,
+    /// The table which the object belongs to.
+    pub table_name: Vec<u8>,
+    /// 0 for views and triggers.
+    pub root_page_id: PageId,
+    /// The original CREATE statement.
+    ///
+    /// This is None for the internal indexes of UNIQUE and PRIMARY KEY
+    /// constraints.
+    pub sql: Option<Vec<u8>>,
+}
+
+impl SchemaEntry {
+    pub(crate) fn parse(columns: &Columns) -> anyhow::Result<Self> {
+        let record = SchemaRecord::parse(columns)?;
+        Ok(Self {
+            type_: record.type_.to_vec(),
+            name: record.name.to_vec(),
+            table_name: record.table_name.to_vec(),
+            root_page_id: record.root_page_id,
+            sql: record.sql.map(|sql| sql.to_vec()),
+        })
+    }
+
+    /// Whether the entry is an object created internally by SQLite.
+    ///
+    /// Internal entries may have no sql (e.g. sqlite_autoindex_*) or a
+    /// structure which differs from user-defined tables.
+    pub fn is_internal(&self) -> bool {
+        is_internal_name(&self.name)
+    }
+
+    /// Parse the CREATE TABLE statement of the table.
+    pub fn parse_create_table(&self) -> anyhow::Result<TableDefinition> {
+        let create_table = parse_complete(self.sql_of(b"table")?, parse_create_table)
+            .map_err(|e| anyhow::anyhow!("parse create table sql: {}", e))?;
+        Ok(TableDefinition::from(&create_table))
+    }
+
+    /// Parse the CREATE INDEX statement of the index.
+    pub fn parse_create_index(&self) -> anyhow::Result<IndexDefinition> {
+        let create_index = parse_complete(self.sql_of(b"index")?, parse_create_index)
+            .map_err(|e| anyhow::anyhow!("parse create index sql: {}", e))?;
+        Ok(IndexDefinition::from(&create_index))
+    }
+
+    fn sql_of(&self, type_: &[u8]) -> anyhow::Result<&[u8]> {
+        let name = String::from_utf8_lossy(&self.name);
+        if self.type_ != type_ {
+            bail!(
+                "{} is not {}: {}",
+                name,
+                String::from_utf8_lossy(type_),
+                String::from_utf8_lossy(&self.type_)
+            );
+        }
+        let Some(sql) = &self.sql else {
+            bail!("no sql for {}", name);
+        };
+        Ok(sql)
+    }
+}
+
 pub struct Schema {
     schema_table: Table,
     // TODO: Use the reference of table name in the value as the key.
     tables: HashMap<Vec<u8>, Table>,
-    indexes: HashMap<Vec<u8>, Rc<Index>>,
+    indexes: HashMap<Vec<u8>, Arc<Index>>,
 }
 
 impl Schema {
@@ -97,32 +326,38 @@ impl Schema {
                     type_affinity: TypeAffinity::Text,
                     primary_key: false,
                     collation: Collation::Binary,
+                    default_value: Some(Value::Null),
                 },
                 Column {
                     name: b"name".to_vec(),
                     type_affinity: TypeAffinity::Text,
                     primary_key: false,
                     collation: Collation::Binary,
+                    default_value: Some(Value::Null),
                 },
                 Column {
                     name: b"tbl_name".to_vec(),
                     type_affinity: TypeAffinity::Text,
                     primary_key: false,
                     collation: Collation::Binary,
+                    default_value: Some(Value::Null),
                 },
                 Column {
                     name: b"rootpage".to_vec(),
                     type_affinity: TypeAffinity::Integer,
                     primary_key: false,
                     collation: Collation::Binary,
+                    default_value: Some(Value::Null),
                 },
                 Column {
                     name: b"sql".to_vec(),
                     type_affinity: TypeAffinity::Text,
                     primary_key: false,
                     collation: Collation::Binary,
+                    default_value: Some(Value::Null),
                 },
             ],
+            rowid_alias: None,
             indexes: None,
         }
     }
@@ -170,7 +405,7 @@ impl Schema {
                         .context("index table not found")?;
                     // TODO: validate the schema.table is equal to table.name.
                     if let Some(sql) = schema.sql {
-                        let (mut index_name, parsed_table_name, mut index) =
+                        let (mut index_name, parsed_table_name, mut index, lookupable) =
                             Index::parse(sql, schema.root_page_id, table)?;
                         if index_name != schema.name {
                             bail!(
@@ -188,14 +423,21 @@ impl Schema {
                                 parsed_table_name
                             );
                         }
-                        index.next = table.indexes.clone();
-                        let index = Rc::new(index);
-                        table.indexes = Some(index.clone());
+                        // TODO: support DESC columns and partial indexes in index lookups.
+                        let index = if lookupable {
+                            index.next = table.indexes.clone();
+                            let index = Arc::new(index);
+                            table.indexes = Some(index.clone());
+                            index
+                        } else {
+                            Arc::new(index)
+                        };
 
                         upper_to_lower(&mut index_name);
                         indexes.insert(index_name, index);
-                    } else {
+                    } else if is_internal_name(schema.name) {
                         // TODO: support autoindex
+                    } else {
                         eprintln!("no sql for index: {:?}", schema.name);
                     }
                 }
@@ -226,42 +468,52 @@ impl Schema {
         }
     }
 
-    #[allow(unused)]
-    pub fn get_index(&self, index: &[u8]) -> Option<&Rc<Index>> {
+    pub fn get_index(&self, index: &[u8]) -> Option<&Arc<Index>> {
         // TODO: use the reference of given index name.
         let mut key = index.to_vec();
         upper_to_lower(&mut key);
         self.indexes.get(&key)
     }
+
+    /// Returns the root page id of the table or the index.
+    ///
+    /// The name is case insensitive. Tables are looked up first.
+    pub fn root_page_of(&self, name: &[u8]) -> Option<PageId> {
+        if let Some(table) = self.get_table(name) {
+            Some(table.root_page_id)
+        } else {
+            self.get_index(name).map(|index| index.root_page_id)
+        }
+    }
 }
 
 #[derive(Debug, PartialEq, Eq)]
 pub struct Index {
     pub root_page_id: PageId,
     pub columns: Vec<ColumnNumber>,
-    pub next: Option<Rc<Index>>,
+    /// The type affinities of the indexed columns.
+    pub type_affinities: Vec<TypeAffinity>,
+    pub next: Option<Arc<Index>>,
 }
 
 impl Index {
+    /// Parse the CREATE INDEX sql.
+    ///
+    /// The last returned value is whether the index can be used to look up rows.
+    /// Indexes with DESC columns or a WHERE clause are not.
     fn parse<'a>(
         sql: &'a [u8],
         root_page_id: PageId,
         table: &Table,
-    ) -> anyhow::Result<(Vec<u8>, MaybeQuotedBytes<'a>, Self)> {
-        let mut parser = Parser::new(sql);
-        let create_index = parse_create_index(&mut parser)
+    ) -> anyhow::Result<(Vec<u8>, MaybeQuotedBytes<'a>, Self, bool)> {
+        let create_index = parse_complete(sql, parse_create_index)
             .map_err(|e| anyhow::anyhow!("parse create index sql: {:?}", e))?;
-        if expect_no_more_token(&mut parser).is_err() {
-            bail!(
-                "create table sql in sqlite_schema contains useless contents at the tail: {:?}",
-                sql
-            );
-        }
         let mut columns = Vec::with_capacity(create_index.columns.len());
+        let mut type_affinities = Vec::with_capacity(create_index.columns.len());
         for column in &create_index.columns {
             // TODO: use the reference of given column name.
             let column_name = column.name.dequote();
-            let Some((column_number, _, _)) = table.get_column(&column_name) else {
+            let Some((column_number, type_affinity, _)) = table.get_column(&column_name) else {
                 bail!(
                     "column {:?} in create index sql is not found in table {:?}",
                     column.name,
@@ -269,25 +521,38 @@ impl Index {
                 );
             };
             columns.push(column_number);
+            type_affinities.push(type_affinity);
         }
+        let lookupable = create_index.where_clause.is_none()
+            && create_index
+                .columns
+                .iter()
+                .all(|column| column.order == SortOrder::Asc);
         Ok((
             create_index.index_name.dequote(),
             create_index.table_name,
             Self {
                 root_page_id,
                 columns,
+                type_affinities,
                 next: None,
             },
+            lookupable,
         ))
     }
 }
 
-#[derive(Debug, PartialEq, Eq)]
+#[derive(Debug, PartialEq)]
 pub struct Column {
     pub name: Vec<u8>,
     pub type_affinity: TypeAffinity,
     pub primary_key: bool,
     pub collation: Collation,
+    /// The value of the column in the rows whose record does not contain the
+    /// column. e.g. rows inserted before ALTER TABLE ADD COLUMN.
+    ///
+    /// None if the DEFAULT value is not supported yet, e.g. CURRENT_TIMESTAMP.
+    pub default_value: Option<Value<'static>>,
 }
 
 #[derive(Debug, PartialEq, Eq)]
@@ -348,27 +613,45 @@ pub fn calc_collation(collation_name: &MaybeQuotedBytes) -> anyhow::Result<Colla
     }
 }
 
-#[derive(Debug, PartialEq, Eq)]
+/// Evaluate the DEFAULT value of a column.
+///
+/// This supports literal values and signed literal values only.
+///
+/// TODO: Support constant expressions and CURRENT_TIMESTAMP etc.
+pub fn calc_default_value(expr: &Expr) -> anyhow::Result<Value<'static>> {
+    match expr {
+        Expr::Null => Ok(Value::Null),
+        Expr::Integer(i) => Ok(Value::Integer(*i)),
+        Expr::Real(f) => Ok(Value::Real(*f)),
+        Expr::Text(text) => Ok(Value::Text(text.dequote().into())),
+        Expr::Blob(hex) => Ok(Value::Blob(hex.decode().into())),
+        // The parser folds unary minus only on decimal numbers, e.g. `-0x10` or
+        // `-'5'` remains. Negation converts the value to a number as 0 - value.
+        Expr::UnaryOperator {
+            operator: UnaryOp::Minus,
+            expr,
+        } => Ok(Value::Integer(0).sqlite_sub(&calc_default_value(expr)?)),
+        _ => bail!("unsupported default value: {:?}", expr),
+    }
+}
+
+#[derive(Debug, PartialEq)]
 pub struct Table {
     pub root_page_id: PageId,
     pub columns: Vec<Column>,
-    pub indexes: Option<Rc<Index>>,
+    /// The index of the column which is an alias of the rowid.
+    pub rowid_alias: Option<usize>,
+    pub indexes: Option<Arc<Index>>,
 }
 
 impl Table {
     fn parse(sql: &[u8], root_page_id: PageId) -> anyhow::Result<(Vec<u8>, Self)> {
-        let mut parser = Parser::new(sql);
-        let create_table = parse_create_table(&mut parser)
+        let create_table = parse_complete(sql, parse_create_table)
             .map_err(|e| anyhow::anyhow!("parse create table sql: {:?}", e))?;
-        if expect_no_more_token(&mut parser).is_err() {
-            bail!(
-                "create table sql in sqlite_schema contains useless contents at the tail: {:?}",
-                sql
-            );
-        }
         let table_name = create_table.table_name.dequote();
         let mut columns: Vec<Column> = Vec::with_capacity(create_table.columns.len());
         let mut has_primary_key = false;
+        let mut rowid_alias = None;
         for column_def in create_table.columns {
             let column_name = column_def.name.dequote();
             let case_insensitive_name = CaseInsensitiveBytes::from(&column_name);
@@ -387,12 +670,30 @@ impl Table {
                     bail!("multiple primary key");
                 }
                 has_primary_key = true;
+                // Only the declared type "INTEGER" makes the column an alias of
+                // the rowid. e.g. "INT PRIMARY KEY" is not.
+                if let [type_name] = column_def.type_name.as_slice() {
+                    if CaseInsensitiveBytes::from(type_name.dequote().as_slice())
+                        .equal_to_lower_bytes(b"integer")
+                    {
+                        rowid_alias = Some(columns.len());
+                    }
+                }
             }
 
             let mut collation = DEFAULT_COLLATION.clone();
+            let mut default_value = Some(Value::Null);
             for constraint in &column_def.constraints {
-                if let ColumnConstraint::Collate(collation_name) = constraint {
-                    collation = calc_collation(collation_name)?;
+                match constraint {
+                    ColumnConstraint::Collate(collation_name) => {
+                        collation = calc_collation(collation_name)?;
+                    }
+                    ColumnConstraint::Default(expr) => {
+                        // Reading a row fails only if it needs the unsupported
+                        // default value.
+                        default_value = calc_default_value(expr).ok();
+                    }
+                    ColumnConstraint::PrinaryKey => {}
                 }
             }
 
@@ -401,6 +702,7 @@ impl Table {
                 type_affinity: calc_type_affinity(&column_def.type_name),
                 primary_key,
                 collation,
+                default_value,
             });
         }
         Ok((
@@ -408,11 +710,36 @@ impl Table {
             Table {
                 root_page_id,
                 columns,
+                rowid_alias,
                 indexes: None,
             },
         ))
     }
 
+    /// Returns the index of the INTEGER PRIMARY KEY column which is an alias of
+    /// the rowid.
+    ///
+    /// The column is stored as NULL in the record.
+    pub fn rowid_alias_column(&self) -> Option<usize> {
+        self.rowid_alias
+    }
+
+    /// Returns the default values of all the columns.
+    pub fn default_values(&self) -> Vec<Option<Value<'static>>> {
+        self.columns
+            .iter()
+            .map(|column| column.default_value.clone())
+            .collect()
+    }
+
+    /// Returns the type affinities of all the columns.
+    pub fn type_affinities(&self) -> Vec<TypeAffinity> {
+        self.columns
+            .iter()
+            .map(|column| column.type_affinity)
+            .collect()
+    }
+
     pub fn get_column(&self, name: &[u8]) -> Option<(ColumnNumber, TypeAffinity, Collation)> {
         let column = CaseInsensitiveBytes::from(name);
         if let Some((i, column)) = self
@@ -421,18 +748,22 @@ impl Table {
             .enumerate()
             .find(|(_, c)| CaseInsensitiveBytes::from(&c.name) == column)
         {
-            let column_number =
-                if column.primary_key && column.type_affinity == TypeAffinity::Integer {
-                    ColumnNumber::RowId
-                } else {
-                    ColumnNumber::Column(i)
-                };
+            let column_number = if self.rowid_alias == Some(i) {
+                ColumnNumber::RowId
+            } else {
+                ColumnNumber::Column(i)
+            };
             Some((
                 column_number,
                 column.type_affinity,
                 column.collation.clone(),
             ))
-        } else if column.equal_to_lower_bytes(b"rowid".as_slice()) {
+        } else if [b"rowid".as_slice(), b"_rowid_", b"oid"]
+            .iter()
+            .any(|rowid_name| column.equal_to_lower_bytes(rowid_name))
+        {
+            // The rowid is accessible by any of these names unless a column
+            // of the table has the name.
             Some((
                 ColumnNumber::RowId,
                 TypeAffinity::Integer,
@@ -447,7 +778,7 @@ impl Table {
         &self,
     ) -> impl Iterator<Item = (ColumnNumber, TypeAffinity, Collation)> + '_ {
         self.columns.iter().enumerate().map(|(i, column)| {
-            if column.primary_key && column.type_affinity == TypeAffinity::Integer {
+            if self.rowid_alias == Some(i) {
                 (
                     ColumnNumber::RowId,
                     TypeAffinity::Integer,
@@ -473,6 +804,7 @@ mod tests {
     use crate::test_utils::*;
     use crate::Connection;
     use crate::Expression;
+    use crate::Plan;
 
     fn generate_schema(filepath: &Path) -> Schema {
         let mut conn = Connection::open(filepath).unwrap();
@@ -481,8 +813,11 @@ mod tests {
             .get_all_columns()
             .map(Expression::Column)
             .collect::<Vec<_>>();
+        let plan = Plan::FullScan {
+            root: schema_table.root_page_id,
+        };
         Schema::generate(
-            Statement::new(&mut conn, schema_table.root_page_id, columns, None),
+            Statement::new(&mut conn, columns, Vec::new(), None, plan, Vec::new()),
             schema_table,
         )
         .unwrap()
@@ -561,6 +896,27 @@ mod tests {
         assert!(schema.get_table(b"invalid").is_none());
     }
 
+    #[test]
+    fn test_internal_tables() {
+        assert!(is_internal_name(b"sqlite_sequence"));
+        assert!(is_internal_name(b"SQLITE_stat1"));
+        assert!(is_internal_name(b"sqlite_autoindex_example_1"));
+        assert!(!is_internal_name(b"sqlite"));
+        assert!(!is_internal_name(b"example"));
+
+        let file = create_sqlite_database(&[
+            "CREATE TABLE example(id TEXT PRIMARY KEY, col);",
+            "CREATE INDEX index1 ON example(col);",
+            "INSERT INTO example(id, col) VALUES ('a', 1), ('b', 2);",
+            "ANALYZE;",
+        ]);
+        let schema = generate_schema(file.path());
+
+        assert!(schema.get_table(b"example").is_some());
+        assert!(schema.get_table(b"sqlite_stat1").is_some());
+        assert!(schema.get_index(b"index1").is_some());
+    }
+
     #[test]
     fn parse_table() {
         let (table_name, table) = Table::parse(
@@ -579,38 +935,45 @@ mod tests {
                         type_affinity: TypeAffinity::Blob,
                         primary_key: false,
                         collation: Collation::Binary,
+                        default_value: Some(Value::Null),
                     },
                     Column {
                         name: b"col1".to_vec(),
                         type_affinity: TypeAffinity::Integer,
                         primary_key: true,
                         collation: Collation::Binary,
+                        default_value: Some(Value::Null),
                     },
                     Column {
                         name: b"col2".to_vec(),
                         type_affinity: TypeAffinity::Text,
                         primary_key: false,
                         collation: Collation::Binary,
+                        default_value: Some(Value::Null),
                     },
                     Column {
                         name: b"co`l3".to_vec(),
                         type_affinity: TypeAffinity::Blob,
                         primary_key: false,
                         collation: Collation::Binary,
+                        default_value: Some(Value::Null),
                     },
                     Column {
                         name: b"col4".to_vec(),
                         type_affinity: TypeAffinity::Real,
                         primary_key: false,
                         collation: Collation::Binary,
+                        default_value: Some(Value::Null),
                     },
                     Column {
                         name: b"col5".to_vec(),
                         type_affinity: TypeAffinity::Numeric,
                         primary_key: false,
                         collation: Collation::Binary,
+                        default_value: Some(Value::Null),
                     },
                 ],
+                rowid_alias: Some(1),
                 indexes: None,
             }
         );
@@ -679,7 +1042,9 @@ mod tests {
                     type_affinity: TypeAffinity::Blob,
                     primary_key: false,
                     collation: Collation::Binary,
+                    default_value: Some(Value::Null),
                 }],
+                rowid_alias: None,
                 indexes: None,
             }
         );
@@ -691,12 +1056,14 @@ mod tests {
                     type_affinity: TypeAffinity::Numeric,
                     primary_key: false,
                     collation: Collation::Binary,
+                    default_value: Some(Value::Null),
                 },
                 Column {
                     name: b"col2".to_vec(),
                     type_affinity: TypeAffinity::Integer,
                     primary_key: false,
                     collation: Collation::Binary,
+                    default_value: Some(Value::Null),
                 }
             ]
         );
@@ -708,24 +1075,28 @@ mod tests {
                     type_affinity: TypeAffinity::Real,
                     primary_key: false,
                     collation: Collation::Binary,
+                    default_value: Some(Value::Null),
                 },
                 Column {
                     name: b"Col2".to_vec(),
                     type_affinity: TypeAffinity::Text,
                     primary_key: true,
                     collation: Collation::Binary,
+                    default_value: Some(Value::Null),
                 },
                 Column {
                     name: b"cOL3".to_vec(),
                     type_affinity: TypeAffinity::Blob,
                     primary_key: false,
                     collation: Collation::Binary,
+                    default_value: Some(Value::Null),
                 },
                 Column {
                     name: b"_".to_vec(),
                     type_affinity: TypeAffinity::Blob,
                     primary_key: false,
                     collation: Collation::Binary,
+                    default_value: Some(Value::Null),
                 }
             ]
         );
@@ -738,36 +1109,42 @@ mod tests {
                     type_affinity: TypeAffinity::Blob,
                     primary_key: false,
                     collation: Collation::Binary,
+                    default_value: Some(Value::Null),
                 },
                 Column {
                     name: b"col1".to_vec(),
                     type_affinity: TypeAffinity::Integer,
                     primary_key: true,
                     collation: Collation::Binary,
+                    default_value: Some(Value::Null),
                 },
                 Column {
                     name: b"col2".to_vec(),
                     type_affinity: TypeAffinity::Text,
                     primary_key: false,
                     collation: Collation::NoCase,
+                    default_value: Some(Value::Null),
                 },
                 Column {
                     name: b"co`l3".to_vec(),
                     type_affinity: TypeAffinity::Blob,
                     primary_key: false,
                     collation: Collation::RTrim,
+                    default_value: Some(Value::Null),
                 },
                 Column {
                     name: b"col4".to_vec(),
                     type_affinity: TypeAffinity::Real,
                     primary_key: false,
                     collation: Collation::Binary,
+                    default_value: Some(Value::Null),
                 },
                 Column {
                     name: b"col5".to_vec(),
                     type_affinity: TypeAffinity::Numeric,
                     primary_key: false,
                     collation: Collation::Binary,
+                    default_value: Some(Value::Null),
                 },
             ]
         );
@@ -840,6 +1217,58 @@ mod tests {
         }
     }
 
+    #[test]
+    fn test_get_table_type_affinity_name_examples() {
+        // https://www.sqlite.org/datatype3.html#affinity_name_examples
+        let examples = [
+            ("INT", TypeAffinity::Integer),
+            ("INTEGER", TypeAffinity::Integer),
+            ("TINYINT", TypeAffinity::Integer),
+            ("SMALLINT", TypeAffinity::Integer),
+            ("MEDIUMINT", TypeAffinity::Integer),
+            ("BIGINT", TypeAffinity::Integer),
+            ("UNSIGNED BIG INT", TypeAffinity::Integer),
+            ("INT2", TypeAffinity::Integer),
+            ("INT8", TypeAffinity::Integer),
+            ("FLOATING POINT", TypeAffinity::Integer),
+            ("CHARACTER(20)", TypeAffinity::Text),
+            ("VARCHAR(255)", TypeAffinity::Text),
+            ("VARYING CHARACTER(255)", TypeAffinity::Text),
+            ("NCHAR(55)", TypeAffinity::Text),
+            ("NATIVE CHARACTER(70)", TypeAffinity::Text),
+            ("NVARCHAR(100)", TypeAffinity::Text),
+            ("TEXT", TypeAffinity::Text),
+            ("CLOB", TypeAffinity::Text),
+            ("BLOB", TypeAffinity::Blob),
+            ("", TypeAffinity::Blob),
+            ("REAL", TypeAffinity::Real),
+            ("DOUBLE", TypeAffinity::Real),
+            ("DOUBLE PRECISION", TypeAffinity::Real),
+            ("FLOAT", TypeAffinity::Real),
+            ("NUMERIC", TypeAffinity::Numeric),
+            ("DECIMAL(10,5)", TypeAffinity::Numeric),
+            ("BOOLEAN", TypeAffinity::Numeric),
+            ("DATE", TypeAffinity::Numeric),
+            ("DATETIME", TypeAffinity::Numeric),
+            ("STRING", TypeAffinity::Numeric),
+        ];
+        let columns = examples
+            .iter()
+            .enumerate()
+            .map(|(i, (type_name, _))| format!("col{} {}", i, type_name))
+            .collect::<Vec<_>>()
+            .join(", ");
+        let create_table = format!("CREATE TABLE example({});", columns);
+        let file = create_sqlite_database(&[create_table.as_str()]);
+        let schema = generate_schema(file.path());
+
+        let table = schema.get_table(b"example").unwrap();
+        assert_eq!(table.columns.len(), examples.len());
+        for (column, (type_name, affinity)) in table.columns.iter().zip(examples.iter()) {
+            assert_eq!(column.type_affinity, *affinity, "type name: {}", type_name);
+        }
+    }
+
     #[test]
     fn test_table_get_column_index() {
         let file = create_sqlite_database(&[
@@ -855,6 +1284,10 @@ mod tests {
         assert_eq!(table.get_column(b"rowid").unwrap().0, ColumnNumber::RowId);
         assert!(table.get_column(b"invalid").is_none());
 
+        assert_eq!(table.get_column(b"_RowId_").unwrap().0, ColumnNumber::RowId);
+        assert_eq!(table.get_column(b"OID").unwrap().0, ColumnNumber::RowId);
+        assert!(table.get_column(b"_rowid").is_none());
+
         let table = schema.get_table(b"example2").unwrap();
         assert_eq!(
             table.get_column(b"col1").unwrap().0,
@@ -868,6 +1301,9 @@ mod tests {
             table.get_column(b"rowid").unwrap().0,
             ColumnNumber::Column(2)
         );
+        // Only the shadowed name refers to the column.
+        assert_eq!(table.get_column(b"_rowid_").unwrap().0, ColumnNumber::RowId);
+        assert_eq!(table.get_column(b"oid").unwrap().0, ColumnNumber::RowId);
         assert!(table.get_column(b"invalid").is_none());
 
         let table = schema.get_table(b"example3").unwrap();
@@ -881,6 +1317,45 @@ mod tests {
         assert_eq!(table.get_column(b"rowid").unwrap().0, ColumnNumber::RowId);
     }
 
+    #[test]
+    fn test_table_rowid_alias_column() {
+        let file = create_sqlite_database(&[
+            "CREATE TABLE example(col1, col2 INTEGER PRIMARY KEY, col3);",
+            "CREATE TABLE example2(col1 INTEGER, col2 TEXT PRIMARY KEY);",
+            "CREATE TABLE example3(col1);",
+            "CREATE TABLE example4(col1 INT PRIMARY KEY, col2);",
+            "CREATE TABLE example5(col1 BIGINT PRIMARY KEY, col2);",
+            "CREATE TABLE example6(col1, col2 \"integer\" PRIMARY KEY);",
+            "CREATE TABLE example7(col1 UNSIGNED INTEGER PRIMARY KEY);",
+        ]);
+        let schema = generate_schema(file.path());
+
+        assert_eq!(
+            schema.get_table(b"example").unwrap().rowid_alias_column(),
+            Some(1)
+        );
+        assert_eq!(
+            schema.get_table(b"example2").unwrap().rowid_alias_column(),
+            None
+        );
+        assert_eq!(
+            schema.get_table(b"example3").unwrap().rowid_alias_column(),
+            None
+        );
+        for table in [b"example4".as_slice(), b"example5", b"example7"] {
+            let table = schema.get_table(table).unwrap();
+            assert_eq!(table.rowid_alias_column(), None);
+            assert_eq!(
+                table.get_column(b"col1").unwrap().0,
+                ColumnNumber::Column(0)
+            );
+        }
+        assert_eq!(
+            schema.get_table(b"example6").unwrap().rowid_alias_column(),
+            Some(1)
+        );
+    }
+
     #[test]
     fn test_table_get_all_columns() {
         let file = create_sqlite_database(&[
@@ -997,14 +1472,16 @@ mod tests {
         ]);
         let schema = generate_schema(file.path());
 
-        let index1 = Rc::new(Index {
+        let index1 = Arc::new(Index {
             root_page_id: 3,
             columns: vec![ColumnNumber::Column(0)],
+            type_affinities: vec![TypeAffinity::Blob],
             next: None,
         });
-        let index2 = Rc::new(Index {
+        let index2 = Arc::new(Index {
             root_page_id: 4,
             columns: vec![ColumnNumber::Column(0), ColumnNumber::Column(1)],
+            type_affinities: vec![TypeAffinity::Blob, TypeAffinity::Blob],
             next: Some(index1.clone()),
         });
         assert_eq!(schema.get_index(b"index1").unwrap(), &index1);
@@ -1020,14 +1497,16 @@ mod tests {
         ]);
         let schema = generate_schema(file.path());
 
-        let index1 = Rc::new(Index {
+        let index1 = Arc::new(Index {
             root_page_id: 3,
             columns: vec![ColumnNumber::Column(0)],
+            type_affinities: vec![TypeAffinity::Blob],
             next: None,
         });
-        let index2 = Rc::new(Index {
+        let index2 = Arc::new(Index {
             root_page_id: 4,
             columns: vec![ColumnNumber::Column(0), ColumnNumber::Column(1)],
+            type_affinities: vec![TypeAffinity::Blob, TypeAffinity::Blob],
             next: Some(index1.clone()),
         });
         assert_eq!(schema.get_index(b"index1").unwrap(), &index1);
@@ -1045,19 +1524,38 @@ mod tests {
 
         let table = schema.get_table(b"example").unwrap();
 
-        let index1 = Rc::new(Index {
+        let index1 = Arc::new(Index {
             root_page_id: 3,
             columns: vec![ColumnNumber::Column(0)],
+            type_affinities: vec![TypeAffinity::Blob],
             next: None,
         });
-        let index2 = Rc::new(Index {
+        let index2 = Arc::new(Index {
             root_page_id: 4,
             columns: vec![ColumnNumber::Column(0), ColumnNumber::Column(1)],
+            type_affinities: vec![TypeAffinity::Blob, TypeAffinity::Blob],
             next: Some(index1.clone()),
         });
         assert_eq!(table.indexes, Some(index2));
     }
 
+    #[test]
+    fn test_root_page_of() {
+        let file = create_sqlite_database(&[
+            "CREATE TABLE example(col);",
+            "CREATE TABLE example2(col1, col2);",
+            "CREATE INDEX index1 ON example2(col2);",
+        ]);
+        let schema = generate_schema(file.path());
+
+        assert_eq!(schema.root_page_of(b"sqlite_schema"), Some(1));
+        assert_eq!(schema.root_page_of(b"example"), Some(2));
+        assert_eq!(schema.root_page_of(b"Example2"), Some(3));
+        assert_eq!(schema.root_page_of(b"index1"), Some(4));
+        assert_eq!(schema.root_page_of(b"INDEX1"), Some(4));
+        assert_eq!(schema.root_page_of(b"invalid"), None);
+    }
+
     #[test]
     fn get_index_case_insensitive() {
         let file = create_sqlite_database(&[
@@ -1090,7 +1588,7 @@ mod tests {
             1,
         )
         .unwrap();
-        let (index_name, table_name, index) =
+        let (index_name, table_name, index, lookupable) =
             Index::parse(b"create index index1 on example(id, col1, col2)", 3, &table).unwrap();
         assert_eq!(index_name, b"index1");
         assert_eq!(table_name, b"example".as_slice().into());
@@ -1103,13 +1601,39 @@ mod tests {
                     ColumnNumber::Column(0),
                     ColumnNumber::Column(2)
                 ],
+                type_affinities: vec![
+                    TypeAffinity::Integer,
+                    TypeAffinity::Blob,
+                    TypeAffinity::Blob
+                ],
                 next: None,
             }
         );
+        assert!(lookupable);
+        // DESC column
+        let (_, _, index, lookupable) = Index::parse(
+            b"create unique index index1 on example(col1, col2 desc)",
+            3,
+            &table,
+        )
+        .unwrap();
+        assert_eq!(
+            index.columns,
+            vec![ColumnNumber::Column(0), ColumnNumber::Column(2)]
+        );
+        assert!(!lookupable);
+        // partial index
+        let (_, _, _, lookupable) = Index::parse(
+            b"create index index1 on example(col1) where col2 > 0",
+            3,
+            &table,
+        )
+        .unwrap();
+        assert!(!lookupable);
         // unknown column
         assert!(Index::parse(b"create index index1 on example(col1, invalid)", 3, &table).is_err());
         // unknown table
-        let (_, table_name, _) =
+        let (_, table_name, _, _) =
             Index::parse(b"create index index1 on invalid(col1)", 3, &table).unwrap();
         assert_eq!(table_name, b"invalid".as_slice().into());
     }
@@ -22,11 +22,8 @@ use tempfile::NamedTempFile;
 use crate::btree::BtreeContext;
 use crate::pager::PageId;
 use crate::pager::Pager;
-use crate::schema::Schema;
 use crate::Connection;
 use crate::DatabaseHeader;
-use crate::Expression;
-use crate::Statement;
 use crate::DATABASE_HEADER_SIZE;
 
 pub fn create_sqlite_database(queries: &[&str]) -> NamedTempFile {
@@ -39,17 +36,39 @@ pub fn create_sqlite_database(queries: &[&str]) -> NamedTempFile {
     file
 }
 
+/// Create a database which reserves the bytes at the tail of each page.
+pub fn create_sqlite_database_with_reserved_bytes(reserved: u8, queries: &[&str]) -> NamedTempFile {
+    let file = NamedTempFile::new().unwrap();
+    let conn = rusqlite::Connection::open(file.path()).unwrap();
+    // SQLITE_FCNTL_RESERVE_BYTES
+    const RESERVE_BYTES: std::os::raw::c_int = 38;
+    let mut n_reserve = reserved as std::os::raw::c_int;
+    let rc = unsafe {
+        rusqlite::ffi::sqlite3_file_control(
+            conn.handle(),
+            std::ffi::CStr::from_bytes_with_nul(b"main\0")
+                .unwrap()
+                .as_ptr(),
+            RESERVE_BYTES,
+            &mut n_reserve as *mut _ as *mut std::os::raw::c_void,
+        )
+    };
+    assert_eq!(rc, rusqlite::ffi::SQLITE_OK);
+    for query in queries {
+        conn.execute(query, []).unwrap();
+    }
+    conn.close().unwrap();
+    file
+}
+
 pub fn create_pager(file: File) -> anyhow::Result<Pager> {
-    let mut header_buf = [0_u8; DATABASE_HEADER_SIZE];
-    file.read_exact_at(&mut header_buf, 0)?;
-    let header = DatabaseHeader::from(&header_buf);
-    Pager::new(file, header.pagesize() as usize)
+    Pager::new(file)
 }
 
 pub fn create_empty_pager(file_content: &[u8], pagesize: usize) -> Pager {
     let file = NamedTempFile::new().unwrap();
     file.as_file().write_all_at(file_content, 0).unwrap();
-    Pager::new(file.as_file().try_clone().unwrap(), pagesize).unwrap()
+    Pager::with_pagesize(file.as_file().try_clone().unwrap(), pagesize).unwrap()
 }
 
 pub fn load_btree_context(file: &File) -> anyhow::Result<BtreeContext> {
@@ -68,30 +87,10 @@ pub fn buffer_to_hex(buf: &[u8]) -> String {
 
 pub fn find_table_page_id(table: &str, filepath: &Path) -> PageId {
     let mut conn = Connection::open(filepath).unwrap();
-    let schema_table = Schema::schema_table();
-    let columns = schema_table
-        .get_all_columns()
-        .map(Expression::Column)
-        .collect::<Vec<_>>();
-    let schema = Schema::generate(
-        Statement::new(&mut conn, schema_table.root_page_id, columns, None),
-        schema_table,
-    )
-    .unwrap();
-    schema.get_table(table.as_bytes()).unwrap().root_page_id
+    conn.root_page_of(table).unwrap().unwrap()
 }
 
 pub fn find_index_page_id(index: &str, filepath: &Path) -> PageId {
     let mut conn = Connection::open(filepath).unwrap();
-    let schema_table = Schema::schema_table();
-    let columns = schema_table
-        .get_all_columns()
-        .map(Expression::Column)
-        .collect::<Vec<_>>();
-    let schema = Schema::generate(
-        Statement::new(&mut conn, schema_table.root_page_id, columns, None),
-        schema_table,
-    )
-    .unwrap();
-    schema.get_index(index.as_bytes()).unwrap().root_page_id
+    conn.root_page_of(index).unwrap().unwrap()
 }
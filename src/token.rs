@@ -31,11 +31,11 @@ static CHAR_LOOKUP_TABLE: [u8; 256] = [
     0xFF, b' ', b' ', 0xFF, b' ', b' ', 0xFF, 0xFF, // 0x08 - 0x0F
     0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, // 0x10 - 0x17
     0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, // 0x18 - 0x1F
-    b' ', b'!', 0x05, 0xFF, 0x04, 0xFF, 0xFF, 0x05, // 0x20 - 0x27
-    b'(', b')', b'*', b'+', b',', b'-', b'.', 0xFF, // 0x28 - 0x2F
+    b' ', b'!', 0x05, 0xFF, 0x04, b'%', 0xFF, 0x05, // 0x20 - 0x27
+    b'(', b')', b'*', b'+', b',', b'-', b'.', b'/', // 0x28 - 0x2F
     0x03, 0x03, 0x03, 0x03, 0x03, 0x03, 0x03, 0x03, // 0x30 - 0x37
-    0x03, 0x03, 0xFF, b';', b'<', b'=', b'>', 0xFF, // 0x38 - 0x3F
-    0xFF, 0x01, 0x01, 0x01, 0x01, 0x01, 0x01, 0x01, // 0x40 - 0x47
+    0x03, 0x03, b':', b';', b'<', b'=', b'>', b'?', // 0x38 - 0x3F
+    b'@', 0x01, 0x01, 0x01, 0x01, 0x01, 0x01, 0x01, // 0x40 - 0x47
     0x01, 0x01, 0x01, 0x01, 0x01, 0x01, 0x01, 0x01, // 0x48 - 0x4F
     0x01, 0x01, 0x01, 0x01, 0x01, 0x01, 0x01, 0x01, // 0x50 - 0x57
     0x00, 0x01, 0x01, 0x06, 0xFF, 0xFF, 0xFF, 0x02, // 0x58 - 0x5F
@@ -64,18 +64,66 @@ static CHAR_LOOKUP_TABLE: [u8; 256] = [
 #[derive(Debug, PartialEq, Clone, Copy)]
 pub enum Token<'a> {
     // Keywords
+    Add,
+    All,
+    Alter,
+    And,
     As,
+    Asc,
+    Between,
+    By,
+    Case,
     Cast,
     Collate,
+    Column,
     Create,
+    CurrentDate,
+    CurrentTime,
+    CurrentTimestamp,
+    Default,
+    Delete,
+    Desc,
+    Distinct,
+    Else,
+    End,
+    Escape,
+    Except,
+    Exists,
+    Explain,
     From,
+    Glob,
+    Group,
+    Having,
+    If,
+    In,
     Index,
+    Inner,
+    Intersect,
+    Is,
+    Join,
     Key,
+    Left,
+    Like,
+    Limit,
+    Not,
     Null,
+    Offset,
     On,
+    Or,
+    Order,
+    Outer,
     Primary,
+    Regexp,
+    Rename,
     Select,
+    Set,
     Table,
+    Then,
+    To,
+    Union,
+    Unique,
+    Update,
+    When,
     Where,
 
     // Symbols
@@ -87,6 +135,8 @@ pub enum Token<'a> {
     Comma,
     Minus,
     Dot,
+    Slash,
+    Percent,
     Semicolon,
     Tilda,
 
@@ -112,11 +162,64 @@ pub enum Token<'a> {
     Blob(HexedBytes<'a>),
     // Only contains 0-9 chars.
     Integer(&'a [u8]),
+    // Only contains hexadecimal chars following "0x".
+    HexInteger(&'a [u8]),
     Float(&'a [u8]),
+    /// `?`, `?NNN`, `:AAAA`, `@AAAA` or `$AAAA` including the prefix.
+    BindParameter(&'a [u8]),
     Illegal,
 }
 
-pub fn get_token(input: &[u8]) -> Option<(usize, Token)> {
+impl Token<'_> {
+    /// Whether the token is a keyword which is parsed as an identifier where
+    /// the keyword does not fit but a name does.
+    ///
+    /// This is the same as `%fallback ID` of SQLite grammar. e.g.
+    /// `CREATE TABLE like (like)` is valid.
+    pub fn is_fallback_keyword(&self) -> bool {
+        matches!(
+            self,
+            Token::Asc
+                | Token::By
+                | Token::Column
+                | Token::CurrentDate
+                | Token::CurrentTime
+                | Token::CurrentTimestamp
+                | Token::Desc
+                | Token::End
+                | Token::Explain
+                | Token::Glob
+                | Token::If
+                | Token::Inner
+                | Token::Left
+                | Token::Like
+                | Token::Offset
+                | Token::Outer
+                | Token::Regexp
+                | Token::Rename
+        )
+    }
+
+    /// Whether the token is a keyword of a join operator.
+    ///
+    /// These are fallback keywords, but not a bare alias of a result column.
+    pub fn is_join_keyword(&self) -> bool {
+        matches!(self, Token::Inner | Token::Left | Token::Outer)
+    }
+
+    /// Whether the token is CURRENT_DATE, CURRENT_TIME or CURRENT_TIMESTAMP.
+    ///
+    /// These are fallback keywords, but an expression takes them as the
+    /// current time rather than a column name.
+    pub fn is_current_time(&self) -> bool {
+        matches!(
+            self,
+            Token::CurrentDate | Token::CurrentTime | Token::CurrentTimestamp
+        )
+    }
+}
+
+pub fn get_token(input: &[u8]) -> Option<(usize, Token<'_>)> {
     if input.is_empty() {
         return None;
     }
@@ -141,7 +244,19 @@ pub fn get_token(input: &[u8]) -> Option<(usize, Token)> {
         b'*' => Some((1, Token::Asterisk)),
         b'+' => Some((1, Token::Plus)),
         b',' => Some((1, Token::Comma)),
-        b'-' => Some((1, Token::Minus)),
+        b'-' => {
+            if input.len() >= 2 && input[1] == b'-' {
+                // A line comment is a space. The newline is not included.
+                let len = input
+                    .iter()
+                    .skip(2)
+                    .position(|&byte| byte == b'\n')
+                    .map_or(input.len(), |i| i + 2);
+                Some((len, Token::Space))
+            } else {
+                Some((1, Token::Minus))
+            }
+        }
         b'.' => {
             if input.len() >= 2 && input[1].is_ascii_digit() {
                 let (len, valid) = len_float(input);
@@ -154,6 +269,18 @@ pub fn get_token(input: &[u8]) -> Option<(usize, Token)> {
                 Some((1, Token::Dot))
             }
         }
+        b'/' => {
+            if input.len() >= 2 && input[1] == b'*' {
+                // A block comment is a space.
+                match input[2..].windows(2).position(|w| w == b"*/") {
+                    Some(i) => Some((i + 4, Token::Space)),
+                    None => Some((input.len(), Token::Illegal)),
+                }
+            } else {
+                Some((1, Token::Slash))
+            }
+        }
+        b'%' => Some((1, Token::Percent)),
         b';' => Some((1, Token::Semicolon)),
         b'<' => {
             if input.len() >= 2 {
@@ -188,6 +315,21 @@ pub fn get_token(input: &[u8]) -> Option<(usize, Token)> {
             }
         }
         b'~' => Some((1, Token::Tilda)),
+        b'?' => {
+            let len = 1 + input[1..]
+                .iter()
+                .take_while(|byte| byte.is_ascii_digit())
+                .count();
+            Some((len, Token::BindParameter(&input[..len])))
+        }
+        b':' | b'@' | CHAR_DOLLAR => {
+            let len = len_identifier(input);
+            if len > 1 {
+                Some((len, Token::BindParameter(&input[..len])))
+            } else {
+                Some((1, Token::Illegal))
+            }
+        }
         CHAR_X => {
             if input.len() >= 2 && input[1] == b'\'' {
                 let mut iter = input.iter().skip(2).enumerate();
@@ -222,34 +364,99 @@ pub fn get_token(input: &[u8]) -> Option<(usize, Token)> {
         CHAR_ALPHABET | CHAR_UNDERSCORE => {
             let len = len_identifier(input);
             let id = &input[..len];
-            const MAX_KEYWORD_LEN: usize = 7;
+            const MAX_KEYWORD_LEN: usize = 9;
             if len <= MAX_KEYWORD_LEN {
                 let mut lower_id = [0; MAX_KEYWORD_LEN];
                 for (i, &byte) in id.iter().take(MAX_KEYWORD_LEN).enumerate() {
                     lower_id[i] = UPPER_TO_LOWER[byte as usize];
                 }
                 match &lower_id {
-                    b"as\0\0\0\0\0" => Some((len, Token::As)),
-                    b"cast\0\0\0" => Some((len, Token::Cast)),
-                    b"collate" => Some((len, Token::Collate)),
-                    b"create\0" => Some((len, Token::Create)),
-                    b"from\0\0\0" => Some((len, Token::From)),
-                    b"index\0\0" => Some((len, Token::Index)),
-                    b"key\0\0\0\0" => Some((len, Token::Key)),
-                    b"null\0\0\0" => Some((len, Token::Null)),
-                    b"on\0\0\0\0\0" => Some((len, Token::On)),
-                    b"primary" => Some((len, Token::Primary)),
-                    b"select\0" => Some((len, Token::Select)),
-                    b"table\0\0" => Some((len, Token::Table)),
-                    b"where\0\0" => Some((len, Token::Where)),
+                    b"add\0\0\0\0\0\0" => Some((len, Token::Add)),
+                    b"all\0\0\0\0\0\0" => Some((len, Token::All)),
+                    b"alter\0\0\0\0" => Some((len, Token::Alter)),
+                    b"and\0\0\0\0\0\0" => Some((len, Token::And)),
+                    b"as\0\0\0\0\0\0\0" => Some((len, Token::As)),
+                    b"asc\0\0\0\0\0\0" => Some((len, Token::Asc)),
+                    b"between\0\0" => Some((len, Token::Between)),
+                    b"by\0\0\0\0\0\0\0" => Some((len, Token::By)),
+                    b"case\0\0\0\0\0" => Some((len, Token::Case)),
+                    b"cast\0\0\0\0\0" => Some((len, Token::Cast)),
+                    b"collate\0\0" => Some((len, Token::Collate)),
+                    b"column\0\0\0" => Some((len, Token::Column)),
+                    b"create\0\0\0" => Some((len, Token::Create)),
+                    b"default\0\0" => Some((len, Token::Default)),
+                    b"delete\0\0\0" => Some((len, Token::Delete)),
+                    b"desc\0\0\0\0\0" => Some((len, Token::Desc)),
+                    b"distinct\0" => Some((len, Token::Distinct)),
+                    b"else\0\0\0\0\0" => Some((len, Token::Else)),
+                    b"end\0\0\0\0\0\0" => Some((len, Token::End)),
+                    b"escape\0\0\0" => Some((len, Token::Escape)),
+                    b"except\0\0\0" => Some((len, Token::Except)),
+                    b"exists\0\0\0" => Some((len, Token::Exists)),
+                    b"explain\0\0" => Some((len, Token::Explain)),
+                    b"from\0\0\0\0\0" => Some((len, Token::From)),
+                    b"glob\0\0\0\0\0" => Some((len, Token::Glob)),
+                    b"group\0\0\0\0" => Some((len, Token::Group)),
+                    b"having\0\0\0" => Some((len, Token::Having)),
+                    b"if\0\0\0\0\0\0\0" => Some((len, Token::If)),
+                    b"in\0\0\0\0\0\0\0" => Some((len, Token::In)),
+                    b"index\0\0\0\0" => Some((len, Token::Index)),
+                    b"inner\0\0\0\0" => Some((len, Token::Inner)),
+                    b"intersect" => Some((len, Token::Intersect)),
+                    b"is\0\0\0\0\0\0\0" => Some((len, Token::Is)),
+                    b"join\0\0\0\0\0" => Some((len, Token::Join)),
+                    b"key\0\0\0\0\0\0" => Some((len, Token::Key)),
+                    b"left\0\0\0\0\0" => Some((len, Token::Left)),
+                    b"like\0\0\0\0\0" => Some((len, Token::Like)),
+                    b"limit\0\0\0\0" => Some((len, Token::Limit)),
+                    b"not\0\0\0\0\0\0" => Some((len, Token::Not)),
+                    b"null\0\0\0\0\0" => Some((len, Token::Null)),
+                    b"offset\0\0\0" => Some((len, Token::Offset)),
+                    b"on\0\0\0\0\0\0\0" => Some((len, Token::On)),
+                    b"or\0\0\0\0\0\0\0" => Some((len, Token::Or)),
+                    b"order\0\0\0\0" => Some((len, Token::Order)),
+                    b"outer\0\0\0\0" => Some((len, Token::Outer)),
+                    b"primary\0\0" => Some((len, Token::Primary)),
+                    b"regexp\0\0\0" => Some((len, Token::Regexp)),
+                    b"rename\0\0\0" => Some((len, Token::Rename)),
+                    b"select\0\0\0" => Some((len, Token::Select)),
+                    b"set\0\0\0\0\0\0" => Some((len, Token::Set)),
+                    b"table\0\0\0\0" => Some((len, Token::Table)),
+                    b"then\0\0\0\0\0" => Some((len, Token::Then)),
+                    b"to\0\0\0\0\0\0\0" => Some((len, Token::To)),
+                    b"union\0\0\0\0" => Some((len, Token::Union)),
+                    b"unique\0\0\0" => Some((len, Token::Unique)),
+                    b"update\0\0\0" => Some((len, Token::Update)),
+                    b"when\0\0\0\0\0" => Some((len, Token::When)),
+                    b"where\0\0\0\0" => Some((len, Token::Where)),
                     _ => Some((len, Token::Identifier(id.into()))),
                 }
+            } else if id.eq_ignore_ascii_case(b"current_date") {
+                Some((len, Token::CurrentDate))
+            } else if id.eq_ignore_ascii_case(b"current_time") {
+                Some((len, Token::CurrentTime))
+            } else if id.eq_ignore_ascii_case(b"current_timestamp") {
+                Some((len, Token::CurrentTimestamp))
             } else {
                 Some((len, Token::Identifier(id.into())))
             }
         }
         CHAR_DIGIT => {
-            // TODO: support hexadecimal.
+            if input[0] == b'0'
+                && input.len() >= 3
+                && (input[1] == b'x' || input[1] == b'X')
+                && input[2].is_ascii_hexdigit()
+            {
+                let mut len = 3;
+                for &byte in input.iter().skip(len) {
+                    if byte.is_ascii_hexdigit() {
+                        len += 1;
+                    } else {
+                        break;
+                    }
+                }
+                return Some((len, Token::HexInteger(&input[2..len])));
+            }
             let mut len = 1;
             for &byte in input.iter().skip(len) {
                 // NOTE: u8::is_ascii_digit() is faster than CHAR_LOOKUP_TABLE.
@@ -371,6 +578,8 @@ mod tests {
             (',', Token::Comma),
             ('-', Token::Minus),
             ('.', Token::Dot),
+            ('/', Token::Slash),
+            ('%', Token::Percent),
             (';', Token::Semicolon),
             ('~', Token::Tilda),
         ] {
@@ -381,6 +590,32 @@ mod tests {
         }
     }
 
+    #[test]
+    fn test_bind_parameter() {
+        for (input, len) in [
+            (b"?".as_slice(), 1),
+            (b"? ", 1),
+            (b"?1", 2),
+            (b"?123abc", 4),
+            (b":name", 5),
+            (b":1a_$ ", 5),
+            (b"@name=", 5),
+            (b"$name)", 5),
+            (b"$a$b", 4),
+        ] {
+            assert_eq!(
+                get_token(input),
+                Some((len, Token::BindParameter(&input[..len]))),
+                "{:?}",
+                input
+            );
+        }
+
+        assert_eq!(get_token(b":"), Some((1, Token::Illegal)));
+        assert_eq!(get_token(b"@ "), Some((1, Token::Illegal)));
+        assert_eq!(get_token(b"$?"), Some((1, Token::Illegal)));
+    }
+
     #[test]
     fn test_space() {
         assert_eq!(get_token(b" a"), Some((1, Token::Space)));
@@ -396,6 +631,27 @@ mod tests {
         assert_eq!(get_token(b"  \t\n\x0b\x0c\r\x0e"), Some((7, Token::Space)));
     }
 
+    #[test]
+    fn test_comment() {
+        assert_eq!(get_token(b"-- comment\nabc"), Some((10, Token::Space)));
+        assert_eq!(get_token(b"-- comment"), Some((10, Token::Space)));
+        assert_eq!(get_token(b"--"), Some((2, Token::Space)));
+        assert_eq!(get_token(b"--\n"), Some((2, Token::Space)));
+        assert_eq!(get_token(b"- -"), Some((1, Token::Minus)));
+        assert_eq!(get_token(b"-"), Some((1, Token::Minus)));
+
+        assert_eq!(get_token(b"/* comment */abc"), Some((13, Token::Space)));
+        assert_eq!(get_token(b"/* multi\nline */"), Some((16, Token::Space)));
+        assert_eq!(get_token(b"/**/"), Some((4, Token::Space)));
+        assert_eq!(get_token(b"/* a */ b */"), Some((7, Token::Space)));
+        assert_eq!(get_token(b"/* -- */"), Some((8, Token::Space)));
+        assert_eq!(get_token(b"/*/"), Some((3, Token::Illegal)));
+        assert_eq!(get_token(b"/* comment"), Some((10, Token::Illegal)));
+        assert_eq!(get_token(b"/*"), Some((2, Token::Illegal)));
+        assert_eq!(get_token(b"/ *"), Some((1, Token::Slash)));
+        assert_eq!(get_token(b"/"), Some((1, Token::Slash)));
+    }
+
     #[test]
     fn test_integer() {
         let mut test_cases = Vec::new();
@@ -429,6 +685,38 @@ mod tests {
         }
     }
 
+    #[test]
+    fn test_hex_integer() {
+        for literal in [
+            "0x0",
+            "0x00",
+            "0x0123456789",
+            "0xabcdef",
+            "0XABCDEF",
+            "0xFfFfFfFfFfFfFfFf",
+            "0x10000000000000000",
+        ] {
+            assert_eq!(
+                get_token(literal.as_bytes()),
+                Some((literal.len(), Token::HexInteger(&literal.as_bytes()[2..]))),
+                "literal: {}",
+                literal
+            );
+            let input = format!("{literal}xyz ");
+            assert_eq!(
+                get_token(input.as_bytes()),
+                Some((literal.len(), Token::HexInteger(&literal.as_bytes()[2..]))),
+                "input: {}",
+                input
+            );
+        }
+        // Not hexadecimal.
+        assert_eq!(get_token(b"0x"), Some((1, Token::Integer(b"0"))));
+        assert_eq!(get_token(b"0xg"), Some((1, Token::Integer(b"0"))));
+        assert_eq!(get_token(b"00x1"), Some((2, Token::Integer(b"00"))));
+        assert_eq!(get_token(b"1x1"), Some((1, Token::Integer(b"1"))));
+    }
+
     #[test]
     fn test_float() {
         for literal in [
@@ -672,18 +960,66 @@ mod tests {
     #[test]
     fn test_keywords() {
         for (keyword, token) in [
+            ("add", Token::Add),
+            ("all", Token::All),
+            ("alter", Token::Alter),
+            ("and", Token::And),
             ("as", Token::As),
+            ("asc", Token::Asc),
+            ("between", Token::Between),
+            ("by", Token::By),
+            ("case", Token::Case),
             ("cast", Token::Cast),
             ("collate", Token::Collate),
+            ("column", Token::Column),
             ("create", Token::Create),
+            ("current_date", Token::CurrentDate),
+            ("current_time", Token::CurrentTime),
+            ("current_timestamp", Token::CurrentTimestamp),
+            ("default", Token::Default),
+            ("delete", Token::Delete),
+            ("desc", Token::Desc),
+            ("distinct", Token::Distinct),
+            ("else", Token::Else),
+            ("end", Token::End),
+            ("escape", Token::Escape),
+            ("except", Token::Except),
+            ("exists", Token::Exists),
+            ("explain", Token::Explain),
             ("from", Token::From),
+            ("glob", Token::Glob),
+            ("group", Token::Group),
+            ("having", Token::Having),
+            ("if", Token::If),
+            ("in", Token::In),
             ("index", Token::Index),
+            ("inner", Token::Inner),
+            ("intersect", Token::Intersect),
+            ("is", Token::Is),
+            ("join", Token::Join),
             ("key", Token::Key),
+            ("left", Token::Left),
+            ("like", Token::Like),
+            ("limit", Token::Limit),
+            ("not", Token::Not),
             ("null", Token::Null),
+            ("offset", Token::Offset),
             ("on", Token::On),
+            ("or", Token::Or),
+            ("order", Token::Order),
+            ("outer", Token::Outer),
             ("primary", Token::Primary),
+            ("regexp", Token::Regexp),
+            ("rename", Token::Rename),
             ("select", Token::Select),
+            ("set", Token::Set),
             ("table", Token::Table),
+            ("then", Token::Then),
+            ("to", Token::To),
+            ("union", Token::Union),
+            ("unique", Token::Unique),
+            ("update", Token::Update),
+            ("when", Token::When),
             ("where", Token::Where),
         ] {
             assert_eq!(get_token(keyword.as_bytes()), Some((keyword.len(), token)));
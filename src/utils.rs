@@ -21,7 +21,8 @@ const VARINT_VAR_MASK: u8 = !VARINT_FLAG_MASK;
 
 /// Parse varint.
 ///
-/// Return None if the buffer is not valid varint.
+/// Returns the value and the number of consumed bytes. A varint is at most 9
+/// bytes. Return None if the buffer ends before the varint is complete.
 pub fn parse_varint(buf: &[u8]) -> Option<(i64, usize)> {
     if valid_varint_buffer(buf) {
         Some(unsafe_parse_varint(buf))
@@ -585,6 +586,25 @@ pub fn hex_to_int(h: u8) -> u8 {
     h & 0x0f
 }
 
+/// Parse hexadecimal digits as a 64-bit two's-complement integer.
+///
+/// Leading zeros are ignored. Returns [None] if there are more than 16
+/// significant digits. The input must consist of valid hexadecimal characters.
+///
+/// This is the hexadecimal part of sqlite3DecOrHexToI64().
+pub fn parse_hex_integer(input: &[u8]) -> Option<i64> {
+    let n_zeros = input.iter().take_while(|&&b| b == b'0').count();
+    let digits = &input[n_zeros..];
+    if digits.len() > 16 {
+        return None;
+    }
+    let mut v: u64 = 0;
+    for &b in digits {
+        v = (v << 4) | hex_to_int(b) as u64;
+    }
+    Some(v as i64)
+}
+
 /// A wrapper for bytes which is hexadecimal data.
 #[derive(Debug, PartialEq, Eq, Copy, Clone)]
 pub struct HexedBytes<'a>(&'a [u8]);
@@ -652,7 +672,12 @@ mod tests {
             assert_eq!(result, v, "buf: {buf:?}");
 
             // valid as varint
-            assert!(parse_varint(buf).is_some());
+            assert_eq!(parse_varint(buf), Some((v, buf.len())), "buf: {buf:?}");
+            // truncated
+            assert!(
+                parse_varint(&buf[..buf.len() - 1]).is_none(),
+                "buf: {buf:?}"
+            );
         }
     }
 
@@ -1176,6 +1201,18 @@ mod tests {
         }
     }
 
+    #[test]
+    fn test_parse_hex_integer() {
+        assert_eq!(parse_hex_integer(b"0"), Some(0));
+        assert_eq!(parse_hex_integer(b"ff"), Some(255));
+        assert_eq!(parse_hex_integer(b"AbCdEf"), Some(0xabcdef));
+        assert_eq!(parse_hex_integer(b"7fffffffffffffff"), Some(i64::MAX));
+        assert_eq!(parse_hex_integer(b"8000000000000000"), Some(i64::MIN));
+        assert_eq!(parse_hex_integer(b"ffffffffffffffff"), Some(-1));
+        assert_eq!(parse_hex_integer(b"0000ffffffffffffffff"), Some(-1));
+        assert_eq!(parse_hex_integer(b"10000000000000000"), None);
+    }
+
     #[test]
     fn test_hexed_bytes() {
         let mut test_cases = Vec::new();
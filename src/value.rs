@@ -13,9 +13,11 @@
 // limitations under the License.
 
 use std::cmp::Ordering;
+use std::fmt::Display;
 use std::io::Write;
 use std::ops::Deref;
 
+use crate::utils::is_space;
 use crate::utils::parse_float;
 use crate::utils::parse_integer;
 use crate::utils::CaseInsensitiveBytes;
@@ -46,6 +48,42 @@ pub enum Collation {
     RTrim,
 }
 
+/// Text encoding of the database.
+///
+/// https://www.sqlite.org/fileformat2.html#text_encoding
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub enum TextEncoding {
+    Utf8,
+    Utf16le,
+    Utf16be,
+}
+
+impl TextEncoding {
+    /// Convert the text encoding number in the database header.
+    pub fn from_header(v: u32) -> Option<Self> {
+        match v {
+            1 => Some(Self::Utf8),
+            2 => Some(Self::Utf16le),
+            3 => Some(Self::Utf16be),
+            _ => None,
+        }
+    }
+
+    /// Convert the text in this encoding to a UTF-8 string.
+    ///
+    /// Invalid sequences are replaced with U+FFFD.
+    pub fn decode(&self, buf: &[u8]) -> String {
+        let from_bytes = match self {
+            Self::Utf8 => return String::from_utf8_lossy(buf).into_owned(),
+            Self::Utf16le => u16::from_le_bytes,
+            Self::Utf16be => u16::from_be_bytes,
+        };
+        char::decode_utf16(buf.chunks_exact(2).map(|c| from_bytes([c[0], c[1]])))
+            .map(|r| r.unwrap_or(char::REPLACEMENT_CHARACTER))
+            .collect()
+    }
+}
+
 #[derive(Debug, Clone)]
 pub enum Buffer<'a> {
     Owned(Vec<u8>),
@@ -111,6 +149,61 @@ impl<'a> Value<'a> {
         }
     }
 
+    /// Format the value as a SQL literal. e.g. `'it''s'` or `X'0A0B'`.
+    ///
+    /// This is the same as the quote() function and the "quote" mode of the
+    /// sqlite3 shell.
+    pub fn to_sql_literal(&self) -> String {
+        match self {
+            Value::Null => "NULL".to_string(),
+            Value::Integer(i) => i.to_string(),
+            // The literal overflows to the infinity.
+            Value::Real(d) if d.is_infinite() => {
+                if d.is_sign_positive() {
+                    "9.0e+999".to_string()
+                } else {
+                    "-9.0e+999".to_string()
+                }
+            }
+            Value::Real(d) => {
+                let literal = format_real(*d);
+                if literal.parse::<f64>().ok() == Some(*d) {
+                    literal
+                } else {
+                    // More digits are required to reproduce the value.
+                    let formatted = format!("{:.20e}", d);
+                    let (mantissa, exponent) = formatted.split_once('e').unwrap();
+                    format!("{mantissa}{}", format_exponent(exponent.parse().unwrap()))
+                }
+            }
+            Value::Text(buf) => {
+                let text = String::from_utf8_lossy(buf);
+                format!("'{}'", text.replace('\'', "''"))
+            }
+            Value::Blob(buf) => {
+                let mut literal = String::with_capacity(3 + 2 * buf.len());
+                literal.push_str("X'");
+                for v in buf.iter() {
+                    literal.push_str(&format!("{v:02X}"));
+                }
+                literal.push('\'');
+                literal
+            }
+        }
+    }
+
+    /// Convert the text value stored in the encoding to UTF-8.
+    ///
+    /// Text in UTF-8 is returned as is without validation.
+    pub fn decode_text(self, encoding: TextEncoding) -> Value<'a> {
+        match (self, encoding) {
+            (Value::Text(buf), TextEncoding::Utf16le | TextEncoding::Utf16be) => {
+                Value::Text(Buffer::Owned(encoding.decode(&buf).into_bytes()))
+            }
+            (v, _) => v,
+        }
+    }
+
     /// Convert the text value to a numeric value if it is well-formed.
     /// Otherwise, return the original value.
     pub fn apply_numeric_affinity(self) -> Value<'a> {
@@ -138,6 +231,39 @@ impl<'a> Value<'a> {
         }
     }
 
+    /// Convert the integer value to a real value.
+    ///
+    /// SQLite may store a whole number in a REAL column as an integer to save
+    /// space. Such values must be read back as real values.
+    pub fn apply_real_affinity(self) -> Value<'a> {
+        match self {
+            Value::Integer(i) => Value::Real(i as f64),
+            v => v,
+        }
+    }
+
+    /// Convert the value to the one which owns its buffer.
+    pub fn into_owned(self) -> Value<'static> {
+        match self {
+            Value::Null => Value::Null,
+            Value::Integer(i) => Value::Integer(i),
+            Value::Real(d) => Value::Real(d),
+            Value::Text(buf) => Value::Text(Buffer::Owned(buf.into_vec())),
+            Value::Blob(buf) => Value::Blob(Buffer::Owned(buf.into_vec())),
+        }
+    }
+
+    /// Returns the value which borrows the buffer of this value.
+    pub fn as_borrowed(&self) -> Value<'_> {
+        match self {
+            Value::Null => Value::Null,
+            Value::Integer(i) => Value::Integer(*i),
+            Value::Real(d) => Value::Real(*d),
+            Value::Text(buf) => Value::Text(Buffer::Ref(buf)),
+            Value::Blob(buf) => Value::Blob(Buffer::Ref(buf)),
+        }
+    }
+
     /// Convert the value to a text value.
     ///
     /// For [Value::Text] and [Value::Blob] values, this just changes the type
@@ -191,6 +317,22 @@ impl<'a> Value<'a> {
         }
     }
 
+    /// Convert the value to a boolean.
+    ///
+    /// The value is true if it is not zero as a numeric value. Text and blob
+    /// are converted as a real number.
+    pub fn as_bool(&self) -> Option<bool> {
+        match self {
+            Value::Null => None,
+            Value::Integer(i) => Some(*i != 0),
+            Value::Real(d) => Some(*d != 0.0),
+            Value::Text(buf) | Value::Blob(buf) => {
+                let (_, _, d) = parse_float(buf);
+                Some(d != 0.0)
+            }
+        }
+    }
+
     /// Convert the value to text and return the [Buffer].
     ///
     /// This does not support [Value::Null] values.
@@ -276,6 +418,234 @@ impl<'a> Value<'a> {
             }
         }
     }
+
+    /// Compare with the other value using the binary collation.
+    ///
+    /// Returns [None] if either value is [Value::Null] because comparison
+    /// operators of SQLite return NULL for NULL operands. No type conversion
+    /// is applied, so values of different storage classes are never equal
+    /// (e.g. integer 1 is less than text '1').
+    fn sqlite_cmp(&self, other: &Value) -> Option<Ordering> {
+        if *self == Value::Null || *other == Value::Null {
+            return None;
+        }
+        Some(ValueCmp::new(self, &DEFAULT_COLLATION).compare(other))
+    }
+
+    /// `self = other` in the three-valued logic of SQLite.
+    pub fn sqlite_eq(&self, other: &Value) -> Option<bool> {
+        self.sqlite_cmp(other).map(|cmp| cmp == Ordering::Equal)
+    }
+
+    /// `self != other` in the three-valued logic of SQLite.
+    pub fn sqlite_ne(&self, other: &Value) -> Option<bool> {
+        self.sqlite_cmp(other).map(|cmp| cmp != Ordering::Equal)
+    }
+
+    /// `self < other` in the three-valued logic of SQLite.
+    pub fn sqlite_lt(&self, other: &Value) -> Option<bool> {
+        self.sqlite_cmp(other).map(|cmp| cmp == Ordering::Less)
+    }
+
+    /// `self <= other` in the three-valued logic of SQLite.
+    pub fn sqlite_le(&self, other: &Value) -> Option<bool> {
+        self.sqlite_cmp(other).map(|cmp| cmp != Ordering::Greater)
+    }
+
+    /// `self > other` in the three-valued logic of SQLite.
+    pub fn sqlite_gt(&self, other: &Value) -> Option<bool> {
+        self.sqlite_cmp(other).map(|cmp| cmp == Ordering::Greater)
+    }
+
+    /// `self >= other` in the three-valued logic of SQLite.
+    pub fn sqlite_ge(&self, other: &Value) -> Option<bool> {
+        self.sqlite_cmp(other).map(|cmp| cmp != Ordering::Less)
+    }
+
+    /// Convert the value to an integer or a real value for arithmetic.
+    ///
+    /// Text and blob are converted from their numeric prefix, or 0. They are
+    /// integers unless they have a decimal point or an exponent, or overflow
+    /// i64. This is numericType() in vdbe.c of SQLite.
+    fn to_numeric(&self) -> Value<'static> {
+        match self {
+            Value::Null => Value::Null,
+            Value::Integer(i) => Value::Integer(*i),
+            Value::Real(d) => Value::Real(*d),
+            Value::Text(buf) | Value::Blob(buf) => {
+                let (_, pure_integer, d) = parse_float(buf);
+                if pure_integer {
+                    if let (_, ParseIntegerResult::Integer(i)) = parse_integer(buf) {
+                        return Value::Integer(i);
+                    }
+                } else if !has_numeric_prefix(buf) {
+                    return Value::Integer(0);
+                }
+                Value::Real(d)
+            }
+        }
+    }
+
+    /// Apply the arithmetic operator.
+    ///
+    /// Integer operands are computed by int_op. If it fails, e.g. on overflow,
+    /// they are computed as reals by real_op. The result is NULL if real_op
+    /// fails or returns NaN.
+    fn arithmetic(
+        &self,
+        other: &Value,
+        int_op: impl FnOnce(i64, i64) -> Option<i64>,
+        real_op: impl FnOnce(f64, f64) -> Option<f64>,
+    ) -> Value<'static> {
+        let (a, b) = match (self.to_numeric(), other.to_numeric()) {
+            (Value::Null, _) | (_, Value::Null) => return Value::Null,
+            (Value::Integer(a), Value::Integer(b)) => {
+                if let Some(v) = int_op(a, b) {
+                    return Value::Integer(v);
+                }
+                (a as f64, b as f64)
+            }
+            (Value::Integer(a), Value::Real(b)) => (a as f64, b),
+            (Value::Real(a), Value::Integer(b)) => (a, b as f64),
+            (Value::Real(a), Value::Real(b)) => (a, b),
+            _ => unreachable!("numeric values"),
+        };
+        match real_op(a, b) {
+            Some(d) if !d.is_nan() => Value::Real(d),
+            _ => Value::Null,
+        }
+    }
+
+    /// `self + other` in SQLite.
+    ///
+    /// Integer operations stay integers unless they overflow. Any real operand
+    /// makes the result real. NULL operands make the result NULL.
+    pub fn sqlite_add(&self, other: &Value) -> Value<'static> {
+        self.arithmetic(other, i64::checked_add, |a, b| Some(a + b))
+    }
+
+    /// `self - other` in SQLite.
+    pub fn sqlite_sub(&self, other: &Value) -> Value<'static> {
+        self.arithmetic(other, i64::checked_sub, |a, b| Some(a - b))
+    }
+
+    /// `self * other` in SQLite.
+    pub fn sqlite_mul(&self, other: &Value) -> Value<'static> {
+        self.arithmetic(other, i64::checked_mul, |a, b| Some(a * b))
+    }
+
+    /// `self / other` in SQLite.
+    ///
+    /// Integer division truncates toward zero. Division by zero is NULL.
+    pub fn sqlite_div(&self, other: &Value) -> Value<'static> {
+        self.arithmetic(other, i64::checked_div, |a, b| {
+            if b == 0.0 {
+                None
+            } else {
+                Some(a / b)
+            }
+        })
+    }
+
+    /// `self % other` in SQLite.
+    ///
+    /// Both operands are converted to integers. The result is real if any
+    /// operand is real. Division by zero is NULL.
+    pub fn sqlite_rem(&self, other: &Value) -> Value<'static> {
+        let (Some(ia), Some(ib)) = (self.as_integer(), other.as_integer()) else {
+            return Value::Null;
+        };
+        if ib == 0 {
+            return Value::Null;
+        }
+        // i64::MIN % -1 overflows.
+        let v = ia % if ib == -1 { 1 } else { ib };
+        match (self.to_numeric(), other.to_numeric()) {
+            (Value::Integer(_), Value::Integer(_)) => Value::Integer(v),
+            _ => Value::Real(v as f64),
+        }
+    }
+}
+
+/// Formats the value as the "list" mode of the sqlite3 shell.
+///
+/// NULL is empty. Text and blob are written as is with invalid UTF-8
+/// sequences replaced. Use [Value::to_sql_literal()] for the quoted form.
+impl Display for Value<'_> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Value::Null => Ok(()),
+            Value::Integer(i) => write!(f, "{i}"),
+            Value::Real(d) => f.write_str(&format_real(*d)),
+            Value::Text(buf) | Value::Blob(buf) => f.write_str(&String::from_utf8_lossy(buf)),
+        }
+    }
+}
+
+/// Format the real value in the same way as the "%!.15g" format of SQLite.
+///
+/// This is "%.15g" except that whole numbers have ".0" as in "10.0" or
+/// "1.0e+20".
+fn format_real(d: f64) -> String {
+    if d.is_nan() {
+        return "NaN".to_string();
+    } else if d.is_infinite() {
+        return if d.is_sign_positive() { "Inf" } else { "-Inf" }.to_string();
+    } else if d == 0.0 {
+        return "0.0".to_string();
+    }
+    // 15 significant digits. e.g. "-1.23450000000000e2".
+    let formatted = format!("{:.14e}", d);
+    let (mantissa, exponent) = formatted.split_once('e').unwrap();
+    let exponent: i32 = exponent.parse().unwrap();
+    let (sign, mantissa) = match mantissa.strip_prefix('-') {
+        Some(mantissa) => ("-", mantissa),
+        None => ("", mantissa),
+    };
+    let digits = mantissa.replace('.', "");
+    let digits = digits.trim_end_matches('0');
+    if !(-4..15).contains(&exponent) {
+        let fraction = if digits.len() > 1 { &digits[1..] } else { "0" };
+        format!(
+            "{sign}{}.{fraction}{}",
+            &digits[..1],
+            format_exponent(exponent)
+        )
+    } else if exponent < 0 {
+        format!("{sign}0.{}{digits}", "0".repeat((-exponent - 1) as usize))
+    } else {
+        let n_int_digits = exponent as usize + 1;
+        if digits.len() > n_int_digits {
+            format!(
+                "{sign}{}.{}",
+                &digits[..n_int_digits],
+                &digits[n_int_digits..]
+            )
+        } else {
+            format!(
+                "{sign}{digits}{}.0",
+                "0".repeat(n_int_digits - digits.len())
+            )
+        }
+    }
+}
+
+/// Whether the text starts with a number, e.g. "1", "-.5" or " 2x".
+fn has_numeric_prefix(buf: &[u8]) -> bool {
+    let start = buf.iter().position(|c| !is_space(*c)).unwrap_or(buf.len());
+    let buf = &buf[start..];
+    let buf = buf
+        .strip_prefix(b"-")
+        .or_else(|| buf.strip_prefix(b"+"))
+        .unwrap_or(buf);
+    let buf = buf.strip_prefix(b".").unwrap_or(buf);
+    buf.first().is_some_and(u8::is_ascii_digit)
+}
+
+/// Format the exponent part of a real value. e.g. "e+20" or "e-05".
+fn format_exponent(exponent: i32) -> String {
+    let sign = if exponent < 0 { '-' } else { '+' };
+    format!("e{sign}{:02}", exponent.abs())
 }
 
 /// sqlite3RealSameAsInt() in vdbemem.c of SQLite
@@ -324,29 +694,7 @@ impl<'a> ValueCmp<'a> {
             (_, Value::Integer(_)) => Ordering::Greater,
             (Value::Real(_), _) => Ordering::Less,
             (_, Value::Real(_)) => Ordering::Greater,
-            (Value::Text(t1), Value::Text(t2)) => match collation {
-                Collation::Binary => t1.cmp(t2),
-                Collation::NoCase => {
-                    CaseInsensitiveBytes::from(&**t1).cmp(&CaseInsensitiveBytes::from(&**t2))
-                }
-                Collation::RTrim => {
-                    let mut tail_t1 = 0;
-                    for (i, b) in t1.iter().enumerate().rev() {
-                        if *b != b' ' {
-                            tail_t1 = i + 1;
-                            break;
-                        }
-                    }
-                    let mut tail_t2 = 0;
-                    for (i, b) in t2.iter().enumerate().rev() {
-                        if *b != b' ' {
-                            tail_t2 = i + 1;
-                            break;
-                        }
-                    }
-                    t1[..tail_t1].cmp(&t2[..tail_t2])
-                }
-            },
+            (Value::Text(t1), Value::Text(t2)) => compare_text(t1, t2, collation),
             (Value::Text(_), Value::Blob(_)) => Ordering::Less,
             (Value::Blob(_), Value::Text(_)) => Ordering::Greater,
             (Value::Blob(b1), Value::Blob(b2)) => b1.cmp(b2),
@@ -354,6 +702,24 @@ impl<'a> ValueCmp<'a> {
     }
 }
 
+/// Compare two texts with the collation sequence.
+///
+/// NOCASE folds only ASCII characters. RTRIM ignores trailing spaces.
+///
+/// https://www.sqlite.org/datatype3.html#collating_sequences
+pub fn compare_text(t1: &[u8], t2: &[u8], collation: &Collation) -> Ordering {
+    match collation {
+        Collation::Binary => t1.cmp(t2),
+        Collation::NoCase => CaseInsensitiveBytes::from(t1).cmp(&CaseInsensitiveBytes::from(t2)),
+        Collation::RTrim => trim_end_spaces(t1).cmp(trim_end_spaces(t2)),
+    }
+}
+
+fn trim_end_spaces(text: &[u8]) -> &[u8] {
+    let len = text.iter().rposition(|b| *b != b' ').map_or(0, |i| i + 1);
+    &text[..len]
+}
+
 /// Compare i64 and f64
 ///
 /// This comes from sqlite3IntFloatCompare().
@@ -641,6 +1007,404 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_compare_text() {
+        for (t1, t2, binary, nocase, rtrim) in [
+            (
+                "abc",
+                "abc",
+                Ordering::Equal,
+                Ordering::Equal,
+                Ordering::Equal,
+            ),
+            ("abc", "abd", Ordering::Less, Ordering::Less, Ordering::Less),
+            (
+                "abc",
+                "ABC",
+                Ordering::Greater,
+                Ordering::Equal,
+                Ordering::Greater,
+            ),
+            ("ABC", "abd", Ordering::Less, Ordering::Less, Ordering::Less),
+            (
+                "abc",
+                "ABD",
+                Ordering::Greater,
+                Ordering::Less,
+                Ordering::Greater,
+            ),
+            (
+                "abc  ",
+                "abc",
+                Ordering::Greater,
+                Ordering::Greater,
+                Ordering::Equal,
+            ),
+            (
+                "abc ",
+                "abc\t",
+                Ordering::Greater,
+                Ordering::Greater,
+                Ordering::Less,
+            ),
+            (
+                " abc",
+                "abc",
+                Ordering::Less,
+                Ordering::Less,
+                Ordering::Less,
+            ),
+            (
+                "ab c",
+                "ab",
+                Ordering::Greater,
+                Ordering::Greater,
+                Ordering::Greater,
+            ),
+            (
+                "  ",
+                "",
+                Ordering::Greater,
+                Ordering::Greater,
+                Ordering::Equal,
+            ),
+            // Non ASCII characters are not folded.
+            (
+                "\u{e9}",
+                "\u{c9}",
+                Ordering::Greater,
+                Ordering::Greater,
+                Ordering::Greater,
+            ),
+            // '_' is between 'Z' and 'a'.
+            ("_", "a", Ordering::Less, Ordering::Less, Ordering::Less),
+            (
+                "_",
+                "A",
+                Ordering::Greater,
+                Ordering::Less,
+                Ordering::Greater,
+            ),
+        ] {
+            let (t1, t2) = (t1.as_bytes(), t2.as_bytes());
+            for (collation, expected) in [
+                (Collation::Binary, binary),
+                (Collation::NoCase, nocase),
+                (Collation::RTrim, rtrim),
+            ] {
+                assert_eq!(
+                    compare_text(t1, t2, &collation),
+                    expected,
+                    "{t1:?} {t2:?} {collation:?}"
+                );
+                assert_eq!(
+                    compare_text(t2, t1, &collation),
+                    expected.reverse(),
+                    "{t1:?} {t2:?} {collation:?}"
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn test_sqlite_compare_operators() {
+        let text_1 = Value::Text(b"1".as_slice().into());
+        let blob_1 = Value::Blob(b"1".as_slice().into());
+        // (left, right, [eq, ne, lt, le, gt, ge])
+        for (left, right, expected) in [
+            (&Value::Null, &Value::Null, None),
+            (&Value::Null, &Value::Integer(1), None),
+            (&Value::Integer(1), &Value::Null, None),
+            (&Value::Null, &text_1, None),
+            (
+                &Value::Integer(1),
+                &Value::Integer(1),
+                Some([true, false, false, true, false, true]),
+            ),
+            (
+                &Value::Integer(1),
+                &Value::Integer(2),
+                Some([false, true, true, true, false, false]),
+            ),
+            (
+                &Value::Integer(2),
+                &Value::Real(1.5),
+                Some([false, true, false, false, true, true]),
+            ),
+            (
+                &Value::Integer(1),
+                &Value::Real(1.0),
+                Some([true, false, false, true, false, true]),
+            ),
+            // No coercion between integer and text.
+            (
+                &Value::Integer(1),
+                &text_1,
+                Some([false, true, true, true, false, false]),
+            ),
+            (
+                &text_1,
+                &Value::Integer(1),
+                Some([false, true, false, false, true, true]),
+            ),
+            (
+                &text_1,
+                &text_1,
+                Some([true, false, false, true, false, true]),
+            ),
+            (
+                &text_1,
+                &blob_1,
+                Some([false, true, true, true, false, false]),
+            ),
+        ] {
+            let results = [
+                left.sqlite_eq(right),
+                left.sqlite_ne(right),
+                left.sqlite_lt(right),
+                left.sqlite_le(right),
+                left.sqlite_gt(right),
+                left.sqlite_ge(right),
+            ];
+            match expected {
+                Some(expected) => {
+                    assert_eq!(
+                        results,
+                        expected.map(Some),
+                        "left: {:?}, right: {:?}",
+                        left,
+                        right
+                    );
+                }
+                None => {
+                    assert_eq!(results, [None; 6], "left: {:?}, right: {:?}", left, right);
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn test_text_encoding_decode() {
+        assert_eq!(TextEncoding::Utf8.decode("héllo".as_bytes()), "héllo");
+        assert_eq!(
+            TextEncoding::Utf16le.decode(&[0x68, 0x00, 0xe9, 0x00, 0x3d, 0xd8, 0x00, 0xde]),
+            "hé😀"
+        );
+        assert_eq!(
+            TextEncoding::Utf16be.decode(&[0x00, 0x68, 0x00, 0xe9, 0xd8, 0x3d, 0xde, 0x00]),
+            "hé😀"
+        );
+        // Unpaired surrogate.
+        assert_eq!(TextEncoding::Utf16le.decode(&[0x3d, 0xd8]), "\u{FFFD}");
+        assert_eq!(TextEncoding::from_header(1), Some(TextEncoding::Utf8));
+        assert_eq!(TextEncoding::from_header(2), Some(TextEncoding::Utf16le));
+        assert_eq!(TextEncoding::from_header(3), Some(TextEncoding::Utf16be));
+        assert_eq!(TextEncoding::from_header(0), None);
+
+        assert_eq!(
+            Value::Text(b"h\0i\0".as_slice().into()).decode_text(TextEncoding::Utf16le),
+            Value::Text(b"hi".as_slice().into())
+        );
+        assert_eq!(
+            Value::Blob(b"h\0i\0".as_slice().into()).decode_text(TextEncoding::Utf16le),
+            Value::Blob(b"h\0i\0".as_slice().into())
+        );
+    }
+
+    #[test]
+    fn test_as_bool() {
+        assert_eq!(Value::Null.as_bool(), None);
+        assert_eq!(Value::Integer(0).as_bool(), Some(false));
+        assert_eq!(Value::Integer(-1).as_bool(), Some(true));
+        assert_eq!(Value::Real(0.0).as_bool(), Some(false));
+        assert_eq!(Value::Real(0.5).as_bool(), Some(true));
+        assert_eq!(Value::Text(b"".as_slice().into()).as_bool(), Some(false));
+        assert_eq!(Value::Text(b"abc".as_slice().into()).as_bool(), Some(false));
+        assert_eq!(Value::Text(b"0.0".as_slice().into()).as_bool(), Some(false));
+        assert_eq!(Value::Text(b" 0.5".as_slice().into()).as_bool(), Some(true));
+        assert_eq!(Value::Text(b"1abc".as_slice().into()).as_bool(), Some(true));
+        assert_eq!(Value::Blob(b"2".as_slice().into()).as_bool(), Some(true));
+    }
+
+    #[test]
+    fn test_arithmetic() {
+        let values = [
+            Value::Null,
+            Value::Integer(0),
+            Value::Integer(1),
+            Value::Integer(-1),
+            Value::Integer(7),
+            Value::Integer(-7),
+            Value::Integer(i64::MAX),
+            Value::Integer(i64::MIN),
+            Value::Real(0.0),
+            Value::Real(2.5),
+            Value::Real(-0.5),
+            Value::Real(3.0),
+            Value::Real(1e300),
+            Value::Real(f64::INFINITY),
+            Value::Text(b"12".as_slice().into()),
+            Value::Text(b" 3 ".as_slice().into()),
+            Value::Text(b"12abc".as_slice().into()),
+            Value::Text(b"0.0abc".as_slice().into()),
+            Value::Text(b".5x".as_slice().into()),
+            Value::Text(b"-".as_slice().into()),
+            Value::Text(b"1e".as_slice().into()),
+            Value::Text(b"1e5x".as_slice().into()),
+            Value::Text(b"abc".as_slice().into()),
+            Value::Text(b"".as_slice().into()),
+            Value::Text(b"1.5".as_slice().into()),
+            Value::Text(b"2.0".as_slice().into()),
+            Value::Text(b"1e2".as_slice().into()),
+            Value::Text(b"9223372036854775807".as_slice().into()),
+            Value::Text(b"9223372036854775808".as_slice().into()),
+            Value::Blob(b"5".as_slice().into()),
+            Value::Blob(b"\x01".as_slice().into()),
+        ];
+        let to_sql = |value: &Value| match value {
+            Value::Null => rusqlite::types::Value::Null,
+            Value::Integer(i) => rusqlite::types::Value::Integer(*i),
+            Value::Real(d) => rusqlite::types::Value::Real(*d),
+            Value::Text(buf) => {
+                rusqlite::types::Value::Text(String::from_utf8(buf.to_vec()).unwrap())
+            }
+            Value::Blob(buf) => rusqlite::types::Value::Blob(buf.to_vec()),
+        };
+        let conn = rusqlite::Connection::open_in_memory().unwrap();
+        let mut stmt = conn
+            .prepare("SELECT ?1 + ?2, ?1 - ?2, ?1 * ?2, ?1 / ?2, ?1 % ?2")
+            .unwrap();
+        for a in values.iter() {
+            for b in values.iter() {
+                let expected = stmt
+                    .query_row([to_sql(a), to_sql(b)], |row| {
+                        Ok((0..5)
+                            .map(|i| match row.get::<_, rusqlite::types::Value>(i).unwrap() {
+                                rusqlite::types::Value::Null => Value::Null,
+                                rusqlite::types::Value::Integer(i) => Value::Integer(i),
+                                rusqlite::types::Value::Real(d) => Value::Real(d),
+                                v => panic!("unexpected value: {v:?}"),
+                            })
+                            .collect::<Vec<_>>())
+                    })
+                    .unwrap();
+                assert_eq!(
+                    vec![
+                        a.sqlite_add(b),
+                        a.sqlite_sub(b),
+                        a.sqlite_mul(b),
+                        a.sqlite_div(b),
+                        a.sqlite_rem(b),
+                    ],
+                    expected,
+                    "{a:?}, {b:?}"
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn test_display() {
+        assert_eq!(Value::Null.to_string(), "");
+        assert_eq!(Value::Integer(-123).to_string(), "-123");
+        assert_eq!(Value::Real(10.0).to_string(), "10.0");
+        assert_eq!(Value::Real(-1.5).to_string(), "-1.5");
+        assert_eq!(Value::Text(b"it's".as_slice().into()).to_string(), "it's");
+        assert_eq!(
+            Value::Text(b"a\xffb".as_slice().into()).to_string(),
+            "a\u{fffd}b"
+        );
+        assert_eq!(Value::Blob(b"abc".as_slice().into()).to_string(), "abc");
+    }
+
+    #[test]
+    fn test_to_sql_literal() {
+        assert_eq!(Value::Null.to_sql_literal(), "NULL");
+        assert_eq!(
+            Value::Integer(i64::MIN).to_sql_literal(),
+            "-9223372036854775808"
+        );
+        assert_eq!(Value::Real(0.5).to_sql_literal(), "0.5");
+        assert_eq!(
+            Value::Real(0.1 + 0.2).to_sql_literal(),
+            "3.00000000000000044409e-01"
+        );
+        assert_eq!(Value::Real(f64::INFINITY).to_sql_literal(), "9.0e+999");
+        assert_eq!(Value::Real(f64::NEG_INFINITY).to_sql_literal(), "-9.0e+999");
+        assert_eq!(
+            Value::Text(b"it's".as_slice().into()).to_sql_literal(),
+            "'it''s'"
+        );
+        assert_eq!(Value::Text(b"".as_slice().into()).to_sql_literal(), "''");
+        assert_eq!(
+            Value::Blob([0x00, 0x0a, 0xff].as_slice().into()).to_sql_literal(),
+            "X'000AFF'"
+        );
+        assert_eq!(Value::Blob(b"".as_slice().into()).to_sql_literal(), "X''");
+    }
+
+    #[test]
+    fn test_format_real() {
+        let conn = rusqlite::Connection::open_in_memory().unwrap();
+        for d in [
+            0.0,
+            -0.0,
+            1.0,
+            -1.0,
+            0.1,
+            1.5,
+            10.0,
+            123.456,
+            1.0 / 3.0,
+            2.0 / 3.0,
+            0.0001,
+            0.00001,
+            1.234e-10,
+            99999999999999.0,
+            100000000000000.0,
+            999999999999999.0,
+            1e15,
+            1e20,
+            -1.5e300,
+            9223372036854775807.0,
+            f64::MAX,
+            f64::MIN_POSITIVE,
+            f64::EPSILON,
+        ] {
+            let expected: String = conn
+                .query_row("SELECT printf('%!.15g', ?)", [d], |row| row.get(0))
+                .unwrap();
+            assert_eq!(format_real(d), expected, "{d}");
+            let literal = Value::Real(d).to_sql_literal();
+            assert_eq!(literal.parse::<f64>().unwrap(), d, "{d}");
+            if expected.parse::<f64>().unwrap() == d {
+                let expected: String = conn
+                    .query_row("SELECT quote(?)", [d], |row| row.get(0))
+                    .unwrap();
+                assert_eq!(literal, expected, "{d}");
+            }
+        }
+    }
+
+    #[test]
+    fn test_apply_real_affinity() {
+        assert_eq!(Value::Null.apply_real_affinity(), Value::Null);
+        assert_eq!(Value::Integer(10).apply_real_affinity(), Value::Real(10.0));
+        assert_eq!(
+            Value::Integer(-12345).apply_real_affinity(),
+            Value::Real(-12345.0)
+        );
+        assert_eq!(Value::Real(1.5).apply_real_affinity(), Value::Real(1.5));
+        assert_eq!(
+            Value::Text(b"10".as_slice().into()).apply_real_affinity(),
+            Value::Text(b"10".as_slice().into())
+        );
+        assert_eq!(
+            Value::Blob(b"10".as_slice().into()).apply_real_affinity(),
+            Value::Blob(b"10".as_slice().into())
+        );
+    }
+
     #[test]
     fn test_apply_numeric_affinity() {
         assert_eq!(Value::Null.apply_numeric_affinity(), Value::Null);
@@ -12,7 +12,16 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 
+use std::os::unix::fs::FileExt;
+
+use prsqlite::split_statements;
+use prsqlite::ColumnDefinition;
 use prsqlite::Connection;
+use prsqlite::IndexDefinition;
+use prsqlite::OwnedRow;
+use prsqlite::SerialTypeKind;
+use prsqlite::TableDefinition;
+use prsqlite::TableRow;
 use prsqlite::Value;
 use tempfile::NamedTempFile;
 
@@ -119,56 +128,1445 @@ fn test_select_all_from_table() {
     assert_eq!(columns.get(3), &Value::Null);
     drop(row);
 
-    let row = rows.next_row().unwrap().unwrap();
-    let columns = row.parse().unwrap();
-    assert_eq!(columns.len(), 3);
+    let row = rows.next_row().unwrap().unwrap();
+    let columns = row.parse().unwrap();
+    assert_eq!(columns.len(), 3);
+    assert_eq!(
+        columns.get(0),
+        &Value::Blob([0xFF; 10000].as_slice().into())
+    );
+    assert_eq!(columns.get(1), &Value::Integer(20000));
+    assert_eq!(columns.get(2), &Value::Null);
+    assert_eq!(columns.get(3), &Value::Null);
+    drop(row);
+
+    assert!(rows.next_row().unwrap().is_none());
+}
+
+#[test]
+fn test_collect_owned_rows() {
+    let blob_query = format!(
+        "INSERT INTO example(col1, col2) VALUES (X'{}', 3);",
+        "FF".repeat(10000)
+    );
+    let file = create_sqlite_database(&[
+        "CREATE TABLE example(col1, col2);",
+        "INSERT INTO example(col1, col2) VALUES ('abc', 1);",
+        "INSERT INTO example(col1, col2) VALUES (1.5, 2);",
+        &blob_query,
+    ]);
+
+    let mut conn = Connection::open(file.path()).unwrap();
+    let mut stmt = conn
+        .prepare("SELECT col2, col1 FROM example WHERE col2 != 2;")
+        .unwrap();
+    let rows = stmt
+        .execute()
+        .unwrap()
+        .collect::<anyhow::Result<Vec<_>>>()
+        .unwrap();
+    assert_eq!(
+        rows,
+        vec![
+            OwnedRow {
+                values: vec![Value::Integer(1), Value::Text(b"abc".to_vec().into())]
+            },
+            OwnedRow {
+                values: vec![Value::Integer(3), Value::Blob(vec![0xFF; 10000].into())]
+            },
+        ]
+    );
+
+    // The rows complete after an error.
+    conn.enable_change_detection().unwrap();
+    let mut stmt = conn.prepare("SELECT col2 FROM example;").unwrap();
+    let mut rows = stmt.execute().unwrap();
+    assert_eq!(
+        rows.next_owned().unwrap().unwrap().values,
+        vec![Value::Integer(1)]
+    );
+    let test_conn = rusqlite::Connection::open(file.path()).unwrap();
+    test_conn
+        .execute("INSERT INTO example(col1, col2) VALUES (4, 4);", [])
+        .unwrap();
+    let rest = rows.by_ref().collect::<Vec<_>>();
+    assert!(rest.last().unwrap().is_err());
+    assert!(rows.next_owned().is_none());
+}
+
+#[test]
+fn test_query() {
+    let file = create_sqlite_database(&[
+        "CREATE TABLE foo(id INTEGER PRIMARY KEY, name TEXT, score);",
+        "INSERT INTO foo(id, name, score) VALUES (1, 'alice', 10);",
+        "INSERT INTO foo(id, name, score) VALUES (5, 'bob', 2.5);",
+        "INSERT INTO foo(id, name, score) VALUES (7, 'carol', NULL);",
+    ]);
+    let mut conn = Connection::open(file.path()).unwrap();
+
+    assert_eq!(
+        conn.query("SELECT id, name FROM foo WHERE id = 5;")
+            .unwrap(),
+        vec![vec![Value::Integer(5), Value::Text(b"bob".to_vec().into())]]
+    );
+    assert_eq!(
+        conn.query("SELECT name, score FROM foo WHERE id != 5;")
+            .unwrap(),
+        vec![
+            vec![Value::Text(b"alice".to_vec().into()), Value::Integer(10)],
+            vec![Value::Text(b"carol".to_vec().into()), Value::Null],
+        ]
+    );
+    assert!(conn
+        .query("SELECT * FROM foo WHERE id = 2;")
+        .unwrap()
+        .is_empty());
+    assert!(conn.query("SELECT * FROM bar;").is_err());
+    assert!(conn.query("SELECT * FROM;").is_err());
+    // Compound select, ORDER BY and LIMIT are parsed but not executed yet.
+    assert!(conn
+        .query("SELECT * FROM foo UNION ALL SELECT * FROM foo;")
+        .is_err());
+    assert!(conn.query("SELECT * FROM foo ORDER BY id;").is_err());
+    assert!(conn.query("SELECT * FROM foo LIMIT 1;").is_err());
+    assert!(conn.query("SELECT * FROM foo, foo;").is_err());
+    assert!(conn.query("SELECT * FROM foo JOIN foo;").is_err());
+    assert!(conn.query("SELECT * FROM foo WHERE id = ?;").is_err());
+    // The first bind parameter in the source order is reported.
+    assert_eq!(
+        conn.query("SELECT * FROM foo LIMIT :offset, :limit;")
+            .unwrap_err()
+            .to_string(),
+        "bind parameter :offset is not supported yet"
+    );
+    assert!(conn.query("SELECT foo.id FROM foo;").is_err());
+}
+
+#[test]
+fn test_open_reader() {
+    let file = create_sqlite_database(&[
+        "CREATE TABLE example(col1, col2);",
+        "CREATE INDEX index1 ON example(col2);",
+        "INSERT INTO example(col1, col2) VALUES (1, 'a');",
+        "INSERT INTO example(col1, col2) VALUES (2, 'b');",
+    ]);
+    let content = std::fs::read(file.path()).unwrap();
+    let mut conn = Connection::open_reader(std::io::Cursor::new(content)).unwrap();
+
+    assert_eq!(
+        conn.query("SELECT col1 FROM example WHERE col2 = 'b';")
+            .unwrap(),
+        vec![vec![Value::Integer(2)]]
+    );
+
+    assert!(Connection::open_reader(std::io::Cursor::new(vec![0; 4096])).is_err());
+    assert!(Connection::open_reader(std::io::Cursor::new(Vec::new())).is_err());
+}
+
+#[test]
+fn test_open_hot_journal() {
+    let file = create_sqlite_database(&[
+        "CREATE TABLE example(col);",
+        "INSERT INTO example(col) VALUES (1);",
+    ]);
+    let mut journal_name = file.path().as_os_str().to_owned();
+    journal_name.push("-journal");
+    let journal = std::fs::File::create(&journal_name).unwrap();
+
+    // An empty journal is not hot.
+    assert!(Connection::open(file.path()).is_ok());
+
+    // The header of a hot journal starts with the magic number.
+    let mut header = vec![0; 512];
+    header[..8].copy_from_slice(&[0xd9, 0xd5, 0x05, 0xf9, 0x20, 0xa1, 0x63, 0xd7]);
+    journal.write_all_at(&header, 0).unwrap();
+    let Err(e) = Connection::open(file.path()) else {
+        panic!("database with a hot journal is opened");
+    };
+    assert_eq!(
+        e.to_string(),
+        "hot journal present; database may be inconsistent"
+    );
+
+    // journal_mode=PERSIST zeroes the header on commit.
+    journal.write_all_at(&[0; 8], 0).unwrap();
+    let mut conn = Connection::open(file.path()).unwrap();
+    assert_eq!(
+        conn.query("SELECT col FROM example;").unwrap(),
+        vec![vec![Value::Integer(1)]]
+    );
+
+    std::fs::remove_file(&journal_name).unwrap();
+}
+
+#[test]
+fn test_schema_entries() {
+    let file = create_sqlite_database(&[
+        "CREATE TABLE example(col1 INTEGER, \"Col 2\" TEXT COLLATE NOCASE);",
+        "CREATE INDEX index1 ON example(col1);",
+        "CREATE TABLE pk(id TEXT PRIMARY KEY);",
+    ]);
+    let mut conn = Connection::open(file.path()).unwrap();
+
+    let entries = conn.schema_entries().unwrap();
+    assert_eq!(
+        entries
+            .iter()
+            .map(|entry| (entry.type_.as_slice(), entry.name.as_slice()))
+            .collect::<Vec<_>>(),
+        vec![
+            (b"table".as_slice(), b"example".as_slice()),
+            (b"index", b"index1"),
+            (b"table", b"pk"),
+            (b"index", b"sqlite_autoindex_pk_1"),
+        ]
+    );
+    assert_eq!(
+        Some(entries[0].root_page_id),
+        conn.root_page_of("example").unwrap()
+    );
+
+    let entry = conn.schema_entry("EXAMPLE").unwrap().unwrap();
+    assert_eq!(entry, entries[0]);
+    assert_eq!(
+        entry.parse_create_table().unwrap(),
+        TableDefinition {
+            name: b"example".to_vec(),
+            columns: vec![
+                ColumnDefinition {
+                    name: b"col1".to_vec(),
+                    type_name: b"INTEGER".to_vec(),
+                    collation: None,
+                    primary_key: false,
+                },
+                ColumnDefinition {
+                    name: b"Col 2".to_vec(),
+                    type_name: b"TEXT".to_vec(),
+                    collation: Some(b"NOCASE".to_vec()),
+                    primary_key: false,
+                },
+            ],
+        }
+    );
+    assert_eq!(
+        entry.parse_create_index().unwrap_err().to_string(),
+        "example is not index: table"
+    );
+
+    let entry = conn.schema_entry("index1").unwrap().unwrap();
+    assert_eq!(entry.table_name, b"example");
+    assert_eq!(
+        entry.parse_create_index().unwrap(),
+        IndexDefinition {
+            name: b"index1".to_vec(),
+            table_name: b"example".to_vec(),
+            unique: false,
+            columns: vec![b"col1".to_vec()],
+        }
+    );
+    assert!(entry.parse_create_table().is_err());
+
+    let entry = conn.schema_entry("pk").unwrap().unwrap();
+    let create_table = entry.parse_create_table().unwrap();
+    assert_eq!(create_table.columns.len(), 1);
+    assert!(create_table.columns[0].primary_key);
+
+    // The index of PRIMARY KEY constraint has no sql.
+    let entry = conn.schema_entry("sqlite_autoindex_pk_1").unwrap().unwrap();
+    assert!(entry.is_internal());
+    assert!(entry.sql.is_none());
+    assert_eq!(
+        entry.parse_create_index().unwrap_err().to_string(),
+        "no sql for sqlite_autoindex_pk_1"
+    );
+
+    assert!(conn.schema_entry("missing").unwrap().is_none());
+}
+
+#[test]
+fn test_apply_alter_table() {
+    let file = create_sqlite_database(&[
+        "CREATE TABLE example(col1 INTEGER, col2);",
+        "ALTER TABLE example ADD COLUMN col3 TEXT COLLATE NOCASE;",
+        "ALTER TABLE example RENAME col2 TO column;",
+        "ALTER TABLE example RENAME TO renamed;",
+    ]);
+    let mut conn = Connection::open(file.path()).unwrap();
+    // SQLite rewrites the sql in sqlite_schema on ALTER TABLE.
+    let expected = conn
+        .schema_entry("renamed")
+        .unwrap()
+        .unwrap()
+        .parse_create_table()
+        .unwrap();
+
+    let file = create_sqlite_database(&["CREATE TABLE example(col1 INTEGER, col2);"]);
+    let mut conn = Connection::open(file.path()).unwrap();
+    let mut definition = conn
+        .schema_entry("example")
+        .unwrap()
+        .unwrap()
+        .parse_create_table()
+        .unwrap();
+    for sql in split_statements(
+        b"ALTER TABLE example ADD COLUMN col3 TEXT COLLATE NOCASE;
+        ALTER TABLE EXAMPLE RENAME col2 TO column;
+        ALTER TABLE example RENAME TO renamed",
+    ) {
+        definition.apply_alter_table(sql).unwrap();
+    }
+    assert_eq!(definition, expected);
+    assert_eq!(
+        definition.columns[2],
+        ColumnDefinition {
+            name: b"col3".to_vec(),
+            type_name: b"TEXT".to_vec(),
+            collation: Some(b"NOCASE".to_vec()),
+            primary_key: false,
+        }
+    );
+
+    assert_eq!(
+        definition
+            .apply_alter_table(b"ALTER TABLE example ADD col4")
+            .unwrap_err()
+            .to_string(),
+        "alter table example is not for renamed"
+    );
+    assert_eq!(
+        definition
+            .apply_alter_table(b"ALTER TABLE renamed ADD COL1")
+            .unwrap_err()
+            .to_string(),
+        "duplicate column name: COL1"
+    );
+    assert_eq!(
+        definition
+            .apply_alter_table(b"ALTER TABLE renamed RENAME missing TO col4")
+            .unwrap_err()
+            .to_string(),
+        "no such column: missing"
+    );
+    assert!(definition
+        .apply_alter_table(b"ALTER TABLE renamed DROP col1")
+        .is_err());
+    assert_eq!(definition, expected);
+}
+
+#[test]
+fn test_concurrent_connections() {
+    let mut queries = vec!["PRAGMA page_size = 512;".to_string()];
+    for i in 1..=4 {
+        queries.push(format!("CREATE TABLE example{i}(col);"));
+        for _ in 0..500 * i {
+            queries.push(format!(
+                "INSERT INTO example{i}(col) VALUES (zeroblob(100));"
+            ));
+        }
+    }
+    let file = create_sqlite_database(&queries.iter().map(|s| s.as_str()).collect::<Vec<_>>());
+    let mut conn = Connection::open(file.path()).unwrap();
+
+    // Two threads scan each table with the clones of the connection.
+    let handles = (1..=4)
+        .chain(1..=4)
+        .map(|i| {
+            let mut conn = conn.clone();
+            std::thread::spawn(move || {
+                let sum = conn
+                    .table_rows(&format!("example{i}"))
+                    .unwrap()
+                    .map(|row| row.unwrap().rowid)
+                    .sum::<i64>();
+                (i, sum)
+            })
+        })
+        .collect::<Vec<_>>();
+    for handle in handles {
+        let (i, sum) = handle.join().unwrap();
+        let n_rows = 500 * i;
+        assert_eq!(sum, n_rows * (n_rows + 1) / 2);
+    }
+
+    // The clones share the page cache.
+    let stats = conn.pager_stats();
+    assert!(stats.cache_hits > 0);
+    assert_eq!(stats.pages_read, stats.cache_misses);
+    let n_rows = conn.table_rows("example4").unwrap().count();
+    assert_eq!(n_rows, 2000);
+    assert_eq!(conn.pager_stats().pages_read, stats.pages_read);
+
+    let clone = conn.clone();
+    assert_eq!(
+        conn.enable_change_detection().unwrap_err().to_string(),
+        "pager is shared with cloned connections"
+    );
+    drop(clone);
+    conn.enable_change_detection().unwrap();
+}
+
+#[test]
+fn test_change_detection() {
+    let file = create_sqlite_database(&[
+        "CREATE TABLE example(col);",
+        "INSERT INTO example(col) VALUES (1);",
+        "INSERT INTO example(col) VALUES (2);",
+    ]);
+    let writer = rusqlite::Connection::open(file.path()).unwrap();
+    let mut conn = Connection::open(file.path()).unwrap();
+    conn.enable_change_detection().unwrap();
+
+    {
+        let mut stmt = conn.prepare("SELECT col FROM example;").unwrap();
+        let mut rows = stmt.execute().unwrap();
+        assert!(rows.next_row().unwrap().is_some());
+        writer
+            .execute("INSERT INTO example(col) VALUES (3);", [])
+            .unwrap();
+        assert!(rows.next_row().unwrap().is_some());
+        let Err(e) = rows.next_row() else {
+            panic!("change is not detected");
+        };
+        assert!(
+            e.to_string().starts_with("database file is changed:"),
+            "{e}"
+        );
+    }
+    // The following scans fail too.
+    assert!(conn.query("SELECT col FROM example;").is_err());
+    assert!(conn.table_rows("example").is_err());
+
+    // Take the current state as the consistent one.
+    conn.enable_change_detection().unwrap();
+    let mut rows = conn.table_rows("example").unwrap();
+    assert!(rows.next().unwrap().is_ok());
+    writer
+        .execute("INSERT INTO example(col) VALUES (4);", [])
+        .unwrap();
+    assert!(rows.next().unwrap().is_ok());
+    assert!(rows.next().unwrap().is_ok());
+    assert!(rows.next().unwrap().is_err());
+    assert!(rows.next().is_none());
+}
+
+#[test]
+fn test_integrity_check() {
+    let mut queries = vec![
+        "PRAGMA page_size = 512;",
+        "CREATE TABLE example(col1, col2);",
+        "CREATE INDEX index1 ON example(col2);",
+        "CREATE TABLE no_rowid(id PRIMARY KEY, col) WITHOUT ROWID;",
+        "CREATE VIEW view1 AS SELECT col1 FROM example;",
+    ];
+    queries.extend(
+        std::iter::repeat("INSERT INTO example(col1, col2) VALUES (randomblob(1000), random());")
+            .take(100),
+    );
+    queries.extend(
+        std::iter::repeat("INSERT INTO no_rowid(id, col) VALUES (random(), randomblob(100));")
+            .take(100),
+    );
+    let file = create_sqlite_database(&queries);
+
+    let mut conn = Connection::open(file.path()).unwrap();
+    assert_eq!(conn.integrity_check(), Vec::<String>::new());
+
+    // Break the btree header of the table.
+    let page_id = conn.schema_entry("example").unwrap().unwrap().root_page_id;
+    file.as_file()
+        .write_all_at(&[0xff], (page_id - 1) as u64 * 512)
+        .unwrap();
+    let mut conn = Connection::open(file.path()).unwrap();
+    assert_eq!(
+        conn.integrity_check(),
+        vec![format!(
+            "table example page {page_id}: invalid btree page type"
+        )]
+    );
+
+    // Break the sqlite_schema table.
+    file.as_file().write_all_at(&[0xff], 100).unwrap();
+    let mut conn = Connection::open(file.path()).unwrap();
+    let problems = conn.integrity_check();
+    assert_eq!(problems.len(), 2);
+    assert_eq!(
+        problems[0],
+        "table sqlite_schema page 1: invalid btree page type"
+    );
+    assert!(problems[1].starts_with("failed to read sqlite_schema:"));
+}
+
+#[test]
+fn test_pager_stats() {
+    let file = create_sqlite_database(&[
+        "CREATE TABLE example(col);",
+        "INSERT INTO example(col) VALUES (1);",
+    ]);
+    let mut conn = Connection::open(file.path()).unwrap();
+    let stats = conn.pager_stats();
+
+    assert_eq!(
+        conn.query("SELECT col FROM example;").unwrap(),
+        vec![vec![Value::Integer(1)]]
+    );
+    let first = conn.pager_stats();
+    assert!(first.pages_read > stats.pages_read);
+
+    // The second query is served from the page cache.
+    assert_eq!(
+        conn.query("SELECT col FROM example;").unwrap(),
+        vec![vec![Value::Integer(1)]]
+    );
+    let second = conn.pager_stats();
+    assert_eq!(second.pages_read, first.pages_read);
+    assert_eq!(second.cache_misses, first.cache_misses);
+    assert!(second.cache_hits > first.cache_hits);
+}
+
+#[test]
+fn test_table_rows() {
+    let mut queries = vec![
+        "CREATE TABLE example(col1, col2);",
+        "CREATE TABLE empty(col);",
+        "INSERT INTO example(col1, col2) VALUES (null, 1.5);",
+        "INSERT INTO example(col1, col2) VALUES ('hello', x'0102');",
+    ];
+    let blob_query = format!(
+        "INSERT INTO example(col1) VALUES (X'{}');",
+        "FF".repeat(10000)
+    );
+    queries.push(&blob_query);
+    queries.extend(std::iter::repeat("INSERT INTO example(col1, col2) VALUES (1, 2);").take(1000));
+    let file = create_sqlite_database(&queries);
+
+    let mut conn = Connection::open(file.path()).unwrap();
+    let rows = conn
+        .table_rows("Example")
+        .unwrap()
+        .collect::<anyhow::Result<Vec<_>>>()
+        .unwrap();
+    assert_eq!(rows.len(), 1003);
+    assert_eq!(
+        rows[0],
+        TableRow {
+            rowid: 1,
+            values: vec![Value::Null, Value::Real(1.5)],
+        }
+    );
+    assert_eq!(
+        rows[1],
+        TableRow {
+            rowid: 2,
+            values: vec![
+                Value::Text(b"hello".to_vec().into()),
+                Value::Blob(vec![1, 2].into())
+            ],
+        }
+    );
+    assert_eq!(
+        rows[2],
+        TableRow {
+            rowid: 3,
+            values: vec![Value::Blob(vec![0xFF; 10000].into()), Value::Null],
+        }
+    );
+    for (i, row) in rows.iter().enumerate().skip(3) {
+        assert_eq!(row.rowid, i as i64 + 1);
+        assert_eq!(row.values, vec![Value::Integer(1), Value::Integer(2)]);
+    }
+
+    assert_eq!(conn.table_rows("empty").unwrap().count(), 0);
+    assert!(conn.table_rows("invalid").is_err());
+}
+
+#[test]
+fn test_table_rows_rowid_alias() {
+    let file = create_sqlite_database(&[
+        "CREATE TABLE example(col1, id INTEGER PRIMARY KEY, col2);",
+        "INSERT INTO example(col1, id, col2) VALUES (1, 10, 'a');",
+        "INSERT INTO example(col1, col2) VALUES (2, 'b');",
+    ]);
+
+    let mut conn = Connection::open(file.path()).unwrap();
+    let rows = conn
+        .table_rows("example")
+        .unwrap()
+        .collect::<anyhow::Result<Vec<_>>>()
+        .unwrap();
+    assert_eq!(
+        rows,
+        vec![
+            TableRow {
+                rowid: 10,
+                values: vec![
+                    Value::Integer(1),
+                    Value::Integer(10),
+                    Value::Text(b"a".to_vec().into())
+                ],
+            },
+            TableRow {
+                rowid: 11,
+                values: vec![
+                    Value::Integer(2),
+                    Value::Integer(11),
+                    Value::Text(b"b".to_vec().into())
+                ],
+            },
+        ]
+    );
+}
+
+#[test]
+fn test_table_rows_projected() {
+    let blob_query = format!(
+        "INSERT INTO example(col1, id, col2) VALUES (X'{}', 3, 'c');",
+        "FF".repeat(10000)
+    );
+    let file = create_sqlite_database(&[
+        "CREATE TABLE example(col1, id INTEGER PRIMARY KEY, col2 REAL);",
+        "INSERT INTO example(col1, id, col2) VALUES (1, 10, 2);",
+        "ALTER TABLE example ADD COLUMN col3 DEFAULT 'x';",
+        "INSERT INTO example(col1, col2, col3) VALUES (2, 3.5, 'y');",
+        &blob_query,
+    ]);
+
+    let mut conn = Connection::open(file.path()).unwrap();
+    let rows = conn
+        .table_rows("example")
+        .unwrap()
+        .collect::<anyhow::Result<Vec<_>>>()
+        .unwrap();
+    let columns = vec![3, 2, 1, 0, 4];
+    let mut table_rows = conn.table_rows("example").unwrap();
+    table_rows.set_projection(columns.clone());
+    let mut values = vec![Value::Null; columns.len()];
+    for row in rows {
+        assert_eq!(
+            table_rows.next_projected(&mut values).unwrap().unwrap(),
+            row.rowid
+        );
+        let expected = columns
+            .iter()
+            .map(|&i| row.values.get(i).cloned().unwrap_or(Value::Null))
+            .collect::<Vec<_>>();
+        assert_eq!(values, expected);
+    }
+    assert!(table_rows.next_projected(&mut values).is_none());
+
+    let mut table_rows = conn.table_rows("example").unwrap();
+    // The projection is not set.
+    assert!(table_rows.next_projected(&mut values).unwrap().is_err());
+    table_rows.set_projection(vec![0, 1]);
+    // The number of values does not match.
+    assert!(table_rows.next_projected(&mut values).unwrap().is_err());
+}
+
+#[test]
+fn test_table_rows_serial_types() {
+    let text_query = format!(
+        "INSERT INTO example(col1, col2, col3) VALUES (0, 1, '{}');",
+        "a".repeat(10000)
+    );
+    let file = create_sqlite_database(&[
+        "CREATE TABLE example(id INTEGER PRIMARY KEY, col1, col2, col3);",
+        "INSERT INTO example(col1, col2, col3) VALUES (NULL, 1000, 1.5);",
+        &text_query,
+        "ALTER TABLE example ADD COLUMN col4;",
+        "INSERT INTO example(col1, col2, col3, col4) VALUES (X'0102', 'abc', -1, 70000);",
+    ]);
+
+    let mut conn = Connection::open(file.path()).unwrap();
+    let mut table_rows = conn.table_rows("example").unwrap();
+    assert_eq!(
+        table_rows.next_serial_types().unwrap().unwrap(),
+        (
+            1,
+            vec![
+                SerialTypeKind::Null,
+                SerialTypeKind::Null,
+                SerialTypeKind::Integer(2),
+                SerialTypeKind::Real,
+            ]
+        )
+    );
+    assert_eq!(
+        table_rows.next_serial_types().unwrap().unwrap(),
+        (
+            2,
+            vec![
+                SerialTypeKind::Null,
+                SerialTypeKind::Zero,
+                SerialTypeKind::One,
+                SerialTypeKind::Text(10000),
+            ]
+        )
+    );
+    assert_eq!(
+        table_rows.next_serial_types().unwrap().unwrap(),
+        (
+            3,
+            vec![
+                SerialTypeKind::Null,
+                SerialTypeKind::Blob(2),
+                SerialTypeKind::Text(3),
+                SerialTypeKind::Integer(1),
+                SerialTypeKind::Integer(3),
+            ]
+        )
+    );
+    assert!(table_rows.next_serial_types().is_none());
+}
+
+#[test]
+fn test_table_rows_payload() {
+    let blob_query = format!(
+        "INSERT INTO example(col) VALUES (X'{}');",
+        "FF".repeat(10000)
+    );
+    let file = create_sqlite_database(&[
+        "CREATE TABLE example(col);",
+        "INSERT INTO example(col) VALUES (1000);",
+        &blob_query,
+    ]);
+
+    let mut conn = Connection::open(file.path()).unwrap();
+    let mut table_rows = conn.table_rows("example").unwrap();
+    let (rowid, size, remaining) = table_rows
+        .next_payload(|rowid, payload| Ok((rowid, payload.size(), payload.remaining(1))))
+        .unwrap()
+        .unwrap();
+    // The record header has 2 bytes and the integer has 2 bytes.
+    assert_eq!((rowid, size, remaining), (1, 4, 3));
+    let (rowid, size, remainings) = table_rows
+        .next_payload(|rowid, payload| {
+            let remainings =
+                [-1, 0, 5000, payload.size(), i32::MAX].map(|offset| payload.remaining(offset));
+            Ok((rowid, payload.size(), remainings))
+        })
+        .unwrap()
+        .unwrap();
+    assert_eq!(rowid, 2);
+    // The record header has 4 bytes.
+    assert_eq!(size, 10004);
+    assert_eq!(remainings, [10004, 10004, 5004, 0, 0]);
+    assert!(table_rows.next_payload(|_, _| Ok(())).is_none());
+
+    let mut table_rows = conn.table_rows("example").unwrap();
+    let payload = table_rows
+        .next_payload(|_, payload| payload.to_vec())
+        .unwrap()
+        .unwrap();
+    assert_eq!(payload, [2, 2, 0x03, 0xe8]);
+    table_rows
+        .next_payload(|_, payload| {
+            let buf = payload.to_vec()?;
+            assert_eq!(buf.len(), 10004);
+            assert_eq!(buf[..payload.buf().len()], *payload.buf());
+            assert_eq!(buf[4..], [0xFF; 10000]);
+            assert_eq!(payload.load_vec(5000, 100)?, [0xFF; 100]);
+            assert!(payload.load_vec(10000, 5).is_err());
+            Ok(())
+        })
+        .unwrap()
+        .unwrap();
+}
+
+#[test]
+fn test_table_rows_progress() {
+    let mut queries = vec!["CREATE TABLE example(col);"];
+    let insert = format!("INSERT INTO example(col) VALUES ('{}');", "x".repeat(100));
+    queries.extend(std::iter::repeat(insert.as_str()).take(1000));
+    let file = create_sqlite_database(&queries);
+
+    let mut conn = Connection::open(file.path()).unwrap();
+    let mut table_rows = conn.table_rows("example").unwrap();
+    assert_eq!(table_rows.current_cell_index(), 0);
+    let mut n_pages = 0;
+    let mut n_cells = 0;
+    let mut last_index = None;
+    while let Some(row) = table_rows.next() {
+        row.unwrap();
+        let index = table_rows.current_cell_index();
+        assert!(index < table_rows.current_page_cell_count());
+        if index == 0 {
+            n_pages += 1;
+            n_cells += table_rows.current_page_cell_count() as usize;
+        } else {
+            assert_eq!(Some(index - 1), last_index);
+        }
+        last_index = Some(index);
+    }
+    assert!(n_pages > 1);
+    assert_eq!(n_cells, 1000);
+}
+
+#[test]
+fn test_count_rows() {
+    let mut queries = vec!["CREATE TABLE example(col);", "CREATE TABLE empty(col);"];
+    let insert = format!("INSERT INTO example(col) VALUES ('{}');", "x".repeat(100));
+    queries.extend(std::iter::repeat(insert.as_str()).take(2000));
+    queries.extend(std::iter::repeat("INSERT INTO example(col) VALUES (NULL);").take(1000));
+    let file = create_sqlite_database(&queries);
+
+    let mut conn = Connection::open(file.path()).unwrap();
+    assert_eq!(conn.count_rows("example").unwrap(), 3000);
+    assert_eq!(
+        conn.count_rows("EXAMPLE").unwrap(),
+        conn.table_rows("example").unwrap().count() as u64
+    );
+    assert_eq!(conn.count_rows("empty").unwrap(), 0);
+    assert!(conn.count_rows("invalid").is_err());
+}
+
+#[test]
+fn test_write_column() {
+    let text = "abcdefghij".repeat(10000);
+    let insert = format!("INSERT INTO example(col1, col2) VALUES ('{text}', X'1234');");
+    let file = create_sqlite_database(&[
+        "CREATE TABLE example(id INTEGER PRIMARY KEY, col1, col2);",
+        &insert,
+        "INSERT INTO example(col1, col2) VALUES (1, NULL);",
+        "ALTER TABLE example ADD COLUMN col3 DEFAULT 'x';",
+    ]);
+
+    let mut conn = Connection::open(file.path()).unwrap();
+    let mut buf = Vec::new();
+    assert_eq!(
+        conn.write_column("example", "COL1", 1, &mut buf).unwrap(),
+        100000
+    );
+    assert_eq!(buf, text.as_bytes());
+    let mut buf = Vec::new();
+    assert_eq!(
+        conn.write_column("example", "col2", 1, &mut buf).unwrap(),
+        2
+    );
+    assert_eq!(buf, [0x12, 0x34]);
+
+    let mut buf = Vec::new();
+    // Not text or blob.
+    assert!(conn.write_column("example", "col1", 2, &mut buf).is_err());
+    assert!(conn.write_column("example", "col2", 2, &mut buf).is_err());
+    assert!(conn.write_column("example", "id", 1, &mut buf).is_err());
+    // Not stored in the row.
+    assert!(conn.write_column("example", "col3", 1, &mut buf).is_err());
+    // No such row, column or table.
+    assert!(conn.write_column("example", "col1", 3, &mut buf).is_err());
+    assert!(conn
+        .write_column("example", "invalid", 1, &mut buf)
+        .is_err());
+    assert!(conn.write_column("invalid", "col1", 1, &mut buf).is_err());
+    assert!(buf.is_empty());
+}
+
+#[test]
+fn test_table_rows_rowid_range() {
+    let mut queries = vec!["CREATE TABLE example(col);"];
+    let inserts = (0..1000)
+        .map(|i| format!("INSERT INTO example(col) VALUES ({});", i))
+        .collect::<Vec<_>>();
+    queries.extend(inserts.iter().map(|s| s.as_str()));
+    let file = create_sqlite_database(&queries);
+
+    let mut conn = Connection::open(file.path()).unwrap();
+    let all_rows = conn
+        .table_rows("example")
+        .unwrap()
+        .collect::<anyhow::Result<Vec<_>>>()
+        .unwrap();
+    assert_eq!(all_rows.len(), 1000);
+
+    let mut rows = Vec::new();
+    for (lo, hi) in [(1, 251), (251, 501), (501, 751), (751, 1001)] {
+        let mut table_rows = conn.table_rows("example").unwrap();
+        table_rows.move_to(lo).unwrap();
+        let mut n_rows = 0;
+        while let Some(row) = table_rows.next_until(hi - 1) {
+            let row = row.unwrap();
+            assert!(lo <= row.rowid && row.rowid < hi);
+            rows.push(row);
+            n_rows += 1;
+        }
+        assert_eq!(n_rows, 250);
+        // The row exceeding the range is not consumed.
+        assert!(table_rows.next_until(hi - 1).is_none());
+        if hi <= 1000 {
+            assert_eq!(table_rows.next().unwrap().unwrap().rowid, hi);
+        } else {
+            assert!(table_rows.next().is_none());
+        }
+    }
+    assert_eq!(rows, all_rows);
+
+    // A rowid which does not exist.
+    let mut table_rows = conn.table_rows("example").unwrap();
+    table_rows.move_to(-10).unwrap();
+    assert_eq!(table_rows.next_until(0).map(|row| row.unwrap()), None);
+    assert_eq!(table_rows.next_until(1).unwrap().unwrap().rowid, 1);
+    table_rows.move_to(2000).unwrap();
+    assert!(table_rows.next_until(3000).is_none());
+    assert!(table_rows.next().is_none());
+    // Rewind after the iteration is completed.
+    table_rows.move_to(1000).unwrap();
+    assert_eq!(table_rows.next().unwrap().unwrap().rowid, 1000);
+    assert!(table_rows.next().is_none());
+}
+
+#[test]
+fn test_table_rows_peek_rowid() {
+    let mut queries = vec!["CREATE TABLE example(col);".to_string()];
+    // 4 rows with 1000 byte blob fill a leaf page.
+    for i in 1..=40 {
+        queries.push(format!(
+            "INSERT INTO example(rowid, col) VALUES ({i}, zeroblob(1000));"
+        ));
+    }
+    queries.push("DELETE FROM example WHERE rowid % 4 = 0;".to_string());
+    let file = create_sqlite_database(&queries.iter().map(|s| s.as_str()).collect::<Vec<_>>());
+
+    let mut conn = Connection::open(file.path()).unwrap();
+    let mut table_rows = conn.table_rows("example").unwrap();
+    let mut rowids = Vec::new();
+    while let Some(rowid) = table_rows.peek_rowid().unwrap() {
+        // Peeking does not consume the row.
+        assert_eq!(table_rows.peek_rowid().unwrap(), Some(rowid));
+        assert_eq!(table_rows.next().unwrap().unwrap().rowid, rowid);
+        rowids.push(rowid);
+    }
+    assert_eq!(rowids, (1..=40).filter(|i| i % 4 != 0).collect::<Vec<_>>());
+    assert!(table_rows.next().is_none());
+
+    // The deleted rowid is at the end of a leaf page.
+    for rowid in [4, 8, 36] {
+        table_rows.move_to(rowid).unwrap();
+        assert_eq!(table_rows.peek_rowid().unwrap(), Some(rowid + 1));
+        assert_eq!(table_rows.next().unwrap().unwrap().rowid, rowid + 1);
+        table_rows.move_to(rowid).unwrap();
+        assert_eq!(table_rows.next().unwrap().unwrap().rowid, rowid + 1);
+        assert_eq!(table_rows.peek_rowid().unwrap(), Some(rowid + 2));
+        table_rows.move_to(rowid).unwrap();
+        assert_eq!(table_rows.prev().unwrap().unwrap().rowid, rowid - 1);
+    }
+    table_rows.move_to(40).unwrap();
+    assert_eq!(table_rows.peek_rowid().unwrap(), None);
+    assert!(table_rows.next().is_none());
+}
+
+#[test]
+fn test_table_rows_reverse() {
+    let mut queries = vec!["CREATE TABLE example(col);", "CREATE TABLE empty(col);"];
+    let inserts = (0..1000)
+        .map(|i| format!("INSERT INTO example(col) VALUES (zeroblob({}));", i % 300))
+        .collect::<Vec<_>>();
+    queries.extend(inserts.iter().map(|s| s.as_str()));
+    let file = create_sqlite_database(&queries);
+
+    let mut conn = Connection::open(file.path()).unwrap();
+    let mut all_rows = conn
+        .table_rows("example")
+        .unwrap()
+        .collect::<anyhow::Result<Vec<_>>>()
+        .unwrap();
+    all_rows.reverse();
+
+    let mut table_rows = conn.table_rows("example").unwrap();
+    table_rows.move_to_last().unwrap();
+    let mut rows = Vec::new();
+    while let Some(row) = table_rows.prev() {
+        rows.push(row.unwrap());
+    }
+    assert_eq!(rows, all_rows);
+    assert!(table_rows.next().is_none());
+
+    // The last row is the row with the max rowid.
+    table_rows.move_to_last().unwrap();
+    assert!(table_rows.next().is_none());
+    assert!(table_rows.prev().is_none());
+    table_rows.move_to_last().unwrap();
+    assert_eq!(table_rows.prev().unwrap().unwrap().rowid, 1000);
+    // prev() and next() return the same row in turn.
+    assert_eq!(table_rows.next().unwrap().unwrap().rowid, 1000);
+    assert_eq!(table_rows.prev().unwrap().unwrap().rowid, 1000);
+    assert_eq!(table_rows.prev().unwrap().unwrap().rowid, 999);
+
+    table_rows.move_to(500).unwrap();
+    assert_eq!(table_rows.prev().unwrap().unwrap().rowid, 499);
+    assert_eq!(table_rows.next().unwrap().unwrap().rowid, 499);
+    assert_eq!(table_rows.next().unwrap().unwrap().rowid, 500);
+    // A rowid greater than all rows.
+    table_rows.move_to(2000).unwrap();
+    assert_eq!(table_rows.prev().unwrap().unwrap().rowid, 1000);
+    table_rows.move_to(1).unwrap();
+    assert!(table_rows.prev().is_none());
+    assert!(table_rows.next().is_none());
+
+    let mut table_rows = conn.table_rows("empty").unwrap();
+    table_rows.move_to_last().unwrap();
+    assert!(table_rows.prev().is_none());
+}
+
+#[test]
+fn test_index_rows() {
+    let mut queries = vec![
+        "CREATE TABLE example(col1, col2, col3);",
+        "CREATE INDEX index1 ON example(col2, col1);",
+        "CREATE INDEX index2 ON example(col3);",
+        "INSERT INTO example(col1, col2) VALUES (1, 'b');",
+        "INSERT INTO example(col1, col2) VALUES (2, 'a');",
+        "INSERT INTO example(col1, col2) VALUES (3, NULL);",
+        "INSERT INTO example(col1, col2) VALUES (0, 'b');",
+    ];
+    let insert_queries = (0..1000)
+        .map(|i| {
+            format!(
+                "INSERT INTO example(col3) VALUES ('{:03}{}');",
+                (i * 7) % 1000,
+                "x".repeat(100)
+            )
+        })
+        .collect::<Vec<_>>();
+    queries.extend(insert_queries.iter().map(|q| q.as_str()));
+    let file = create_sqlite_database(&queries);
+
+    let mut conn = Connection::open(file.path()).unwrap();
+    let entries = conn
+        .index_rows("INDEX1")
+        .unwrap()
+        // Skip the rows having NULL in both columns.
+        .skip(1000)
+        .collect::<anyhow::Result<Vec<_>>>()
+        .unwrap();
+    assert_eq!(
+        entries,
+        vec![
+            (vec![Value::Null, Value::Integer(3)], 3),
+            (
+                vec![Value::Text(b"a".to_vec().into()), Value::Integer(2)],
+                2
+            ),
+            (
+                vec![Value::Text(b"b".to_vec().into()), Value::Integer(0)],
+                4
+            ),
+            (
+                vec![Value::Text(b"b".to_vec().into()), Value::Integer(1)],
+                1
+            ),
+        ]
+    );
+
+    // The index spans multiple levels of pages.
+    let entries = conn
+        .index_rows("index2")
+        .unwrap()
+        .collect::<anyhow::Result<Vec<_>>>()
+        .unwrap();
+    assert_eq!(entries.len(), 1004);
+    for (keys, _) in &entries[..4] {
+        assert_eq!(keys, &vec![Value::Null]);
+    }
+    for (i, (keys, rowid)) in entries[4..].iter().enumerate() {
+        let expected = format!("{:03}{}", i, "x".repeat(100));
+        assert_eq!(keys, &vec![Value::Text(expected.into_bytes().into())]);
+        // (rowid - 5) * 7 % 1000 == i
+        assert_eq!((*rowid - 5) * 7 % 1000, i as i64);
+    }
+
+    assert!(conn.index_rows("example").is_err());
+    assert!(conn.index_rows("invalid").is_err());
+}
+
+#[test]
+fn test_index_rows_real_affinity() {
+    let file = create_sqlite_database(&[
+        "CREATE TABLE example(col1 REAL, col2 INTEGER, id INTEGER PRIMARY KEY);",
+        "CREATE INDEX index1 ON example(col1, col2);",
+        "INSERT INTO example(col1, col2, id) VALUES (2, 3, 10);",
+        "INSERT INTO example(col1, col2, id) VALUES (1.5, 2.0, 20);",
+    ]);
+
+    let mut conn = Connection::open(file.path()).unwrap();
+    // Whole numbers in REAL columns are stored as integers in the index.
+    let entries = conn
+        .index_rows("index1")
+        .unwrap()
+        .collect::<anyhow::Result<Vec<_>>>()
+        .unwrap();
+    assert_eq!(
+        entries,
+        vec![
+            (vec![Value::Real(1.5), Value::Integer(2)], 20),
+            (vec![Value::Real(2.0), Value::Integer(3)], 10),
+        ]
+    );
+}
+
+#[test]
+fn test_select_utf16_text() {
+    for encoding in ["UTF-16le", "UTF-16be"] {
+        let pragma = format!("PRAGMA encoding = '{encoding}';");
+        let file = create_sqlite_database(&[
+            &pragma,
+            "CREATE TABLE example(col1, col2);",
+            "INSERT INTO example(col1, col2) VALUES ('hello', x'0102');",
+            "INSERT INTO example(col1, col2) VALUES ('h\u{e9}llo \u{1F600}', 1);",
+            "CREATE INDEX index1 ON example(col1);",
+        ]);
+
+        let mut conn = Connection::open(file.path()).unwrap();
+        // A text key does not seek the index of a UTF-16 database.
+        let mut stmt = conn
+            .prepare("SELECT col2 FROM example WHERE col1 = 'h\u{e9}llo \u{1F600}';")
+            .unwrap();
+        let mut rows = stmt.execute().unwrap();
+        let row = rows.next_row().unwrap().unwrap();
+        assert_eq!(row.parse().unwrap().get(0), &Value::Integer(1));
+        drop(row);
+        assert!(rows.next_row().unwrap().is_none());
+        drop(rows);
+        let mut stmt = conn
+            .prepare("SELECT col1, col2 FROM example WHERE col1 >= 'h\u{e9}';")
+            .unwrap();
+        let mut rows = stmt.execute().unwrap();
+        let row = rows.next_row().unwrap().unwrap();
+        let columns = row.parse().unwrap();
+        assert_eq!(
+            columns.get(0),
+            &Value::Text("h\u{e9}llo \u{1F600}".as_bytes().into())
+        );
+        assert_eq!(columns.get(1), &Value::Integer(1));
+        drop(row);
+        assert!(rows.next_row().unwrap().is_none());
+
+        let mut conn = Connection::open(file.path()).unwrap();
+        let rows = conn
+            .table_rows("example")
+            .unwrap()
+            .collect::<anyhow::Result<Vec<_>>>()
+            .unwrap();
+        assert_eq!(
+            rows[0].values,
+            vec![
+                Value::Text(b"hello".to_vec().into()),
+                Value::Blob(vec![1, 2].into())
+            ]
+        );
+    }
+}
+
+#[test]
+fn test_select_partial() {
+    let file = create_sqlite_database(&[
+        "CREATE TABLE example(col1, col2, col3);",
+        "INSERT INTO example(col1, col2, col3) VALUES (1, 2, 3);",
+        "INSERT INTO example(col1, col2, col3) VALUES (4, 5, 6);",
+        "INSERT INTO example(col1, col2, col3) VALUES (7, 8, 9);",
+    ]);
+
+    let mut conn = Connection::open(file.path()).unwrap();
+    let mut stmt = conn.prepare("SELECT col3, col1 FROM example;").unwrap();
+    let mut rows = stmt.execute().unwrap();
+
+    let row = rows.next_row().unwrap().unwrap();
+    let columns = row.parse().unwrap();
+    assert_eq!(columns.len(), 2);
+    assert_eq!(columns.get(0), &Value::Integer(3));
+    assert_eq!(columns.get(1), &Value::Integer(1));
+    drop(row);
+
+    let row = rows.next_row().unwrap().unwrap();
+    let columns = row.parse().unwrap();
+    assert_eq!(columns.len(), 2);
+    assert_eq!(columns.get(0), &Value::Integer(6));
+    assert_eq!(columns.get(1), &Value::Integer(4));
+    drop(row);
+
+    let row = rows.next_row().unwrap().unwrap();
+    let columns = row.parse().unwrap();
+    assert_eq!(columns.len(), 2);
+    assert_eq!(columns.get(0), &Value::Integer(9));
+    assert_eq!(columns.get(1), &Value::Integer(7));
+    drop(row);
+
+    assert!(rows.next_row().unwrap().is_none());
+}
+
+#[test]
+fn test_select_added_columns() {
+    let file = create_sqlite_database(&[
+        "CREATE TABLE example(col1);",
+        "INSERT INTO example(col1) VALUES (1);",
+        "ALTER TABLE example ADD COLUMN col2;",
+        "ALTER TABLE example ADD COLUMN col3 DEFAULT 'abc';",
+        "ALTER TABLE example ADD COLUMN col4 INTEGER DEFAULT -10;",
+        "INSERT INTO example(col1, col2, col3, col4) VALUES (2, 3, 4, 5);",
+    ]);
+
+    let mut conn = Connection::open(file.path()).unwrap();
+    assert_eq!(
+        conn.query("SELECT * FROM example;").unwrap(),
+        vec![
+            vec![
+                Value::Integer(1),
+                Value::Null,
+                Value::Text(b"abc".to_vec().into()),
+                Value::Integer(-10)
+            ],
+            vec![
+                Value::Integer(2),
+                Value::Integer(3),
+                Value::Integer(4),
+                Value::Integer(5)
+            ],
+        ]
+    );
+    assert_eq!(
+        conn.query("SELECT col1 FROM example WHERE col4 = -10;")
+            .unwrap(),
+        vec![vec![Value::Integer(1)]]
+    );
+
+    let rows = conn
+        .table_rows("example")
+        .unwrap()
+        .collect::<anyhow::Result<Vec<_>>>()
+        .unwrap();
+    assert_eq!(
+        rows[0],
+        TableRow {
+            rowid: 1,
+            values: vec![
+                Value::Integer(1),
+                Value::Null,
+                Value::Text(b"abc".to_vec().into()),
+                Value::Integer(-10)
+            ],
+        }
+    );
+    assert_eq!(rows[1].values.len(), 4);
+}
+
+#[test]
+fn test_select_real_stored_as_integer() {
+    let file = create_sqlite_database(&[
+        "CREATE TABLE example(col1 REAL, col2 NUMERIC, col3, col4 REAL DEFAULT 7);",
+        "INSERT INTO example(col1, col2, col3) VALUES (10, 10, 10);",
+        "INSERT INTO example(col1, col2, col3) VALUES (1.5, 1.5, 1.5);",
+        "INSERT INTO example(col1, col2, col3) VALUES ('abc', NULL, 'abc');",
+    ]);
+
+    let mut conn = Connection::open(file.path()).unwrap();
+    assert_eq!(
+        conn.query("SELECT * FROM example;").unwrap(),
+        vec![
+            vec![
+                Value::Real(10.0),
+                Value::Integer(10),
+                Value::Integer(10),
+                Value::Real(7.0)
+            ],
+            vec![
+                Value::Real(1.5),
+                Value::Real(1.5),
+                Value::Real(1.5),
+                Value::Real(7.0)
+            ],
+            vec![
+                Value::Text(b"abc".to_vec().into()),
+                Value::Null,
+                Value::Text(b"abc".to_vec().into()),
+                Value::Real(7.0)
+            ],
+        ]
+    );
+    assert_eq!(
+        conn.query("SELECT col1 FROM example WHERE col1 = 10;")
+            .unwrap(),
+        vec![vec![Value::Real(10.0)]]
+    );
+
+    let rows = conn
+        .table_rows("example")
+        .unwrap()
+        .map(|row| row.map(|row| row.values))
+        .collect::<anyhow::Result<Vec<_>>>()
+        .unwrap();
     assert_eq!(
-        columns.get(0),
-        &Value::Blob([0xFF; 10000].as_slice().into())
+        rows[0],
+        vec![
+            Value::Real(10.0),
+            Value::Integer(10),
+            Value::Integer(10),
+            Value::Real(7.0)
+        ]
     );
-    assert_eq!(columns.get(1), &Value::Integer(20000));
-    assert_eq!(columns.get(2), &Value::Null);
-    assert_eq!(columns.get(3), &Value::Null);
-    drop(row);
+}
 
-    assert!(rows.next_row().unwrap().is_none());
+#[test]
+fn test_select_arithmetic() {
+    let file = create_sqlite_database(&[
+        "CREATE TABLE example(col1, col2);",
+        "INSERT INTO example(col1, col2) VALUES (7, 2);",
+        "INSERT INTO example(col1, col2) VALUES (7.5, 0);",
+        "INSERT INTO example(col1, col2) VALUES ('3', NULL);",
+    ]);
+
+    let mut conn = Connection::open(file.path()).unwrap();
+    assert_eq!(
+        conn.query(
+            "SELECT col1 + col2, col1 - col2, col1 * col2, col1 / col2, col1 % col2 FROM example;"
+        )
+        .unwrap(),
+        vec![
+            vec![
+                Value::Integer(9),
+                Value::Integer(5),
+                Value::Integer(14),
+                Value::Integer(3),
+                Value::Integer(1)
+            ],
+            vec![
+                Value::Real(7.5),
+                Value::Real(7.5),
+                Value::Real(0.0),
+                Value::Null,
+                Value::Null
+            ],
+            vec![
+                Value::Null,
+                Value::Null,
+                Value::Null,
+                Value::Null,
+                Value::Null
+            ],
+        ]
+    );
+    assert_eq!(
+        conn.query("SELECT col1 FROM example WHERE col1 * 2 + 1 = 4 + -1 * -3;")
+            .unwrap(),
+        vec![vec![Value::Text(b"3".to_vec().into())]]
+    );
 }
 
 #[test]
-fn test_select_partial() {
+fn test_select_keyword_literals() {
     let file = create_sqlite_database(&[
-        "CREATE TABLE example(col1, col2, col3);",
-        "INSERT INTO example(col1, col2, col3) VALUES (1, 2, 3);",
-        "INSERT INTO example(col1, col2, col3) VALUES (4, 5, 6);",
-        "INSERT INTO example(col1, col2, col3) VALUES (7, 8, 9);",
+        "CREATE TABLE example(col1, col2 DEFAULT CURRENT_TIMESTAMP, col3 DEFAULT (1 + 2));",
+        "INSERT INTO example(col1) VALUES (true);",
+        "INSERT INTO example(col1) VALUES (false);",
+        "INSERT INTO example(col1) VALUES (NULL);",
     ]);
 
     let mut conn = Connection::open(file.path()).unwrap();
-    let mut stmt = conn.prepare("SELECT col3, col1 FROM example;").unwrap();
-    let mut rows = stmt.execute().unwrap();
+    assert_eq!(
+        conn.query("SELECT col1, TRUE, FALSE FROM example WHERE col1 = true;")
+            .unwrap(),
+        vec![vec![
+            Value::Integer(1),
+            Value::Integer(1),
+            Value::Integer(0)
+        ]]
+    );
+    assert_eq!(
+        conn.query("SELECT col1 FROM example WHERE col1 = FALSE;")
+            .unwrap(),
+        vec![vec![Value::Integer(0)]]
+    );
+    assert_eq!(
+        conn.query("SELECT col1 FROM example WHERE col1 IS NULL;")
+            .unwrap(),
+        vec![vec![Value::Null]]
+    );
+    assert!(conn
+        .query("SELECT CURRENT_TIMESTAMP FROM example;")
+        .is_err());
 
-    let row = rows.next_row().unwrap().unwrap();
-    let columns = row.parse().unwrap();
-    assert_eq!(columns.len(), 2);
-    assert_eq!(columns.get(0), &Value::Integer(3));
-    assert_eq!(columns.get(1), &Value::Integer(1));
-    drop(row);
+    // TRUE and FALSE are names of columns if any.
+    let file = create_sqlite_database(&[
+        "CREATE TABLE \"true\"(true, false);",
+        "INSERT INTO \"true\" VALUES (2, 3);",
+    ]);
+    let mut conn = Connection::open(file.path()).unwrap();
+    assert_eq!(
+        conn.query("SELECT true, FALSE, \"true\" FROM true WHERE true = 2;")
+            .unwrap(),
+        vec![vec![
+            Value::Integer(2),
+            Value::Integer(3),
+            Value::Integer(2)
+        ]]
+    );
+}
 
-    let row = rows.next_row().unwrap().unwrap();
-    let columns = row.parse().unwrap();
-    assert_eq!(columns.len(), 2);
-    assert_eq!(columns.get(0), &Value::Integer(6));
-    assert_eq!(columns.get(1), &Value::Integer(4));
-    drop(row);
+#[test]
+fn test_select_signed_defaults() {
+    let file = create_sqlite_database(&[
+        "CREATE TABLE example(col1);",
+        "INSERT INTO example(col1) VALUES (1);",
+        "ALTER TABLE example ADD COLUMN col2 DEFAULT -0x10;",
+        "ALTER TABLE example ADD COLUMN col3 DEFAULT -'5';",
+        "ALTER TABLE example ADD COLUMN col4 DEFAULT +'5';",
+        "ALTER TABLE example ADD COLUMN col5 DEFAULT -'1.5';",
+        "ALTER TABLE example ADD COLUMN col6 DEFAULT -NULL;",
+        "ALTER TABLE example ADD COLUMN col7 DEFAULT true;",
+        "ALTER TABLE example ADD COLUMN col8 DEFAULT \"true\";",
+    ]);
 
-    let row = rows.next_row().unwrap().unwrap();
-    let columns = row.parse().unwrap();
-    assert_eq!(columns.len(), 2);
-    assert_eq!(columns.get(0), &Value::Integer(9));
-    assert_eq!(columns.get(1), &Value::Integer(7));
-    drop(row);
+    let mut conn = Connection::open(file.path()).unwrap();
+    let expected = vec![
+        Value::Integer(1),
+        Value::Integer(-16),
+        Value::Integer(-5),
+        Value::Text(b"5".to_vec().into()),
+        Value::Real(-1.5),
+        Value::Null,
+        Value::Integer(1),
+        Value::Text(b"true".to_vec().into()),
+    ];
+    assert_eq!(
+        conn.query("SELECT * FROM example;").unwrap(),
+        vec![expected.clone()]
+    );
+    assert_eq!(
+        conn.table_rows("example")
+            .unwrap()
+            .map(|row| row.unwrap().values)
+            .collect::<Vec<_>>(),
+        vec![expected]
+    );
+}
 
-    assert!(rows.next_row().unwrap().is_none());
+#[test]
+fn test_select_unsupported_default() {
+    let file = create_sqlite_database(&[
+        "CREATE TABLE example(col1);",
+        "INSERT INTO example(col1) VALUES (1);",
+        // ALTER TABLE ADD COLUMN rejects CURRENT_TIMESTAMP.
+        "PRAGMA writable_schema = ON;",
+        "UPDATE sqlite_schema SET sql = 'CREATE TABLE example(col1, col2 DEFAULT CURRENT_TIMESTAMP)' WHERE name = 'example';",
+    ]);
+
+    let mut conn = Connection::open(file.path()).unwrap();
+    // The default value is not needed.
+    assert_eq!(
+        conn.query("SELECT col1 FROM example;").unwrap(),
+        vec![vec![Value::Integer(1)]]
+    );
+    assert_eq!(
+        conn.query("SELECT col2 FROM example;")
+            .unwrap_err()
+            .to_string(),
+        "unsupported default value of missing column"
+    );
+    assert_eq!(
+        conn.table_rows("example")
+            .unwrap()
+            .next()
+            .unwrap()
+            .unwrap_err()
+            .to_string(),
+        "unsupported default value of missing column"
+    );
 }
 
 #[test]
@@ -221,6 +1619,57 @@ fn test_select_rowid() {
     drop(row);
 
     assert!(rows.next_row().unwrap().is_none());
+
+    assert_eq!(
+        conn.query("SELECT _ROWID_, Oid, col FROM example WHERE oid = 2;")
+            .unwrap(),
+        vec![vec![
+            Value::Integer(2),
+            Value::Integer(2),
+            Value::Integer(20)
+        ]]
+    );
+    // Names other than the column named rowid still refer to the rowid.
+    assert_eq!(
+        conn.query("SELECT rowid, _rowid_, oid FROM example2;")
+            .unwrap(),
+        vec![
+            vec![Value::Null, Value::Integer(1), Value::Integer(1)],
+            vec![Value::Integer(100), Value::Integer(2), Value::Integer(2)],
+        ]
+    );
+}
+
+#[test]
+fn test_select_large_rowid() {
+    let file = create_sqlite_database(&[
+        "CREATE TABLE example(col);",
+        "INSERT INTO example(rowid, col) VALUES (9000000000000000000, 1);",
+        "INSERT INTO example(rowid, col) VALUES (9223372036854775807, 2);",
+        "INSERT INTO example(rowid, col) VALUES (-9223372036854775808, 3);",
+    ]);
+
+    let mut conn = Connection::open(file.path()).unwrap();
+    assert_eq!(
+        conn.query("SELECT rowid, col FROM example;").unwrap(),
+        vec![
+            vec![Value::Integer(i64::MIN), Value::Integer(3)],
+            vec![Value::Integer(9000000000000000000), Value::Integer(1)],
+            vec![Value::Integer(i64::MAX), Value::Integer(2)],
+        ]
+    );
+    assert_eq!(
+        conn.query("SELECT col FROM example WHERE rowid = 9000000000000000000;")
+            .unwrap(),
+        vec![vec![Value::Integer(1)]]
+    );
+    let rowids = conn
+        .table_rows("example")
+        .unwrap()
+        .map(|row| row.map(|row| row.rowid))
+        .collect::<anyhow::Result<Vec<_>>>()
+        .unwrap();
+    assert_eq!(rowids, vec![i64::MIN, 9000000000000000000, i64::MAX]);
 }
 
 #[test]
@@ -274,6 +1723,110 @@ fn test_select_column_name_and_all() {
     assert!(rows.next_row().unwrap().is_none());
 }
 
+#[test]
+fn test_select_get_by_name() {
+    let file = create_sqlite_database(&[
+        "CREATE TABLE example(col1, \"Col 2\", col3);",
+        "INSERT INTO example(col1, \"Col 2\", col3) VALUES (1, 2, 3);",
+    ]);
+
+    let mut conn = Connection::open(file.path()).unwrap();
+    let mut stmt = conn.prepare("SELECT * FROM example;").unwrap();
+    let mut rows = stmt.execute().unwrap();
+    let row = rows.next_row().unwrap().unwrap();
+    let columns = row.parse().unwrap();
+    assert_eq!(columns.get_by_name("col1"), Some(&Value::Integer(1)));
+    assert_eq!(columns.get_by_name("col 2"), Some(&Value::Integer(2)));
+    assert_eq!(columns.get_by_name("COL3"), Some(&Value::Integer(3)));
+    assert_eq!(columns.get_by_name("col4"), None);
+    assert_eq!(columns.name(1), Some(b"Col 2".as_slice()));
+    assert_eq!(columns.get(0), &Value::Integer(1));
+    drop(row);
+    drop(rows);
+
+    let mut stmt = conn
+        .prepare("SELECT col3, col1 AS a, col1 = 1, \"Col 2\" b, col1 FROM example;")
+        .unwrap();
+    let mut rows = stmt.execute().unwrap();
+    let row = rows.next_row().unwrap().unwrap();
+    let columns = row.parse().unwrap();
+    assert_eq!(columns.get_by_name("col3"), Some(&Value::Integer(3)));
+    assert_eq!(columns.get_by_name("a"), Some(&Value::Integer(1)));
+    assert_eq!(columns.get_by_name("b"), Some(&Value::Integer(2)));
+    assert_eq!(columns.get_by_name("col1"), Some(&Value::Integer(1)));
+    // Aliased columns are not accessible by the original name.
+    assert_eq!(columns.get_by_name("col 2"), None);
+    // Expressions without alias have no name.
+    assert_eq!(columns.name(2), None);
+    assert_eq!(columns.get(2), &Value::Integer(1));
+}
+
+#[test]
+fn test_select_quoted_identifier() {
+    let file = create_sqlite_database(&[
+        "CREATE TABLE \"select\"(\"order\", [group], `where`, \"a\"\"b\", `c``d`);",
+        "INSERT INTO \"select\" VALUES (1, 2, 3, 4, 5);",
+    ]);
+
+    let mut conn = Connection::open(file.path()).unwrap();
+    for query in [
+        "SELECT \"order\", [group], `where`, \"a\"\"b\", `c``d` FROM \"select\";",
+        "SELECT [order], `group`, \"where\", `a\"b`, \"c`d\" FROM [select];",
+    ] {
+        let mut stmt = conn.prepare(query).unwrap();
+        let mut rows = stmt.execute().unwrap();
+
+        let row = rows.next_row().unwrap().unwrap();
+        let columns = row.parse().unwrap();
+        assert_eq!(columns.len(), 5);
+        assert_eq!(columns.get(0), &Value::Integer(1), "query: {}", query);
+        assert_eq!(columns.get(1), &Value::Integer(2), "query: {}", query);
+        assert_eq!(columns.get(2), &Value::Integer(3), "query: {}", query);
+        assert_eq!(columns.get(3), &Value::Integer(4), "query: {}", query);
+        assert_eq!(columns.get(4), &Value::Integer(5), "query: {}", query);
+        drop(row);
+
+        assert!(rows.next_row().unwrap().is_none());
+    }
+}
+
+#[test]
+fn test_select_case_insensitive_names() {
+    let file = create_sqlite_database(&[
+        "CREATE TABLE example(col1, Col2);",
+        "CREATE INDEX index1 ON example(col2);",
+        "INSERT INTO example VALUES (1, 2);",
+    ]);
+
+    let mut conn = Connection::open(file.path()).unwrap();
+    for query in [
+        "SELECT col1, col2 FROM example;",
+        "SELECT COL1, COL2 FROM EXAMPLE;",
+        "SELECT \"Col1\", [cOL2] FROM \"ExAmPlE\";",
+        "SELECT col1, col2 FROM EXAMPLE WHERE COL2 = 2;",
+    ] {
+        let mut stmt = conn.prepare(query).unwrap();
+        let mut rows = stmt.execute().unwrap();
+
+        let row = rows.next_row().unwrap().unwrap();
+        let columns = row.parse().unwrap();
+        assert_eq!(columns.len(), 2);
+        assert_eq!(columns.get(0), &Value::Integer(1), "query: {}", query);
+        assert_eq!(columns.get(1), &Value::Integer(2), "query: {}", query);
+        drop(row);
+
+        assert!(rows.next_row().unwrap().is_none());
+    }
+
+    let root_page_id = conn.root_page_of("example").unwrap();
+    assert!(root_page_id.is_some());
+    assert_eq!(conn.root_page_of("EXAMPLE").unwrap(), root_page_id);
+    assert_eq!(conn.table_rows("EXAMPLE").unwrap().count(), 1);
+    let dump = conn.dump_page(root_page_id.unwrap()).unwrap();
+    assert!(dump.contains("page type: LeafTable\n"), "{}", dump);
+    assert!(dump.contains("cells: 1\n"), "{}", dump);
+}
+
 #[test]
 fn test_select_expression() {
     let file = create_sqlite_database(&[
@@ -415,6 +1968,25 @@ fn test_select_expression_operators() {
         (Value::Integer(0), "1 = 'a' = 'a'"),
         (Value::Integer(1), "1 < 2 = 1"),
         (Value::Integer(0), "1 = 2 <= 1"),
+        (Value::Integer(1), "null is null"),
+        (Value::Integer(0), "null is not null"),
+        (Value::Integer(0), "1 is null"),
+        (Value::Integer(1), "'1' is not 1"),
+        (Value::Integer(1), "1 = 2 is 0"),
+        (Value::Integer(1), "0xff = 255"),
+        (Value::Integer(1), "0xffffffffffffffff = -1"),
+        (Value::Integer(1), "0x10 < 1.5e2"),
+        (Value::Integer(0), "not 1"),
+        (Value::Integer(1), "not 0"),
+        (Value::Integer(0), "not 0.5"),
+        (Value::Integer(1), "not 'abc'"),
+        (Value::Integer(0), "not '1abc'"),
+        (Value::Null, "not null"),
+        (Value::Integer(1), "not not 2"),
+        (Value::Integer(1), "not 1 = 2"),
+        (Value::Integer(0), "not (1 = 1)"),
+        (Value::Integer(1), "not null is not null"),
+        (Value::Integer(0), "(1 < 2) = 0"),
     ] {
         let query = format!("SELECT {} FROM example;", expr);
         assert_same_results(&[expected], &query, &test_conn, &mut conn);
@@ -458,6 +2030,33 @@ fn test_select_primary_key() {
     assert!(rows.next_row().unwrap().is_none());
 }
 
+#[test]
+fn test_select_int_primary_key() {
+    // INT PRIMARY KEY is not an alias of the rowid.
+    let file = create_sqlite_database(&[
+        "CREATE TABLE example(id int primary key, col text);",
+        "INSERT INTO example(id, col) VALUES (10, 'a');",
+        "INSERT INTO example(id, col) VALUES (30, 'b');",
+        "INSERT INTO example(id, col) VALUES (20, 'c');",
+    ]);
+    let test_conn = rusqlite::Connection::open(file.path()).unwrap();
+    let mut conn = Connection::open(file.path()).unwrap();
+
+    for query in [
+        "SELECT id FROM example;",
+        "SELECT rowid FROM example;",
+        "SELECT rowid FROM example WHERE id = 20;",
+        "SELECT id FROM example WHERE rowid = 2;",
+    ] {
+        let mut results = load_rowids(&mut conn, query);
+        // SQLite may scan the automatic index of the primary key.
+        let mut expected = load_test_rowids(&test_conn, query);
+        results.sort();
+        expected.sort();
+        assert_eq!(results, expected, "{query}");
+    }
+}
+
 #[test]
 fn test_select_type_conversions_prior_to_comparison() {
     // Test case from https://www.sqlite.org/datatype3.html#comparison_example
@@ -958,8 +2557,9 @@ fn test_select_filter_compare() {
         "'world'",
         "x''",
         "x'0123456789abcdef'",
+        "X'68656C6C6F'",
     ] {
-        for op in ["==", "=", "!=", "<", "<=", ">", ">="] {
+        for op in ["==", "=", "!=", "<>", "<", "<=", ">", ">=", "IS", "IS NOT"] {
             let query = format!(
                 "SELECT rowid FROM example WHERE col {} {};",
                 op, compare_value
@@ -974,6 +2574,76 @@ fn test_select_filter_compare() {
     }
 }
 
+#[test]
+fn test_select_filter_not() {
+    let file = create_sqlite_database(&[
+        "CREATE TABLE example(col);",
+        "INSERT INTO example(col) VALUES (null);",
+        "INSERT INTO example(col) VALUES (0);",
+        "INSERT INTO example(col) VALUES (1);",
+        "INSERT INTO example(col) VALUES (0.0);",
+        "INSERT INTO example(col) VALUES (0.1);",
+        "INSERT INTO example(col) VALUES ('');",
+        "INSERT INTO example(col) VALUES ('0');",
+        "INSERT INTO example(col) VALUES ('abc');",
+        "INSERT INTO example(col) VALUES ('2abc');",
+        "INSERT INTO example(col) VALUES (x'30');",
+        "INSERT INTO example(col) VALUES (x'31');",
+    ]);
+
+    let mut conn = Connection::open(file.path()).unwrap();
+    let test_conn = rusqlite::Connection::open(file.path()).unwrap();
+
+    for filter in ["not col", "not not col", "not col = 1", "not (col is null)"] {
+        let query = format!("SELECT rowid FROM example WHERE {filter};");
+        assert_eq!(
+            load_rowids(&mut conn, &query),
+            load_test_rowids(&test_conn, &query),
+            "query: {}",
+            query
+        );
+    }
+}
+
+#[test]
+fn test_select_filter_and_or() {
+    let file = create_sqlite_database(&[
+        "CREATE TABLE example(col1, col2);",
+        "INSERT INTO example(col1, col2) VALUES (null, null);",
+        "INSERT INTO example(col1, col2) VALUES (null, 0);",
+        "INSERT INTO example(col1, col2) VALUES (null, 1);",
+        "INSERT INTO example(col1, col2) VALUES (0, null);",
+        "INSERT INTO example(col1, col2) VALUES (0, 0);",
+        "INSERT INTO example(col1, col2) VALUES (0, 1);",
+        "INSERT INTO example(col1, col2) VALUES (1, null);",
+        "INSERT INTO example(col1, col2) VALUES (1, 0);",
+        "INSERT INTO example(col1, col2) VALUES (1, 1);",
+        "INSERT INTO example(col1, col2) VALUES ('abc', 0.5);",
+    ]);
+
+    let mut conn = Connection::open(file.path()).unwrap();
+    let test_conn = rusqlite::Connection::open(file.path()).unwrap();
+
+    for filter in [
+        "col1 and col2",
+        "col1 or col2",
+        "(col1 and col2) is null",
+        "(col1 or col2) is null",
+        "not col1 = 1 and col2 = 1",
+        "col1 = 1 or col2 = 1 and col1 = 0",
+        "col1 or col2 and not col1",
+        "col1 and col2 or not col1 and not col2",
+    ] {
+        let query = format!("SELECT rowid FROM example WHERE {filter};");
+        assert_eq!(
+            load_rowids(&mut conn, &query),
+            load_test_rowids(&test_conn, &query),
+            "query: {}",
+            query
+        );
+    }
+}
+
 #[test]
 fn test_select_filter_with_rowid() {
     let file = create_sqlite_database(&[
@@ -1106,6 +2776,12 @@ fn test_select_with_index() {
     drop(row);
 
     assert!(rows.next_row().unwrap().is_none());
+
+    let mut conn = Connection::open(file.path()).unwrap();
+    assert_eq!(
+        load_rowids(&mut conn, "SELECT col1 FROM example WHERE 5 == col2;"),
+        vec![10, 4]
+    );
 }
 
 #[test]
@@ -1201,3 +2877,210 @@ fn test_select_with_index_same_items() {
         assert_eq!(results, expected, "query: {}", query);
     }
 }
+
+#[test]
+fn test_select_keywords_as_names() {
+    // Like SQLite, these keywords are names where a name is expected.
+    for keyword in [
+        "like", "LIKE", "by", "asc", "desc", "offset", "if", "glob", "regexp", "inner", "left",
+        "outer", "column", "rename", "explain", "end",
+    ] {
+        let file = create_sqlite_database(&[
+            // "CREATE TABLE if" starts "IF NOT EXISTS".
+            &format!("CREATE TABLE \"{keyword}\"({keyword}, col);"),
+            &format!("INSERT INTO {keyword} VALUES (1, 2);"),
+            &format!("INSERT INTO {keyword} VALUES (3, 4);"),
+        ]);
+
+        let test_conn = rusqlite::Connection::open(file.path()).unwrap();
+        let mut conn = Connection::open(file.path()).unwrap();
+        assert_same_results(
+            &[Value::Integer(3), Value::Integer(4)],
+            &format!("SELECT {keyword}, col AS {keyword} FROM {keyword} WHERE {keyword} = 3;"),
+            &test_conn,
+            &mut conn,
+        );
+    }
+
+    // An expression takes these keywords as the current time, but they are
+    // names in the schema.
+    for keyword in ["current_date", "current_time", "CURRENT_TIMESTAMP"] {
+        let file = create_sqlite_database(&[
+            &format!("CREATE TABLE {keyword}({keyword}, col {keyword});"),
+            &format!("INSERT INTO {keyword} VALUES (1, 2);"),
+            &format!("INSERT INTO {keyword} VALUES (3, 4);"),
+        ]);
+
+        let test_conn = rusqlite::Connection::open(file.path()).unwrap();
+        let mut conn = Connection::open(file.path()).unwrap();
+        assert_same_results(
+            &[Value::Integer(3), Value::Integer(4)],
+            &format!("SELECT \"{keyword}\", col AS {keyword} FROM {keyword} WHERE col = 4;"),
+            &test_conn,
+            &mut conn,
+        );
+    }
+}
+
+#[test]
+fn test_select_with_desc_and_partial_index() {
+    let mut queries = vec![
+        "CREATE TABLE example(col1, col2);",
+        "CREATE UNIQUE INDEX index1 ON example(col1 DESC);",
+        "CREATE INDEX index2 ON example(col2) WHERE col2 > 10;",
+    ];
+    let inserts = (1..=100)
+        .map(|i| format!("INSERT INTO example(col1, col2) VALUES ({i}, {});", i % 20))
+        .collect::<Vec<_>>();
+    queries.extend(inserts.iter().map(|s| s.as_str()));
+    let file = create_sqlite_database(&queries);
+
+    let test_conn = rusqlite::Connection::open(file.path()).unwrap();
+    let mut conn = Connection::open(file.path()).unwrap();
+
+    for query in [
+        "SELECT rowid FROM example WHERE col1 = 30;",
+        "SELECT rowid FROM example WHERE col2 = 5;",
+        "SELECT rowid FROM example WHERE col2 = 15;",
+    ] {
+        assert_eq!(
+            load_rowids(&mut conn, query),
+            load_test_rowids(&test_conn, query),
+            "query: {}",
+            query
+        );
+    }
+}
+
+#[test]
+fn test_select_pagesize_65536() {
+    let mut queries = vec![
+        "PRAGMA page_size = 65536;",
+        "CREATE TABLE example(col1, col2);",
+    ];
+    let inserts = (1..=1000)
+        .map(|i| {
+            format!(
+                "INSERT INTO example(col1, col2) VALUES ({i}, randomblob({}));",
+                i * 10
+            )
+        })
+        .collect::<Vec<_>>();
+    queries.extend(inserts.iter().map(|s| s.as_str()));
+    let file = create_sqlite_database(&queries);
+    let buf = std::fs::read(file.path()).unwrap();
+    // The page size 65536 is stored as 1.
+    assert_eq!(&buf[16..18], &[0, 1]);
+    assert!(buf.len() > 2 * 65536);
+
+    let test_conn = rusqlite::Connection::open(file.path()).unwrap();
+    let mut conn = Connection::open(file.path()).unwrap();
+    for query in [
+        "SELECT rowid FROM example;",
+        "SELECT rowid FROM example WHERE col1 >= 500;",
+        "SELECT rowid FROM example WHERE rowid = 777;",
+    ] {
+        assert_eq!(
+            load_rowids(&mut conn, query),
+            load_test_rowids(&test_conn, query),
+            "query: {}",
+            query
+        );
+    }
+
+    let rows = conn
+        .table_rows("example")
+        .unwrap()
+        .collect::<anyhow::Result<Vec<_>>>()
+        .unwrap();
+    assert_eq!(rows.len(), 1000);
+    for (i, row) in rows.iter().enumerate() {
+        let i = i as i64 + 1;
+        assert_eq!(row.values[0], Value::Integer(i));
+        let Value::Blob(blob) = &row.values[1] else {
+            panic!("not blob: {:?}", row.values[1]);
+        };
+        assert_eq!(blob.len(), i as usize * 10);
+    }
+}
+
+#[cfg(feature = "mmap")]
+#[test]
+fn test_select_mmap() {
+    let mut queries = vec!["CREATE TABLE example(col1, col2);"];
+    let inserts = (1..=1000)
+        .map(|i| {
+            format!(
+                "INSERT INTO example(col1, col2) VALUES ({i}, randomblob({}));",
+                i * 10
+            )
+        })
+        .collect::<Vec<_>>();
+    queries.extend(inserts.iter().map(|s| s.as_str()));
+    let file = create_sqlite_database(&queries);
+
+    let mut conn = Connection::open(file.path()).unwrap();
+    // SAFETY: the file is not modified while the connection is alive.
+    let mut mmap_conn = unsafe { Connection::open_mmap(file.path()) }.unwrap();
+    for query in [
+        "SELECT rowid FROM example;",
+        "SELECT rowid FROM example WHERE col1 >= 500;",
+    ] {
+        assert_eq!(
+            load_rowids(&mut mmap_conn, query),
+            load_rowids(&mut conn, query),
+            "query: {}",
+            query
+        );
+    }
+    let rows = conn
+        .table_rows("example")
+        .unwrap()
+        .collect::<anyhow::Result<Vec<_>>>()
+        .unwrap();
+    let mmap_rows = mmap_conn
+        .table_rows("example")
+        .unwrap()
+        .collect::<anyhow::Result<Vec<_>>>()
+        .unwrap();
+    assert_eq!(mmap_rows, rows);
+}
+
+#[test]
+fn test_comments() {
+    let file = create_sqlite_database(&[
+        "CREATE TABLE example( -- a table with comments\n  id INTEGER PRIMARY KEY, /* rowid */\n  col TEXT -- the value\n);",
+        "INSERT INTO example(id, col) VALUES (1, 'abc');",
+        "INSERT INTO example(id, col) VALUES (2, 'def');",
+    ]);
+
+    let mut conn = Connection::open(file.path()).unwrap();
+    assert_eq!(
+        conn.query("SELECT /* all */ col FROM example -- filter\nWHERE id = 2;")
+            .unwrap(),
+        vec![vec![Value::Text(b"def".to_vec().into())]]
+    );
+    assert!(conn.query("SELECT col FROM example /* where;").is_err());
+}
+
+#[test]
+fn test_select_computed_defaults() {
+    let file = create_sqlite_database(&[
+        "CREATE TABLE example(id INTEGER PRIMARY KEY, created INTEGER DEFAULT (strftime('%s', 'now')), updated DEFAULT CURRENT_TIMESTAMP, flag DEFAULT FALSE);",
+        "INSERT INTO example(id) VALUES (1);",
+        "ALTER TABLE example ADD COLUMN name DEFAULT unknown;",
+        "ALTER TABLE example ADD COLUMN score DEFAULT -0.5;",
+    ]);
+
+    let mut conn = Connection::open(file.path()).unwrap();
+    assert_eq!(
+        conn.query("SELECT id, flag, name, score FROM example;")
+            .unwrap(),
+        vec![vec![
+            Value::Integer(1),
+            Value::Integer(0),
+            Value::Text(b"unknown".to_vec().into()),
+            Value::Real(-0.5)
+        ]]
+    );
+}